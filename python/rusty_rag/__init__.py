@@ -6,20 +6,116 @@ Rust core functions are available directly:
 
 from .rusty_rag_core import (
     extract_pdf_text,
+    extract_pdf_pages,
+    extract_pdf_sections,
+    extract_pdf_metadata,
+    extract_pdf_form_fields,
+    pdf_text_coverage,
+    extract_pdf_images,
+    extract_docx_text,
+    extract_html_text,
+    extract_latex_text,
+    extract_epub,
+    extract_pptx,
+    extract_email,
+    extract_subtitles,
+    extract_document,
+    document_formats,
+    read_text_file,
+    extract_spreadsheet_text,
+    markdown_sections,
     chunk_text_parallel,
     chunk_text,
     chunk_by_tokens,
+    chunk_by_tokens_parallel,
+    token_spans,
+    text_spans,
+    chunk_by_model_tokens,
+    chunk_by_sentences,
+    chunk_recursive,
+    chunk_code,
+    chunk_markdown,
+    markdown_tables,
+    chunk_markdown_table,
+    split_sentences,
+    chunk_semantic,
+    Chunk,
+    ChunkStream,
     tokenize,
+    tokenize_batch,
+    tokenize_ngrams,
+    tokenize_with_spans,
     token_count,
+    SubwordTokenizer,
+    detect_language,
+    content_hashes,
+    near_duplicates,
+    garbage_chunks,
+    chunk_keywords,
     BM25Index,
+    TfIdfIndex,
+    rrf_fuse,
+    SpellCorrector,
+    TokenStats,
+    encrypt_bytes,
+    decrypt_bytes,
+    is_encrypted,
 )
 
 __all__ = [
     "extract_pdf_text",
+    "extract_pdf_pages",
+    "extract_pdf_sections",
+    "extract_pdf_metadata",
+    "extract_pdf_form_fields",
+    "pdf_text_coverage",
+    "extract_pdf_images",
+    "extract_docx_text",
+    "extract_html_text",
+    "extract_latex_text",
+    "extract_epub",
+    "extract_pptx",
+    "extract_email",
+    "extract_subtitles",
+    "extract_document",
+    "document_formats",
+    "read_text_file",
+    "extract_spreadsheet_text",
+    "markdown_sections",
     "chunk_text_parallel",
     "chunk_text",
     "chunk_by_tokens",
+    "chunk_by_tokens_parallel",
+    "token_spans",
+    "text_spans",
+    "chunk_by_model_tokens",
+    "chunk_by_sentences",
+    "chunk_recursive",
+    "chunk_code",
+    "chunk_markdown",
+    "markdown_tables",
+    "chunk_markdown_table",
+    "split_sentences",
+    "chunk_semantic",
+    "Chunk",
+    "ChunkStream",
     "tokenize",
+    "tokenize_batch",
+    "tokenize_ngrams",
+    "tokenize_with_spans",
     "token_count",
+    "SubwordTokenizer",
+    "detect_language",
+    "content_hashes",
+    "near_duplicates",
+    "garbage_chunks",
+    "chunk_keywords",
     "BM25Index",
+    "TfIdfIndex",
+    "rrf_fuse",
+    "SpellCorrector",
+    "TokenStats",
+    "encrypt_bytes",
+    "decrypt_bytes",
+    "is_encrypted",
 ]
@@ -0,0 +1,35 @@
+"""Figure captioning with a local multimodal model (e.g. llava).
+
+Captions turn the content of charts, diagrams, and photos in PDFs into
+text, so they can be chunked and retrieved like the rest of the document.
+"""
+
+import os
+
+import ollama
+
+CAPTION_PROMPT = (
+    "This image is a figure from a document. Describe what it shows so it "
+    "can be found by a search: its subject, any title, axis labels, legend "
+    "entries, and the key text or numbers visible in it. Answer in one short "
+    "paragraph without preamble."
+)
+
+
+def captions_enabled() -> bool:
+    """Whether ingestion captions figures by default (CAPTION_IMAGES=true)."""
+    return os.getenv("CAPTION_IMAGES", "false").lower() == "true"
+
+
+def caption_model() -> str:
+    return os.getenv("CAPTION_MODEL", "llava")
+
+
+def caption_image(image: bytes, model: str | None = None) -> str:
+    """Describe a JPEG or PNG image with the vision model."""
+    response = ollama.generate(
+        model=model or caption_model(),
+        prompt=CAPTION_PROMPT,
+        images=[image],
+    )
+    return response["response"].strip()
@@ -1,5 +1,7 @@
 """RustyRAG CLI — Chat with your local PDF documents using RAG."""
 
+from pathlib import Path
+
 import click
 from dotenv import load_dotenv
 from rich.console import Console
@@ -10,7 +12,13 @@ console = Console()
 
 @click.group()
 @click.version_option(version="0.1.0")
-def main():
+@click.option(
+    "--profile",
+    "-p",
+    default=None,
+    help="Named profile from the config file (e.g. fast, quality).",
+)
+def main(profile: str | None):
     """RustyRAG — Chat with your local PDF documents using RAG.
 
     Powered by Ollama (local LLM) and Qdrant (vector database).
@@ -21,19 +29,177 @@ def main():
     """
     load_dotenv()
 
+    from .config import TOMLDecodeError, apply_config, find_config_file
+
+    try:
+        apply_config(profile)
+    except (KeyError, FileNotFoundError) as e:
+        console.print(f"\n[bold red]❌ Error:[/bold red] {e.args[0]}")
+        raise SystemExit(1)
+    except TOMLDecodeError as e:
+        console.print(
+            f"\n[bold red]❌ Error:[/bold red] Invalid config file "
+            f"{find_config_file()}: {e}"
+        )
+        raise SystemExit(1)
+
 
 @main.command()
-@click.argument("file_path", type=click.Path(exists=True))
-def ingest(file_path: str):
-    """Ingest a PDF file into the knowledge base.
+@click.argument("file_paths", nargs=-1, required=True, type=click.Path(allow_dash=True))
+@click.option(
+    "--source",
+    default=None,
+    help="Source name to record for the ingested text (required for stdin).",
+)
+@click.option(
+    "--password",
+    default=None,
+    help="Password for an encrypted PDF.",
+)
+@click.option(
+    "--captions/--no-captions",
+    default=None,
+    help="Caption PDF figures with a local vision model (default: CAPTION_IMAGES).",
+)
+@click.option(
+    "--jobs",
+    "-j",
+    type=click.IntRange(min=1),
+    default=None,
+    help="Documents to extract in parallel (default: INGEST_WORKERS or CPU count).",
+)
+@click.option(
+    "--text-field",
+    default="text",
+    show_default=True,
+    help="Field holding each record's text in .jsonl/.json datasets.",
+)
+@click.option(
+    "--metadata-fields",
+    default=None,
+    help="Comma-separated record fields to store as metadata, e.g. url,meta.author.",
+)
+@click.option(
+    "--normalize",
+    default=None,
+    help="PDF text normalization steps: paragraphs, join-lines, nfc, or none "
+    "(default: NORMALIZE).",
+)
+@click.option(
+    "--chunking",
+    default=None,
+    help="Chunking strategy: tokens, model_tokens, sentences, recursive, semantic, "
+    "markdown, or code (default: CHUNK_STRATEGY).",
+)
+@click.option(
+    "--skip-duplicates/--keep-duplicates",
+    default=None,
+    help="Skip documents and pages already ingested (default: SKIP_DUPLICATES).",
+)
+def ingest(
+    file_paths: tuple[str, ...],
+    source: str | None,
+    password: str | None,
+    captions: bool | None,
+    jobs: int | None,
+    text_field: str,
+    metadata_fields: str | None,
+    normalize: str | None,
+    chunking: str | None,
+    skip_duplicates: bool | None,
+):
+    """Ingest documents into the knowledge base.
+
+    Supports PDF, Word (.docx), PowerPoint (.pptx), HTML, Markdown, EPUB,
+    LaTeX (.tex), CSV/Excel (.xlsx), email (.eml, .mbox), JSON Lines
+    datasets, subtitles (.srt, .vtt), and plain-text and source-code files.
+
+    Extracts text from each document, splits it into semantic chunks,
+    generates embeddings, and stores everything in Qdrant. Several files,
+    or directories (searched recursively), can be given at once; their
+    text is extracted in parallel:
+
+        rusty-rag ingest papers/ notes.md -j 8
+
+    A path may also be an HTTP(S) URL of a web page or remote document,
+    which is recorded as the source:
+
+        rusty-rag ingest https://example.com/guide.html
+
+    Pass `-` as the only path to read raw text from stdin instead:
 
-    Extracts text from the PDF, splits it into semantic chunks,
-    generates embeddings, and stores everything in Qdrant.
+        some-tool | rusty-rag ingest - --source "meeting-notes"
+
+    Each line of a .jsonl dataset (or object of a .json array) is ingested
+    as a record, its text taken from --text-field and the fields named by
+    --metadata-fields stored alongside:
+
+        rusty-rag ingest corpus.jsonl --text-field body --metadata-fields url,title
+
+    Documents whose content is already in the knowledge base (the same
+    file again, or another copy of it) are skipped before embedding, as are
+    duplicate pages; pass --keep-duplicates to ingest them anyway.
     """
-    from .rag import ingest as do_ingest
+    from .fetch import is_url
+    from .rag import collect_documents, ingest as do_ingest, ingest_files, ingest_text
+    from .rag import chunk_strategy, parse_normalize
+    from .records import parse_fields
+
+    options = {
+        "text_field": text_field,
+        "metadata_fields": parse_fields(metadata_fields),
+        "skip_duplicates": skip_duplicates,
+    }
+
+    if "-" in file_paths:
+        if len(file_paths) > 1:
+            raise click.UsageError("`-` (stdin) cannot be combined with other paths.")
+        if not source:
+            raise click.UsageError("--source is required when reading from stdin.")
+    for path in file_paths:
+        if path != "-" and not is_url(path) and not Path(path).exists():
+            raise click.BadParameter(
+                f"File '{path}' does not exist.", param_hint="'FILE_PATHS'"
+            )
+
+    single = len(file_paths) == 1 and not Path(file_paths[0]).is_dir()
+    if source and not single:
+        raise click.UsageError("--source can only be used with a single document.")
 
     try:
-        do_ingest(file_path)
+        options["normalize"] = parse_normalize(normalize) if normalize else None
+        options["chunking"] = chunk_strategy(chunking) if chunking else None
+        if file_paths == ("-",):
+            ingest_text(
+                click.get_text_stream("stdin").read(),
+                source=source,
+                skip_duplicates=skip_duplicates,
+                chunking=options["chunking"],
+            )
+        elif single:
+            do_ingest(
+                file_paths[0],
+                source=source,
+                password=password,
+                captions=captions,
+                **options,
+            )
+        else:
+            documents = collect_documents(list(file_paths))
+            if not documents:
+                raise ValueError("No supported documents found.")
+            failures = ingest_files(
+                documents, password=password, captions=captions, workers=jobs, **options
+            )
+            ingested = len(documents) - len(failures)
+            console.print(
+                f"\n[bold green]✓[/bold green] Ingested {ingested} of "
+                f"{len(documents)} documents."
+            )
+            if failures:
+                for path, error in failures:
+                    console.print(f"  [red]✗[/red] {path}: {error}")
+                raise SystemExit(1)
     except Exception as e:
         console.print(f"\n[bold red]❌ Error:[/bold red] {e}")
         raise SystemExit(1)
@@ -41,16 +207,22 @@ def ingest(file_path: str):
 
 @main.command()
 @click.argument("question")
-def query(question: str):
+@click.option(
+    "--weights",
+    default=None,
+    help="Fusion weights for this query, e.g. vector=0.7,bm25=0.3.",
+)
+def query(question: str, weights: str | None):
     """Query the knowledge base with a question.
 
     Searches for relevant chunks in the vector database,
     then uses the LLM to generate an answer based on the context.
     """
-    from .rag import query as do_query
+    from .rag import parse_fusion_weights, query as do_query
 
     try:
-        response = do_query(question)
+        override = parse_fusion_weights(weights) if weights else None
+        response = do_query(question, weights=override)
         console.print()
         console.print(Panel(response, title="📝 Answer", border_style="green"))
         console.print()
@@ -59,5 +231,222 @@ def query(question: str):
         raise SystemExit(1)
 
 
+def _export_transcript(session, path: str | None) -> None:
+    """Write a session's Markdown transcript and report where it went."""
+    from .export import export_is_encrypted, export_session
+
+    out = export_session(session, Path(path) if path else None)
+    note = " [dim](encrypted)[/dim]" if export_is_encrypted() else ""
+    console.print(f"  Exported transcript to [bold]{out}[/bold]{note}")
+
+
+@main.command()
+@click.option("--resume", "resume_id", default=None, help="Resume a saved session by ID.")
+@click.option(
+    "--export",
+    "export_path",
+    default=None,
+    type=click.Path(dir_okay=False),
+    help="Write a Markdown transcript with sources to this file on exit.",
+)
+def chat(resume_id: str | None, export_path: str | None):
+    """Chat with the knowledge base in an interactive session.
+
+    Every turn (with the chunks retrieved for it) is saved to disk, so the
+    conversation can be picked up later with --resume <id>.
+    Type /help for commands, /exit to leave.
+    """
+    import os
+
+    from .rag import query_with_context
+    from .sessions import load_session, new_session, save_session
+
+    try:
+        session = load_session(resume_id) if resume_id else new_session()
+    except Exception as e:
+        console.print(f"\n[bold red]❌ Error:[/bold red] {e}")
+        raise SystemExit(1)
+
+    history_turns = int(os.getenv("CHAT_HISTORY_TURNS", "4"))
+    verb = "Resumed" if resume_id else "Started"
+    console.print(
+        f"\n{verb} session [bold]{session.id}[/bold] "
+        f"({len(session.turns)} previous turns). Type /help for commands.\n"
+    )
+
+    while True:
+        try:
+            question = console.input("[bold cyan]You ›[/bold cyan] ").strip()
+        except (EOFError, KeyboardInterrupt):
+            console.print()
+            break
+
+        if not question:
+            continue
+        if question in ("/exit", "/quit"):
+            break
+        if question == "/help":
+            console.print(
+                "  /id             show the session ID\n"
+                "  /export [file]  write a Markdown transcript with sources\n"
+                "  /exit           save and quit"
+            )
+            continue
+        if question == "/id":
+            console.print(f"  Session ID: [bold]{session.id}[/bold]")
+            continue
+        if question.split(maxsplit=1)[0] == "/export":
+            parts = question.split(maxsplit=1)
+            _export_transcript(session, parts[1] if len(parts) > 1 else None)
+            continue
+
+        try:
+            answer, context = query_with_context(
+                question, history=session.history(history_turns)
+            )
+        except Exception as e:
+            console.print(f"\n[bold red]❌ Error:[/bold red] {e}")
+            continue
+
+        console.print()
+        console.print(Panel(answer, title="📝 Answer", border_style="green"))
+        console.print()
+
+        session.add_turn(question, answer, context)
+        save_session(session)
+
+    if export_path:
+        _export_transcript(session, export_path)
+    console.print(f"Session saved. Resume with: rusty-rag chat --resume {session.id}")
+
+
+@main.group()
+def sessions():
+    """Manage saved chat sessions."""
+
+
+@sessions.command("list")
+def sessions_list():
+    """List saved chat sessions, most recent first."""
+    from .sessions import list_sessions
+
+    saved = list_sessions()
+    if not saved:
+        console.print("No saved sessions.")
+        return
+
+    for s in saved:
+        console.print(
+            f"  [bold]{s.id}[/bold]  {s.updated_at}  "
+            f"{len(s.turns):>3} turns  [dim]{s.title}[/dim]"
+        )
+
+
+@sessions.command("export")
+@click.argument("session_id")
+@click.option(
+    "-o",
+    "--output",
+    default=None,
+    type=click.Path(dir_okay=False),
+    help="Output file (default: ./chat-<id>.md).",
+)
+def sessions_export(session_id: str, output: str | None):
+    """Export a saved session as Markdown with a sources appendix."""
+    from .sessions import load_session
+
+    try:
+        _export_transcript(load_session(session_id), output)
+    except Exception as e:
+        console.print(f"\n[bold red]❌ Error:[/bold red] {e}")
+        raise SystemExit(1)
+
+
+@main.command()
+@click.argument("source")
+def summarize(source: str):
+    """Summarize a whole ingested document.
+
+    SOURCE is the file path (or --source name) used at ingest time.
+    Runs a map-reduce summarization over all of its chunks.
+    """
+    from .summarize import summarize as do_summarize
+
+    try:
+        summary = do_summarize(source)
+        console.print()
+        console.print(Panel(summary, title="📄 Summary", border_style="green"))
+        console.print()
+    except Exception as e:
+        console.print(f"\n[bold red]❌ Error:[/bold red] {e}")
+        raise SystemExit(1)
+
+
+@main.command()
+@click.option("-n", "count", default=5, show_default=True, help="Number of questions.")
+def suggest(count: int):
+    """Suggest questions the knowledge base can answer well.
+
+    Samples representative chunks and asks the LLM to propose questions —
+    a quick way to get oriented in an unfamiliar corpus.
+    """
+    from .rag import suggest_questions
+
+    try:
+        questions = suggest_questions(count)
+        body = "\n".join(f"{i + 1}. {q}" for i, q in enumerate(questions))
+        console.print()
+        console.print(
+            Panel(body or "(no suggestions)", title="💡 Try asking", border_style="cyan")
+        )
+        console.print()
+    except Exception as e:
+        console.print(f"\n[bold red]❌ Error:[/bold red] {e}")
+        raise SystemExit(1)
+
+
+@main.command()
+def doctor():
+    """Diagnose the local setup (Ollama, models, Qdrant, disk space).
+
+    Prints a fix-it suggestion for every failing check and exits non-zero
+    if any check fails.
+    """
+    from .doctor import run_checks
+
+    checks = run_checks()
+    console.print()
+    for check in checks:
+        mark = "[green]✓[/green]" if check.ok else "[bold red]✗[/bold red]"
+        console.print(f"  {mark} [bold]{check.name}[/bold]  [dim]{check.detail}[/dim]")
+        if not check.ok:
+            console.print(f"      → {check.fix}")
+    console.print()
+
+    failed = sum(not c.ok for c in checks)
+    if failed:
+        console.print(f"[bold red]{failed} check(s) failed.[/bold red]")
+        raise SystemExit(1)
+    console.print("[bold green]All checks passed.[/bold green]")
+
+
+@main.command()
+def profiles():
+    """List the profiles defined in the config file."""
+    from .config import find_config_file, list_profiles
+
+    path = find_config_file()
+    if path is None:
+        console.print("No config file found.")
+        return
+
+    names = list_profiles()
+    console.print(f"Config: [bold]{path}[/bold]")
+    if not names:
+        console.print("  (no profiles defined)")
+    for name in names:
+        console.print(f"  • {name}")
+
+
 if __name__ == "__main__":
     main()
@@ -0,0 +1,96 @@
+"""Config file loading with named profiles.
+
+Settings live in a TOML file whose keys mirror the environment variables
+used throughout the pipeline (case-insensitive):
+
+    embedding_model = "all-minilm"
+    completion_model = "llama3.2"
+
+    [profile.fast]
+    completion_model = "llama3.2:1b"
+    chunk_max_tokens = 128
+    search_top_k = 5
+
+    [profile.quality]
+    completion_model = "llama3.1:8b"
+    chunk_max_tokens = 384
+    context_top_k = 5
+
+Top-level keys act as defaults and never override variables that are
+already set (e.g. from the shell or `.env`). Keys of the selected profile
+always win, since picking a profile is an explicit choice.
+"""
+
+import os
+import sys
+from pathlib import Path
+
+if sys.version_info >= (3, 11):
+    import tomllib
+else:
+    import tomli as tomllib
+
+# Raised by `load_config` (and so `apply_config`) for a malformed file
+TOMLDecodeError = tomllib.TOMLDecodeError
+
+CONFIG_FILENAME = "rusty_rag.toml"
+USER_CONFIG = Path.home() / ".rusty_rag" / "config.toml"
+
+
+def find_config_file() -> Path | None:
+    """Locate the config file.
+
+    Search order: $RUSTY_RAG_CONFIG, ./rusty_rag.toml, ~/.rusty_rag/config.toml.
+    """
+    explicit = os.getenv("RUSTY_RAG_CONFIG")
+    if explicit:
+        path = Path(explicit)
+        if not path.exists():
+            raise FileNotFoundError(f"Config file not found: {explicit}")
+        return path
+
+    for candidate in (Path.cwd() / CONFIG_FILENAME, USER_CONFIG):
+        if candidate.exists():
+            return candidate
+    return None
+
+
+def load_config(path: Path | None = None) -> dict:
+    """Parse the config file, returning an empty dict if there is none."""
+    path = path or find_config_file()
+    if path is None:
+        return {}
+    with open(path, "rb") as f:
+        return tomllib.load(f)
+
+
+def list_profiles(config: dict | None = None) -> list[str]:
+    """Return the names of all profiles defined in the config file."""
+    config = load_config() if config is None else config
+    return sorted(config.get("profile", {}))
+
+
+def apply_config(profile: str | None = None, config: dict | None = None) -> None:
+    """Export config file settings (and the chosen profile) as env vars.
+
+    Raises KeyError if the requested profile is not defined.
+    """
+    config = load_config() if config is None else config
+    profile = profile or os.getenv("RUSTY_RAG_PROFILE")
+
+    for key, value in config.items():
+        if isinstance(value, dict):
+            continue
+        os.environ.setdefault(key.upper(), str(value))
+
+    if not profile:
+        return
+
+    profiles = config.get("profile", {})
+    if profile not in profiles:
+        available = ", ".join(sorted(profiles)) or "none defined"
+        raise KeyError(f"Unknown profile '{profile}' (available: {available})")
+
+    for key, value in profiles[profile].items():
+        os.environ[key.upper()] = str(value)
+    os.environ["RUSTY_RAG_PROFILE"] = profile
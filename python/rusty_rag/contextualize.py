@@ -0,0 +1,80 @@
+"""Contextual chunk enrichment with the local LLM.
+
+A chunk cut from the middle of a document often lacks what it is about
+("Revenue grew 3% over the previous quarter" — whose revenue, which
+quarter?). Before embedding, the LLM writes a short sentence situating each
+chunk in its document; the sentence is embedded and keyword-indexed with
+the chunk, while answers still quote the chunk's own text.
+
+The LLM can also summarize each chunk in one line, stored with it so
+citations can say what a source passage covers.
+"""
+
+import os
+
+from .llm import complete
+
+CONTEXT_SYSTEM = (
+    "You situate passages within the document they come from, to improve "
+    "search retrieval of the passage. Given a document and a passage from "
+    "it, reply with one short sentence saying what the passage is about and "
+    "where it sits in the document (which part, topic, entity, or period). "
+    "Reply with the sentence only, without preamble."
+)
+
+SUMMARY_SYSTEM = (
+    "You summarize passages for a list of sources. Reply with one line of "
+    "at most 20 words saying what the passage covers, without preamble."
+)
+
+
+def contextualize_enabled() -> bool:
+    """Whether ingestion enriches chunks with context (CONTEXTUALIZE_CHUNKS,
+    default false). It costs one LLM call per chunk."""
+    return os.getenv("CONTEXTUALIZE_CHUNKS", "false").lower() == "true"
+
+
+def context_document(pages: list[str]) -> str:
+    """The document text shown to the LLM with each chunk: its pages, cut
+    to the first CONTEXTUALIZE_DOCUMENT_CHARS characters (default 6000) so
+    the prompt fits a small local model's context window."""
+    max_chars = int(os.getenv("CONTEXTUALIZE_DOCUMENT_CHARS", "6000"))
+    document = "\n\n".join(pages)
+    if len(document) > max_chars:
+        document = document[:max_chars] + "\n[...]"
+    return document
+
+
+def chunk_context(document: str, chunk: str, model: str | None = None) -> str:
+    """One sentence situating `chunk` within `document`, by the completion
+    model (CONTEXTUALIZE_MODEL, default COMPLETION_MODEL)."""
+    prompt = (
+        f"<document>\n{document}\n</document>\n\n"
+        f"<passage>\n{chunk}\n</passage>\n\n"
+        "Situate the passage within the document."
+    )
+    model = model or os.getenv("CONTEXTUALIZE_MODEL") or None
+    response = complete(prompt, system=CONTEXT_SYSTEM, model=model)
+    return " ".join(response.split())
+
+
+def summaries_enabled() -> bool:
+    """Whether ingestion stores a one-line summary of each chunk
+    (CHUNK_SUMMARIES, default false). It costs one LLM call per chunk."""
+    return os.getenv("CHUNK_SUMMARIES", "false").lower() == "true"
+
+
+def chunk_summary(chunk: str, model: str | None = None) -> str:
+    """A one-line summary of `chunk`, by the completion model
+    (CONTEXTUALIZE_MODEL, default COMPLETION_MODEL)."""
+    model = model or os.getenv("CONTEXTUALIZE_MODEL") or None
+    response = complete(
+        f"<passage>\n{chunk}\n</passage>", system=SUMMARY_SYSTEM, model=model
+    )
+    return " ".join(response.split())
+
+
+def enriched_text(chunk: str, context: str | None) -> str:
+    """The text a chunk is embedded and keyword-indexed as: its context
+    sentence, if any, followed by the chunk."""
+    return f"{context}\n\n{chunk}" if context else chunk
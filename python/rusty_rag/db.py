@@ -2,13 +2,68 @@
 
 import os
 import uuid
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
 
 from qdrant_client import QdrantClient
-from qdrant_client.models import Distance, PointStruct, VectorParams
+from qdrant_client.models import (
+    Distance,
+    FieldCondition,
+    Filter,
+    MatchAny,
+    MatchValue,
+    PointStruct,
+    VectorParams,
+)
 
 VECTOR_SIZE = 384  # Dimension for all-minilm embeddings
 
 
+def ingest_timestamp() -> str:
+    """The current UTC time, as stored in a chunk's `ingested_at`."""
+    return datetime.now(timezone.utc).isoformat(timespec="seconds")
+
+
+@dataclass
+class ChunkPayload:
+    """Where a chunk came from, stored with it so search results can be
+    attributed to (and filtered by) their document, page, and ingestion.
+
+    `content_hash` and `page_hash` are the whitespace-insensitive hashes of
+    the chunk's document and page (see `content_hashes`). `fields` holds any
+    further fields for the chunk (keywords, parent link, ...); they can't
+    shadow the ones above, nor the document's metadata.
+    """
+
+    source: str | None = None
+    page: int | None = None
+    chunk_index: int = 0
+    section: str | None = None
+    ingested_at: str | None = None
+    content_hash: str | None = None
+    page_hash: str | None = None
+    fields: dict = field(default_factory=dict)
+
+    def to_dict(self, text: str, metadata: dict | None = None) -> dict:
+        """The Qdrant payload of the chunk `text` of a document with
+        `metadata` (title, author, ...), leaving out empty values."""
+        own = {
+            "text": text,
+            "source": self.source,
+            "page": self.page,
+            "chunk_index": self.chunk_index,
+            "section": self.section or None,
+            "ingested_at": self.ingested_at,
+            "content_hash": self.content_hash,
+            "page_hash": self.page_hash,
+        }
+        payload: dict = {}
+        # Later layers win, but an empty value never hides a set one
+        for layer in (self.fields, metadata or {}, own):
+            payload.update((k, v) for k, v in layer.items() if v is not None)
+        return payload
+
+
 def create_client(url: str | None = None) -> QdrantClient:
     """Create a Qdrant client connected to the configured URL."""
     url = url or os.getenv("QDRANT_URL", "http://localhost:6333")
@@ -42,40 +97,105 @@ def upsert_chunks(
     chunks: list[str],
     vectors: list[list[float]],
     collection: str | None = None,
+    payloads: list[ChunkPayload] | None = None,
+    metadata: dict | None = None,
 ) -> None:
-    """Upsert text chunks with their embedding vectors into Qdrant."""
+    """Upsert text chunks with their embedding vectors into Qdrant.
+
+    `payloads` gives each chunk's origin (see `ChunkPayload`); without
+    them, chunks only record their position in the list. Document
+    `metadata` (title, author, ...) is copied into every payload, skipping
+    empty values.
+    """
     collection = collection or get_collection_name()
+    payloads = payloads or [ChunkPayload(chunk_index=i) for i in range(len(chunks))]
 
     points = [
         PointStruct(
             id=str(uuid.uuid4()),
             vector=vector,
-            payload={"text": chunk},
+            payload=payload.to_dict(chunk, metadata),
         )
-        for chunk, vector in zip(chunks, vectors)
+        for chunk, vector, payload in zip(chunks, vectors, payloads)
     ]
 
     client.upsert(collection_name=collection, points=points)
 
 
-def search(
+def search_points(
     client: QdrantClient,
     query_vector: list[float],
     top_k: int = 3,
     min_score: float = 0.3,
     collection: str | None = None,
-) -> list[tuple[str, float]]:
-    """Search for the most similar chunks to the query vector.
+    keywords: list[str] | None = None,
+) -> list[tuple[dict, float]]:
+    """Search for the most similar chunks, returning full payloads.
 
-    Returns (text, score) pairs filtered by minimum relevance score.
+    With `keywords`, only chunks tagged with at least one of them are
+    searched. Returns (payload, score) pairs filtered by minimum relevance
+    score.
     """
     collection = collection or get_collection_name()
+    keyword_filter = None
+    if keywords:
+        wanted = [keyword.lower() for keyword in keywords]
+        keyword_filter = Filter(
+            must=[FieldCondition(key="keywords", match=MatchAny(any=wanted))]
+        )
 
     results = client.search(
         collection_name=collection,
         query_vector=query_vector,
         limit=top_k,
         score_threshold=min_score,
+        query_filter=keyword_filter,
+    )
+
+    return [(point.payload, point.score) for point in results]
+
+
+def search(
+    client: QdrantClient,
+    query_vector: list[float],
+    top_k: int = 3,
+    min_score: float = 0.3,
+    collection: str | None = None,
+) -> list[tuple[str, float]]:
+    """Search for the most similar chunks to the query vector.
+
+    Returns (text, score) pairs filtered by minimum relevance score.
+    """
+    points = search_points(client, query_vector, top_k, min_score, collection)
+    return [(payload["text"], score) for payload, score in points]
+
+
+def get_source_chunks(
+    client: QdrantClient,
+    source: str,
+    collection: str | None = None,
+    batch_size: int = 256,
+) -> list[str]:
+    """Fetch every chunk stored for `source`, in document order."""
+    collection = collection or get_collection_name()
+    source_filter = Filter(
+        must=[FieldCondition(key="source", match=MatchValue(value=source))]
     )
 
-    return [(point.payload["text"], point.score) for point in results]
+    payloads = []
+    offset = None
+    while True:
+        points, offset = client.scroll(
+            collection_name=collection,
+            scroll_filter=source_filter,
+            limit=batch_size,
+            offset=offset,
+            with_payload=True,
+            with_vectors=False,
+        )
+        payloads.extend(point.payload for point in points)
+        if offset is None:
+            break
+
+    payloads.sort(key=lambda p: p.get("chunk_index", 0))
+    return [p["text"] for p in payloads]
@@ -0,0 +1,168 @@
+"""Environment diagnostics for `rusty-rag doctor`.
+
+Each check returns a Check with a fix-it hint, so a failing setup can be
+repaired without digging through stack traces.
+"""
+
+import json
+import os
+import shutil
+import urllib.request
+from dataclasses import dataclass
+from pathlib import Path
+
+from .db import VECTOR_SIZE, create_client, get_collection_name
+
+DATA_DIR = Path.home() / ".rusty_rag"
+MIN_FREE_BYTES = 1024**3  # 1 GiB
+
+
+@dataclass
+class Check:
+    name: str
+    ok: bool
+    detail: str
+    fix: str = ""
+
+
+def _model_names(listing) -> set[str]:
+    """Pull model names out of `ollama.list()` across client versions."""
+    models = listing["models"] if isinstance(listing, dict) else listing.models
+    names = set()
+    for m in models:
+        if isinstance(m, dict):
+            name = m.get("model") or m.get("name")
+        else:
+            name = m.model
+        if name:
+            names.add(name)
+            if name.endswith(":latest"):
+                names.add(name.removesuffix(":latest"))
+    return names
+
+
+def check_ollama() -> tuple[list[Check], int | None]:
+    """Check Ollama, the configured models, and the embedding dimension."""
+    import ollama
+
+    host = os.getenv("OLLAMA_HOST", "http://localhost:11434")
+    try:
+        names = _model_names(ollama.list())
+    except Exception as e:
+        return [
+            Check(
+                "Ollama reachable",
+                False,
+                f"{host}: {e}",
+                "Start Ollama with `ollama serve`, or set OLLAMA_HOST.",
+            )
+        ], None
+
+    checks = [Check("Ollama reachable", True, host)]
+    models = [
+        ("Embedding model", "EMBEDDING_MODEL", "all-minilm"),
+        ("Completion model", "COMPLETION_MODEL", "llama3.2"),
+    ]
+    from .captions import captions_enabled
+
+    if captions_enabled():
+        models.append(("Caption model", "CAPTION_MODEL", "llava"))
+    for label, var, default in models:
+        model = os.getenv(var, default)
+        if model in names:
+            checks.append(Check(f"{label} pulled", True, model))
+        else:
+            checks.append(
+                Check(
+                    f"{label} pulled",
+                    False,
+                    f"'{model}' not found locally",
+                    f"Run `ollama pull {model}`, or set {var} to an installed model.",
+                )
+            )
+
+    if not checks[1].ok:
+        return checks, None
+
+    from .embeddings import embed_query
+
+    try:
+        return checks, len(embed_query("dimension check"))
+    except Exception as e:
+        checks.append(
+            Check(
+                "Embedding works",
+                False,
+                str(e),
+                "Make sure EMBEDDING_MODEL is an embedding model (e.g. all-minilm).",
+            )
+        )
+        return checks, None
+
+
+def check_qdrant(embedding_dim: int | None) -> list[Check]:
+    """Check Qdrant reachability, version, and the collection's dimension."""
+    url = os.getenv("QDRANT_URL", "http://localhost:6333")
+    try:
+        with urllib.request.urlopen(url, timeout=5) as resp:
+            version = json.load(resp).get("version", "unknown")
+    except Exception as e:
+        return [
+            Check(
+                "Qdrant reachable",
+                False,
+                f"{url}: {e}",
+                "Start Qdrant with `docker compose up -d`, or set QDRANT_URL.",
+            )
+        ]
+
+    checks = [Check("Qdrant reachable", True, f"{url} (version {version})")]
+    if embedding_dim is None:
+        return checks
+
+    name = get_collection_name()
+    client = create_client(url)
+    existing = [c.name for c in client.get_collections().collections]
+    if name in existing:
+        vectors = client.get_collection(name).config.params.vectors
+        size = vectors.size if hasattr(vectors, "size") else None
+        where = f"collection '{name}'"
+    else:
+        # New collections are created with VECTOR_SIZE on first ingest
+        size = VECTOR_SIZE
+        where = f"new collections (collection '{name}' not created yet)"
+
+    if size == embedding_dim:
+        checks.append(Check("Vector dimension", True, f"{size} for {where}"))
+    else:
+        checks.append(
+            Check(
+                "Vector dimension",
+                False,
+                f"{where} uses {size}, embedding model produces {embedding_dim}",
+                "Set EMBEDDING_MODEL to the model the collection was built with, "
+                "or set COLLECTION_NAME to a new collection and re-ingest.",
+            )
+        )
+    return checks
+
+
+def check_disk() -> Check:
+    """Check free space where local caches and sessions are stored."""
+    path = DATA_DIR if DATA_DIR.exists() else Path.home()
+    free = shutil.disk_usage(path).free
+    detail = f"{free / 1024**3:.1f} GiB free at {path}"
+    if free >= MIN_FREE_BYTES:
+        return Check("Disk space", True, detail)
+    return Check(
+        "Disk space",
+        False,
+        detail,
+        "Free up at least 1 GiB; Qdrant and the chunk cache grow with each ingest.",
+    )
+
+
+def run_checks() -> list[Check]:
+    """Run every diagnostic in order."""
+    ollama_checks, dim = check_ollama()
+    return [*ollama_checks, *check_qdrant(dim), check_disk()]
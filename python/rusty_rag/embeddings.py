@@ -1,8 +1,14 @@
 """Ollama embedding generation."""
 
+import functools
 import os
 import ollama
 
+# Rough characters and words per model token in English text, to compare
+# character- and word-based chunk sizes with a model's token limit
+CHARS_PER_TOKEN = 4
+WORDS_PER_TOKEN = 0.75
+
 
 def embed_texts(texts: list[str], model: str | None = None) -> list[list[float]]:
     """Generate embedding vectors for a batch of text chunks.
@@ -19,3 +25,32 @@ def embed_query(query: str, model: str | None = None) -> list[float]:
     model = model or os.getenv("EMBEDDING_MODEL", "all-minilm")
     response = ollama.embed(model=model, input=query)
     return response["embeddings"][0]
+
+
+@functools.cache
+def embedding_context_length(model: str | None = None) -> int | None:
+    """The most tokens the embedding model reads per input (Ollama truncates
+    longer inputs), or None if Ollama can't tell.
+
+    A `num_ctx` parameter set on the model wins over the context length of
+    its architecture.
+    """
+    model = model or os.getenv("EMBEDDING_MODEL", "all-minilm")
+    try:
+        info = ollama.show(model)
+    except Exception:
+        return None
+    if not isinstance(info, dict):
+        info = {
+            "parameters": getattr(info, "parameters", None),
+            "model_info": getattr(info, "modelinfo", None),
+        }
+    for line in (info.get("parameters") or "").splitlines():
+        name, _, value = line.partition(" ")
+        if name == "num_ctx" and value.strip().isdigit():
+            return int(value)
+    model_info = info.get("model_info") or info.get("modelinfo") or {}
+    for key, value in model_info.items():
+        if key.endswith(".context_length") and isinstance(value, int):
+            return value
+    return None
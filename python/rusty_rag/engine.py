@@ -0,0 +1,115 @@
+"""Programmatic API for embedding RustyRAG in Python apps and notebooks.
+
+    from rusty_rag.engine import RagEngine
+
+    engine = RagEngine()
+    await engine.ingest_async("paper.pdf", on_progress=print)
+    print(engine.query("What datasets were used?"))
+
+The engine wraps the same pipeline as the CLI; configuration still comes
+from environment variables and the config file.
+"""
+
+import asyncio
+
+from . import rag
+from .rag import ProgressCallback
+
+
+class RagEngine:
+    """Ingest documents and query the knowledge base from Python."""
+
+    def __init__(self, profile: str | None = None):
+        from .config import apply_config
+
+        apply_config(profile)
+
+    def ingest(
+        self,
+        path: str,
+        source: str | None = None,
+        on_progress: ProgressCallback | None = None,
+        password: str | None = None,
+        captions: bool | None = None,
+    ) -> None:
+        """Ingest a document, blocking until it is stored.
+
+        `on_progress(stage, completed, total)` is called from this thread.
+        """
+        rag.ingest(
+            path,
+            source=source,
+            on_progress=on_progress,
+            password=password,
+            captions=captions,
+        )
+
+    async def ingest_async(
+        self,
+        path: str,
+        source: str | None = None,
+        on_progress: ProgressCallback | None = None,
+        password: str | None = None,
+        captions: bool | None = None,
+    ) -> None:
+        """Ingest a document in a worker thread without blocking the event loop.
+
+        `on_progress(stage, completed, total)` is scheduled on the event
+        loop's thread, so it can update GUI widgets or notebook progress
+        bars directly.
+        """
+        loop = asyncio.get_running_loop()
+        callback = None
+        if on_progress is not None:
+
+            def callback(stage: str, done: int, total: int) -> None:
+                loop.call_soon_threadsafe(on_progress, stage, done, total)
+
+        await asyncio.to_thread(rag.ingest, path, source, callback, password, captions)
+
+    def ingest_files(
+        self,
+        paths: list[str],
+        on_progress: ProgressCallback | None = None,
+        password: str | None = None,
+        captions: bool | None = None,
+        workers: int | None = None,
+    ) -> list[tuple[str, Exception]]:
+        """Ingest several documents (or directories of them), extracting
+        them in parallel on up to `workers` threads.
+
+        Returns the (path, error) pairs of documents that failed.
+        """
+        return rag.ingest_files(
+            rag.collect_documents(paths),
+            on_progress=on_progress,
+            password=password,
+            captions=captions,
+            workers=workers,
+        )
+
+    def ingest_text(
+        self,
+        text: str,
+        source: str,
+        on_progress: ProgressCallback | None = None,
+    ) -> None:
+        """Ingest raw text under the given source name."""
+        rag.ingest_text(text, source, on_progress=on_progress)
+
+    def retrieve(
+        self,
+        question: str,
+        weights: dict[str, float] | None = None,
+        keywords: list[str] | None = None,
+    ) -> list[dict]:
+        """Return the hits hybrid search finds for a question.
+
+        `weights` (e.g. {"bm25": 0.5}) overrides the fusion blend, and
+        `keywords` limits the search to chunks tagged with any of them.
+        """
+        return rag.retrieve(question, weights=weights, keywords=keywords)
+
+    def query(self, question: str, weights: dict[str, float] | None = None) -> str:
+        """Answer a question from the knowledge base."""
+        return rag.query(question, weights=weights)
@@ -0,0 +1,89 @@
+"""Export chat sessions as Markdown transcripts with cited sources."""
+
+from pathlib import Path
+
+from .sessions import Session
+from .storage import get_passphrase, write_private
+
+EXCERPT_CHARS = 500
+
+
+def _citation_key(hit: dict) -> tuple:
+    return (hit.get("source"), hit.get("page"), hit["text"])
+
+
+def session_to_markdown(session: Session) -> str:
+    """Render a session as Markdown: the conversation, then a sources appendix.
+
+    Every retrieved chunk gets a numbered citation that the turns refer to,
+    and the appendix lists each chunk's file, page and section (when known),
+    summary (when stored), and text.
+    """
+    citations: dict[tuple, int] = {}
+    cited: list[dict] = []
+
+    lines = [
+        f"# Chat transcript: {session.title or session.id}",
+        "",
+        f"- Session: `{session.id}`",
+        f"- Started: {session.created_at}",
+        f"- Last updated: {session.updated_at}",
+        "",
+    ]
+
+    for i, turn in enumerate(session.turns, 1):
+        refs = []
+        for hit in turn.context:
+            key = _citation_key(hit)
+            if key not in citations:
+                citations[key] = len(cited) + 1
+                cited.append(hit)
+            refs.append(f"[{citations[key]}]")
+
+        lines += [
+            f"## Q{i}: {turn.question}",
+            "",
+            f"*{turn.timestamp}*",
+            "",
+            turn.answer.strip(),
+            "",
+        ]
+        if refs:
+            lines += [f"**Sources:** {' '.join(refs)}", ""]
+
+    if cited:
+        lines += ["---", "", "## Appendix: Cited chunks", ""]
+        for n, hit in enumerate(cited, 1):
+            location = hit.get("source") or "unknown source"
+            if hit.get("title"):
+                location = f"{hit['title']} ({location})"
+            if hit.get("page") is not None:
+                location += f", page {hit['page']}"
+            if hit.get("section"):
+                location += f", {hit['section']}"
+            text = hit["text"].strip()
+            if len(text) > EXCERPT_CHARS:
+                text = text[:EXCERPT_CHARS].rstrip() + " …"
+            quoted = "\n".join(f"> {line}" for line in text.splitlines())
+            lines += [f"**[{n}]** {location} (score {hit['score']:.3f})", ""]
+            if hit.get("summary"):
+                lines += [f"*{hit['summary']}*", ""]
+            lines += [quoted, ""]
+
+    return "\n".join(lines)
+
+
+def export_session(session: Session, path: Path | None = None) -> Path:
+    """Write the session's Markdown transcript to `path`.
+
+    Defaults to ./chat-<id>.md. The file is encrypted like other local
+    data when a passphrase is configured.
+    """
+    path = path or Path(f"chat-{session.id}.md")
+    write_private(path, session_to_markdown(session).encode("utf-8"))
+    return path
+
+
+def export_is_encrypted() -> bool:
+    """Whether exports will be written encrypted."""
+    return get_passphrase() is not None
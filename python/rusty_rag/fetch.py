@@ -0,0 +1,93 @@
+"""Fetching remote documents for ingestion.
+
+A URL's content is downloaded to a local file named so the usual
+extension-based extractor dispatch applies: an HTML page becomes a .html
+file, a remote PDF a .pdf, and so on.
+"""
+
+import tempfile
+import urllib.error
+import urllib.request
+from collections.abc import Iterator
+from contextlib import contextmanager
+from pathlib import Path, PurePosixPath
+from urllib.parse import urlparse
+
+FETCH_TIMEOUT = 30
+# Refuse responses larger than this rather than filling memory
+MAX_FETCH_BYTES = 200 * 1024 * 1024
+USER_AGENT = "rusty-rag (+https://github.com/Dev-Sinha13/Local-RAG-engine)"
+
+# Extension to save a response under, by its Content-Type
+CONTENT_TYPE_SUFFIXES = {
+    "text/html": ".html",
+    "application/xhtml+xml": ".html",
+    "application/pdf": ".pdf",
+    "application/epub+zip": ".epub",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document": ".docx",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet": ".xlsx",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation": ".pptx",
+    "message/rfc822": ".eml",
+    "application/mbox": ".mbox",
+    "application/json": ".json",
+    "application/x-ndjson": ".jsonl",
+    "application/jsonl": ".jsonl",
+    "text/csv": ".csv",
+    "text/tab-separated-values": ".tsv",
+    "text/markdown": ".md",
+    "text/vtt": ".vtt",
+    "application/x-subrip": ".srt",
+    "application/x-tex": ".tex",
+    "text/x-tex": ".tex",
+    "text/plain": ".txt",
+}
+
+
+def is_url(path: str) -> bool:
+    """Whether `path` is an HTTP(S) URL rather than a local file."""
+    return urlparse(path).scheme.lower() in ("http", "https")
+
+
+def download(url: str, directory: str | Path) -> Path:
+    """Download `url` into `directory` and return the saved file's path.
+
+    The file's extension comes from the response's Content-Type, falling
+    back to the extension in the (final, post-redirect) URL for generic
+    types such as application/octet-stream. Without either, the file has no
+    extension and is ingested as plain text.
+    """
+    request = urllib.request.Request(url, headers={"User-Agent": USER_AGENT})
+    try:
+        with urllib.request.urlopen(request, timeout=FETCH_TIMEOUT) as resp:
+            content_type = resp.headers.get_content_type()
+            final_url = resp.geturl()
+            data = resp.read(MAX_FETCH_BYTES + 1)
+    except urllib.error.HTTPError as e:
+        raise ValueError(f"Failed to fetch {url}: HTTP {e.code} {e.reason}") from e
+    except (urllib.error.URLError, OSError) as e:
+        reason = getattr(e, "reason", e)
+        raise ValueError(f"Failed to fetch {url}: {reason}") from e
+
+    if len(data) > MAX_FETCH_BYTES:
+        raise ValueError(
+            f"Refusing to ingest {url}: larger than {MAX_FETCH_BYTES // (1024 * 1024)} MB"
+        )
+
+    url_path = PurePosixPath(urlparse(final_url).path)
+    suffix = CONTENT_TYPE_SUFFIXES.get(content_type) or url_path.suffix.lower()
+    path = Path(directory) / f"{url_path.stem or 'index'}{suffix}"
+    path.write_bytes(data)
+    return path
+
+
+@contextmanager
+def local_file(path: str) -> Iterator[str]:
+    """Yield a local path for `path`, downloading it first if it is a URL.
+
+    Downloads are deleted when the block exits.
+    """
+    if not is_url(path):
+        yield path
+        return
+    with tempfile.TemporaryDirectory() as tmp:
+        yield str(download(path, tmp))
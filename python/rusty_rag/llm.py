@@ -4,11 +4,17 @@ import os
 import ollama
 
 
-def ask(question: str, context: str = "", model: str | None = None) -> str:
+def ask(
+    question: str,
+    context: str = "",
+    model: str | None = None,
+    history: list[dict] | None = None,
+) -> str:
     """Send a prompt to the local LLM with optional RAG context.
 
     If context is provided, the model is instructed to only answer
     based on the given context. Otherwise, it acts as a general assistant.
+    `history` holds earlier conversation turns as chat messages.
     """
     model = model or os.getenv("COMPLETION_MODEL", "llama3.2")
 
@@ -27,8 +33,24 @@ def ask(question: str, context: str = "", model: str | None = None) -> str:
         model=model,
         messages=[
             {"role": "system", "content": system},
+            *(history or []),
             {"role": "user", "content": question},
         ],
     )
 
     return response["message"]["content"]
+
+
+def complete(prompt: str, system: str, model: str | None = None) -> str:
+    """Run a single-turn completion with an explicit system prompt."""
+    model = model or os.getenv("COMPLETION_MODEL", "llama3.2")
+
+    response = ollama.chat(
+        model=model,
+        messages=[
+            {"role": "system", "content": system},
+            {"role": "user", "content": prompt},
+        ],
+    )
+
+    return response["message"]["content"]
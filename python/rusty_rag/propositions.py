@@ -0,0 +1,52 @@
+"""Proposition-based chunking with the local LLM (experimental).
+
+A chunk of a few hundred tokens mixes many facts, so its embedding matches
+any one of them only loosely. The LLM decomposes each chunk into
+propositions: short, standalone statements of one fact each, with pronouns
+and references resolved ("The index is rebuilt nightly" rather than "It is
+rebuilt nightly"). Each proposition is embedded on its own and linked to its
+chunk like a child chunk, so fact lookups match precisely while answers
+still see the whole chunk.
+"""
+
+import os
+
+from .llm import complete
+
+PROPOSITION_SYSTEM = (
+    "You decompose passages into propositions for a search index. A "
+    "proposition is a short, standalone sentence stating one fact from the "
+    "passage. Split compound sentences, replace pronouns and references with "
+    "the names they refer to, and add the context needed to understand each "
+    "proposition on its own. Don't add facts the passage doesn't state. "
+    "Reply with one proposition per line, without preamble."
+)
+
+
+def propositions_enabled() -> bool:
+    """Whether ingestion embeds chunks as propositions (CHUNK_PROPOSITIONS,
+    default false). It costs one LLM call per chunk."""
+    return os.getenv("CHUNK_PROPOSITIONS", "false").lower() == "true"
+
+
+def chunk_propositions(chunk: str, model: str | None = None) -> list[str]:
+    """The propositions of `chunk`, by the completion model
+    (PROPOSITION_MODEL, default COMPLETION_MODEL).
+
+    A chunk the model finds no propositions in is its own proposition.
+    """
+    model = model or os.getenv("PROPOSITION_MODEL") or None
+    response = complete(
+        f"<passage>\n{chunk}\n</passage>", system=PROPOSITION_SYSTEM, model=model
+    )
+
+    propositions = []
+    for line in response.splitlines():
+        line = line.strip().lstrip("-*•").strip()
+        # Drop list numbering like "1." or "2)"
+        head, _, rest = line.partition(" ")
+        if head.rstrip(".)").isdigit():
+            line = rest.strip()
+        if line:
+            propositions.append(line)
+    return propositions or [chunk]
@@ -4,67 +4,1028 @@ Uses hybrid retrieval (vector similarity + BM25 keyword matching) for
 higher quality results than either method alone.
 """
 
+import functools
+import hashlib
 import json
 import os
+import uuid
+from collections import Counter
+from collections.abc import Callable
+from concurrent.futures import ThreadPoolExecutor, as_completed
 from pathlib import Path
 
 from rich.console import Console
 
-from . import extract_pdf_text, chunk_by_tokens, BM25Index
-from .embeddings import embed_texts, embed_query
-from .llm import ask
-from .db import create_client, init_collection, upsert_chunks, search
+from . import (
+    extract_pdf_sections,
+    extract_pdf_metadata,
+    extract_pdf_form_fields,
+    extract_pdf_images,
+    pdf_text_coverage,
+    extract_epub,
+    extract_pptx,
+    extract_email,
+    extract_subtitles,
+    extract_document,
+    document_formats,
+    read_text_file,
+    markdown_sections,
+    chunk_by_tokens,
+    chunk_by_tokens_parallel,
+    chunk_by_model_tokens,
+    chunk_by_sentences,
+    chunk_recursive,
+    chunk_code,
+    chunk_markdown,
+    markdown_tables,
+    chunk_markdown_table,
+    split_sentences,
+    chunk_semantic,
+    token_count,
+    detect_language,
+    content_hashes,
+    near_duplicates,
+    garbage_chunks,
+    chunk_keywords,
+    BM25Index,
+    SpellCorrector,
+    rrf_fuse,
+)
+from .embeddings import (
+    CHARS_PER_TOKEN,
+    WORDS_PER_TOKEN,
+    embed_query,
+    embed_texts,
+    embedding_context_length,
+)
+from .llm import ask, complete
+from .rerank import get_reranker, reranker_enabled
+from .captions import caption_image, caption_model, captions_enabled
+from .propositions import chunk_propositions, propositions_enabled
+from .contextualize import (
+    chunk_context,
+    chunk_summary,
+    context_document,
+    contextualize_enabled,
+    enriched_text,
+    summaries_enabled,
+)
+from .fetch import CONTENT_TYPE_SUFFIXES, is_url, local_file
+from .records import DEFAULT_TEXT_FIELD, load_records, map_records
+from .db import (
+    ChunkPayload,
+    create_client,
+    ingest_timestamp,
+    init_collection,
+    search_points,
+    upsert_chunks,
+)
+from .retrieval_cache import SemanticRetrievalCache
+from .storage import read_private, write_private
 
 console = Console()
 
 # Local cache for BM25 index (chunks stored on disk between sessions)
 CACHE_DIR = Path.home() / ".rusty_rag"
 CHUNK_CACHE = CACHE_DIR / "chunks.json"
+# A hash of CHUNK_CACHE, rewritten with it, so what is built from the chunks
+# can tell it is stale without reading them
+CHUNK_CACHE_GENERATION = CACHE_DIR / "chunks.generation"
+# The BM25 index of the cached chunks, kept until they or the settings change
+BM25_INDEX_CACHE = CACHE_DIR / "bm25.index"
 
+# Formats read by the core's extractor registry (`extract_document`), by
+# file extension. Those without a case of their own in `_extract` (Word,
+# HTML, Markdown, spreadsheets, ...) are ingested as the registry splits
+# them: a span per markdown section, tagged with its heading path.
+CORE_SUFFIXES = frozenset(
+    suffix for format in document_formats() for suffix in format["extensions"]
+)
 
-def _load_chunk_cache() -> list[str]:
-    """Load cached chunks from disk for BM25 indexing."""
-    if CHUNK_CACHE.exists():
-        with open(CHUNK_CACHE, "r", encoding="utf-8") as f:
-            return json.load(f)
-    return []
+# Called as extract(path), returning (page_number, section, text) triples
+# like `extract_document`
+Extractor = Callable[[str], list[tuple[int | None, str | None, str]]]
 
+# Extractors added with `register_extractor`, by file extension
+_custom_extractors: dict[str, Extractor] = {}
 
-def _save_chunk_cache(chunks: list[str]) -> None:
-    """Append new chunks to the local cache."""
-    CACHE_DIR.mkdir(parents=True, exist_ok=True)
+# Formats with their own structure (pages, chapters, slides, messages,
+# records, timed transcripts), handled case by case in `_extract`
+SUBTITLE_SUFFIXES = (".srt", ".vtt")
+MARKDOWN_SUFFIXES = (".md", ".markdown")
+DOCUMENT_SUFFIXES = (".pdf", ".epub", ".pptx", ".eml", ".mbox", ".jsonl", *SUBTITLE_SUFFIXES)
+
+# Email headers prepended to each message's text, as (label, key) pairs
+EMAIL_HEADERS = (("Subject", "subject"), ("From", "sender"), ("Date", "date"))
+
+# Source code, chunked along its functions and classes unless CHUNK_CODE
+# is false (see `chunk_strategy`)
+CODE_SUFFIXES = frozenset(
+    """
+    .sql .rs .py .pyi .js .mjs .jsx .ts .tsx .go .java .kt .scala .c .h
+    .cc .cpp .hpp .cs .rb .php .swift .m .r .jl .lua .pl .hs .ex .exs .erl
+    .clj .dart .zig .nim .sh .bash .zsh .fish .ps1 .bat .css .scss .vue
+    .svelte .proto .graphql .gradle .cmake .dockerfile
+    """.split()
+)
+
+# Plain-text and source-code files, ingested as they are (as are files
+# without an extension, e.g. Makefile)
+PLAIN_TEXT_SUFFIXES = CODE_SUFFIXES | frozenset(
+    ".txt .text .log .rst .org .json .yaml .yml .toml .ini .cfg .conf .env .xml".split()
+)
+
+# Text normalization steps for PDFs, selected by NORMALIZE or --normalize:
+# keep blank lines between paragraphs, join the lines within a paragraph,
+# and compose Unicode to NFC
+NORMALIZE_STEPS = ("paragraphs", "join-lines", "nfc")
+DEFAULT_NORMALIZE = "paragraphs,nfc"
+
+# Chunking strategies, selected by CHUNK_STRATEGY or --chunking: windows of
+# CHUNK_MAX_TOKENS words, or of CHUNK_MAX_TOKENS tokens of the model whose
+# tokenizer.json is at CHUNK_TOKENIZER, whole sentences up to CHUNK_MAX_CHARS
+# characters, the largest of CHUNK_SEPARATORS' units (paragraphs, lines, ...)
+# that fit in CHUNK_MAX_CHARS, runs of sentences whose embeddings stay
+# similar, markdown sections headed by their heading path, or whole
+# functions and classes of source code
+CHUNK_STRATEGIES = (
+    "tokens",
+    "model_tokens",
+    "sentences",
+    "recursive",
+    "semantic",
+    "markdown",
+    "code",
+)
+DEFAULT_SEPARATORS = "paragraph,line,sentence,word"
+# Pages at least this many characters long are split into "tokens" chunks
+# on all CPU cores
+PARALLEL_CHUNK_CHARS = 100_000
+
+# Why `garbage_chunks` drops a chunk, as ingestion reports it
+GARBAGE_REASONS = {
+    "digits": "mostly digits",
+    "repeated": "repeated characters",
+    "noise": "OCR noise",
+    "alphabetic": "too few letters",
+}
+
+# Splits a page's text into (heading_path, chunk) pairs; the path is empty
+# unless the chunking strategy follows the text's headings
+PageChunker = Callable[[str], list[tuple[list[str], str]]]
+
+# Where a chunk came from and what it's about, as stored in payloads, the
+# chunk cache, and hits
+ORIGIN_FIELDS = ("source", "page", "title", "section", "keywords", "summary")
+
+# Fields ingestion may add to a chunk's payload and cache entry to describe
+# it: a sentence situating it in its document, its keywords, and a summary
+ENRICHMENT_FIELDS = ("context", "keywords", "summary")
+
+# Called as on_progress(stage, completed, total) during ingestion.
+# Stages, in order: "extract", "caption" (PDF figures, if enabled), "chunk",
+# "propositions", "summarize" and "contextualize" (if enabled), "embed",
+# "store", "cache".
+ProgressCallback = Callable[[str, int, int], None]
+
+
+def _report(
+    on_progress: ProgressCallback | None, stage: str, done: int, total: int
+) -> None:
+    if on_progress is not None:
+        on_progress(stage, done, total)
+
+
+def _load_chunk_cache() -> list[dict]:
+    """Load cached chunks from disk for BM25 indexing.
+
+    Each entry is {"text": ..., "source": ..., "page": ..., "title": ...,
+    "section": ..., "language": ..., "content_hash": ..., "page_hash": ...},
+    plus "parent_id" and "parent_text" for child chunks and any of the
+    ENRICHMENT_FIELDS.
+    Caches written before sources were recorded hold bare strings and load
+    with source None.
+    """
+    if not CHUNK_CACHE.exists():
+        return []
+    entries = json.loads(read_private(CHUNK_CACHE).decode("utf-8"))
+    return [
+        {"text": e, "source": None} if isinstance(e, str) else e for e in entries
+    ]
+
+
+def _save_chunk_cache(
+    chunks: list[str],
+    source: str | None = None,
+    pages: list[int | None] | None = None,
+    title: str | None = None,
+    sections: list[str | None] | None = None,
+    language: str | None = None,
+    content_hash: str | None = None,
+    page_hashes: list[str | None] | None = None,
+    parents: list[tuple[str, str] | None] | None = None,
+    enrichments: list[dict] | None = None,
+) -> None:
+    """Append new chunks, and where each came from, to the local cache.
+
+    `parents` gives each child chunk's (parent_id, parent_text), or None
+    for chunks without a parent (see `parent_chunks_enabled` and
+    `propositions_enabled`).
+    `enrichments` gives each chunk's ENRICHMENT_FIELDS that were set.
+    """
     existing = _load_chunk_cache()
-    existing.extend(chunks)
-    with open(CHUNK_CACHE, "w", encoding="utf-8") as f:
-        json.dump(existing, f, ensure_ascii=False)
+    pages = pages or [None] * len(chunks)
+    sections = sections or [None] * len(chunks)
+    page_hashes = page_hashes or [None] * len(chunks)
+    parents = parents or [None] * len(chunks)
+    enrichments = enrichments or [{}] * len(chunks)
+    existing.extend(
+        {
+            "text": chunk,
+            "source": source,
+            "page": page,
+            "title": title,
+            "section": section,
+            "language": language,
+            "content_hash": content_hash,
+            "page_hash": page_hash,
+            **({"parent_id": parent[0], "parent_text": parent[1]} if parent else {}),
+            **enrichment,
+        }
+        for chunk, page, section, page_hash, parent, enrichment in zip(
+            chunks, pages, sections, page_hashes, parents, enrichments
+        )
+    )
+    data = json.dumps(existing, ensure_ascii=False).encode("utf-8")
+    write_private(CHUNK_CACHE, data)
+    write_private(CHUNK_CACHE_GENERATION, _generation_of(data))
+
+
+def _generation_of(data: bytes) -> bytes:
+    """The generation of a chunk cache holding `data`."""
+    return hashlib.sha256(data).hexdigest().encode("ascii")
+
+
+def _chunk_cache_generation() -> bytes:
+    """The generation of the chunk cache, which changes whenever chunks are
+    added; empty without a cache. Caches written before generations were
+    recorded get theirs on first use."""
+    if CHUNK_CACHE_GENERATION.exists():
+        return read_private(CHUNK_CACHE_GENERATION)
+    if not CHUNK_CACHE.exists():
+        return b""
+    generation = _generation_of(read_private(CHUNK_CACHE))
+    write_private(CHUNK_CACHE_GENERATION, generation)
+    return generation
+
+
+def _known_hashes() -> dict[str, str]:
+    """Content hashes of the documents and pages already ingested, each
+    mapped to the source it was ingested from."""
+    known: dict[str, str] = {}
+    for entry in _load_chunk_cache():
+        for key in ("content_hash", "page_hash"):
+            if entry.get(key):
+                known.setdefault(entry[key], entry["source"])
+    return known
+
+
+def parent_chunks_enabled() -> bool:
+    """Whether chunks are split into small children for embedding, each
+    stored with a link to its parent chunk (CHUNK_PARENTS, default false).
+
+    Retrieval then matches the precise children but returns their parents,
+    so the LLM sees the fuller passage."""
+    return os.getenv("CHUNK_PARENTS", "false").lower() == "true"
+
+
+def quality_limits() -> dict[str, float] | None:
+    """The limits chunks must stay within to be embedded (see
+    `garbage_chunks`), or None if CHUNK_FILTER is false.
+
+    CHUNK_MAX_DIGIT_RATIO, CHUNK_MAX_REPEAT_RATIO, CHUNK_MAX_NOISE_RATIO,
+    and CHUNK_MIN_ALPHA_RATIO each override one limit."""
+    if os.getenv("CHUNK_FILTER", "true").lower() != "true":
+        return None
+    return {
+        "max_digit_ratio": float(os.getenv("CHUNK_MAX_DIGIT_RATIO", "0.8")),
+        "max_repeat_ratio": float(os.getenv("CHUNK_MAX_REPEAT_RATIO", "0.5")),
+        "max_noise_ratio": float(os.getenv("CHUNK_MAX_NOISE_RATIO", "0.5")),
+        "min_alpha_ratio": float(os.getenv("CHUNK_MIN_ALPHA_RATIO", "0.25")),
+    }
+
 
+def skip_duplicates_enabled(override: bool | None = None) -> bool:
+    """Whether ingestion skips content already in the knowledge base
+    (SKIP_DUPLICATES, default true), unless `override` says otherwise."""
+    if override is not None:
+        return override
+    return os.getenv("SKIP_DUPLICATES", "true").lower() == "true"
 
-def ingest(file_path: str) -> None:
-    """Ingest a PDF document into the knowledge base.
+
+def ingest(
+    file_path: str,
+    source: str | None = None,
+    on_progress: ProgressCallback | None = None,
+    password: str | None = None,
+    captions: bool | None = None,
+    text_field: str = DEFAULT_TEXT_FIELD,
+    metadata_fields: tuple[str, ...] = (),
+    normalize: tuple[str, ...] | None = None,
+    skip_duplicates: bool | None = None,
+    chunking: str | None = None,
+) -> None:
+    """Ingest a document into the knowledge base.
+
+    Supports PDF, Word (.docx), PowerPoint (.pptx), HTML, Markdown, EPUB,
+    LaTeX (.tex), CSV/Excel (.xlsx), email (.eml, .mbox), JSON Lines
+    datasets, subtitles (.srt, .vtt), and plain-text and source-code files
+    (see `_extract`). Spreadsheet rows are
+    ingested as "column: value" records. `file_path` may also be an
+    HTTP(S) URL, in which case the page or remote file is downloaded first.
 
     Pipeline:
-        Extract text (Rust/mmap)
-        → Token-aware chunking (Rust)
+        Extract text per page (Rust/mmap)
+        → Token-aware chunking per page (Rust)
         → Generate embeddings (Python/Ollama)
         → Store vectors (Python/Qdrant)
         → Cache chunks for BM25 (local file)
+
+    The file path (or URL) is recorded as the chunks' source unless
+    `source` is given, and each PDF chunk records the page it came from
+    along with the document's metadata (title, author, dates). Chunks of
+    other formats record the heading path of their section instead,
+    starting with the chapter title for EPUBs. `password` opens encrypted
+    PDFs, and `normalize` overrides the NORMALIZE steps applied to their
+    text (see `normalize_steps`).
+
+    Each record of a .jsonl file (or .json array of objects) is ingested
+    with its text taken from `text_field`; its `metadata_fields` (dotted
+    paths for nested fields) are stored with its chunks.
+
+    Subtitle files (.srt, .vtt) are ingested as paragraphs of cues, each
+    sectioned by its time range (e.g. "00:01:05 – 00:01:40") with its start
+    and end (in seconds) stored with its chunks as start_time and end_time.
+
+    With `captions` (default: CAPTION_IMAGES), figures in PDFs are
+    described by a local vision model (CAPTION_MODEL) and each caption is
+    ingested as a chunk of its page, in a "Figure N" section.
+    With CONTEXTUALIZE_CHUNKS, each chunk is embedded and keyword-indexed
+    after an LLM-written sentence situating it in the document (see
+    `contextualize`). `on_progress`, if given, is called as each stage
+    advances.
+
+    A document whose content was already ingested (e.g. the same file
+    again, or another copy of the same paper) is skipped before any
+    embedding work, as are its pages already ingested from any document;
+    see `ingest_pages`. `chunking` overrides the CHUNK_STRATEGY its text is
+    split with (see `chunk_strategy`).
     """
-    max_tokens = int(os.getenv("CHUNK_MAX_TOKENS", "256"))
-    overlap_tokens = int(os.getenv("CHUNK_OVERLAP_TOKENS", "32"))
+    pages, sections, metadata, page_metadata = _extract_document(
+        file_path,
+        password,
+        captions,
+        on_progress,
+        text_field=text_field,
+        metadata_fields=metadata_fields,
+        normalize=normalize,
+        chunking=chunking,
+    )
+    ingest_pages(
+        pages,
+        source=source or file_path,
+        on_progress=on_progress,
+        metadata=metadata,
+        sections=sections,
+        page_metadata=page_metadata,
+        skip_duplicates=skip_duplicates,
+        chunking=chunking,
+    )
+
+
+def ingest_files(
+    file_paths: list[str],
+    on_progress: ProgressCallback | None = None,
+    password: str | None = None,
+    captions: bool | None = None,
+    workers: int | None = None,
+    text_field: str = DEFAULT_TEXT_FIELD,
+    metadata_fields: tuple[str, ...] = (),
+    normalize: tuple[str, ...] | None = None,
+    skip_duplicates: bool | None = None,
+    chunking: str | None = None,
+) -> list[tuple[str, Exception]]:
+    """Ingest several documents, extracting them concurrently.
+
+    Extraction runs on up to `workers` threads (default: INGEST_WORKERS, or
+    the number of CPUs); the Rust extractors release the GIL, so documents
+    are parsed in parallel. Each document is chunked, embedded, and stored
+    as soon as its extraction finishes, while the rest keep extracting.
+    Each file path is recorded as its chunks' source; `text_field`,
+    `metadata_fields`, `normalize`, `skip_duplicates`, and `chunking` apply
+    as for `ingest`.
+
+    A document that fails is reported and skipped rather than aborting the
+    batch; the (path, error) pairs of failures are returned. The "extract"
+    stage of `on_progress` counts documents.
+    """
+    workers = workers or int(os.getenv("INGEST_WORKERS", "0")) or os.cpu_count() or 1
+    failures: list[tuple[str, Exception]] = []
+    console.print(
+        f"  Extracting [green]{len(file_paths)}[/green] documents "
+        f"[dim]\\[Rust · {workers} workers][/dim]..."
+    )
+    _report(on_progress, "extract", 0, len(file_paths))
+    with ThreadPoolExecutor(max_workers=workers) as pool:
+        futures = {
+            pool.submit(
+                _extract_document,
+                path,
+                password,
+                captions,
+                text_field=text_field,
+                metadata_fields=metadata_fields,
+                normalize=normalize,
+                chunking=chunking,
+            ): path
+            for path in file_paths
+        }
+        for done, future in enumerate(as_completed(futures), 1):
+            path = futures[future]
+            _report(on_progress, "extract", done, len(file_paths))
+            try:
+                pages, sections, metadata, page_metadata = future.result()
+                console.print(f"\n  [bold]{path}[/bold]")
+                ingest_pages(
+                    pages,
+                    source=path,
+                    on_progress=on_progress,
+                    metadata=metadata,
+                    sections=sections,
+                    page_metadata=page_metadata,
+                    skip_duplicates=skip_duplicates,
+                    chunking=chunking,
+                )
+            except Exception as e:
+                console.print(f"  [red]✗[/red] {path}: {e}")
+                failures.append((path, e))
+    return failures
+
+
+def register_extractor(
+    suffixes: str | list[str], extract: Extractor, mime_types: tuple[str, ...] = ()
+) -> None:
+    """Ingest files with the given extensions (e.g. ".rtf") using `extract`.
+
+    `extract(path)` returns (page_number, section, text) triples, each
+    ingested like a page. Registered extractors take precedence over the
+    built-in ones, so they can also replace how a supported format is read.
+    URLs served with one of `mime_types` are saved under the first suffix.
+    """
+    if isinstance(suffixes, str):
+        suffixes = [suffixes]
+    suffixes = ["." + suffix.lower().lstrip(".") for suffix in suffixes]
+    for suffix in suffixes:
+        _custom_extractors[suffix] = extract
+    for mime_type in mime_types:
+        CONTENT_TYPE_SUFFIXES[mime_type.lower()] = suffixes[0]
+
+
+def _from_spans(
+    spans: list[tuple[int | None, str | None, str]],
+) -> tuple[list[tuple[int | None, str]], list[str | None] | None]:
+    """Split (page_number, section, text) triples into pages and sections."""
+    pages = [(page, text) for page, _, text in spans if text.strip()]
+    sections = [section for _, section, text in spans if text.strip()]
+    return pages, sections if any(sections) else None
+
+
+def collect_documents(paths: list[str]) -> list[str]:
+    """Expand directories into the supported documents they contain.
+
+    Directories are searched recursively, skipping hidden files and
+    directories and files of unsupported types; other paths (files and
+    URLs) are kept as given.
+    """
+    supported = {
+        *DOCUMENT_SUFFIXES, *CORE_SUFFIXES, *PLAIN_TEXT_SUFFIXES, *_custom_extractors
+    }
+    documents = []
+    for path in paths:
+        if is_url(path) or not Path(path).is_dir():
+            documents.append(path)
+            continue
+        for found in sorted(Path(path).rglob("*")):
+            hidden = any(part.startswith(".") for part in found.relative_to(path).parts)
+            if found.is_file() and not hidden and found.suffix.lower() in supported:
+                documents.append(str(found))
+    return documents
+
+
+def _extract_document(
+    file_path: str,
+    password: str | None,
+    captions: bool | None,
+    on_progress: ProgressCallback | None = None,
+    text_field: str = DEFAULT_TEXT_FIELD,
+    metadata_fields: tuple[str, ...] = (),
+    normalize: tuple[str, ...] | None = None,
+    chunking: str | None = None,
+) -> tuple[
+    list[tuple[int | None, str]], list[str | None] | None, dict, list[dict] | None
+]:
+    """Extract a document (downloading it if it's a URL) and caption its
+    figures, returning (page_number, text) pairs, sections, metadata, and
+    per-page metadata (see `_extract`)."""
+    if captions is None:
+        captions = captions_enabled()
 
     console.print(f"  Extracting text from: [bold]{file_path}[/bold]")
-    text = extract_pdf_text(file_path)
-    console.print(f"  Extracted [green]{len(text):,}[/green] characters.")
+    _report(on_progress, "extract", 0, 1)
+    with local_file(file_path) as path:
+        if Path(path).suffix.lower() == ".pdf":
+            _report_text_coverage(path, password)
+        pages, sections, metadata, page_metadata = _extract(
+            path, password, text_field, metadata_fields, normalize, chunking
+        )
+        _report(on_progress, "extract", 1, 1)
+        n_chars = sum(len(text) for _, text in pages)
+        if page_metadata is not None:
+            subtitles = Path(path).suffix.lower() in SUBTITLE_SUFFIXES
+            unit = "paragraphs" if subtitles else "records"
+            console.print(
+                f"  Extracted [green]{n_chars:,}[/green] characters "
+                f"from [green]{len(pages)}[/green] {unit}."
+            )
+        elif pages[0][0] is None:
+            console.print(f"  Extracted [green]{n_chars:,}[/green] characters.")
+        else:
+            console.print(
+                f"  Extracted [green]{n_chars:,}[/green] characters "
+                f"from [green]{len({page for page, _ in pages})}[/green] pages."
+            )
+        if metadata.get("title"):
+            console.print(f"  Title: [bold]{metadata['title']}[/bold]")
+
+        figures = []
+        if captions and Path(path).suffix.lower() == ".pdf":
+            figures = _caption_figures(path, password, on_progress)
+
+    if figures:
+        sections = sections or [None] * len(pages)
+        for n, (page, caption) in enumerate(figures, 1):
+            pages.append((page, f"Figure: {caption}"))
+            sections.append(f"Figure {n}")
+    return pages, sections, metadata, page_metadata
+
+
+def _report_text_coverage(path: str, password: str | None) -> None:
+    """Print how many pages of a PDF have a text layer, warning about
+    image-only (scanned) pages, whose text is missing unless OCR'd."""
+    coverage = pdf_text_coverage(path, password)
+    console.print(
+        f"  Text layer on [green]{len(coverage['text_pages'])}[/green] "
+        f"of {coverage['page_count']} pages."
+    )
+    image_pages = coverage["image_pages"]
+    if not image_pages:
+        return
+    if coverage["ocr"]:
+        action = "running OCR on them"
+    else:
+        action = "their text is missing (rebuild with --features ocr to OCR them)"
+    console.print(
+        f"  [yellow]⚠ {len(image_pages)} image-only pages "
+        f"({_page_ranges(image_pages)}): {action}.[/yellow]"
+    )
+
+
+def _page_ranges(pages: list[int]) -> str:
+    """Format sorted page numbers compactly, e.g. [3, 4, 5, 9] as "3-5, 9"."""
+    ranges: list[list[int]] = []
+    for page in pages:
+        if ranges and page == ranges[-1][1] + 1:
+            ranges[-1][1] = page
+        else:
+            ranges.append([page, page])
+    return ", ".join(
+        str(start) if start == end else f"{start}-{end}" for start, end in ranges
+    )
+
+
+def _caption_figures(
+    pdf_path: str, password: str | None, on_progress: ProgressCallback | None
+) -> list[tuple[int, str]]:
+    """Caption a PDF's embedded images, returning (page_number, caption) pairs.
+
+    Images smaller than CAPTION_MIN_SIZE pixels on a side are skipped.
+    """
+    min_size = int(os.getenv("CAPTION_MIN_SIZE", "100"))
+    images = extract_pdf_images(pdf_path, password, min_size)
+    if not images:
+        return []
 
     console.print(
-        f"  Chunking text (max_tokens={max_tokens}, overlap={overlap_tokens}) "
-        f"[dim]\\[Rust · token-aware][/dim]..."
+        f"  Captioning [green]{len(images)}[/green] figures "
+        f"[dim]\\[Ollama · {caption_model()}][/dim]..."
+    )
+    figures = []
+    _report(on_progress, "caption", 0, len(images))
+    for done, (page, image) in enumerate(images, 1):
+        caption = caption_image(image)
+        if caption:
+            figures.append((page, caption))
+        _report(on_progress, "caption", done, len(images))
+    return figures
+
+
+def _extract(
+    file_path: str,
+    password: str | None,
+    text_field: str = DEFAULT_TEXT_FIELD,
+    metadata_fields: tuple[str, ...] = (),
+    normalize: tuple[str, ...] | None = None,
+    chunking: str | None = None,
+) -> tuple[
+    list[tuple[int | None, str]], list[str | None] | None, dict, list[dict] | None
+]:
+    """Extract (page_number, text) pairs, their sections, and metadata.
+
+    The extractor is picked by file extension. PDFs yield a pair per page,
+    minus running headers and footers (unless STRIP_PAGE_FURNITURE=false)
+    and normalized as `normalize_steps(normalize)` selects, split where
+    their outline (bookmarks) starts a section, with the bookmark's title
+    path in the parallel sections list; a filled-in form adds a pair per
+    page of "Label: Value" lines, in a "Form fields" section. Other formats
+    the core's extractor registry reads, such as markup without pages,
+    yield its spans: one (None, text) pair per markdown section, with the
+    section's heading path (e.g. "Installation > Linux"); PowerPoint decks
+    yield a pair per slide, numbered like pages and titled by the slide
+    title; emails yield a pair per message, headed by its subject, sender,
+    and date; plain text and code yield a single pair and no sections.
+
+    JSON datasets (.jsonl, or .json holding an array of objects) yield a
+    pair per record, its text from `text_field`, sectioned by its line (or
+    position) and with its `metadata_fields` in the per-page metadata list.
+    Subtitles yield a pair per paragraph of cues, sectioned by its time
+    range, with its start_time and end_time (in seconds) as per-page
+    metadata. The per-page metadata list is None for every other format.
+    Extensions added with `register_extractor` are read by their extractor
+    before anything else. Markdown to be chunked with the "markdown"
+    `chunking` strategy yields a single pair, as the chunks record their
+    own heading paths.
+    """
+    suffix = Path(file_path).suffix.lower()
+    custom = _custom_extractors.get(suffix)
+    if custom is not None:
+        pages, sections = _from_spans(custom(file_path))
+        if not pages:
+            raise ValueError(f"No text could be extracted from: {file_path}")
+        return pages, sections, {}, None
+
+    if suffix in MARKDOWN_SUFFIXES and chunk_strategy(chunking) == "markdown":
+        return [(None, read_text_file(file_path))], None, {}, None
+
+    if suffix == ".pdf":
+        strip_furniture = os.getenv("STRIP_PAGE_FURNITURE", "true").lower() == "true"
+        steps = normalize_steps(normalize)
+        spans = extract_pdf_sections(
+            file_path,
+            password,
+            strip_furniture,
+            preserve_paragraphs="paragraphs" in steps,
+            join_lines="join-lines" in steps,
+            nfc="nfc" in steps,
+        )
+        pages = [(page, text) for page, _, text in spans]
+        sections = [" > ".join(path) or None for _, path, _ in spans]
+        if not any(sections):
+            sections = None
+        # Form field values aren't part of the page text
+        form: dict[int | None, list[str]] = {}
+        for page, label, value in extract_pdf_form_fields(file_path, password):
+            form.setdefault(page, []).append(f"{label}: {value}")
+        if form:
+            sections = sections or [None] * len(pages)
+            for page, lines in form.items():
+                pages.append((page, "\n".join(lines)))
+                sections.append("Form fields")
+        return pages, sections, extract_pdf_metadata(file_path, password), None
+
+    if suffix == ".epub":
+        book = extract_epub(file_path)
+        pages, sections = [], []
+        for n, (chapter, text) in enumerate(book.pop("chapters"), 1):
+            # Text before a chapter's first heading is filed under the
+            # chapter's title, or its number
+            for path, section_text in markdown_sections(text):
+                pages.append((None, section_text))
+                sections.append(" > ".join(path) or chapter or f"Chapter {n}")
+        return pages, sections, book, None
+
+    if suffix == ".pptx":
+        # Slide numbers are stored as page numbers, titles as sections
+        slides = extract_pptx(file_path)
+        pages = [(number, text) for number, _, text in slides]
+        sections = [title for _, title, _ in slides]
+        return pages, sections, {}, None
+
+    if suffix in (".eml", ".mbox"):
+        messages = extract_email(file_path)
+        pages, sections = [], []
+        for message in messages:
+            headers = [
+                f"{label}: {message[key]}" for label, key in EMAIL_HEADERS if message[key]
+            ]
+            pages.append((None, "\n".join([*headers, "", message["text"]]).strip()))
+            sections.append(message["subject"])
+        # A single message's headers become document metadata; an archive's
+        # are only in each message's text
+        metadata = {}
+        if len(messages) == 1:
+            message = messages[0]
+            metadata = {
+                "title": message["subject"],
+                "author": message["sender"],
+                "creation_date": message["date"],
+            }
+        return pages, sections, metadata, None
+
+    if suffix in SUBTITLE_SUFFIXES:
+        paragraphs = extract_subtitles(file_path)
+        pages = [(None, text) for _, _, text in paragraphs]
+        sections = [
+            f"{_timestamp(start)} – {_timestamp(end)}" for start, end, _ in paragraphs
+        ]
+        times = [{"start_time": start, "end_time": end} for start, end, _ in paragraphs]
+        return pages, sections, {}, times
+
+    # A .json file that isn't an array of objects is ingested as plain text
+    records = load_records(file_path) if suffix in (".jsonl", ".json") else None
+    if records is not None:
+        mapped = map_records(records, text_field, metadata_fields)
+        if not mapped:
+            raise ValueError(
+                f"No records have text in their '{text_field}' field: {file_path}"
+            )
+        label = "Line" if suffix == ".jsonl" else "Record"
+        pages = [(None, text) for _, text, _ in mapped]
+        sections = [f"{label} {n}" for n, _, _ in mapped]
+        return pages, sections, {}, [metadata for _, _, metadata in mapped]
+
+    if suffix in PLAIN_TEXT_SUFFIXES or not suffix:
+        text = read_text_file(file_path)
+        if not text.strip():
+            raise ValueError(f"No text could be extracted from: {file_path}")
+        return [(None, text)], None, {}, None
+
+    if suffix not in CORE_SUFFIXES:
+        supported = sorted({*DOCUMENT_SUFFIXES, *CORE_SUFFIXES, *_custom_extractors})
+        raise ValueError(
+            f"Unsupported file type '{suffix}': {file_path}. Supported: "
+            + ", ".join(supported)
+            + ", and plain text/code files."
+        )
+    pages, sections = _from_spans(extract_document(file_path))
+    return pages, sections, {}, None
+
+
+def _timestamp(seconds: float) -> str:
+    """A time in seconds as "hh:mm:ss"."""
+    seconds = int(seconds)
+    return f"{seconds // 3600:02}:{seconds // 60 % 60:02}:{seconds % 60:02}"
+
+
+def ingest_text(
+    text: str,
+    source: str,
+    on_progress: ProgressCallback | None = None,
+    skip_duplicates: bool | None = None,
+    chunking: str | None = None,
+) -> None:
+    """Ingest raw text (e.g. piped from stdin) under the given source name.
+
+    Runs the same chunk → embed → store pipeline as `ingest`, skipping
+    extraction. The chunks carry no page numbers.
+    """
+    if not text.strip():
+        raise ValueError(f"No text to ingest from '{source}'.")
+
+    ingest_pages(
+        [(None, text)],
+        source=source,
+        on_progress=on_progress,
+        skip_duplicates=skip_duplicates,
+        chunking=chunking,
     )
-    chunks = chunk_by_tokens(text, max_tokens, overlap_tokens)
+
+
+def ingest_pages(
+    pages: list[tuple[int | None, str]],
+    source: str,
+    on_progress: ProgressCallback | None = None,
+    metadata: dict | None = None,
+    sections: list[str | None] | None = None,
+    page_metadata: list[dict] | None = None,
+    skip_duplicates: bool | None = None,
+    chunking: str | None = None,
+) -> None:
+    """Chunk, embed, and store (page_number, text) pairs.
+
+    Pages are chunked separately so every chunk maps to a single page, with
+    the `chunking` strategy (default: CHUNK_STRATEGY). Chunks of pages
+    without a section are sectioned by their heading path, if the strategy
+    follows headings.
+    `sections`, if given, holds each page's heading path, stored with its
+    chunks. Document `metadata` (e.g. from `extract_pdf_metadata`) is stored
+    with every chunk, along with the document's detected language code;
+    `page_metadata`, if given, holds fields stored only with each page's
+    chunks (e.g. a dataset record's fields). Each chunk's Qdrant payload
+    also records its index and when it was ingested (see `ChunkPayload`).
+    Embeddings are generated in batches of EMBED_BATCH_SIZE chunks so
+    `on_progress` can report them as they complete.
+
+    The document and each page are hashed by content (ignoring whitespace)
+    and the hashes stored with the chunks. Unless `skip_duplicates` (default:
+    SKIP_DUPLICATES) is false, a document already ingested is skipped, and
+    so are pages already ingested or repeated within the document.
+    """
+    page_hashes, content_hash = content_hashes([text for _, text in pages])
+    sections = sections or [None] * len(pages)
+    page_metadata = page_metadata or [{}] * len(pages)
+    if skip_duplicates_enabled(skip_duplicates):
+        known = _known_hashes()
+        if content_hash in known:
+            console.print(
+                f"  [yellow]Skipping '{source}': same content as "
+                f"'{known[content_hash]}', already ingested.[/yellow]"
+            )
+            return
+        keep = []
+        for i, page_hash in enumerate(page_hashes):
+            if page_hash not in known:
+                keep.append(i)
+                known[page_hash] = source
+        if not keep:
+            console.print(
+                f"  [yellow]Skipping '{source}': all its pages were already "
+                "ingested.[/yellow]"
+            )
+            return
+        if len(keep) < len(pages):
+            console.print(
+                f"  Skipping [yellow]{len(pages) - len(keep)}[/yellow] "
+                "duplicate pages."
+            )
+            pages = [pages[i] for i in keep]
+            sections = [sections[i] for i in keep]
+            page_metadata = [page_metadata[i] for i in keep]
+            page_hashes = [page_hashes[i] for i in keep]
+
+    language = detect_language("\n".join(text for _, text in pages))
+    if language:
+        console.print(f"  Language: [bold]{language}[/bold]")
+        metadata = {**(metadata or {}), "language": language}
+
+    strategy = chunk_strategy(chunking, source)
+    settings, kind, chunk_page = _page_chunker(strategy)
+    console.print(f"  Chunking text ({settings}) [dim]\\[Rust · {kind}][/dim]...")
+    chunks: list[str] = []
+    chunk_pages: list[int | None] = []
+    chunk_sections: list[str | None] = []
+    chunk_metadata: list[dict] = []
+    chunk_hashes: list[str] = []
+    _report(on_progress, "chunk", 0, len(pages))
+    for done, ((page, text), section, fields, page_hash) in enumerate(
+        zip(pages, sections, page_metadata, page_hashes), 1
+    ):
+        for path, chunk in chunk_page(text):
+            chunks.append(chunk)
+            chunk_pages.append(page)
+            chunk_sections.append(section or " > ".join(path) or None)
+            chunk_metadata.append(dict(fields))
+            chunk_hashes.append(page_hash)
+        _report(on_progress, "chunk", done, len(pages))
     console.print(f"  Created [green]{len(chunks)}[/green] chunks.")
 
+    keep = list(range(len(chunks)))
+    limits = quality_limits()
+    # Source code is mostly symbols, so it isn't judged as prose
+    if limits is not None and strategy != "code":
+        reasons = garbage_chunks(chunks, **limits)
+        dropped = Counter(reason for reason in reasons if reason is not None)
+        if dropped:
+            counts = ", ".join(
+                f"{count} {GARBAGE_REASONS[reason]}"
+                for reason, count in dropped.most_common()
+            )
+            console.print(
+                f"  Dropped [yellow]{sum(dropped.values())}[/yellow] garbage chunks "
+                f"({counts}) [dim]\\[Rust · quality][/dim]."
+            )
+            keep = [i for i in keep if reasons[i] is None]
+
+    if os.getenv("CHUNK_DEDUP", "true").lower() == "true":
+        threshold = float(os.getenv("CHUNK_DEDUP_THRESHOLD", "0.95"))
+        duplicates = near_duplicates([chunks[i] for i in keep], threshold)
+        unique = [i for i, original in zip(keep, duplicates) if original is None]
+        if len(unique) < len(keep):
+            console.print(
+                f"  Dropped [yellow]{len(keep) - len(unique)}[/yellow] "
+                "near-duplicate chunks [dim]\\[Rust · MinHash][/dim]."
+            )
+            keep = unique
+
+    if chunks and not keep:
+        console.print(
+            f"  [yellow]Skipping '{source}': none of its chunks are worth "
+            "embedding.[/yellow]"
+        )
+        return
+    if len(keep) < len(chunks):
+        chunks = [chunks[i] for i in keep]
+        chunk_pages = [chunk_pages[i] for i in keep]
+        chunk_sections = [chunk_sections[i] for i in keep]
+        chunk_metadata = [chunk_metadata[i] for i in keep]
+        chunk_hashes = [chunk_hashes[i] for i in keep]
+
+    children: list[tuple[int, str]] = []
+    if propositions_enabled():
+        # Embed each chunk's propositions as its children
+        console.print("  Decomposing chunks into propositions [dim]\\[Ollama][/dim]...")
+        _report(on_progress, "propositions", 0, len(chunks))
+        for i, chunk in enumerate(chunks):
+            children.extend((i, p) for p in chunk_propositions(chunk))
+            _report(on_progress, "propositions", i + 1, len(chunks))
+        split = "propositions"
+    elif parent_chunks_enabled():
+        # Embed small children of each chunk, each linked to its parent so
+        # retrieval can hand the LLM the whole parent
+        max_tokens = int(os.getenv("CHUNK_CHILD_MAX_TOKENS", "64"))
+        overlap_tokens = int(os.getenv("CHUNK_CHILD_OVERLAP_TOKENS", "8"))
+        children = [
+            (i, child)
+            for i, chunk in enumerate(chunks)
+            for child in chunk_by_tokens(
+                chunk, max_tokens, overlap_tokens, word_chars=token_word_chars()
+            )
+        ]
+        split = f"child chunks (max_tokens={max_tokens}, overlap={overlap_tokens})"
+    if children:
+        parent_ids = [str(uuid.uuid4()) for _ in chunks]
+        parent_of = [i for i, _ in children]
+        chunk_metadata = [
+            {**chunk_metadata[i], "parent_id": parent_ids[i], "parent_text": chunks[i]}
+            for i in parent_of
+        ]
+        chunks = [child for _, child in children]
+        chunk_pages = [chunk_pages[i] for i in parent_of]
+        chunk_sections = [chunk_sections[i] for i in parent_of]
+        chunk_hashes = [chunk_hashes[i] for i in parent_of]
+        console.print(f"  Split into [green]{len(chunks)}[/green] {split} for embedding.")
+
+    if os.getenv("CHUNK_KEYWORDS", "true").lower() == "true":
+        top_k = int(os.getenv("CHUNK_KEYWORDS_TOP_K", "5"))
+        keywords = chunk_keywords(chunks, top_k, language=language)
+        chunk_metadata = [
+            {**fields, "keywords": words}
+            for fields, words in zip(chunk_metadata, keywords)
+        ]
+
+    if summaries_enabled():
+        console.print("  Summarizing chunks [dim]\\[Ollama][/dim]...")
+        _report(on_progress, "summarize", 0, len(chunks))
+        for i, chunk in enumerate(chunks):
+            chunk_metadata[i] = {**chunk_metadata[i], "summary": chunk_summary(chunk)}
+            _report(on_progress, "summarize", i + 1, len(chunks))
+
+    contexts: list[str | None] = [None] * len(chunks)
+    if contextualize_enabled():
+        # Children are situated by their parent's text, once per parent
+        document = context_document([text for _, text in pages])
+        console.print("  Situating chunks in their document [dim]\\[Ollama][/dim]...")
+        by_passage: dict[str, str] = {}
+        _report(on_progress, "contextualize", 0, len(chunks))
+        for i, chunk in enumerate(chunks):
+            passage = chunk_metadata[i].get("parent_text", chunk)
+            if passage not in by_passage:
+                by_passage[passage] = chunk_context(document, passage)
+            contexts[i] = by_passage[passage]
+            _report(on_progress, "contextualize", i + 1, len(chunks))
+        chunk_metadata = [
+            {**fields, "context": context}
+            for fields, context in zip(chunk_metadata, contexts)
+        ]
+        console.print(f"  Wrote [green]{len(by_passage)}[/green] context sentences.")
+
     console.print("  Generating embeddings [dim]\\[Ollama][/dim]...")
-    vectors = embed_texts(chunks)
+    batch_size = max(1, int(os.getenv("EMBED_BATCH_SIZE", "64")))
+    vectors: list[list[float]] = []
+    embedded = [enriched_text(c, context) for c, context in zip(chunks, contexts)]
+    _report(on_progress, "embed", 0, len(chunks))
+    for start in range(0, len(chunks), batch_size):
+        vectors.extend(embed_texts(embedded[start : start + batch_size]))
+        _report(on_progress, "embed", len(vectors), len(chunks))
     console.print(f"  Generated [green]{len(vectors)}[/green] embeddings.")
 
     console.print("  Connecting to Qdrant...")
@@ -72,96 +1033,842 @@ def ingest(file_path: str) -> None:
     init_collection(client)
 
     console.print("  Upserting chunks to Qdrant...")
-    upsert_chunks(client, chunks, vectors)
+    _report(on_progress, "store", 0, len(chunks))
+    ingested_at = ingest_timestamp()
+    payloads = [
+        ChunkPayload(
+            source=source,
+            page=page,
+            chunk_index=i,
+            section=section,
+            ingested_at=ingested_at,
+            content_hash=content_hash,
+            page_hash=page_hash,
+            fields=fields,
+        )
+        for i, (page, section, page_hash, fields) in enumerate(
+            zip(chunk_pages, chunk_sections, chunk_hashes, chunk_metadata)
+        )
+    ]
+    upsert_chunks(client, chunks, vectors, payloads=payloads, metadata=metadata)
+    _report(on_progress, "store", len(chunks), len(chunks))
 
     console.print("  Caching chunks for BM25 index...")
-    _save_chunk_cache(chunks)
+    _report(on_progress, "cache", 0, 1)
+    title = (metadata or {}).get("title")
+    _save_chunk_cache(
+        chunks,
+        source=source,
+        pages=chunk_pages,
+        title=title,
+        sections=chunk_sections,
+        language=language,
+        content_hash=content_hash,
+        page_hashes=chunk_hashes,
+        parents=[
+            (fields["parent_id"], fields["parent_text"])
+            if "parent_id" in fields
+            else None
+            for fields in chunk_metadata
+        ],
+        enrichments=[
+            {key: fields[key] for key in ENRICHMENT_FIELDS if fields.get(key)}
+            for fields in chunk_metadata
+        ],
+    )
+    _report(on_progress, "cache", 1, 1)
+
+    # Cached retrievals predate the new chunks
+    if _retrieval_cache is not None:
+        _retrieval_cache.clear()
 
     console.print(
         f"  [bold green]✓ Successfully ingested {len(chunks)} chunks "
-        f"from '{file_path}'.[/bold green]"
+        f"from '{source}'.[/bold green]"
     )
 
 
-def query(question: str) -> str:
-    """Query the knowledge base using hybrid search (vector + BM25).
+_retrieval_cache: SemanticRetrievalCache | None = None
+
+
+def _get_retrieval_cache() -> SemanticRetrievalCache | None:
+    """Return the process-wide retrieval cache, or None if disabled.
+
+    Set RETRIEVAL_CACHE_THRESHOLD=0 to turn the cache off.
+    """
+    global _retrieval_cache
+    threshold = float(os.getenv("RETRIEVAL_CACHE_THRESHOLD", "0.95"))
+    if threshold <= 0:
+        return None
+    if _retrieval_cache is None:
+        max_entries = int(os.getenv("RETRIEVAL_CACHE_SIZE", "64"))
+        _retrieval_cache = SemanticRetrievalCache(threshold, max_entries)
+    _retrieval_cache.threshold = threshold
+    return _retrieval_cache
+
+
+# The last spell corrector built, with the chunk cache generation and
+# max_distance it was built with
+_spell_corrector: tuple[tuple[bytes, int], SpellCorrector] | None = None
+
+
+def _get_spell_corrector(cached: list[dict], max_distance: int) -> SpellCorrector:
+    """The spell corrector of the `cached` chunks, reused across queries
+    until the chunk cache generation or `max_distance` change, as building
+    its deletion index touches the whole vocabulary."""
+    global _spell_corrector
+    key = (_chunk_cache_generation(), max_distance)
+    if _spell_corrector is None or _spell_corrector[0] != key:
+        texts = [entry["text"] for entry in cached]
+        corrector = SpellCorrector(texts, max_distance=max_distance)
+        _spell_corrector = (key, corrector)
+    return _spell_corrector[1]
+
+
+def _correct_spelling(question: str, cached: list[dict]) -> str:
+    """Fix typos in the question against the corpus vocabulary.
+
+    Off unless SPELL_CORRECTION=true. Misspelled words are replaced with
+    the closest term found in the ingested chunks, so the query embeds and
+    keyword-matches against words the documents actually use.
+    """
+    if not cached or os.getenv("SPELL_CORRECTION", "false").lower() != "true":
+        return question
+
+    max_distance = int(os.getenv("SPELL_MAX_DISTANCE", "2"))
+    corrector = _get_spell_corrector(cached, max_distance)
+    corrected = corrector.correct(question)
+    if corrected != question:
+        console.print(
+            f'  Corrected query: "[italic]{corrected}[/italic]" '
+            f'[dim](was "{question}")[/dim]'
+        )
+    return corrected
+
+
+RETRIEVERS = ("vector", "bm25")
+
+
+def parse_fusion_weights(spec: str) -> dict[str, float]:
+    """Parse "vector=0.7,bm25=0.3" into {"vector": 0.7, "bm25": 0.3}."""
+    weights: dict[str, float] = {}
+    for part in spec.split(","):
+        if not part.strip():
+            continue
+        name, sep, value = part.partition("=")
+        name = name.strip().lower()
+        if not sep or name not in RETRIEVERS:
+            raise ValueError(
+                f"Invalid fusion weight '{part.strip()}'. "
+                f"Expected name=weight with name in: {', '.join(RETRIEVERS)}."
+            )
+        weights[name] = float(value)
+        if weights[name] < 0:
+            raise ValueError(f"Fusion weight for '{name}' must not be negative.")
+    return weights
+
+
+def parse_normalize(spec: str) -> tuple[str, ...]:
+    """Parse "paragraphs,nfc" into normalization steps ("none" for none)."""
+    steps = tuple(step.strip().lower() for step in spec.split(",") if step.strip())
+    if steps == ("none",):
+        return ()
+    for step in steps:
+        if step not in NORMALIZE_STEPS:
+            raise ValueError(
+                f"Unknown normalization step '{step}'. "
+                f"Expected any of: {', '.join(NORMALIZE_STEPS)}, or none."
+            )
+    return steps
+
+
+def normalize_steps(override: tuple[str, ...] | None = None) -> tuple[str, ...]:
+    """Resolve the normalization steps applied to extracted PDF text.
+
+    NORMALIZE (default "paragraphs,nfc") sets them for every ingest and
+    `override` replaces them for one.
+    """
+    if override is not None:
+        return override
+    return parse_normalize(os.getenv("NORMALIZE", DEFAULT_NORMALIZE))
+
+
+def chunk_strategy(override: str | None = None, source: str | None = None) -> str:
+    """Resolve the strategy pages are chunked with.
+
+    CHUNK_STRATEGY (default "tokens") sets it for every ingest and
+    `override` replaces it for one. Source code files (by `source`'s
+    extension) are chunked with "code" instead of CHUNK_STRATEGY, unless
+    CHUNK_CODE is false.
+    """
+    strategy = override or os.getenv("CHUNK_STRATEGY", "tokens")
+    if (
+        override is None
+        and source is not None
+        and Path(source).suffix.lower() in CODE_SUFFIXES
+        and os.getenv("CHUNK_CODE", "true").lower() == "true"
+    ):
+        strategy = "code"
+    strategy = strategy.strip().lower()
+    if strategy not in CHUNK_STRATEGIES:
+        raise ValueError(
+            f"Unknown chunking strategy '{strategy}'. "
+            f"Expected one of: {', '.join(CHUNK_STRATEGIES)}."
+        )
+    return strategy
+
+
+def _chunk_max_chars() -> int:
+    """CHUNK_MAX_CHARS (default 1000), fitted to the embedding model (see
+    `_fit_to_embedding_model`)."""
+    max_chars = int(os.getenv("CHUNK_MAX_CHARS", "1000"))
+    return _fit_to_embedding_model("CHUNK_MAX_CHARS", max_chars, CHARS_PER_TOKEN)
+
+
+def _chunk_max_tokens(model_tokens: bool = False) -> int:
+    """CHUNK_MAX_TOKENS (default 256), fitted to the embedding model (see
+    `_fit_to_embedding_model`). The tokens are words unless `model_tokens`."""
+    max_tokens = int(os.getenv("CHUNK_MAX_TOKENS", "256"))
+    per_token = 1 if model_tokens else WORDS_PER_TOKEN
+    return _fit_to_embedding_model("CHUNK_MAX_TOKENS", max_tokens, per_token)
+
+
+def token_word_chars() -> str | None:
+    """Characters kept inside words besides letters and digits
+    (TOKEN_WORD_CHARS), for token chunking and BM25 alike; None for the
+    default apostrophe."""
+    return os.getenv("TOKEN_WORD_CHARS") or None
+
+
+@functools.cache
+def _fit_to_embedding_model(setting: str, size: int, per_token: float) -> int:
+    """`size`, the chunk size `setting`, capped to the embedding model's
+    input limit (about `per_token` units of `size` per model token), since
+    Ollama silently truncates longer inputs before embedding them.
+
+    The size is kept, with a warning, if CHUNK_FIT_EMBEDDING_MODEL is false
+    or the limit is unknown. Each setting is checked once per process.
+    """
+    limit = embedding_context_length()
+    fitted = size if limit is None else int(limit * per_token)
+    if size <= fitted:
+        return size
+    if os.getenv("CHUNK_FIT_EMBEDDING_MODEL", "true").lower() != "true":
+        console.print(
+            f"  [yellow]{setting}={size} exceeds the embedding model's "
+            f"{limit}-token input; longer chunks will be truncated.[/yellow]"
+        )
+        return size
+    console.print(
+        f"  [yellow]{setting}={size} exceeds the embedding model's "
+        f"{limit}-token input; chunking with {setting}={fitted}.[/yellow]"
+    )
+    return fitted
+
+
+def _page_chunker(strategy: str) -> tuple[str, str, PageChunker]:
+    """The function that chunks a page's text with `strategy`, and its
+    settings and kind, for display."""
+    repeat_header = os.getenv("CHUNK_TABLE_HEADERS", "true").lower() == "true"
+    min_chars = int(os.getenv("CHUNK_MIN_CHARS", "100"))
+    if strategy == "markdown":
+        max_chars = _chunk_max_chars()
+        overlap = int(os.getenv("CHUNK_OVERLAP_CHARS", "100"))
+        return (
+            f"max_chars={max_chars}, overlap={overlap} chars",
+            "markdown headings",
+            lambda text: chunk_markdown(
+                text, max_chars, overlap, repeat_header, min_chunk_size=min_chars
+            ),
+        )
+    if strategy == "code":
+        max_chars = _chunk_max_chars()
+        return (
+            f"max_chars={max_chars}",
+            "code-aware",
+            lambda text: [
+                ([], piece)
+                for piece in chunk_code(text, max_chars, min_chunk_size=min_chars)
+            ],
+        )
+    settings, kind, chunk = _text_chunker(strategy)
+    if os.getenv("CHUNK_TABLES", "true").lower() == "true":
+        max_chars = _chunk_max_chars()
+        chunk = _keep_tables(chunk, max_chars, repeat_header)
+    return settings, kind, lambda text: [([], piece) for piece in chunk(text)]
+
+
+def _keep_tables(
+    chunk: Callable[[str], list[str]], max_chars: int, repeat_header: bool
+) -> Callable[[str], list[str]]:
+    """Wrap `chunk` so the markdown tables in a text (as PDF, Word, and HTML
+    extraction render them) are chunks of their own, split only between
+    rows if longer than `max_chars` characters, and the text around them is
+    chunked by `chunk`."""
+
+    def chunk_keeping_tables(text: str) -> list[str]:
+        chunks = []
+        for is_table, part in markdown_tables(text):
+            if is_table:
+                chunks.extend(chunk_markdown_table(part, max_chars, repeat_header))
+            else:
+                chunks.extend(chunk(part))
+        return chunks
+
+    return chunk_keeping_tables
+
+
+def _text_chunker(strategy: str) -> tuple[str, str, Callable[[str], list[str]]]:
+    """`_page_chunker` for the strategies that don't follow headings.
+
+    Chunks shorter than CHUNK_MIN_CHARS characters (default 100), such as
+    the fragment left at the end of a page, are merged into a neighbour.
+    """
+    min_chars = int(os.getenv("CHUNK_MIN_CHARS", "100"))
+    if strategy == "sentences":
+        max_chars = _chunk_max_chars()
+        overlap = int(os.getenv("CHUNK_OVERLAP_SENTENCES", "1"))
+        return (
+            f"max_chars={max_chars}, overlap={overlap} sentences",
+            "sentence-aware",
+            lambda text: chunk_by_sentences(
+                text, max_chars, overlap, min_chunk_size=min_chars
+            ),
+        )
+    if strategy == "recursive":
+        max_chars = _chunk_max_chars()
+        overlap = int(os.getenv("CHUNK_OVERLAP_CHARS", "100"))
+        separators = [
+            name.strip()
+            for name in os.getenv("CHUNK_SEPARATORS", DEFAULT_SEPARATORS).split(",")
+            if name.strip()
+        ]
+        return (
+            f"max_chars={max_chars}, overlap={overlap} chars, "
+            f"separators={','.join(separators)}",
+            "recursive",
+            lambda text: chunk_recursive(
+                text, max_chars, overlap, separators, min_chunk_size=min_chars
+            ),
+        )
+    if strategy == "semantic":
+        max_chars = _chunk_max_chars()
+        threshold = float(os.getenv("CHUNK_SIMILARITY_THRESHOLD", "0.3"))
+        return (
+            f"max_chars={max_chars}, similarity threshold={threshold}",
+            "semantic · Ollama sentence embeddings",
+            lambda text: _chunk_semantic(text, threshold, max_chars, min_chars),
+        )
+    max_tokens = _chunk_max_tokens(model_tokens=strategy == "model_tokens")
+    overlap_tokens = int(os.getenv("CHUNK_OVERLAP_TOKENS", "32"))
+    if strategy == "model_tokens":
+        tokenizer = os.getenv("CHUNK_TOKENIZER", "").strip()
+        if not tokenizer:
+            raise ValueError(
+                "Chunking by model tokens needs CHUNK_TOKENIZER, the path of the "
+                "model's tokenizer.json."
+            )
+        return (
+            f"max_tokens={max_tokens}, overlap={overlap_tokens}, "
+            f"tokenizer={Path(tokenizer).parent.name or tokenizer}",
+            "model-token-aware",
+            lambda text: chunk_by_model_tokens(
+                text, tokenizer, max_tokens, overlap_tokens, min_chunk_size=min_chars
+            ),
+        )
+    if os.getenv("CHUNK_TOKEN_OVERLAP", "words").strip().lower() == "sentences":
+        overlap_sentences = int(os.getenv("CHUNK_OVERLAP_SENTENCES", "1"))
+        return (
+            f"max_tokens={max_tokens}, overlap={overlap_sentences} sentences",
+            "token-aware",
+            lambda text: chunk_by_tokens(
+                text,
+                max_tokens,
+                overlap_tokens,
+                overlap_sentences=overlap_sentences,
+                min_chunk_size=min_chars,
+                word_chars=token_word_chars(),
+            ),
+        )
+
+    def chunk_tokens(text: str) -> list[str]:
+        if len(text) >= PARALLEL_CHUNK_CHARS:
+            chunk = chunk_by_tokens_parallel
+        else:
+            chunk = chunk_by_tokens
+        return chunk(
+            text,
+            max_tokens,
+            overlap_tokens,
+            min_chunk_size=min_chars,
+            word_chars=token_word_chars(),
+        )
+
+    return f"max_tokens={max_tokens}, overlap={overlap_tokens}", "token-aware", chunk_tokens
+
+
+def _chunk_semantic(
+    text: str, threshold: float, max_chars: int, min_chars: int = 0
+) -> list[str]:
+    """Chunk `text` where adjacent sentences' embeddings fall below
+    `threshold` cosine similarity (see `chunk_semantic`)."""
+    sentences = split_sentences(text)
+    batch_size = max(1, int(os.getenv("EMBED_BATCH_SIZE", "64")))
+    embeddings: list[list[float]] = []
+    for start in range(0, len(sentences), batch_size):
+        embeddings.extend(embed_texts(sentences[start : start + batch_size]))
+    return chunk_semantic(
+        text, embeddings, threshold, max_chars, min_chunk_size=min_chars
+    )
+
+
+def fusion_weights(override: dict[str, float] | None = None) -> dict[str, float]:
+    """Resolve each retriever's fusion weight.
+
+    Every retriever defaults to 1.0; FUSION_WEIGHTS (e.g. "vector=0.7,bm25=0.3")
+    sets the corpus-wide blend and `override` adjusts it for one query.
+    A weight of 0 turns a retriever off.
+    """
+    weights = dict.fromkeys(RETRIEVERS, 1.0)
+    weights.update(parse_fusion_weights(os.getenv("FUSION_WEIGHTS", "")))
+    weights.update(override or {})
+    return weights
+
+
+def bm25_stopwords() -> str | list[str] | None:
+    """The stopwords BM25 drops without stemming (see `BM25Index`).
+
+    BM25_STOPWORDS_FILE, a file of words one per line, wins over
+    BM25_STOPWORDS, a language code or "auto" to detect it from the corpus.
+    Neither is needed with BM25_LANGUAGE, which drops its stopwords anyway.
+    """
+    path = os.getenv("BM25_STOPWORDS_FILE", "").strip()
+    if path:
+        lines = Path(path).expanduser().read_text(encoding="utf-8").splitlines()
+        return [line.strip() for line in lines if line.strip()]
+    return os.getenv("BM25_STOPWORDS", "").strip() or None
+
+
+def bm25_synonyms() -> str | dict[str, list[str]] | None:
+    """The synonyms BM25 expands query words with (see `BM25Index`):
+    BM25_SYNONYMS, "builtin" for the bundled table or the path of a JSON
+    file mapping words to lists of synonyms. Unset = no expansion.
+    """
+    spec = os.getenv("BM25_SYNONYMS", "").strip()
+    if not spec or spec == "builtin":
+        return spec or None
+    return json.loads(Path(spec).expanduser().read_text(encoding="utf-8"))
+
+
+# The fields chunks are indexed as with BM25_FIELD_WEIGHTS
+BM25_FIELDS = ("title", "section", "body")
+
+
+def bm25_field_weights() -> dict[str, float] | None:
+    """Weights of the BM25 fields chunks are indexed as, from
+    BM25_FIELD_WEIGHTS ("title=3,section=2"), or None to index chunks as
+    plain text.
+
+    With weights, each chunk is a "title" and "section" field (its
+    document's title and section heading) and a "body" field, its text, so
+    a query term in a heading ranks higher than one in the body. Fields
+    left out weigh 1.
+    """
+    spec = os.getenv("BM25_FIELD_WEIGHTS", "").strip()
+    if not spec:
+        return None
+    weights: dict[str, float] = {}
+    for part in spec.split(","):
+        if not part.strip():
+            raise ValueError(
+                f"Empty entry in BM25_FIELD_WEIGHTS '{spec}'. "
+                "Expected name=weight pairs separated by commas."
+            )
+        name, sep, value = part.partition("=")
+        name = name.strip().lower()
+        if not sep or name not in BM25_FIELDS:
+            raise ValueError(
+                f"Invalid BM25 field weight '{part.strip()}'. "
+                f"Expected name=weight with name in: {', '.join(BM25_FIELDS)}."
+            )
+        try:
+            weights[name] = float(value)
+        except ValueError:
+            raise ValueError(
+                f"BM25 field weight for '{name}' must be a number, "
+                f"got '{value.strip()}'."
+            ) from None
+        if weights[name] < 0:
+            raise ValueError(f"BM25 field weight for '{name}' must not be negative.")
+    return weights
+
+
+def _bm25_index(cached: list[dict], document_languages: bool, **settings) -> BM25Index:
+    """The BM25 index of all the `cached` chunks, built with `settings`
+    (keyword arguments of `BM25Index`), each chunk analyzed in its
+    document's language if `document_languages`.
+
+    Each chunk's position in the cache is its "chunk" metadata, so a query
+    limited to some chunks filters this one index rather than building its
+    own. The index is kept in BM25_INDEX_CACHE, tagged with the chunk cache
+    generation and a hash of the settings, so queries reuse it until either
+    changes instead of re-tokenizing the whole cache each time.
+    """
+    built_from = json.dumps(
+        [_chunk_cache_generation().decode("ascii"), document_languages, settings],
+        sort_keys=True,
+    )
+    key = hashlib.sha256(built_from.encode("utf-8")).hexdigest().encode("ascii")
+    if BM25_INDEX_CACHE.exists():
+        try:
+            stored_key, _, data = read_private(BM25_INDEX_CACHE).partition(b"\n")
+            if stored_key == key:
+                return BM25Index.from_bytes(data)
+        except (RuntimeError, ValueError):
+            pass  # Written by another version or with another passphrase
+    # Enriched chunks are matched by their context sentence too
+    documents = [enriched_text(e["text"], e.get("context")) for e in cached]
+    if settings.get("field_weights") is not None:
+        documents = [
+            {
+                "title": e.get("title") or "",
+                "section": e.get("section") or "",
+                "body": text,
+            }
+            for e, text in zip(cached, documents)
+        ]
+    index = BM25Index(
+        documents,
+        languages=(
+            [e.get("language") for e in cached] if document_languages else None
+        ),
+        metadata=[{"chunk": i} for i in range(len(cached))],
+        **settings,
+    )
+    write_private(BM25_INDEX_CACHE, key + b"\n" + index.to_bytes())
+    return index
+
+
+def retrieve(
+    question: str,
+    weights: dict[str, float] | None = None,
+    keywords: list[str] | None = None,
+) -> list[dict]:
+    """Find the chunks most relevant to the question using hybrid search.
 
     Pipeline:
-        Embed query (Python/Ollama)
+        Spelling correction (optional, Rust)
+        → Embed query (Python/Ollama)
+        → Semantic cache lookup (reuse hits for near-identical queries)
         → Vector search (Python/Qdrant)
         → BM25 keyword search (Rust)
-        → Reciprocal Rank Fusion (merge results)
-        → Build context
-        → LLM response (Python/Ollama)
+        → Weighted Reciprocal Rank Fusion (merge results)
+        → Cross-encoder rerank (optional, ONNX)
+
+    `weights` overrides the configured fusion weights for this query (see
+    `fusion_weights`), and `keywords` limits it to chunks tagged with any of
+    them at ingest (see `chunk_keywords`). Returns hits as {"text", "score",
+    "source", "page", "title", "section", "keywords", "summary"} dicts,
+    best first.
     """
+    blend = fusion_weights(weights)
+    search_top_k = int(os.getenv("SEARCH_TOP_K", "10"))
+    min_score = float(os.getenv("SEARCH_MIN_SCORE", "0.2"))
+    context_top_k = int(os.getenv("CONTEXT_TOP_K", "3"))
+
+    # With reranking on, retrieve a wider candidate pool for the
+    # cross-encoder to rescore
+    use_reranker = reranker_enabled()
+    rerank_candidates = int(os.getenv("RERANK_CANDIDATES", "50"))
+    if use_reranker:
+        search_top_k = max(search_top_k, rerank_candidates)
+
+    cached = _load_chunk_cache()
+    question = _correct_spelling(question, cached)
+
     console.print(f'  Searching knowledge base for: "[italic]{question}[/italic]"')
+    query_vector = embed_query(question)
+
+    # 0. Reuse retrieval for paraphrased repeats of a recent query
+    # (only for the configured blend; cached hits don't reflect overrides)
+    cache = _get_retrieval_cache() if not weights and not keywords else None
+    cached_hits = cache.lookup(query_vector) if cache else None
+    if cached_hits:
+        hits, similarity = cached_hits
+        console.print(
+            f"  Reusing {len(hits)} chunks from a similar earlier query "
+            f"[dim](similarity {similarity:.3f})[/dim]"
+        )
+        return hits
 
     # 1. Vector search via Qdrant
-    console.print("  Running vector search [dim]\\[Qdrant][/dim]...")
-    query_vector = embed_query(question)
-    client = create_client()
-    vector_results = search(client, query_vector, top_k=10, min_score=0.2)
-    console.print(f"    → {len(vector_results)} vector matches")
+    points: list[tuple[dict, float]] = []
+    if blend["vector"] > 0:
+        console.print("  Running vector search [dim]\\[Qdrant][/dim]...")
+        client = create_client()
+        points = search_points(
+            client,
+            query_vector,
+            top_k=search_top_k,
+            min_score=min_score,
+            keywords=keywords,
+        )
+        console.print(f"    → {len(points)} vector matches")
+    vector_results = [(payload["text"], score) for payload, score in points]
+
+    # Remember where each chunk came from so answers can cite it, and the
+    # parent of each child chunk
+    origins: dict[str, dict] = {
+        payload["text"]: _origin(payload) for payload, _ in points
+    }
+    parents: dict[str, tuple[str, str]] = {
+        payload["text"]: (payload["parent_id"], payload["parent_text"])
+        for payload, _ in points
+        if payload.get("parent_id")
+    }
 
     # 2. BM25 keyword search via Rust
-    cached_chunks = _load_chunk_cache()
     bm25_results: list[tuple[str, float]] = []
 
-    if cached_chunks:
+    # Limited to chunks with any of the keywords, by their "chunk" metadata
+    bm25_filter = None
+    if keywords:
+        wanted = {keyword.lower() for keyword in keywords}
+        bm25_filter = {
+            "chunk": [
+                i
+                for i, e in enumerate(cached)
+                if wanted.intersection(e.get("keywords", ()))
+            ]
+        }
+    if cached and blend["bm25"] > 0 and (bm25_filter is None or bm25_filter["chunk"]):
         console.print("  Running BM25 keyword search [dim]\\[Rust][/dim]...")
-        index = BM25Index(cached_chunks)
-        bm25_hits = index.search(question, top_k=10)
-        bm25_results = [(cached_chunks[idx], score) for idx, score in bm25_hits]
+        cached_texts = [entry["text"] for entry in cached]
+        language = os.getenv("BM25_LANGUAGE") or None
+        index = _bm25_index(
+            cached,
+            # Each chunk analyzed in its document's detected language
+            document_languages=(
+                os.getenv("BM25_DOCUMENT_LANGUAGES", "false").lower() == "true"
+            ),
+            language=language,
+            stopwords=bm25_stopwords(),
+            # A language analyzer stems already
+            stemmer=None if language else os.getenv("BM25_STEMMER") or None,
+            fold_accents=os.getenv("BM25_FOLD_ACCENTS", "false").lower() == "true",
+            ngrams=int(os.getenv("BM25_NGRAMS", "1")),
+            word_chars=token_word_chars(),
+            lemmatize=os.getenv("BM25_LEMMATIZE", "false").lower() == "true",
+            normalize_numbers=(
+                os.getenv("BM25_NORMALIZE_NUMBERS", "false").lower() == "true"
+            ),
+            keep_compounds=(
+                os.getenv("BM25_KEEP_COMPOUNDS", "false").lower() == "true"
+            ),
+            field_weights=bm25_field_weights(),
+            synonyms=bm25_synonyms(),
+            fuzzy=int(os.getenv("BM25_FUZZY", "0")),
+            fuzzy_penalty=float(os.getenv("BM25_FUZZY_PENALTY", "0.5")),
+        )
+        bm25_hits = index.search(question, top_k=search_top_k, filter=bm25_filter)
+        bm25_results = [(cached_texts[idx], score) for idx, score in bm25_hits]
+        for idx, _ in bm25_hits:
+            origins.setdefault(cached_texts[idx], _origin(cached[idx]))
+            if cached[idx].get("parent_id"):
+                parents.setdefault(
+                    cached_texts[idx],
+                    (cached[idx]["parent_id"], cached[idx]["parent_text"]),
+                )
         console.print(f"    → {len(bm25_results)} keyword matches")
 
-    # 3. Merge results using Reciprocal Rank Fusion
-    merged = _reciprocal_rank_fusion(vector_results, bm25_results, top_k=3)
+    # 3. Merge results using weighted Reciprocal Rank Fusion
+    merged = _reciprocal_rank_fusion(
+        {"vector": vector_results, "bm25": bm25_results},
+        weights=blend,
+        top_k=rerank_candidates if use_reranker else search_top_k,
+    )
+
+    # Swap child chunks for their parents, each parent once at its best
+    # child's rank
+    if parents:
+        merged = _parent_results(merged, parents, origins)
+    if not use_reranker:
+        merged = merged[:context_top_k]
+
+    # 4. Optionally rescore candidates with the cross-encoder
+    if use_reranker and merged:
+        console.print(
+            f"  Reranking {len(merged)} candidates [dim]\\[ONNX cross-encoder][/dim]..."
+        )
+        merged = get_reranker().rerank(question, merged, top_k=context_top_k)
+
+    if merged:
+        scores_str = ", ".join(f"{score:.3f}" for _, score in merged)
+        score_kind = "rerank" if use_reranker else "hybrid"
+        console.print(
+            f"  Found [green]{len(merged)}[/green] relevant chunks "
+            f"({score_kind} scores: {scores_str})"
+        )
+
+    unknown = _origin({})
+    hits = [
+        {"text": text, "score": score, **origins.get(text, unknown)}
+        for text, score in merged
+    ]
+    if cache and hits:
+        cache.add(query_vector, hits)
+    return hits
+
+
+def _parent_results(
+    results: list[tuple[str, float]],
+    parents: dict[str, tuple[str, str]],
+    origins: dict[str, dict],
+) -> list[tuple[str, float]]:
+    """Replace the child chunks among ranked `results` with their parent
+    chunks' text, keeping each parent only where it first ranks. `parents`
+    maps child texts to (parent_id, parent_text); `origins` gains entries
+    for the parents' texts."""
+    seen: set[str] = set()
+    out = []
+    for text, score in results:
+        if text in parents:
+            parent_id, parent_text = parents[text]
+            if parent_id in seen:
+                continue
+            seen.add(parent_id)
+            origins.setdefault(parent_text, origins.get(text, _origin({})))
+            text = parent_text
+        out.append((text, score))
+    return out
 
-    if not merged:
+
+def _origin(entry: dict) -> dict:
+    """The ORIGIN_FIELDS of a payload or cache entry (None where missing)."""
+    return {field: entry.get(field) for field in ORIGIN_FIELDS}
+
+
+def _location_label(hit: dict) -> str:
+    label = f" | Page {hit['page']}" if hit.get("page") is not None else ""
+    if hit.get("section"):
+        label += f" | {hit['section']}"
+    return label
+
+
+def query_with_context(
+    question: str,
+    history: list[dict] | None = None,
+    weights: dict[str, float] | None = None,
+) -> tuple[str, list[dict]]:
+    """Answer a question and return the retrieved hits used as context.
+
+    `history` holds earlier chat turns as {"role", "content"} messages.
+    `weights` overrides the fusion weights for this question.
+    """
+    hits = retrieve(question, weights=weights)
+
+    if not hits:
         return (
             "I couldn't find any relevant information in the knowledge base. "
             "Please make sure you've ingested documents first with "
+            "`rusty-rag ingest <file>`.",
+            [],
+        )
+
+    # Build context from retrieved chunks, with pages and sections so the
+    # LLM can cite them
+    context = "\n\n".join(
+        f"[Chunk {i + 1}{_location_label(hit)} | Score: {hit['score']:.3f}]\n"
+        f"{hit['text']}"
+        for i, hit in enumerate(hits)
+    )
+
+    # Generate LLM response
+    console.print("  Generating response [dim]\\[Ollama][/dim]...")
+    return ask(question, context=context, history=history), hits
+
+
+def query(question: str, weights: dict[str, float] | None = None) -> str:
+    """Query the knowledge base using hybrid search (vector + BM25).
+
+    Pipeline:
+        Hybrid retrieval (see `retrieve`)
+        → Build context
+        → LLM response (Python/Ollama)
+    """
+    answer, _ = query_with_context(question, weights=weights)
+    return answer
+
+
+SUGGEST_SYSTEM = (
+    "You help new users explore a document collection. Given sample "
+    "passages from the collection, propose questions that these passages "
+    "answer well. Each question must be self-contained and answerable from "
+    "the passages alone. Reply with one question per line and nothing else."
+)
+
+
+def suggest_questions(n: int = 5, sample_size: int | None = None) -> list[str]:
+    """Propose questions the knowledge base can answer well.
+
+    Samples chunks spread evenly across the local chunk cache (so every
+    ingested document is represented) and asks the LLM for questions.
+    """
+    sample_size = sample_size or int(os.getenv("SUGGEST_SAMPLE_CHUNKS", "8"))
+    min_tokens = 20
+
+    chunks = [
+        e["text"] for e in _load_chunk_cache() if token_count(e["text"]) >= min_tokens
+    ]
+    if not chunks:
+        raise ValueError(
+            "The knowledge base is empty. Ingest documents first with "
             "`rusty-rag ingest <file>`."
         )
 
-    scores_str = ", ".join(f"{score:.3f}" for _, score in merged)
+    step = max(1, len(chunks) // sample_size)
+    sample = chunks[::step][:sample_size]
     console.print(
-        f"  Found [green]{len(merged)}[/green] relevant chunks "
-        f"(hybrid scores: {scores_str})"
+        f"  Sampled [green]{len(sample)}[/green] of {len(chunks)} chunks "
+        "[dim]\\[Ollama][/dim]..."
     )
 
-    # 4. Build context from retrieved chunks
-    context = "\n\n".join(
-        f"[Chunk {i + 1} | Score: {score:.3f}]\n{text}"
-        for i, (text, score) in enumerate(merged)
+    passages = "\n\n".join(
+        f"[Passage {i + 1}]\n{text}" for i, text in enumerate(sample)
     )
+    response = complete(f"{passages}\n\nPropose {n} questions.", system=SUGGEST_SYSTEM)
 
-    # 5. Generate LLM response
-    console.print("  Generating response [dim]\\[Ollama][/dim]...")
-    return ask(question, context=context)
+    questions = []
+    for line in response.splitlines():
+        line = line.strip().lstrip("-*•").strip()
+        # Drop list numbering like "1." or "2)"
+        head, _, rest = line.partition(" ")
+        if head.rstrip(".)").isdigit():
+            line = rest.strip()
+        if line.endswith("?"):
+            questions.append(line)
+    return questions[:n]
 
 
 def _reciprocal_rank_fusion(
-    vector_results: list[tuple[str, float]],
-    bm25_results: list[tuple[str, float]],
+    results: dict[str, list[tuple[str, float]]],
+    weights: dict[str, float] | None = None,
     top_k: int = 3,
     k: int = 60,
 ) -> list[tuple[str, float]]:
-    """Merge two ranked result lists using Reciprocal Rank Fusion (RRF).
+    """Merge ranked result lists using weighted Reciprocal Rank Fusion (RRF).
 
-    RRF is a simple, parameter-free method for combining ranked lists:
-        RRF_score(d) = Σ 1 / (k + rank_i(d))
+    RRF is a simple method for combining ranked lists:
+        RRF_score(d) = Σ w_i / (k + rank_i(d))
 
-    where k=60 is the standard constant and rank_i is the position of
-    document d in result list i.
+    where k=60 is the standard constant, rank_i is the position of
+    document d in result list i, and w_i is that retriever's weight
+    (1.0 when not given, which is plain RRF). Fused in Rust by `rrf_fuse`.
     """
-    scores: dict[str, float] = {}
-
-    for rank, (text, _) in enumerate(vector_results):
-        scores[text] = scores.get(text, 0.0) + 1.0 / (k + rank + 1)
-
-    for rank, (text, _) in enumerate(bm25_results):
-        scores[text] = scores.get(text, 0.0) + 1.0 / (k + rank + 1)
-
-    sorted_results = sorted(scores.items(), key=lambda x: x[1], reverse=True)
-    return sorted_results[:top_k]
+    names = list(results)
+    return rrf_fuse(
+        [results[name] for name in names],
+        k=k,
+        weights=[(weights or {}).get(name, 1.0) for name in names],
+        top_k=top_k,
+    )
@@ -0,0 +1,92 @@
+"""Reading datasets of JSON records as documents.
+
+Each line of a JSON Lines (.jsonl) file, or each object of a .json file
+holding an array of objects, is one record: its text is taken from one
+field and any other fields can be kept as metadata. Nested fields are
+named by dotted paths, e.g. "meta.url".
+"""
+
+import json
+from pathlib import Path
+
+from . import read_text_file
+
+DEFAULT_TEXT_FIELD = "text"
+
+# A record: its position (line number, or index in a JSON array), text, and
+# metadata fields
+Record = tuple[int, str, dict]
+
+
+def parse_fields(spec: str | None) -> tuple[str, ...]:
+    """Parse a comma-separated field list such as "url, meta.author"."""
+    return tuple(field.strip() for field in (spec or "").split(",") if field.strip())
+
+
+def field_value(record: dict, field: str):
+    """Look up a (dotted) field in a record, or None if it is missing."""
+    value = record
+    for key in field.split("."):
+        if not isinstance(value, dict) or key not in value:
+            return None
+        value = value[key]
+    return value
+
+
+def load_records(file_path: str) -> list[tuple[int, dict]] | None:
+    """Load the raw records of a .jsonl file, or of a .json file holding an
+    array of objects, numbered from 1.
+
+    Returns None for a .json file of any other shape (e.g. a config file),
+    which is better ingested as plain text. Raises ValueError for invalid
+    JSON Lines, naming the line.
+    """
+    text = read_text_file(file_path)
+    if Path(file_path).suffix.lower() != ".jsonl":
+        try:
+            data = json.loads(text)
+        except json.JSONDecodeError:
+            return None
+        if not isinstance(data, list) or not all(isinstance(r, dict) for r in data):
+            return None
+        return list(enumerate(data, 1))
+
+    records = []
+    for n, line in enumerate(text.splitlines(), 1):
+        if not line.strip():
+            continue
+        try:
+            record = json.loads(line)
+        except json.JSONDecodeError as e:
+            raise ValueError(f"Invalid JSON on line {n} of {file_path}: {e.msg}") from e
+        if not isinstance(record, dict):
+            raise ValueError(f"Line {n} of {file_path} is not a JSON object")
+        records.append((n, record))
+    return records
+
+
+def map_records(
+    records: list[tuple[int, dict]],
+    text_field: str = DEFAULT_TEXT_FIELD,
+    metadata_fields: tuple[str, ...] = (),
+) -> list[Record]:
+    """Take each record's text from `text_field` and keep `metadata_fields`.
+
+    A text field holding a list of strings is joined into paragraphs.
+    Records whose text is missing or empty are skipped, as are missing
+    metadata fields.
+    """
+    mapped = []
+    for n, record in records:
+        text = field_value(record, text_field)
+        if isinstance(text, list):
+            text = "\n\n".join(item for item in text if isinstance(item, str))
+        if not isinstance(text, str) or not text.strip():
+            continue
+        metadata = {}
+        for field in metadata_fields:
+            value = field_value(record, field)
+            if value is not None:
+                metadata[field] = value
+        mapped.append((n, text, metadata))
+    return mapped
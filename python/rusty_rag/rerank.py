@@ -0,0 +1,100 @@
+"""In-process cross-encoder reranking with ONNX Runtime.
+
+A cross-encoder (e.g. bge-reranker-base exported to ONNX) scores each
+(question, chunk) pair jointly, which is far more precise than comparing
+independent embeddings. It is run over the top fused candidates right
+before the context is built.
+
+Enable with:
+    RERANKER=onnx
+    RERANKER_MODEL_DIR=~/.rusty_rag/models/bge-reranker-base   # model.onnx + tokenizer.json
+
+Requires the optional dependencies: pip install "rusty-rag[rerank]"
+"""
+
+import os
+from functools import lru_cache
+from pathlib import Path
+
+DEFAULT_MODEL_DIR = Path.home() / ".rusty_rag" / "models" / "bge-reranker-base"
+
+
+def reranker_enabled() -> bool:
+    """Whether reranking is turned on in the config."""
+    return os.getenv("RERANKER", "none").lower() == "onnx"
+
+
+class CrossEncoderReranker:
+    """Scores (query, passage) pairs with an ONNX cross-encoder model."""
+
+    def __init__(self, model_dir: Path, max_length: int = 512, batch_size: int = 16):
+        try:
+            import onnxruntime as ort
+            from tokenizers import Tokenizer
+        except ImportError as e:
+            raise RuntimeError(
+                "Reranking needs extra packages. Install with: "
+                'pip install "rusty-rag[rerank]"'
+            ) from e
+
+        model_path = model_dir / "model.onnx"
+        tokenizer_path = model_dir / "tokenizer.json"
+        for path in (model_path, tokenizer_path):
+            if not path.exists():
+                raise FileNotFoundError(f"Reranker file not found: {path}")
+
+        self.tokenizer = Tokenizer.from_file(str(tokenizer_path))
+        self.tokenizer.enable_truncation(max_length=max_length)
+        self.tokenizer.enable_padding()
+        self.session = ort.InferenceSession(
+            str(model_path), providers=["CPUExecutionProvider"]
+        )
+        self.input_names = {i.name for i in self.session.get_inputs()}
+        self.batch_size = batch_size
+
+    def score(self, query: str, passages: list[str]) -> list[float]:
+        """Return one relevance logit per passage (higher is better)."""
+        import numpy as np
+
+        scores: list[float] = []
+        for start in range(0, len(passages), self.batch_size):
+            batch = passages[start : start + self.batch_size]
+            encodings = self.tokenizer.encode_batch([(query, p) for p in batch])
+
+            feeds = {
+                "input_ids": np.array([e.ids for e in encodings], dtype=np.int64),
+                "attention_mask": np.array(
+                    [e.attention_mask for e in encodings], dtype=np.int64
+                ),
+            }
+            if "token_type_ids" in self.input_names:
+                feeds["token_type_ids"] = np.array(
+                    [e.type_ids for e in encodings], dtype=np.int64
+                )
+
+            logits = self.session.run(None, feeds)[0]
+            scores.extend(float(x) for x in np.asarray(logits).reshape(len(batch), -1)[:, 0])
+
+        return scores
+
+    def rerank(
+        self, query: str, results: list[tuple[str, float]], top_k: int
+    ) -> list[tuple[str, float]]:
+        """Re-order (text, score) results by cross-encoder score."""
+        if not results:
+            return []
+        scores = self.score(query, [text for text, _ in results])
+        ranked = sorted(
+            zip((text for text, _ in results), scores),
+            key=lambda x: x[1],
+            reverse=True,
+        )
+        return ranked[:top_k]
+
+
+@lru_cache(maxsize=1)
+def get_reranker() -> CrossEncoderReranker:
+    """Load the configured reranker once per process."""
+    model_dir = Path(os.getenv("RERANKER_MODEL_DIR", str(DEFAULT_MODEL_DIR))).expanduser()
+    max_length = int(os.getenv("RERANKER_MAX_LENGTH", "512"))
+    return CrossEncoderReranker(model_dir, max_length=max_length)
@@ -0,0 +1,61 @@
+"""In-memory semantic cache for retrieval results.
+
+Paraphrased repeats are common in chat ("what datasets were used?" /
+"which datasets did they use?"). Their query embeddings are nearly
+identical, so the previous retrieval can be reused without another
+Qdrant + BM25 round trip.
+"""
+
+import math
+from collections import OrderedDict
+
+
+def cosine_similarity(a: list[float], b: list[float]) -> float:
+    """Cosine similarity of two equal-length vectors (0.0 if either is zero)."""
+    dot = sum(x * y for x, y in zip(a, b))
+    norm_a = math.sqrt(sum(x * x for x in a))
+    norm_b = math.sqrt(sum(y * y for y in b))
+    if norm_a == 0.0 or norm_b == 0.0:
+        return 0.0
+    return dot / (norm_a * norm_b)
+
+
+class SemanticRetrievalCache:
+    """LRU cache of (query embedding → retrieval hits)."""
+
+    def __init__(self, threshold: float = 0.95, max_entries: int = 64):
+        self.threshold = threshold
+        self.max_entries = max_entries
+        self._entries: OrderedDict[int, tuple[list[float], list[dict]]] = OrderedDict()
+        self._next_key = 0
+
+    def lookup(self, vector: list[float]) -> tuple[list[dict], float] | None:
+        """Return (hits, similarity) for the most similar cached query.
+
+        Returns None unless the best similarity reaches the threshold.
+        """
+        best_key, best_sim = None, -1.0
+        for key, (cached_vector, _) in self._entries.items():
+            sim = cosine_similarity(vector, cached_vector)
+            if sim > best_sim:
+                best_key, best_sim = key, sim
+
+        if best_key is None or best_sim < self.threshold:
+            return None
+
+        self._entries.move_to_end(best_key)
+        return self._entries[best_key][1], best_sim
+
+    def add(self, vector: list[float], hits: list[dict]) -> None:
+        """Cache the hits retrieved for a query embedding."""
+        self._entries[self._next_key] = (vector, hits)
+        self._next_key += 1
+        while len(self._entries) > self.max_entries:
+            self._entries.popitem(last=False)
+
+    def clear(self) -> None:
+        """Drop all entries (e.g. after new documents are ingested)."""
+        self._entries.clear()
+
+    def __len__(self) -> int:
+        return len(self._entries)
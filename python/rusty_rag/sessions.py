@@ -0,0 +1,105 @@
+"""Persistent chat sessions stored under ~/.rusty_rag/sessions.
+
+Each session is one JSON file (encrypted at rest when a passphrase is
+configured) holding every turn together with the chunks retrieved for it,
+so a research thread can be resumed — and audited — after a restart.
+"""
+
+import json
+import uuid
+from dataclasses import asdict, dataclass, field
+from datetime import datetime, timezone
+from pathlib import Path
+
+from rich.console import Console
+
+from .storage import read_private, write_private
+
+SESSIONS_DIR = Path.home() / ".rusty_rag" / "sessions"
+
+console = Console()
+
+
+def _now() -> str:
+    return datetime.now(timezone.utc).isoformat(timespec="seconds")
+
+
+@dataclass
+class Turn:
+    question: str
+    answer: str
+    context: list[dict] = field(default_factory=list)  # [{"text", "score", "source"}]
+    timestamp: str = field(default_factory=_now)
+
+
+@dataclass
+class Session:
+    id: str
+    created_at: str
+    updated_at: str
+    title: str = ""
+    turns: list[Turn] = field(default_factory=list)
+
+    def add_turn(self, question: str, answer: str, context: list[dict]) -> None:
+        """Record a turn along with the retrieval hits it used."""
+        if not self.title:
+            self.title = question[:60]
+        self.turns.append(Turn(question=question, answer=answer, context=context))
+        self.updated_at = _now()
+
+    def history(self, max_turns: int) -> list[dict]:
+        """Return the last `max_turns` turns as chat messages for the LLM."""
+        messages = []
+        for turn in self.turns[-max_turns:] if max_turns > 0 else []:
+            messages.append({"role": "user", "content": turn.question})
+            messages.append({"role": "assistant", "content": turn.answer})
+        return messages
+
+
+def _session_path(session_id: str) -> Path:
+    return SESSIONS_DIR / f"{session_id}.json"
+
+
+def new_session() -> Session:
+    """Create a new, empty session with a short random ID."""
+    now = _now()
+    return Session(id=uuid.uuid4().hex[:8], created_at=now, updated_at=now)
+
+
+def save_session(session: Session) -> None:
+    """Write the session to disk."""
+    data = json.dumps(asdict(session), ensure_ascii=False, indent=2).encode("utf-8")
+    write_private(_session_path(session.id), data)
+
+
+def load_session(session_id: str) -> Session:
+    """Load a session by ID (a unique prefix is enough)."""
+    path = _session_path(session_id)
+    if not path.exists():
+        matches = sorted(SESSIONS_DIR.glob(f"{session_id}*.json"))
+        if len(matches) != 1:
+            raise FileNotFoundError(f"No unique session matching '{session_id}'.")
+        path = matches[0]
+
+    raw = json.loads(read_private(path).decode("utf-8"))
+    raw["turns"] = [Turn(**t) for t in raw.get("turns", [])]
+    return Session(**raw)
+
+
+def list_sessions() -> list[Session]:
+    """Return all saved sessions, most recently updated first.
+
+    Sessions that can't be read (corrupt, or encrypted under another
+    passphrase) are skipped with a warning.
+    """
+    if not SESSIONS_DIR.exists():
+        return []
+    sessions = []
+    for path in sorted(SESSIONS_DIR.glob("*.json")):
+        try:
+            sessions.append(load_session(path.stem))
+        except (ValueError, RuntimeError, TypeError, json.JSONDecodeError) as e:
+            console.print(
+                f"[yellow]Skipping unreadable session {path.name}: {e}[/yellow]"
+            )
+    return sorted(sessions, key=lambda s: s.updated_at, reverse=True)
@@ -0,0 +1,66 @@
+"""Reading and writing local files with optional encryption at rest.
+
+When RUSTY_RAG_PASSPHRASE or RUSTY_RAG_KEYFILE is set, everything written
+through `write_private` (chunk cache, BM25 index cache, chat sessions,
+exports) is encrypted with the Rust core (Argon2id + AES-256-GCM).
+Existing plaintext files are still readable and get encrypted on their
+next write.
+"""
+
+import os
+import tempfile
+from pathlib import Path
+
+from . import encrypt_bytes, decrypt_bytes, is_encrypted
+
+
+def get_passphrase() -> bytes | None:
+    """Return the configured passphrase (or keyfile contents), if any."""
+    keyfile = os.getenv("RUSTY_RAG_KEYFILE")
+    if keyfile:
+        key = Path(keyfile).expanduser().read_bytes().strip()
+        if not key:
+            raise ValueError(f"Keyfile is empty: {keyfile}")
+        return key
+
+    passphrase = os.getenv("RUSTY_RAG_PASSPHRASE")
+    return passphrase.encode("utf-8") if passphrase else None
+
+
+def write_private(path: Path, data: bytes) -> None:
+    """Write bytes to disk, encrypting them if a passphrase is configured.
+
+    The file is readable by its owner only, encrypted or not. It is written
+    to a temporary file of its own and moved into place, so readers never
+    see a partial file and concurrent writers don't clobber each other.
+    """
+    passphrase = get_passphrase()
+    if passphrase:
+        data = encrypt_bytes(data, passphrase)
+
+    path.parent.mkdir(parents=True, exist_ok=True)
+    # mkstemp creates the file with mode 0o600, whatever the umask
+    fd, tmp = tempfile.mkstemp(
+        dir=path.parent, prefix=f".{path.name}.", suffix=".tmp"
+    )
+    try:
+        with os.fdopen(fd, "wb") as f:
+            f.write(data)
+        os.replace(tmp, path)
+    except BaseException:
+        Path(tmp).unlink(missing_ok=True)
+        raise
+
+
+def read_private(path: Path) -> bytes:
+    """Read bytes written by `write_private`, decrypting when needed."""
+    data = path.read_bytes()
+    if not is_encrypted(data):
+        return data
+
+    passphrase = get_passphrase()
+    if not passphrase:
+        raise ValueError(
+            f"{path} is encrypted. Set RUSTY_RAG_PASSPHRASE or RUSTY_RAG_KEYFILE."
+        )
+    return decrypt_bytes(data, passphrase)
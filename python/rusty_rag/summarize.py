@@ -0,0 +1,102 @@
+"""Whole-document summarization with map-reduce over stored chunks.
+
+Documents are usually far larger than the model's context window, so:
+    map:    summarize consecutive groups of chunks that fit the budget
+    reduce: merge partial summaries (recursively if they still don't fit)
+    final:  turn the merged notes into a structured summary
+"""
+
+import os
+
+from rich.console import Console
+
+from . import token_count
+from .db import create_client, get_source_chunks
+from .llm import complete
+
+console = Console()
+
+MAP_SYSTEM = (
+    "You are summarizing one section of a longer document. Write concise "
+    "bullet-point notes covering the key facts, figures, and claims in the "
+    "text. Do not add information that is not in the text."
+)
+
+REDUCE_SYSTEM = (
+    "You are combining partial notes taken from consecutive sections of one "
+    "document. Merge them into a single set of concise bullet-point notes, "
+    "removing duplicates while keeping every distinct fact."
+)
+
+FINAL_SYSTEM = (
+    "You are writing the final summary of a document from notes covering "
+    "the whole of it. Respond in Markdown with exactly these sections:\n"
+    "## Overview\n(2-3 sentences)\n"
+    "## Key Points\n(bullet list)\n"
+    "## Conclusions\n(bullet list)\n"
+    "Only use information from the notes."
+)
+
+
+def _group_by_budget(texts: list[str], budget: int) -> list[list[str]]:
+    """Split texts into consecutive groups whose token counts fit `budget`.
+
+    A single text larger than the budget gets a group of its own.
+    """
+    groups: list[list[str]] = []
+    current: list[str] = []
+    used = 0
+
+    for text in texts:
+        n = token_count(text)
+        if current and used + n > budget:
+            groups.append(current)
+            current, used = [], 0
+        current.append(text)
+        used += n
+
+    if current:
+        groups.append(current)
+    return groups
+
+
+def summarize_texts(texts: list[str], budget: int) -> str:
+    """Map-reduce summarize an ordered list of texts."""
+    # Map: one set of notes per budget-sized group of chunks
+    groups = _group_by_budget(texts, budget)
+    console.print(f"  Summarizing {len(groups)} section(s) [dim]\\[Ollama · map][/dim]...")
+    notes = [complete("\n\n".join(group), system=MAP_SYSTEM) for group in groups]
+
+    # Reduce: merge notes until they fit in a single prompt
+    round_no = 1
+    while len(notes) > 1 and sum(token_count(n) for n in notes) > budget:
+        groups = _group_by_budget(notes, budget)
+        if len(groups) == len(notes):
+            # Each note alone exceeds the budget; merging pairwise still shrinks them
+            groups = [notes[i : i + 2] for i in range(0, len(notes), 2)]
+        console.print(
+            f"  Merging {len(notes)} partial summaries "
+            f"[dim]\\[Ollama · reduce {round_no}][/dim]..."
+        )
+        notes = [complete("\n\n".join(group), system=REDUCE_SYSTEM) for group in groups]
+        round_no += 1
+
+    console.print("  Writing final summary [dim]\\[Ollama][/dim]...")
+    return complete("\n\n".join(notes), system=FINAL_SYSTEM)
+
+
+def summarize(source: str) -> str:
+    """Summarize every chunk ingested from `source`."""
+    budget = int(os.getenv("SUMMARY_CONTEXT_TOKENS", "2048"))
+
+    console.print(f"  Loading chunks for: [bold]{source}[/bold]")
+    client = create_client()
+    chunks = get_source_chunks(client, source)
+    if not chunks:
+        raise ValueError(
+            f"No chunks found for source '{source}'. "
+            "Check the name used when the document was ingested."
+        )
+    console.print(f"  Found [green]{len(chunks)}[/green] chunks.")
+
+    return summarize_texts(chunks, budget)
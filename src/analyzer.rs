@@ -0,0 +1,393 @@
+//! Language-aware text analysis for BM25 indexing.
+//!
+//! An analyzer runs the word tokenizer, drops stopwords for its language,
+//! and reduces the remaining tokens to their Snowball stems, so "running"
+//! and "runs" both index as "run" and "the"/"der"/"le" don't dominate scores.
+
+use anyhow::Result;
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::stopwords;
+use crate::tokenizer;
+
+/// Languages with bundled stopword lists and stemmers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+    Italian,
+    Portuguese,
+    Dutch,
+    Swedish,
+    Danish,
+    Norwegian,
+    Finnish,
+    Russian,
+}
+
+impl Language {
+    pub const ALL: [Language; 12] = [
+        Language::English,
+        Language::German,
+        Language::French,
+        Language::Spanish,
+        Language::Italian,
+        Language::Portuguese,
+        Language::Dutch,
+        Language::Swedish,
+        Language::Danish,
+        Language::Norwegian,
+        Language::Finnish,
+        Language::Russian,
+    ];
+
+    /// Parse an ISO 639-1 code ("en") or English name ("english").
+    pub fn from_name(name: &str) -> Option<Language> {
+        let lang = match name.trim().to_lowercase().as_str() {
+            "en" | "english" => Language::English,
+            "de" | "german" => Language::German,
+            "fr" | "french" => Language::French,
+            "es" | "spanish" => Language::Spanish,
+            "it" | "italian" => Language::Italian,
+            "pt" | "portuguese" => Language::Portuguese,
+            "nl" | "dutch" => Language::Dutch,
+            "sv" | "swedish" => Language::Swedish,
+            "da" | "danish" => Language::Danish,
+            "no" | "nb" | "norwegian" => Language::Norwegian,
+            "fi" | "finnish" => Language::Finnish,
+            "ru" | "russian" => Language::Russian,
+            _ => return None,
+        };
+        Some(lang)
+    }
+
+    /// ISO 639-1 code for this language.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::French => "fr",
+            Language::Spanish => "es",
+            Language::Italian => "it",
+            Language::Portuguese => "pt",
+            Language::Dutch => "nl",
+            Language::Swedish => "sv",
+            Language::Danish => "da",
+            Language::Norwegian => "no",
+            Language::Finnish => "fi",
+            Language::Russian => "ru",
+        }
+    }
+
+    pub fn stopwords(self) -> &'static [&'static str] {
+        match self {
+            Language::English => stopwords::ENGLISH,
+            Language::German => stopwords::GERMAN,
+            Language::French => stopwords::FRENCH,
+            Language::Spanish => stopwords::SPANISH,
+            Language::Italian => stopwords::ITALIAN,
+            Language::Portuguese => stopwords::PORTUGUESE,
+            Language::Dutch => stopwords::DUTCH,
+            Language::Swedish => stopwords::SWEDISH,
+            Language::Danish => stopwords::DANISH,
+            Language::Norwegian => stopwords::NORWEGIAN,
+            Language::Finnish => stopwords::FINNISH,
+            Language::Russian => stopwords::RUSSIAN,
+        }
+    }
+
+    /// The Snowball stemmer of this language.
+    pub fn stemmer(self) -> Stemmer {
+        Stemmer::create(self.algorithm())
+    }
+
+    fn algorithm(self) -> Algorithm {
+        match self {
+            Language::English => Algorithm::English,
+            Language::German => Algorithm::German,
+            Language::French => Algorithm::French,
+            Language::Spanish => Algorithm::Spanish,
+            Language::Italian => Algorithm::Italian,
+            Language::Portuguese => Algorithm::Portuguese,
+            Language::Dutch => Algorithm::Dutch,
+            Language::Swedish => Algorithm::Swedish,
+            Language::Danish => Algorithm::Danish,
+            Language::Norwegian => Algorithm::Norwegian,
+            Language::Finnish => Algorithm::Finnish,
+            Language::Russian => Algorithm::Russian,
+        }
+    }
+
+    /// Whether elided articles ("l'homme", "dell'anno") should be split off.
+    fn uses_elision(self) -> bool {
+        matches!(self, Language::French | Language::Italian)
+    }
+}
+
+/// Characters at the start of a document sampled by `detect_language`.
+const DETECT_SAMPLE_CHARS: usize = 20_000;
+/// Stopword hits needed before `detect_language` commits to a language.
+const DETECT_MIN_HITS: usize = 5;
+
+/// Guess the language of `text` by counting stopword hits per language.
+///
+/// Returns `None` when no language's stopwords appear at all.
+pub fn guess_language(text: &str) -> Option<Language> {
+    best_language(text, 1)
+}
+
+/// Detect the language of a document from the start of its text.
+///
+/// Stricter than `guess_language`: text with only a few stopwords (code,
+/// tables of figures, a stray English word in a list) yields `None`.
+pub fn detect_language(text: &str) -> Option<Language> {
+    let sample = match text.char_indices().nth(DETECT_SAMPLE_CHARS) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    };
+    best_language(sample, DETECT_MIN_HITS)
+}
+
+/// The language with the most stopword hits in `text`, if it has at least
+/// `min_hits`.
+fn best_language(text: &str, min_hits: usize) -> Option<Language> {
+    let tokens = tokenizer::tokenize(text);
+    Language::ALL
+        .iter()
+        .map(|&lang| {
+            let words: HashSet<&str> = lang.stopwords().iter().copied().collect();
+            let hits = tokens.iter().filter(|t| words.contains(t.as_str())).count();
+            (lang, hits)
+        })
+        .filter(|&(_, hits)| hits >= min_hits)
+        .max_by_key(|&(_, hits)| hits)
+        .map(|(lang, _)| lang)
+}
+
+/// The stopwords of the language `name` (a name or code), or of the
+/// language guessed from `sample()` for "auto" (none if it can't be).
+pub fn language_stopwords(name: &str, sample: impl FnOnce() -> String) -> Result<HashSet<String>> {
+    let language = if name.trim().eq_ignore_ascii_case("auto") {
+        guess_language(&sample())
+    } else {
+        match Language::from_name(name) {
+            Some(language) => Some(language),
+            None => anyhow::bail!("Unsupported language: {}", name),
+        }
+    };
+    Ok(language
+        .map(|language| language.stopwords().iter().map(|w| w.to_string()).collect())
+        .unwrap_or_default())
+}
+
+/// The language whose Snowball stemmer `name` (a name or code) stands
+/// for, or English for "porter".
+pub fn stemmer_language(name: &str) -> Result<Language> {
+    let language = if name.trim().eq_ignore_ascii_case("porter") {
+        Some(Language::English)
+    } else {
+        Language::from_name(name)
+    };
+    match language {
+        Some(language) => Ok(language),
+        None => anyhow::bail!("Unsupported stemmer: {}", name),
+    }
+}
+
+/// Tokenizer + stopword filter + stemmer for one language.
+pub struct Analyzer {
+    language: Language,
+    stopwords: HashSet<String>,
+    stemmer: Stemmer,
+    fold_accents: bool,
+}
+
+impl Analyzer {
+    pub fn new(language: Language) -> Self {
+        Analyzer {
+            language,
+            stopwords: language.stopwords().iter().map(|w| w.to_string()).collect(),
+            stemmer: language.stemmer(),
+            fold_accents: false,
+        }
+    }
+
+    /// Fold accents off tokens and stopwords before stemming, so "café" and
+    /// "cafe" are one term.
+    pub fn folding_accents(mut self) -> Self {
+        self.fold_accents = true;
+        self.stopwords = self
+            .stopwords
+            .iter()
+            .map(|w| tokenizer::fold_accents(w))
+            .collect();
+        self
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Whether a lowercase token is one of the language's stopwords.
+    pub fn is_stopword(&self, token: &str) -> bool {
+        self.stopwords.contains(token)
+    }
+
+    /// The stem of a lowercase token.
+    pub fn stem(&self, token: &str) -> String {
+        self.stemmer.stem(token).into_owned()
+    }
+
+    /// Turn text into analyzed terms: lowercase, stopword-free, stemmed.
+    pub fn analyze(&self, text: &str) -> Vec<String> {
+        let tokens = tokenizer::tokenize_with(text, self.fold_accents);
+        self.analyze_tokens(tokens, &HashSet::new())
+    }
+
+    /// `analyze` for already tokenized text (accents folded if the analyzer
+    /// folds them), also dropping the lowercase words in `extra_stopwords`.
+    pub fn analyze_tokens(
+        &self,
+        tokens: Vec<String>,
+        extra_stopwords: &HashSet<String>,
+    ) -> Vec<String> {
+        tokens
+            .into_iter()
+            .filter_map(|token| {
+                let token = if self.language.uses_elision() {
+                    strip_elision(&token)
+                } else {
+                    &token
+                };
+                if token.is_empty()
+                    || self.stopwords.contains(token)
+                    || extra_stopwords.contains(token)
+                {
+                    return None;
+                }
+                Some(self.stemmer.stem(token).into_owned())
+            })
+            .collect()
+    }
+}
+
+/// Drop a short elided prefix such as "l'", "qu'" or "dell'".
+fn strip_elision(token: &str) -> &str {
+    match token.split_once('\'') {
+        Some((prefix, rest)) if prefix.chars().count() <= 4 => rest,
+        _ => token,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_stopwords_and_stems() {
+        let analyzer = Analyzer::new(Language::English);
+        let terms = analyzer.analyze("The runners were running through the forests");
+        assert_eq!(terms, vec!["runner", "run", "forest"]);
+    }
+
+    #[test]
+    fn test_german_analyzer() {
+        let analyzer = Analyzer::new(Language::German);
+        let terms = analyzer.analyze("Die Häuser und das Haus");
+        assert_eq!(terms, vec!["haus", "haus"]);
+    }
+
+    #[test]
+    fn test_french_elision() {
+        let analyzer = Analyzer::new(Language::French);
+        let terms = analyzer.analyze("l'ordinateur et les ordinateurs");
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0], terms[1]);
+    }
+
+    #[test]
+    fn test_extra_stopwords() {
+        let analyzer = Analyzer::new(Language::English);
+        let extra: HashSet<String> = ["figure".to_string()].into();
+        let tokens = tokenizer::tokenize("The figure shows running costs");
+        let terms = analyzer.analyze_tokens(tokens, &extra);
+        assert_eq!(terms, vec!["show", "run", "cost"]);
+    }
+
+    #[test]
+    fn test_language_stopwords() {
+        let english = language_stopwords("en", String::new).unwrap();
+        assert!(english.contains("the"));
+        let guessed = language_stopwords("auto", || "Der Hund und die Katze".into()).unwrap();
+        assert!(guessed.contains("und"));
+        assert!(language_stopwords("auto", || "12345".into())
+            .unwrap()
+            .is_empty());
+        assert!(language_stopwords("klingon", String::new).is_err());
+    }
+
+    #[test]
+    fn test_folding_accents() {
+        let analyzer = Analyzer::new(Language::French).folding_accents();
+        // "où" is a stopword with or without its accent
+        assert_eq!(
+            analyzer.analyze("Où est le café ?"),
+            analyzer.analyze("ou est le cafe")
+        );
+        assert_eq!(analyzer.analyze("ou est le cafe").len(), 1);
+    }
+
+    #[test]
+    fn test_stemmer_language() {
+        let stem = |name, word| {
+            stemmer_language(name)
+                .unwrap()
+                .stemmer()
+                .stem(word)
+                .into_owned()
+        };
+        assert_eq!(stem("english", "running"), "run");
+        assert_eq!(stem("porter", "runs"), "run");
+        assert_eq!(stem("de", "häuser"), "haus");
+        assert!(stemmer_language("klingon").is_err());
+    }
+
+    #[test]
+    fn test_language_from_name() {
+        assert_eq!(Language::from_name("en"), Some(Language::English));
+        assert_eq!(Language::from_name("German"), Some(Language::German));
+        assert_eq!(Language::from_name("klingon"), None);
+        assert_eq!(Language::Spanish.code(), "es");
+    }
+
+    #[test]
+    fn test_guess_language() {
+        assert_eq!(
+            guess_language("This is a document about the history of the city"),
+            Some(Language::English)
+        );
+        assert_eq!(
+            guess_language("Der Hund und die Katze sind nicht im Haus"),
+            Some(Language::German)
+        );
+        assert_eq!(
+            guess_language("El perro y el gato están en la casa con los niños"),
+            Some(Language::Spanish)
+        );
+        assert_eq!(guess_language("12345 67890"), None);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        let french = "Le chat est sur la table et le chien dort dans le jardin avec les enfants.";
+        assert_eq!(detect_language(french), Some(Language::French));
+        // A couple of stopwords aren't enough to go on
+        assert!(guess_language("Revenue of 1200 in 2023").is_some());
+        assert_eq!(detect_language("Revenue of 1200 in 2023"), None);
+        assert_eq!(detect_language(""), None);
+    }
+}
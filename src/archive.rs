@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::io::{Cursor, Read};
+use zip::result::ZipError;
+
+/// Reader for ZIP archives, the container behind DOCX, EPUB, and other
+/// Office formats, on top of the `zip` crate.
+///
+/// Archives come from untrusted files, so an entry is only read up to
+/// `MAX_ENTRY_SIZE`, whatever size the archive claims for it.
+pub struct ZipArchive<'a> {
+    archive: zip::ZipArchive<Cursor<&'a [u8]>>,
+}
+
+/// The most an entry may inflate to: far above any real document part, but
+/// low enough that a zip bomb fails instead of exhausting memory.
+const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+impl<'a> ZipArchive<'a> {
+    /// Parse the archive's central directory.
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        let archive = zip::ZipArchive::new(Cursor::new(data)).map_err(|e| match e {
+            ZipError::InvalidArchive(_) => anyhow::anyhow!("Not a ZIP archive"),
+            e => anyhow::Error::new(e).context("Corrupt ZIP archive"),
+        })?;
+        Ok(Self { archive })
+    }
+
+    /// Whether the archive has an entry called `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.archive.index_for_name(name).is_some()
+    }
+
+    /// Decompress the entry called `name`.
+    pub fn read(&self, name: &str) -> Result<Vec<u8>> {
+        if !self.contains(name) {
+            anyhow::bail!("Missing archive entry: {}", name);
+        }
+        // Reading needs a cursor of its own; the parsed directory is shared
+        let mut archive = self.archive.clone();
+        let entry = archive
+            .by_name(name)
+            .with_context(|| format!("Corrupt ZIP entry: {}", name))?;
+        if entry.size() > MAX_ENTRY_SIZE {
+            anyhow::bail!("ZIP entry too large: {} ({} bytes)", name, entry.size());
+        }
+        let mut out = Vec::new();
+        entry
+            .take(MAX_ENTRY_SIZE + 1)
+            .read_to_end(&mut out)
+            .with_context(|| format!("Failed to inflate ZIP entry: {}", name))?;
+        if out.len() as u64 > MAX_ENTRY_SIZE {
+            anyhow::bail!("ZIP entry inflates past {} bytes: {}", MAX_ENTRY_SIZE, name);
+        }
+        Ok(out)
+    }
+
+    /// Decompress the entry called `name` as UTF-8 text.
+    pub fn read_string(&self, name: &str) -> Result<String> {
+        let bytes = self.read(name)?;
+        String::from_utf8(bytes).with_context(|| format!("Archive entry is not UTF-8: {}", name))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use flate2::{Compression, Crc};
+    use std::io::Write;
+
+    const END_OF_CENTRAL_DIR: u32 = 0x0605_4b50;
+    const CENTRAL_DIR_ENTRY: u32 = 0x0201_4b50;
+    const LOCAL_HEADER: u32 = 0x0403_4b50;
+
+    /// Build an in-memory ZIP archive; entries are deflated unless `stored`.
+    pub(crate) fn build_zip(files: &[(&str, &[u8])], stored: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for (name, content) in files {
+            let (method, data) = if stored {
+                (0u16, content.to_vec())
+            } else {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(content).unwrap();
+                (8u16, encoder.finish().unwrap())
+            };
+            let offset = out.len() as u32;
+            let mut crc = Crc::new();
+            crc.update(content);
+            // Time and date, then the CRC
+            let mut stamp = vec![0; 4];
+            stamp.extend(crc.sum().to_le_bytes());
+
+            out.extend(LOCAL_HEADER.to_le_bytes());
+            out.extend([20, 0, 0, 0]);
+            out.extend(method.to_le_bytes());
+            out.extend(&stamp);
+            out.extend((data.len() as u32).to_le_bytes());
+            out.extend((content.len() as u32).to_le_bytes());
+            out.extend((name.len() as u16).to_le_bytes());
+            out.extend([0, 0]);
+            out.extend(name.as_bytes());
+            out.extend(&data);
+
+            central.extend(CENTRAL_DIR_ENTRY.to_le_bytes());
+            central.extend([20, 0, 20, 0, 0, 0]);
+            central.extend(method.to_le_bytes());
+            central.extend(&stamp);
+            central.extend((data.len() as u32).to_le_bytes());
+            central.extend((content.len() as u32).to_le_bytes());
+            central.extend((name.len() as u16).to_le_bytes());
+            central.extend([0; 12]); // extra, comment, disk, attributes
+            central.extend(offset.to_le_bytes());
+            central.extend(name.as_bytes());
+        }
+
+        let central_offset = out.len() as u32;
+        out.extend(&central);
+        out.extend(END_OF_CENTRAL_DIR.to_le_bytes());
+        out.extend([0; 4]);
+        out.extend((files.len() as u16).to_le_bytes());
+        out.extend((files.len() as u16).to_le_bytes());
+        out.extend((central.len() as u32).to_le_bytes());
+        out.extend(central_offset.to_le_bytes());
+        out.extend([0, 0]);
+        out
+    }
+
+    #[test]
+    fn test_read_deflated_and_stored() {
+        for stored in [false, true] {
+            let data = build_zip(
+                &[("a.txt", b"hello"), ("dir/b.xml", b"<b>world</b>")],
+                stored,
+            );
+            let archive = ZipArchive::new(&data).unwrap();
+            assert_eq!(archive.read_string("a.txt").unwrap(), "hello");
+            assert_eq!(archive.read("dir/b.xml").unwrap(), b"<b>world</b>");
+        }
+    }
+
+    #[test]
+    fn test_missing_entry_and_bad_archive() {
+        let data = build_zip(&[("a.txt", b"hello")], false);
+        let archive = ZipArchive::new(&data).unwrap();
+        assert!(archive.contains("a.txt") && !archive.contains("b.txt"));
+        let err = archive.read("b.txt").err().unwrap();
+        assert!(err.to_string().contains("Missing archive entry"));
+
+        let err = ZipArchive::new(b"definitely not a zip file").err().unwrap();
+        assert!(err.to_string().contains("Not a ZIP archive"));
+    }
+
+    #[test]
+    fn test_claimed_size_is_capped() {
+        let mut data = build_zip(&[("a.txt", b"hello")], false);
+        // Claim a 4 GiB entry in the central directory
+        let eocd = data.len() - 22;
+        let central = u32::from_le_bytes(data[eocd + 16..eocd + 20].try_into().unwrap()) as usize;
+        data[central + 24..central + 28].copy_from_slice(&u32::MAX.to_le_bytes());
+        let archive = ZipArchive::new(&data).unwrap();
+        let err = archive.read("a.txt").err().unwrap();
+        assert!(err.to_string().contains("too large"), "{:#}", err);
+    }
+}
@@ -1,28 +1,101 @@
-/// BM25 (Okapi BM25) search index for keyword-based document retrieval.
-///
-/// Implements the standard BM25 ranking function:
-///   score(D, Q) = Σ IDF(qi) × (f(qi,D) × (k1+1)) / (f(qi,D) + k1 × (1 - b + b × |D|/avgdl))
-///
-/// Built entirely in Rust for performance when scoring thousands of chunks.
+//! BM25 (Okapi BM25) search index for keyword-based document retrieval.
+//!
+//! Implements the standard BM25 ranking function:
+//!   score(D, Q) = Σ IDF(qi) × (f(qi,D) × (k1+1)) / (f(qi,D) + k1 × (1 - b + b × |D|/avgdl))
+//!
+//! Built entirely in Rust for performance when scoring thousands of chunks:
+//! an inverted index maps each term to the documents containing it, so a
+//! query only scores documents sharing a term with it, in parallel batches
+//! on all CPU cores.
+//!
+//! Documents may also be indexed as weighted fields (title, heading, body),
+//! scored by BM25F: each field's term frequency is length-normalized
+//! against that field's average length and weighted, and the weighted sum
+//! takes the place of f(qi,D) in the saturation k1 controls.
 
+use anyhow::{Context, Result};
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use rayon::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-use crate::tokenizer;
+use crate::analyzer::{self, Analyzer, Language};
+use crate::lemmatizer;
+use crate::query::BooleanQuery;
+use crate::spell;
+use crate::synonyms;
+use crate::tokenizer::{self, TokenizerOptions};
 
 /// A BM25 search index built from a collection of text documents.
 ///
 /// Construct from Python with:
 ///     index = BM25Index(["chunk 1 text", "chunk 2 text", ...])
 ///     results = index.search("my query", top_k=5)
-#[pyclass]
+///
+/// Pass `language="en"` (or "de", "fr", ..., or "auto") to enable stopword
+/// removal and stemming for that language, or `stopwords` to only drop
+/// stopwords, so common words don't inflate document lengths, and
+/// `stemmer="english"` to only stem words, so "running" matches "runs".
+/// With `ngrams=2` (or 3), runs of words are indexed too, so a query for
+/// "machine learning" favours documents with the phrase over ones that
+/// merely mention "machine" and "learning". `word_chars` sets the
+/// characters kept in words besides letters and digits (e.g. "'#+" so
+/// "C++" and "C#" are terms), as for `tokenize`.
+///
+/// In a corpus of several languages, pass `languages`, each document's
+/// language code (as detected at ingestion), so every document goes
+/// through its own language's analyzer pipeline; queries are analyzed by
+/// each pipeline and matched against that pipeline's documents.
+///
+/// `index.search_phrase("neural network")` only matches documents with the
+/// words in a row, which single-term scoring can't require.
+///
+/// With `store_documents=True` (and optionally `ids`, the documents' own
+/// IDs, e.g. keys of their metadata), `index.search_with_docs(query)`
+/// returns each match's ID and text, so callers needn't keep a parallel
+/// list of documents, and `index.highlight(query, doc_idx)` the passage of
+/// a match with the query's words marked.
+///
+/// With `synonyms="builtin"` (or a dict of word → synonyms), each query
+/// word's synonyms are searched for too, so "car" also matches
+/// "automobile". With `fuzzy=1` or `2`, a query term the corpus lacks
+/// matches the indexed terms that many edits away, at a lower score, so
+/// "tranformer" still finds "transformer".
+///
+/// With `metadata`, a dict per document (e.g. `{"source": "report.pdf"}`),
+/// `index.search(query, filter={"source": "report.pdf"})` only returns
+/// documents whose metadata has those values, so keyword search can be
+/// scoped to part of the corpus without an index of its own.
+///
+/// Documents can be dicts of field name → text, e.g. `{"title": ...,
+/// "body": ...}`, with `field_weights={"title": 3.0}`, so a query term in a
+/// title or heading counts for more than the same term in body text.
+///
+/// `index.save(path)` writes the index to disk and `BM25Index.load(path)`
+/// reads it back, settings included, without re-tokenizing the corpus.
+/// Indexes pickle the same way, so they pass to worker processes and
+/// joblib caches.
+#[pyclass(module = "rusty_rag.rusty_rag_core")]
+#[derive(Serialize, Deserialize)]
 pub struct BM25Index {
-    /// Term → number of documents containing it
-    df: HashMap<String, usize>,
-    /// Per-document term frequencies
-    tf: Vec<HashMap<String, usize>>,
+    /// Term → its ID, the index of its postings; each distinct term is
+    /// stored once, however many documents contain it
+    terms: HashMap<String, u32>,
+    /// Per term ID, the documents containing the term, in document order;
+    /// the list's length is the term's document frequency
+    postings: Vec<Vec<Posting>>,
     /// Token count per document
     doc_lengths: Vec<usize>,
+    /// The fields documents are split into, in position order; empty when
+    /// documents are plain text
+    fields: Vec<Field>,
+    /// Token count per document and field, if documents have fields
+    field_lengths: Vec<Vec<usize>>,
     /// Average document length
     avg_dl: f64,
     /// Total number of documents
@@ -30,114 +103,1987 @@ pub struct BM25Index {
     /// BM25 tuning parameters
     k1: f64,
     b: f64,
+    /// How documents and queries become terms: the index-wide options,
+    /// then those of each document language with its own analyzer
+    pipelines: Vec<TermOptions>,
+    /// Each document's pipeline, as an index into `pipelines`
+    doc_pipelines: Vec<usize>,
+    /// The documents' text, if stored for `search_with_docs`
+    documents: Option<Vec<String>>,
+    /// The documents' external IDs, if given
+    ids: Option<Vec<String>>,
+    /// The documents' metadata, if given, for search filters
+    metadata: Option<Vec<Metadata>>,
+    /// Lowercase query word → the words and phrases also searched for
+    synonyms: HashMap<String, Vec<String>>,
+    /// Most edits between a query term the index lacks and the indexed
+    /// terms searched for instead; 0 to match terms exactly
+    fuzzy: usize,
+    /// Factor on the score of a fuzzy match, once per edit
+    fuzzy_penalty: f64,
+}
+
+/// A document containing a term, and where.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Posting {
+    pub(crate) doc: usize,
+    /// Positions of the term among the document's terms, ascending; as many
+    /// as the term's frequency in the document. Each field's terms follow
+    /// the previous field's after a gap of one position.
+    pub(crate) positions: Vec<u32>,
+}
+
+/// A part of every document, scored with its own weight and length
+/// normalization.
+#[derive(Serialize, Deserialize)]
+struct Field {
+    name: String,
+    /// Factor on the field's normalized term frequencies
+    weight: f64,
+    /// Average token count of the field
+    avg_len: f64,
+}
+
+/// A pipeline's query terms: the postings of each, with its IDF (scaled
+/// down for fuzzy matches).
+type QueryPostings<'a> = Vec<(&'a [Posting], f64)>;
+
+/// How documents and queries are turned into index terms.
+#[derive(Default)]
+pub struct TermOptions {
+    /// Language analyzer (stopwords + stemming); plain tokenizer if None
+    pub analyzer: Option<Analyzer>,
+    /// Words dropped besides the analyzer's
+    pub stopwords: HashSet<String>,
+    /// Language whose Snowball stemmer stems the words left, when there is
+    /// no analyzer
+    pub stemmer: Option<Language>,
+    /// Whether words are reduced to their English lemmas ("better" →
+    /// "good") before stopwords and stemming; ignored under an analyzer
+    /// for another language
+    pub lemmatize: bool,
+    /// Whether accents are folded off words ("résumé" → "resume")
+    pub fold_accents: bool,
+    /// Longest run of words indexed as a term of its own; 0 or 1 for words
+    /// only
+    pub ngrams: usize,
+    /// Which characters make up words
+    pub words: TokenizerOptions,
+    /// A tokenizer used in place of `words`, if given
+    pub tokenizer: Option<Arc<dyn CustomTokenizer>>,
+}
+
+/// Splits text into tokens in place of the built-in tokenizer, for text
+/// it can't handle (code, chemistry, ...).
+pub trait CustomTokenizer: Send + Sync {
+    /// The tokens of `text`.
+    fn tokenize(&self, text: &str) -> Vec<String>;
+
+    /// Raise the first error of the tokenizer since the last check, for
+    /// tokenizers that can fail.
+    fn check(&self) -> PyResult<()> {
+        Ok(())
+    }
+}
+
+/// A Python callable splitting text into tokens.
+///
+/// Tokenizing can't fail in Rust, so a call that raises gives no tokens,
+/// and the error is kept for the Python method that ran it to raise.
+pub struct PyTokenizer {
+    callable: PyObject,
+    error: Mutex<Option<PyErr>>,
+}
+
+impl PyTokenizer {
+    pub fn new(callable: PyObject) -> Self {
+        PyTokenizer {
+            callable,
+            error: Mutex::new(None),
+        }
+    }
+}
+
+impl CustomTokenizer for PyTokenizer {
+    /// The callable's tokens of `text`, taking the GIL to run it.
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        Python::with_gil(|py| {
+            let tokens = self.callable.call1(py, (text,));
+            match tokens.and_then(|tokens| tokens.extract(py)) {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    self.error.lock().unwrap().get_or_insert(e);
+                    Vec::new()
+                }
+            }
+        })
+    }
+
+    fn check(&self) -> PyResult<()> {
+        match self.error.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TermOptions {
+    /// These options with `language`'s analyzer, which stems in place of
+    /// the stemmer.
+    pub fn for_language(&self, language: Language) -> Self {
+        let analyzer = Analyzer::new(language);
+        TermOptions {
+            analyzer: Some(if self.fold_accents {
+                analyzer.folding_accents()
+            } else {
+                analyzer
+            }),
+            stopwords: self.stopwords.clone(),
+            stemmer: None,
+            lemmatize: self.lemmatize,
+            fold_accents: self.fold_accents,
+            ngrams: self.ngrams,
+            words: self.words.clone(),
+            tokenizer: self.tokenizer.clone(),
+        }
+    }
+
+    /// Fold accents off words, the analyzer's and listed stopwords included.
+    pub fn folding_accents(self) -> Self {
+        TermOptions {
+            analyzer: self.analyzer.map(Analyzer::folding_accents),
+            stopwords: self
+                .stopwords
+                .iter()
+                .map(|w| tokenizer::fold_accents(w))
+                .collect(),
+            fold_accents: true,
+            ..self
+        }
+    }
+
+    /// Turn text into terms: words, without stopwords, stemmed if
+    /// configured, followed by their n-grams.
+    pub fn terms(&self, text: &str) -> Vec<String> {
+        let words = self.words(text);
+        if self.ngrams < 2 {
+            return words;
+        }
+        let grams: Vec<String> = (2..=self.ngrams)
+            .flat_map(|n| tokenizer::ngrams(&words, n))
+            .collect();
+        [words, grams].concat()
+    }
+
+    /// The terms of each text, computed on all CPU cores or on a pool of
+    /// `n_threads`.
+    pub fn terms_batch(
+        &self,
+        texts: &[String],
+        n_threads: Option<usize>,
+    ) -> Result<Vec<Vec<String>>> {
+        let run = || texts.par_iter().map(|text| self.terms(text)).collect();
+        match n_threads {
+            Some(n) => Ok(rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()?
+                .install(run)),
+            None => Ok(run()),
+        }
+    }
+
+    /// The tokens of `text`, by the custom tokenizer if there is one, and
+    /// without accents if `fold_accents`.
+    fn tokens(&self, text: &str, fold_accents: bool) -> Vec<String> {
+        let Some(custom) = &self.tokenizer else {
+            return self.words.tokenize(text, fold_accents);
+        };
+        let tokens = custom.tokenize(text);
+        if fold_accents {
+            tokens.iter().map(|t| tokenizer::fold_accents(t)).collect()
+        } else {
+            tokens
+        }
+    }
+
+    /// The tokens of `text` with the byte range of each, unfolded. A custom
+    /// tokenizer's tokens are looked for in order; one not in the text as
+    /// written gets an empty range.
+    fn token_spans(&self, text: &str) -> Vec<(String, usize, usize)> {
+        let Some(custom) = &self.tokenizer else {
+            return self.words.tokenize_with_spans(text);
+        };
+        let mut from = 0;
+        custom
+            .tokenize(text)
+            .into_iter()
+            .map(|token| match text[from..].find(&token) {
+                Some(i) => {
+                    let start = from + i;
+                    from = start + token.len();
+                    (token, start, from)
+                }
+                None => (token, from, from),
+            })
+            .collect()
+    }
+
+    /// The term one token of `token_spans` becomes, or None if it's a
+    /// stopword.
+    fn token_term(&self, token: &str) -> Option<String> {
+        let token = if self.fold_accents {
+            tokenizer::fold_accents(token)
+        } else {
+            token.to_string()
+        };
+        self.analyze_words(vec![token]).pop()
+    }
+
+    /// The words of `text`, lemmatized if configured, after the analyzer or
+    /// the stopwords and stemmer.
+    fn words(&self, text: &str) -> Vec<String> {
+        self.analyze_words(self.tokens(text, self.fold_accents))
+    }
+
+    /// `tokens` as `words` makes them words.
+    fn analyze_words(&self, mut tokens: Vec<String>) -> Vec<String> {
+        let english = self
+            .analyzer
+            .as_ref()
+            .is_none_or(|a| a.language() == Language::English);
+        if self.lemmatize && english {
+            tokens = tokens.iter().map(|t| lemmatizer::lemmatize(t)).collect();
+        }
+        if let Some(analyzer) = &self.analyzer {
+            return analyzer.analyze_tokens(tokens, &self.stopwords);
+        }
+        tokens.retain(|token| !self.stopwords.contains(token));
+        match self.stemmer {
+            Some(language) => {
+                let stemmer = language.stemmer();
+                tokens
+                    .iter()
+                    .map(|token| stemmer.stem(token).into_owned())
+                    .collect()
+            }
+            None => tokens,
+        }
+    }
+}
+
+/// A sparse vector as Qdrant takes it: dimensions in ascending order, and
+/// the value of each.
+#[derive(Debug, PartialEq)]
+pub struct SparseVector {
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
+impl SparseVector {
+    /// The vector of (dimension, value) `entries`, in any order; values of
+    /// the same dimension are added up.
+    fn new(mut entries: Vec<(u32, f32)>) -> Self {
+        entries.sort_unstable_by_key(|&(index, _)| index);
+        let mut vector = SparseVector {
+            indices: Vec::with_capacity(entries.len()),
+            values: Vec::with_capacity(entries.len()),
+        };
+        for (index, value) in entries {
+            if vector.indices.last() == Some(&index) {
+                *vector.values.last_mut().unwrap() += value;
+            } else {
+                vector.indices.push(index);
+                vector.values.push(value);
+            }
+        }
+        vector
+    }
+
+    /// The vector as a dict of "indices" and "values", the form Qdrant's
+    /// client takes for a `SparseVector`.
+    fn into_dict(self, py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("indices", self.indices)?;
+        dict.set_item("values", self.values)?;
+        Ok(dict)
+    }
+}
+
+/// A term's dimension in sparse vectors: its 32-bit FNV-1a hash, the same
+/// in every index and process, so vectors of separately built indexes
+/// (a rebuilt one, or one per shard) line up.
+fn sparse_index(term: &str) -> u32 {
+    term.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// `TermOptions` as saved with an index: analyzers by their language.
+#[derive(Serialize, Deserialize)]
+struct SavedTermOptions {
+    language: Option<Language>,
+    stopwords: HashSet<String>,
+    stemmer: Option<Language>,
+    lemmatize: bool,
+    fold_accents: bool,
+    ngrams: usize,
+    words: TokenizerOptions,
+}
+
+impl Serialize for TermOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedTermOptions {
+            language: self.analyzer.as_ref().map(Analyzer::language),
+            stopwords: self.stopwords.clone(),
+            stemmer: self.stemmer,
+            lemmatize: self.lemmatize,
+            fold_accents: self.fold_accents,
+            ngrams: self.ngrams,
+            words: self.words.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TermOptions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedTermOptions::deserialize(deserializer)?;
+        // Stopwords are saved folded already
+        let analyzer = saved.language.map(Analyzer::new).map(|analyzer| {
+            if saved.fold_accents {
+                analyzer.folding_accents()
+            } else {
+                analyzer
+            }
+        });
+        Ok(TermOptions {
+            analyzer,
+            stopwords: saved.stopwords,
+            stemmer: saved.stemmer,
+            lemmatize: saved.lemmatize,
+            fold_accents: saved.fold_accents,
+            ngrams: saved.ngrams,
+            words: saved.words,
+            tokenizer: None,
+        })
+    }
+}
+
+/// A document's metadata: values by key, e.g. {"source": "report.pdf",
+/// "page": 3}.
+pub type Metadata = HashMap<String, MetadataValue>;
+
+/// A search filter: the values each metadata key must have.
+pub type Filter = HashMap<String, FilterValue>;
+
+/// A metadata value, as passed from Python.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromPyObject)]
+pub enum MetadataValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// What a filter accepts for one key: a value, or any of a list of them.
+#[derive(Debug, FromPyObject)]
+pub enum FilterValue {
+    One(MetadataValue),
+    AnyOf(Vec<MetadataValue>),
+}
+
+impl FilterValue {
+    /// Whether `value` is one the filter accepts; integers equal to
+    /// floats match them.
+    fn accepts(&self, value: &MetadataValue) -> bool {
+        let same = |wanted: &MetadataValue| match (wanted, value) {
+            (MetadataValue::Int(a), MetadataValue::Float(b))
+            | (MetadataValue::Float(b), MetadataValue::Int(a)) => *a as f64 == *b,
+            _ => wanted == value,
+        };
+        match self {
+            FilterValue::One(wanted) => same(wanted),
+            FilterValue::AnyOf(values) => values.iter().any(same),
+        }
+    }
+}
+
+/// Stopwords passed from Python: a language name or code ("en", or "auto"
+/// to guess it from the text), or a list of words.
+#[derive(FromPyObject)]
+pub enum StopwordList {
+    Language(String),
+    Words(Vec<String>),
+}
+
+impl StopwordList {
+    /// The lowercase stopwords, guessing the language from `sample()` for
+    /// "auto".
+    pub fn resolve(self, sample: impl FnOnce() -> String) -> Result<HashSet<String>> {
+        match self {
+            StopwordList::Language(name) => analyzer::language_stopwords(&name, sample),
+            StopwordList::Words(words) => Ok(words.iter().map(|w| w.to_lowercase()).collect()),
+        }
+    }
+}
+
+/// Synonyms passed from Python: "builtin" for the bundled table, or a
+/// dict of word → synonyms.
+#[derive(FromPyObject)]
+pub enum SynonymMap {
+    Named(String),
+    Words(HashMap<String, Vec<String>>),
+}
+
+impl SynonymMap {
+    /// Lowercase word → its synonyms.
+    pub fn resolve(self) -> Result<HashMap<String, Vec<String>>> {
+        match self {
+            SynonymMap::Named(name) if name == "builtin" => Ok(synonyms::builtin()),
+            SynonymMap::Named(name) => {
+                anyhow::bail!("Unknown synonyms {:?}: pass \"builtin\" or a dict", name)
+            }
+            SynonymMap::Words(words) => Ok(words
+                .into_iter()
+                .map(|(word, synonyms)| (word.to_lowercase(), synonyms))
+                .collect()),
+        }
+    }
+}
+
+/// A document passed from Python: its text, or its fields' text by field
+/// name.
+#[derive(FromPyObject)]
+pub enum DocumentInput {
+    Text(String),
+    Fields(HashMap<String, String>),
+}
+
+/// The field a plain-text document fills among documents with fields.
+const BODY_FIELD: &str = "body";
+
+/// Documents split into fields, as `BM25Index::with_fields` takes them.
+struct FieldedDocuments {
+    /// (name, weight) pairs, heaviest first
+    fields: Vec<(String, f64)>,
+    /// Each document's text per field, in field order
+    texts: Vec<Vec<String>>,
+}
+
+/// Each document's whole text (its fields' text, heaviest field first),
+/// and its fields if any document has fields or `weights` are given.
+/// Fields missing from `weights` weigh 1.0.
+fn split_fields(
+    documents: Vec<DocumentInput>,
+    weights: Option<HashMap<String, f64>>,
+) -> Result<(Vec<String>, Option<FieldedDocuments>)> {
+    let has_fields = weights.is_some()
+        || documents
+            .iter()
+            .any(|doc| matches!(doc, DocumentInput::Fields(_)));
+    if !has_fields {
+        let texts = documents
+            .into_iter()
+            .filter_map(|doc| match doc {
+                DocumentInput::Text(text) => Some(text),
+                DocumentInput::Fields(_) => None,
+            })
+            .collect();
+        return Ok((texts, None));
+    }
+
+    let mut weights = weights.unwrap_or_default();
+    let invalid = |&(_, &weight): &(&String, &f64)| weight.is_nan() || weight < 0.0;
+    if let Some((name, weight)) = weights.iter().find(invalid) {
+        anyhow::bail!("Field {:?} has an invalid weight {}", name, weight);
+    }
+    for doc in &documents {
+        match doc {
+            DocumentInput::Text(_) => {
+                weights.entry(BODY_FIELD.to_string()).or_insert(1.0);
+            }
+            DocumentInput::Fields(fields) => {
+                for name in fields.keys() {
+                    weights.entry(name.clone()).or_insert(1.0);
+                }
+            }
+        }
+    }
+    let mut fields: Vec<(String, f64)> = weights.into_iter().collect();
+    fields.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut whole = Vec::with_capacity(documents.len());
+    let mut texts = Vec::with_capacity(documents.len());
+    for doc in documents {
+        let field_texts: Vec<String> = match doc {
+            DocumentInput::Text(text) => {
+                let mut text = Some(text);
+                fields
+                    .iter()
+                    .map(|(name, _)| {
+                        if name == BODY_FIELD {
+                            text.take().unwrap_or_default()
+                        } else {
+                            String::new()
+                        }
+                    })
+                    .collect()
+            }
+            DocumentInput::Fields(mut by_name) => fields
+                .iter()
+                .map(|(name, _)| by_name.remove(name).unwrap_or_default())
+                .collect(),
+        };
+        let parts: Vec<&str> = field_texts
+            .iter()
+            .map(String::as_str)
+            .filter(|text| !text.is_empty())
+            .collect();
+        whole.push(parts.join("\n\n"));
+        texts.push(field_texts);
+    }
+    Ok((whole, Some(FieldedDocuments { fields, texts })))
+}
+
+/// Number of leading documents sampled when `language="auto"`.
+const LANGUAGE_SAMPLE_DOCS: usize = 200;
+
+/// Documents scored per parallel task in `search`.
+const SCORE_BATCH_DOCS: usize = 2048;
+
+/// The error of methods needing the documents' text, if it isn't stored.
+const NOT_STORED: &str = "Documents aren't stored; build the index with store_documents=True";
+
+/// Most edits a fuzzy query term may be from the terms it matches; more
+/// would match unrelated words.
+const MAX_FUZZY_EDITS: usize = 2;
+
+/// Leads a saved index file: a tag, then the format version, bumped
+/// whenever the saved fields change.
+const SAVE_MAGIC: &[u8; 8] = b"RRBM25\0\x0a";
+
+impl BM25Index {
+    /// Build an index with the plain tokenizer.
+    pub fn new(documents: Vec<String>, k1: f64, b: f64) -> Self {
+        Self::with_analyzer(documents, k1, b, None)
+    }
+
+    /// Build an index whose documents and queries go through `analyzer`.
+    pub fn with_analyzer(
+        documents: Vec<String>,
+        k1: f64,
+        b: f64,
+        analyzer: Option<Analyzer>,
+    ) -> Self {
+        let options = TermOptions {
+            analyzer,
+            ..TermOptions::default()
+        };
+        Self::with_options(documents, k1, b, options)
+    }
+
+    /// Build an index whose documents and queries become terms as
+    /// `options` says.
+    pub fn with_options(documents: Vec<String>, k1: f64, b: f64, options: TermOptions) -> Self {
+        let doc_pipelines = vec![0; documents.len()];
+        let documents = documents.into_iter().map(|doc| vec![doc]).collect();
+        Self::with_pipelines(documents, Vec::new(), k1, b, vec![options], doc_pipelines)
+    }
+
+    /// Build an index whose documents go through the pipeline of their
+    /// language in `languages` (`options` with that language's analyzer),
+    /// or through `options` where it is None or missing.
+    pub fn with_languages(
+        documents: Vec<String>,
+        k1: f64,
+        b: f64,
+        options: TermOptions,
+        languages: &[Option<Language>],
+    ) -> Self {
+        let documents = documents.into_iter().map(|doc| vec![doc]).collect();
+        Self::with_fields(documents, Vec::new(), k1, b, options, languages)
+    }
+
+    /// Build an index of documents split into `fields`, (name, weight)
+    /// pairs: each document is its fields' text in that order, scored by
+    /// BM25F. With no fields, each document is a single text scored by
+    /// plain BM25. Documents go through pipelines as in `with_languages`.
+    pub fn with_fields(
+        documents: Vec<Vec<String>>,
+        fields: Vec<(String, f64)>,
+        k1: f64,
+        b: f64,
+        options: TermOptions,
+        languages: &[Option<Language>],
+    ) -> Self {
+        let index_language = options.analyzer.as_ref().map(Analyzer::language);
+        let mut pipelines = vec![options];
+        let mut pipeline_languages: Vec<Language> = Vec::new();
+        let mut doc_pipelines = vec![0; documents.len()];
+        for (pipeline, language) in doc_pipelines.iter_mut().zip(languages) {
+            let Some(language) = *language else {
+                continue;
+            };
+            if Some(language) == index_language {
+                continue;
+            }
+            *pipeline = match pipeline_languages.iter().position(|&l| l == language) {
+                Some(i) => i + 1,
+                None => {
+                    pipelines.push(pipelines[0].for_language(language));
+                    pipeline_languages.push(language);
+                    pipelines.len() - 1
+                }
+            };
+        }
+        Self::with_pipelines(documents, fields, k1, b, pipelines, doc_pipelines)
+    }
+
+    /// Build an index whose documents' fields become terms through
+    /// `pipelines[doc_pipelines[i]]`.
+    fn with_pipelines(
+        documents: Vec<Vec<String>>,
+        fields: Vec<(String, f64)>,
+        k1: f64,
+        b: f64,
+        pipelines: Vec<TermOptions>,
+        doc_pipelines: Vec<usize>,
+    ) -> Self {
+        let n_docs = documents.len();
+        let mut terms: HashMap<String, u32> = HashMap::new();
+        let mut postings: Vec<Vec<Posting>> = Vec::new();
+        let mut doc_lengths: Vec<usize> = Vec::with_capacity(n_docs);
+        let mut field_lengths: Vec<Vec<usize>> = Vec::new();
+
+        for (doc_idx, (doc, &pipeline)) in documents.iter().zip(&doc_pipelines).enumerate() {
+            // Keyed by term ID, so a document's terms aren't copied again
+            let mut positions: HashMap<u32, Vec<u32>> = HashMap::new();
+            let mut lengths: Vec<usize> = Vec::with_capacity(doc.len());
+            let mut start = 0;
+            for text in doc {
+                let tokens = pipelines[pipeline].terms(text);
+                lengths.push(tokens.len());
+                for (position, token) in (start..).zip(tokens) {
+                    let next_id = terms.len() as u32;
+                    let id = *terms.entry(token).or_insert(next_id);
+                    if id == next_id {
+                        postings.push(Vec::new());
+                    }
+                    positions.entry(id).or_default().push(position);
+                }
+                // The gap keeps phrases from running across fields
+                start += lengths[lengths.len() - 1] as u32 + 1;
+            }
+            doc_lengths.push(lengths.iter().sum());
+            if !fields.is_empty() {
+                field_lengths.push(lengths);
+            }
+
+            for (id, positions) in positions {
+                postings[id as usize].push(Posting {
+                    doc: doc_idx,
+                    positions,
+                });
+            }
+        }
+
+        let average = |total: usize| {
+            if n_docs > 0 {
+                total as f64 / n_docs as f64
+            } else {
+                0.0
+            }
+        };
+        let avg_dl = average(doc_lengths.iter().sum());
+        let fields = fields
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, weight))| Field {
+                name,
+                weight,
+                avg_len: average(field_lengths.iter().map(|lengths| lengths[i]).sum()),
+            })
+            .collect();
+
+        BM25Index {
+            terms,
+            postings,
+            doc_lengths,
+            fields,
+            field_lengths,
+            avg_dl,
+            n_docs,
+            k1,
+            b,
+            pipelines,
+            doc_pipelines,
+            documents: None,
+            ids: None,
+            metadata: None,
+            synonyms: HashMap::new(),
+            fuzzy: 0,
+            fuzzy_penalty: 1.0,
+        }
+    }
+
+    /// The `top_k` documents scoring highest against `query`, as
+    /// (document index, score) pairs, best first; ties go to the earlier
+    /// document. Documents with no query term are left out, as are those
+    /// lacking a word the query requires (`+word`, `AND`) or having one it
+    /// excludes (`-word`, `NOT`).
+    pub fn search(&self, query: &str, top_k: usize) -> Vec<(usize, f64)> {
+        self.search_in(query, top_k, None)
+    }
+
+    /// Like `search`, among the documents whose metadata passes `filter`.
+    /// Term statistics stay those of the whole index, so a document scores
+    /// the same with or without a filter.
+    pub fn search_filtered(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &Filter,
+    ) -> Result<Vec<(usize, f64)>> {
+        let docs = self.filter_docs(filter)?;
+        Ok(self.search_in(query, top_k, Some(&docs)))
+    }
+
+    /// The results of `search` (or `search_filtered`, with a `filter`) for
+    /// each of `queries`, in order; the queries run in parallel, sharing
+    /// the filter's document set.
+    pub fn search_many(
+        &self,
+        queries: &[String],
+        top_k: usize,
+        filter: Option<&Filter>,
+    ) -> Result<Vec<Vec<(usize, f64)>>> {
+        let docs = filter.map(|filter| self.filter_docs(filter)).transpose()?;
+        Ok(queries
+            .par_iter()
+            .map(|query| self.search_in(query, top_k, docs.as_deref()))
+            .collect())
+    }
+
+    /// The score of every document against `query`, in document order;
+    /// 0 for documents with no query term, or ruled out by its operators.
+    pub fn get_scores(&self, query: &str) -> Vec<f64> {
+        let query = BooleanQuery::parse(query);
+        let docs = query
+            .has_operators()
+            .then(|| self.boolean_docs(&query, None));
+        let query_postings = self.query_postings(&query.text);
+        let mut scores = vec![0.0; self.n_docs];
+        scores
+            .par_chunks_mut(SCORE_BATCH_DOCS)
+            .enumerate()
+            .for_each(|(batch, batch_scores)| {
+                let first = batch * SCORE_BATCH_DOCS;
+                let end = first + batch_scores.len();
+                let docs = docs.as_deref();
+                for (doc, score) in self.score_batch(first, end, &query_postings, docs) {
+                    batch_scores[doc - first] = score;
+                }
+            });
+        scores
+    }
+
+    /// `search` among the documents `docs` marks, or all of them.
+    fn search_in(&self, query: &str, top_k: usize, docs: Option<&[bool]>) -> Vec<(usize, f64)> {
+        let query = BooleanQuery::parse(query);
+        let allowed = query
+            .has_operators()
+            .then(|| self.boolean_docs(&query, docs));
+        let docs = allowed.as_deref().or(docs);
+        let query_postings = self.query_postings(&query.text);
+        if query_postings.iter().all(Vec::is_empty) {
+            return vec![];
+        }
+
+        // The top k of each batch of documents, merged pairwise, so no
+        // more than 2k scores are kept at once
+        let n_batches = self.n_docs.div_ceil(SCORE_BATCH_DOCS);
+        (0..n_batches)
+            .into_par_iter()
+            .map(|batch| {
+                let first = batch * SCORE_BATCH_DOCS;
+                let end = (first + SCORE_BATCH_DOCS).min(self.n_docs);
+                let mut scores = self.score_batch(first, end, &query_postings, docs);
+                keep_top(&mut scores, top_k);
+                scores
+            })
+            .reduce(Vec::new, |mut scores, other| {
+                scores.extend(other);
+                keep_top(&mut scores, top_k);
+                scores
+            })
+    }
+
+    /// The documents `docs` marks (or all of them) that have every word
+    /// `query` requires and none it excludes, each word analyzed as the
+    /// document was. Words that analyze to no term, like stopwords, rule
+    /// nothing out.
+    fn boolean_docs(&self, query: &BooleanQuery, docs: Option<&[bool]>) -> Vec<bool> {
+        let mut allowed = docs.map_or_else(|| vec![true; self.n_docs], <[bool]>::to_vec);
+        let words = (query.required.iter().map(|word| (word, true)))
+            .chain(query.excluded.iter().map(|word| (word, false)));
+        for (word, wanted) in words {
+            for (pipeline, options) in self.pipelines.iter().enumerate() {
+                let terms = options.terms(word);
+                if terms.is_empty() {
+                    continue;
+                }
+                let has = self.docs_with_all(&terms);
+                for (doc, allowed) in allowed.iter_mut().enumerate() {
+                    if self.doc_pipelines[doc] == pipeline && has[doc] != wanted {
+                        *allowed = false;
+                    }
+                }
+            }
+        }
+        allowed
+    }
+
+    /// The `window` tokens of document `doc` that best match `query`, each
+    /// token matching a query term (as `search` matches them, synonyms and
+    /// fuzzy matches included) between `pre` and `post`. The best window
+    /// has the highest total IDF of distinct matching terms, the earliest
+    /// of equals; a document matching nothing gives its first tokens.
+    /// None if documents aren't stored.
+    pub fn highlight(
+        &self,
+        query: &str,
+        doc: usize,
+        window: usize,
+        pre: &str,
+        post: &str,
+    ) -> Option<String> {
+        let text = &self.documents.as_ref()?[doc];
+        let pipeline = &self.pipelines[self.doc_pipelines[doc]];
+        let query_terms = self.query_terms(pipeline, &BooleanQuery::parse(query).text);
+        let tokens = pipeline.token_spans(text);
+        if tokens.is_empty() {
+            return Some(text.clone());
+        }
+
+        // Each token's matching term, as an index into `matched`
+        let mut matched: Vec<(String, f64)> = Vec::new();
+        let token_matches: Vec<Option<usize>> = tokens
+            .iter()
+            .map(|(token, _, _)| {
+                let term = pipeline.token_term(token)?;
+                if let Some(i) = matched.iter().position(|(t, _)| *t == term) {
+                    return Some(i);
+                }
+                let postings = self.term_postings(&term)?;
+                if !query_terms.contains(&term) && !self.fuzzy_matches(&term, &query_terms) {
+                    return None;
+                }
+                matched.push((term, self.idf(postings.len())));
+                Some(matched.len() - 1)
+            })
+            .collect();
+
+        // Slide the window, counting each term's tokens in it
+        let window = window.clamp(1, tokens.len());
+        let mut counts = vec![0usize; matched.len()];
+        let (mut best_score, mut best_start) = (0.0, 0);
+        for end in 0..tokens.len() {
+            if let Some(i) = token_matches[end] {
+                counts[i] += 1;
+            }
+            if end >= window {
+                if let Some(i) = token_matches[end - window] {
+                    counts[i] -= 1;
+                }
+            }
+            // Summed in term order, so equal windows score exactly equal
+            let score: f64 = (counts.iter().zip(&matched))
+                .filter(|&(&count, _)| count > 0)
+                .map(|(_, &(_, idf))| idf)
+                .sum();
+            if end + 1 >= window && score > best_score {
+                (best_score, best_start) = (score, end + 1 - window);
+            }
+        }
+
+        let in_window = best_start..best_start + window;
+        let from = tokens[in_window.clone()].iter().map(|t| t.1).min()?;
+        let to = tokens[in_window.clone()].iter().map(|t| t.2).max()?;
+        // Compounds overlap their parts, so overlapping spans are merged
+        let mut marks: Vec<(usize, usize)> = in_window
+            .filter(|&i| token_matches[i].is_some())
+            .map(|i| (tokens[i].1, tokens[i].2))
+            .collect();
+        marks.sort_unstable();
+        let mut snippet = String::new();
+        let mut last = from;
+        let mut marks = marks.into_iter().peekable();
+        while let Some((start, mut end)) = marks.next() {
+            while let Some(&(next_start, next_end)) = marks.peek() {
+                if next_start > end {
+                    break;
+                }
+                end = end.max(next_end);
+                marks.next();
+            }
+            // A custom tokenizer's token missing from the text
+            if start == end {
+                continue;
+            }
+            snippet.push_str(&text[last..start]);
+            snippet.push_str(pre);
+            snippet.push_str(&text[start..end]);
+            snippet.push_str(post);
+            last = end;
+        }
+        snippet.push_str(&text[last..to]);
+        Some(snippet)
+    }
+
+    /// Whether `term` is a fuzzy match of a query term the index lacks.
+    fn fuzzy_matches(&self, term: &str, query_terms: &[String]) -> bool {
+        query_terms.iter().any(|query_term| {
+            let max_edits = spell::allowed_edits(query_term, self.fuzzy);
+            max_edits > 0
+                && self.term_postings(query_term).is_none()
+                && query_term.chars().count().abs_diff(term.chars().count()) <= max_edits
+                && spell::osa_distance(query_term, term) <= max_edits
+        })
+    }
+
+    /// Each document's terms as a sparse vector, in document order: the
+    /// weight of each term is its BM25 term-frequency part (saturated and
+    /// length-normalized), times its IDF if `include_idf`. The dot product
+    /// with `sparse_query`'s vector is then the document's BM25 score,
+    /// without synonyms or fuzzy matches.
+    pub fn sparse_vectors(&self, include_idf: bool) -> Vec<SparseVector> {
+        let mut entries: Vec<Vec<(u32, f32)>> = vec![Vec::new(); self.n_docs];
+        for (term, &id) in &self.terms {
+            let index = sparse_index(term);
+            let postings = &self.postings[id as usize];
+            let idf = if include_idf {
+                self.idf(postings.len())
+            } else {
+                1.0
+            };
+            for posting in postings {
+                let weight = idf * self.tf_norm(posting.doc, &posting.positions);
+                entries[posting.doc].push((index, weight as f32));
+            }
+        }
+        entries.into_par_iter().map(SparseVector::new).collect()
+    }
+
+    /// The terms of `query` as a sparse vector, each weighted by how often
+    /// the query has it, in the index-wide pipeline's terms (synonyms
+    /// included; words the query excludes left out).
+    pub fn sparse_query(&self, query: &str) -> SparseVector {
+        let query = BooleanQuery::parse(query);
+        let terms = self.query_terms(&self.pipelines[0], &query.text);
+        SparseVector::new(terms.iter().map(|term| (sparse_index(term), 1.0)).collect())
+    }
+
+    /// Which documents contain every one of `terms`.
+    fn docs_with_all(&self, terms: &[String]) -> Vec<bool> {
+        let mut has = vec![false; self.n_docs];
+        let Some(lists) = terms
+            .iter()
+            .map(|term| self.term_postings(term))
+            .collect::<Option<Vec<&[Posting]>>>()
+        else {
+            return has;
+        };
+        let Some((first, rest)) = lists.split_first() else {
+            return has;
+        };
+        for posting in *first {
+            let doc = posting.doc;
+            if rest
+                .iter()
+                .all(|list| list.binary_search_by_key(&doc, |p| p.doc).is_ok())
+            {
+                has[doc] = true;
+            }
+        }
+        has
+    }
+
+    /// The query as each pipeline's documents were analyzed: the postings
+    /// of each indexed term, with its IDF.
+    fn query_postings(&self, query: &str) -> Vec<QueryPostings<'_>> {
+        self.pipelines
+            .iter()
+            .map(|pipeline| {
+                let terms = self.query_terms(pipeline, query);
+                let mut postings: QueryPostings<'_> = Vec::new();
+                for term in &terms {
+                    match self.term_postings(term) {
+                        Some(list) => postings.push((list, self.idf(list.len()))),
+                        None => postings.extend(self.fuzzy_postings(term, &terms)),
+                    }
+                }
+                postings
+            })
+            .collect()
+    }
+
+    /// The postings of the indexed terms within `fuzzy` edits of `term`
+    /// (fewer for short terms, none for very short ones or numbers), with
+    /// IDFs scaled by `fuzzy_penalty` per edit, in term ID order. Terms
+    /// among `query_terms` are left out, as the query has them already.
+    fn fuzzy_postings(&self, term: &str, query_terms: &[String]) -> QueryPostings<'_> {
+        let max_edits = spell::allowed_edits(term, self.fuzzy);
+        if max_edits == 0 {
+            return vec![];
+        }
+        let len = term.chars().count();
+        let mut matches: Vec<(u32, usize)> = self
+            .terms
+            .par_iter()
+            .filter(|&(candidate, _)| candidate.chars().count().abs_diff(len) <= max_edits)
+            .filter_map(|(candidate, &id)| {
+                let edits = spell::osa_distance(term, candidate);
+                (edits <= max_edits && !query_terms.contains(candidate)).then_some((id, edits))
+            })
+            .collect();
+        // In a fixed order, so scores add up the same every time
+        matches.sort_unstable();
+        matches
+            .into_iter()
+            .map(|(id, edits)| {
+                let postings = self.postings[id as usize].as_slice();
+                let penalty = self.fuzzy_penalty.powi(edits as i32);
+                (postings, self.idf(postings.len()) * penalty)
+            })
+            .collect()
+    }
+
+    /// The terms of `query` as `pipeline` analyzes it, followed by those of
+    /// its words' synonyms that aren't among them.
+    fn query_terms(&self, pipeline: &TermOptions, query: &str) -> Vec<String> {
+        let mut terms = pipeline.terms(query);
+        if self.synonyms.is_empty() {
+            return terms;
+        }
+        let mut seen: HashSet<String> = terms.iter().cloned().collect();
+        for word in pipeline.tokens(query, false) {
+            for synonym in self.synonyms.get(&word).into_iter().flatten() {
+                for term in pipeline.terms(synonym) {
+                    if seen.insert(term.clone()) {
+                        terms.push(term);
+                    }
+                }
+            }
+        }
+        terms
+    }
+
+    /// The scores of the documents from `first` to `end` (exclusive) that
+    /// contain a query term (and are marked in `docs`, if given), given
+    /// each pipeline's query postings.
+    fn score_batch(
+        &self,
+        first: usize,
+        end: usize,
+        query_postings: &[QueryPostings<'_>],
+        docs: Option<&[bool]>,
+    ) -> Vec<(usize, f64)> {
+        let mut scores: Vec<f64> = Vec::new();
+        for (pipeline, terms) in query_postings.iter().enumerate() {
+            for &(postings, idf) in terms {
+                let start = postings.partition_point(|p| p.doc < first);
+                let in_batch = postings[start..].iter().take_while(|p| p.doc < end);
+                for posting in in_batch {
+                    if self.doc_pipelines[posting.doc] != pipeline
+                        || docs.is_some_and(|docs| !docs[posting.doc])
+                    {
+                        continue;
+                    }
+                    if scores.is_empty() {
+                        scores = vec![0.0; end - first];
+                    }
+                    scores[posting.doc - first] +=
+                        idf * self.tf_norm(posting.doc, &posting.positions);
+                }
+            }
+        }
+        scores
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, score)| score > 0.0)
+            .map(|(i, score)| (first + i, score))
+            .collect()
+    }
+
+    /// The `top_k` documents containing `phrase`: its words in a row, as
+    /// analyzed (so stopwords in between are skipped), within one field.
+    /// They're scored by BM25 with the phrase as one term, counting its
+    /// occurrences; ties go to the earlier document.
+    pub fn search_phrase(&self, phrase: &str, top_k: usize) -> Vec<(usize, f64)> {
+        self.search_phrase_in(phrase, top_k, None)
+    }
+
+    /// Like `search_phrase`, among the documents whose metadata passes
+    /// `filter`.
+    pub fn search_phrase_filtered(
+        &self,
+        phrase: &str,
+        top_k: usize,
+        filter: &Filter,
+    ) -> Result<Vec<(usize, f64)>> {
+        let docs = self.filter_docs(filter)?;
+        Ok(self.search_phrase_in(phrase, top_k, Some(&docs)))
+    }
+
+    /// `search_phrase` among the documents `docs` marks, or all of them.
+    fn search_phrase_in(
+        &self,
+        phrase: &str,
+        top_k: usize,
+        docs: Option<&[bool]>,
+    ) -> Vec<(usize, f64)> {
+        let matches: Vec<(usize, Vec<u32>)> = self
+            .pipelines
+            .iter()
+            .enumerate()
+            .flat_map(|(pipeline, options)| self.phrase_matches(pipeline, &options.words(phrase)))
+            .collect();
+        let idf = self.idf(matches.len());
+        let mut scores: Vec<(usize, f64)> = matches
+            .into_iter()
+            .filter(|&(doc, _)| docs.is_none_or(|docs| docs[doc]))
+            .map(|(doc, starts)| (doc, idf * self.tf_norm(doc, &starts)))
+            .collect();
+        keep_top(&mut scores, top_k);
+        scores
+    }
+
+    /// The documents of `pipeline` where `words` occur in a row, with the
+    /// positions where they start, in document order.
+    fn phrase_matches(&self, pipeline: usize, words: &[String]) -> Vec<(usize, Vec<u32>)> {
+        let Some(lists) = words
+            .iter()
+            .map(|word| self.term_postings(word))
+            .collect::<Option<Vec<&[Posting]>>>()
+        else {
+            return vec![];
+        };
+        let Some((first, rest)) = lists.split_first() else {
+            return vec![];
+        };
+        first
+            .par_iter()
+            .filter(|posting| self.doc_pipelines[posting.doc] == pipeline)
+            .filter_map(|posting| {
+                // The positions of each later word in the document
+                let later: Vec<&[u32]> = rest
+                    .iter()
+                    .map(|list| {
+                        let i = list.binary_search_by_key(&posting.doc, |p| p.doc).ok()?;
+                        Some(list[i].positions.as_slice())
+                    })
+                    .collect::<Option<_>>()?;
+                let starts: Vec<u32> = posting
+                    .positions
+                    .iter()
+                    .copied()
+                    .filter(|&start| {
+                        later.iter().zip(1..).all(|(positions, offset)| {
+                            positions.binary_search(&(start + offset)).is_ok()
+                        })
+                    })
+                    .collect();
+                (!starts.is_empty()).then_some((posting.doc, starts))
+            })
+            .collect()
+    }
+
+    /// The postings of an indexed term, or None if no document has it.
+    fn term_postings(&self, term: &str) -> Option<&[Posting]> {
+        let &id = self.terms.get(term)?;
+        Some(&self.postings[id as usize])
+    }
+
+    /// The ID of an indexed term, or None if no document has it.
+    pub(crate) fn term_id(&self, term: &str) -> Option<u32> {
+        self.terms.get(term).copied()
+    }
+
+    /// The postings of each term, by term ID.
+    pub(crate) fn postings(&self) -> &[Vec<Posting>] {
+        &self.postings
+    }
+
+    /// The terms of `text` as the index-wide pipeline makes them.
+    pub(crate) fn analyze(&self, text: &str) -> Vec<String> {
+        self.pipelines[0].terms(text)
+    }
+
+    /// Number of indexed documents.
+    pub(crate) fn n_docs(&self) -> usize {
+        self.n_docs
+    }
+
+    /// The IDF of an indexed term, or None if no document has it.
+    pub fn term_idf(&self, term: &str) -> Option<f64> {
+        let postings = self.term_postings(term)?;
+        Some(self.idf(postings.len()))
+    }
+
+    /// Each term of document `doc` with its frequency there.
+    pub fn term_frequencies(&self, doc: usize) -> HashMap<String, usize> {
+        self.terms
+            .par_iter()
+            .filter_map(|(term, &id)| {
+                let postings = &self.postings[id as usize];
+                let i = postings.binary_search_by_key(&doc, |p| p.doc).ok()?;
+                Some((term.clone(), postings[i].positions.len()))
+            })
+            .collect()
+    }
+
+    /// Each indexed term with its document frequency.
+    pub fn vocabulary(&self) -> HashMap<String, usize> {
+        self.terms
+            .iter()
+            .map(|(term, &id)| (term.clone(), self.postings[id as usize].len()))
+            .collect()
+    }
+
+    /// Which documents have metadata passing `filter`: a value it accepts
+    /// for each of its keys.
+    fn filter_docs(&self, filter: &Filter) -> Result<Vec<bool>> {
+        let Some(metadata) = &self.metadata else {
+            anyhow::bail!("Documents have no metadata; build the index with metadata=[...]");
+        };
+        Ok(metadata
+            .iter()
+            .map(|doc| {
+                filter
+                    .iter()
+                    .all(|(key, wanted)| doc.get(key).is_some_and(|value| wanted.accepts(value)))
+            })
+            .collect())
+    }
+
+    /// The IDF of a term in `df` documents: log((N - df + 0.5) / (df + 0.5) + 1).
+    fn idf(&self, df: usize) -> f64 {
+        let df = df as f64;
+        ((self.n_docs as f64 - df + 0.5) / (df + 0.5) + 1.0).ln()
+    }
+
+    /// The frequency of a term at `positions` in document `doc`, length
+    /// normalized and saturated. With fields, the frequency is BM25F's:
+    /// the sum of each field's frequency over its length norm, by weight.
+    fn tf_norm(&self, doc: usize, positions: &[u32]) -> f64 {
+        let tf = if self.fields.is_empty() {
+            positions.len() as f64 / self.length_norm(self.doc_lengths[doc], self.avg_dl)
+        } else {
+            let mut tf = 0.0;
+            let mut rest = positions;
+            let mut end = 0;
+            for (field, &len) in self.fields.iter().zip(&self.field_lengths[doc]) {
+                end += len as u32;
+                let in_field = rest.partition_point(|&p| p < end);
+                if in_field > 0 {
+                    tf += field.weight * in_field as f64 / self.length_norm(len, field.avg_len);
+                }
+                rest = &rest[in_field..];
+                // Past the gap before the next field
+                end += 1;
+            }
+            tf
+        };
+        tf * (self.k1 + 1.0) / (tf + self.k1)
+    }
+
+    /// The BM25 length norm of a text `len` terms long, in a collection
+    /// averaging `avg_len`: 1 - b + b × len/avg_len.
+    fn length_norm(&self, len: usize, avg_len: f64) -> f64 {
+        1.0 - self.b + self.b * len as f64 / avg_len
+    }
+
+    /// Write the index to `path`, replacing any file there.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the index, tagged with `SAVE_MAGIC`, to `writer`.
+    fn write_to(&self, mut writer: impl Write) -> Result<()> {
+        if self.pipelines[0].tokenizer.is_some() {
+            anyhow::bail!("An index with a custom tokenizer can't be saved");
+        }
+        writer.write_all(SAVE_MAGIC)?;
+        bincode::serialize_into(writer, self)?;
+        Ok(())
+    }
+
+    /// Raise the first error of the custom tokenizer, if any, since the
+    /// last check.
+    fn check_tokenizer(&self) -> PyResult<()> {
+        match &self.pipelines[0].tokenizer {
+            Some(tokenizer) => tokenizer.check(),
+            None => Ok(()),
+        }
+    }
+
+    /// Read an index written by `save`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+        Self::read_from(BufReader::new(file), &path.display().to_string())
+    }
+
+    /// Read an index written by `write_to` from `reader`; `source` names
+    /// it in errors.
+    fn read_from(mut reader: impl Read, source: &str) -> Result<Self> {
+        let mut magic = [0u8; SAVE_MAGIC.len()];
+        // The tag without the version
+        if reader.read_exact(&mut magic).is_err() || magic[..6] != SAVE_MAGIC[..6] {
+            anyhow::bail!("{} is not a saved BM25 index", source);
+        }
+        if magic != *SAVE_MAGIC {
+            anyhow::bail!(
+                "{} was saved by an incompatible version; rebuild the index",
+                source
+            );
+        }
+        bincode::deserialize_from(reader)
+            .with_context(|| format!("{} is not a valid BM25 index", source))
+    }
+}
+
+/// The term options an index over `documents` is built with, from the
+/// constructor arguments shared by `BM25Index` and `TfIdfIndex`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn term_options(
+    documents: &[String],
+    language: Option<&str>,
+    stopwords: Option<StopwordList>,
+    stemmer: Option<&str>,
+    fold_accents: bool,
+    ngrams: usize,
+    word_chars: Option<&str>,
+    lemmatize: bool,
+    normalize_numbers: bool,
+    keep_compounds: bool,
+    lowercase: bool,
+) -> Result<TermOptions> {
+    let analyzer = resolve_analyzer(language, documents)?;
+    let stopwords = match stopwords {
+        Some(list) => list.resolve(|| language_sample(documents))?,
+        None => HashSet::new(),
+    };
+    if language.is_some() && stemmer.is_some() {
+        anyhow::bail!("Pass either language or stemmer: a language analyzer stems already");
+    }
+    let stemmer = stemmer.map(analyzer::stemmer_language).transpose()?;
+    let mut options = TermOptions {
+        analyzer,
+        stopwords,
+        stemmer,
+        lemmatize,
+        fold_accents: false,
+        ngrams,
+        words: word_chars.map(TokenizerOptions::new).unwrap_or_default(),
+        tokenizer: None,
+    };
+    if !lowercase {
+        options.words = options.words.keeping_case();
+    }
+    if normalize_numbers {
+        options.words = options.words.normalizing_numbers();
+    }
+    if keep_compounds {
+        options.words = options.words.keeping_compounds();
+    }
+    if fold_accents {
+        options = options.folding_accents();
+    }
+    Ok(options)
+}
+
+/// The leading documents, joined, to guess the corpus language from.
+pub fn language_sample(documents: &[String]) -> String {
+    documents
+        .iter()
+        .take(LANGUAGE_SAMPLE_DOCS)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-#[pymethods]
-impl BM25Index {
-    /// Build a BM25 index from a list of document strings.
-    ///
-    /// Args:
-    ///     documents: List of text strings to index.
-    ///     k1: Term frequency saturation parameter (default 1.2).
-    ///     b: Length normalization parameter (default 0.75).
-    #[new]
-    #[pyo3(signature = (documents, k1=1.2, b=0.75))]
-    fn new(documents: Vec<String>, k1: f64, b: f64) -> Self {
-        let n_docs = documents.len();
-        let mut df: HashMap<String, usize> = HashMap::new();
-        let mut tf: Vec<HashMap<String, usize>> = Vec::with_capacity(n_docs);
-        let mut doc_lengths: Vec<usize> = Vec::with_capacity(n_docs);
+/// Resolve the `language` constructor argument into an analyzer.
+fn resolve_analyzer(language: Option<&str>, documents: &[String]) -> Result<Option<Analyzer>> {
+    let Some(name) = language else {
+        return Ok(None);
+    };
+
+    if name.eq_ignore_ascii_case("auto") {
+        return Ok(analyzer::guess_language(&language_sample(documents)).map(Analyzer::new));
+    }
+
+    parse_language(name).map(|lang| Some(Analyzer::new(lang)))
+}
+
+/// Keep the best `top_k` (document, score) pairs, best first, the earlier
+/// document first on a tie.
+///
+/// The top k are selected in linear time and only they are sorted, rather
+/// than all the scores.
+pub(crate) fn keep_top(scores: &mut Vec<(usize, f64)>, top_k: usize) {
+    let best_first = |a: &(usize, f64), b: &(usize, f64)| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0));
+    if top_k == 0 {
+        scores.clear();
+        return;
+    }
+    if scores.len() > top_k {
+        scores.select_nth_unstable_by(top_k - 1, best_first);
+        scores.truncate(top_k);
+    }
+    scores.sort_by(best_first);
+}
 
-        for doc in &documents {
-            let tokens = tokenizer::tokenize(doc);
-            doc_lengths.push(tokens.len());
+/// The language of a name or code.
+fn parse_language(name: &str) -> Result<Language> {
+    match Language::from_name(name) {
+        Some(lang) => Ok(lang),
+        None => anyhow::bail!("Unsupported language: {}", name),
+    }
+}
 
-            let mut term_freq: HashMap<String, usize> = HashMap::new();
-            let mut seen: HashSet<String> = HashSet::new();
+// See `bindings` in lib.rs.
+#[allow(clippy::useless_conversion)]
+mod py_bm25_index {
+    use super::*;
 
-            for token in &tokens {
-                *term_freq.entry(token.clone()).or_insert(0) += 1;
-                if seen.insert(token.clone()) {
-                    *df.entry(token.clone()).or_insert(0) += 1;
+    #[pymethods]
+    impl BM25Index {
+        /// Build a BM25 index from a list of document strings.
+        ///
+        /// Args:
+        ///     documents: List of text strings to index, or of dicts of field
+        ///         name → text (e.g. {"title": ..., "body": ...}); a string
+        ///         among dicts is a "body" field.
+        ///     k1: Term frequency saturation parameter (default 1.2).
+        ///     b: Length normalization parameter (default 0.75).
+        ///     language: Analyzer language code ("en", "de", ...), "auto" to
+        ///         detect it from the documents, or None for plain tokenization.
+        ///     stopwords: Words to drop without stemming the rest: a language
+        ///         code, "auto" to detect it, or a list of words (default none).
+        ///     stemmer: Snowball stemmer language ("english", "de", ...) or
+        ///         "porter", to stem words without dropping stopwords; not
+        ///         needed with `language`, which stems already.
+        ///     fold_accents: Whether to fold accents off words, so "résumé"
+        ///         matches "resume" (default false).
+        ///     ngrams: Longest run of words also indexed as one term (2 for
+        ///         bigrams, 3 for trigrams too); default 1, words only.
+        ///     word_chars: Characters kept in words besides letters and digits
+        ///         (default an apostrophe).
+        ///     lemmatize: Whether to reduce English words to their dictionary
+        ///         form first, so "better" matches "good" and "mice" "mouse"
+        ///         (default false); ignored for other analyzer languages.
+        ///     normalize_numbers: Whether numbers and dates are single terms in
+        ///         one spelling, so "1,000" matches "1000" and "March 1, 2024"
+        ///         matches "2024-03-01" (default false).
+        ///     keep_compounds: Whether hyphenated compounds are terms as well as
+        ///         their parts, so "state-of-the-art" and "state of the art"
+        ///         both match either (default false).
+        ///     languages: Each document's language code, analyzing it with
+        ///         that language's stopwords and stemmer (and the other
+        ///         settings); documents whose code is None use `language`.
+        ///     store_documents: Whether the index keeps the documents' text for
+        ///         `search_with_docs` (default false).
+        ///     ids: Each document's ID, returned by `search_with_docs` in place
+        ///         of its index.
+        ///     field_weights: Weight of each field (e.g. {"title": 3.0,
+        ///         "heading": 2.0}); unlisted fields weigh 1.0. With fields,
+        ///         documents are scored by BM25F, each field length-normalized
+        ///         against its own average, and stored as their fields' text
+        ///         joined, heaviest first.
+        ///     metadata: Each document's metadata, a dict of str, int, float,
+        ///         or bool values, for the `filter` of searches.
+        ///     synonyms: "builtin" for a bundled table of common synonyms, or
+        ///         a dict of word → words or phrases also searched for when a
+        ///         query has the word (e.g. {"car": ["automobile"]}); phrase
+        ///         searches aren't expanded.
+        ///     fuzzy: Most edits (0, 1, or 2; default 0) between a query term
+        ///         the index lacks and the indexed terms searched for in its
+        ///         place, so "tranformer" finds "transformer". Terms of five
+        ///         letters or fewer get one edit at most, shorter ones than
+        ///         four and numbers none; phrase searches stay exact.
+        ///     fuzzy_penalty: Factor (0 to 1; default 0.5) on a fuzzy match's
+        ///         score for each edit, so exact matches rank first.
+        ///     lowercase: Whether words are lowercased (default true); False
+        ///         keeps case for text where it matters, like code or chemical
+        ///         formulas. Stopwords and stemmers only match lowercase words.
+        ///     tokenizer: A callable splitting a text into a list of tokens,
+        ///         used for documents and queries in place of the built-in
+        ///         tokenizer (so `word_chars`, `normalize_numbers`,
+        ///         `keep_compounds`, and `lowercase` don't apply); stopwords,
+        ///         stemming, and n-grams still do. An index with one can't be
+        ///         saved.
+        #[new]
+        #[pyo3(signature = (
+            documents, k1=1.2, b=0.75, language=None, stopwords=None, stemmer=None, fold_accents=false,
+            ngrams=1, word_chars=None, lemmatize=false, normalize_numbers=false, keep_compounds=false,
+            languages=None, store_documents=false, ids=None, field_weights=None,
+            metadata=None, synonyms=None, lowercase=true, tokenizer=None, fuzzy=0,
+            fuzzy_penalty=0.5,
+        ))]
+        #[allow(clippy::too_many_arguments)]
+        fn py_new(
+            documents: Vec<DocumentInput>,
+            k1: f64,
+            b: f64,
+            language: Option<&str>,
+            stopwords: Option<StopwordList>,
+            stemmer: Option<&str>,
+            fold_accents: bool,
+            ngrams: usize,
+            word_chars: Option<&str>,
+            lemmatize: bool,
+            normalize_numbers: bool,
+            keep_compounds: bool,
+            languages: Option<Vec<Option<String>>>,
+            store_documents: bool,
+            ids: Option<Vec<String>>,
+            field_weights: Option<HashMap<String, f64>>,
+            metadata: Option<Vec<Metadata>>,
+            synonyms: Option<SynonymMap>,
+            lowercase: bool,
+            tokenizer: Option<PyObject>,
+            fuzzy: usize,
+            fuzzy_penalty: f64,
+        ) -> PyResult<Self> {
+            let to_py_err = |e: anyhow::Error| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e))
+            };
+            let (documents, fielded) = split_fields(documents, field_weights).map_err(to_py_err)?;
+            let mut options = term_options(
+                &documents,
+                language,
+                stopwords,
+                stemmer,
+                fold_accents,
+                ngrams,
+                word_chars,
+                lemmatize,
+                normalize_numbers,
+                keep_compounds,
+                lowercase,
+            )
+            .map_err(to_py_err)?;
+            if let Some(callable) = tokenizer {
+                options.tokenizer = Some(Arc::new(PyTokenizer::new(callable)));
+            }
+            let languages = match languages {
+                Some(codes) if codes.len() != documents.len() => {
+                    return Err(to_py_err(anyhow::anyhow!(
+                        "Got {} languages for {} documents",
+                        codes.len(),
+                        documents.len()
+                    )));
+                }
+                Some(codes) => codes
+                    .iter()
+                    .map(|code| code.as_deref().map(parse_language).transpose())
+                    .collect::<Result<Vec<_>>>()
+                    .map_err(to_py_err)?,
+                None => Vec::new(),
+            };
+            if let Some(ids) = &ids {
+                if ids.len() != documents.len() {
+                    return Err(to_py_err(anyhow::anyhow!(
+                        "Got {} ids for {} documents",
+                        ids.len(),
+                        documents.len()
+                    )));
+                }
+            }
+            if let Some(metadata) = &metadata {
+                if metadata.len() != documents.len() {
+                    return Err(to_py_err(anyhow::anyhow!(
+                        "Got {} metadata dicts for {} documents",
+                        metadata.len(),
+                        documents.len()
+                    )));
                 }
             }
+            let synonyms = synonyms
+                .map(SynonymMap::resolve)
+                .transpose()
+                .map_err(to_py_err)?
+                .unwrap_or_default();
+            if fuzzy > MAX_FUZZY_EDITS {
+                return Err(to_py_err(anyhow::anyhow!(
+                    "fuzzy must be 0, 1, or 2 edits, got {}",
+                    fuzzy
+                )));
+            }
+            if fuzzy_penalty.is_nan() || fuzzy_penalty <= 0.0 || fuzzy_penalty > 1.0 {
+                return Err(to_py_err(anyhow::anyhow!(
+                    "fuzzy_penalty must be above 0 and at most 1, got {}",
+                    fuzzy_penalty
+                )));
+            }
+            let (index, stored) = match fielded {
+                Some(FieldedDocuments { fields, texts }) => {
+                    let index = Self::with_fields(texts, fields, k1, b, options, &languages);
+                    (index, store_documents.then_some(documents))
+                }
+                None => {
+                    let stored = store_documents.then(|| documents.clone());
+                    let index = Self::with_languages(documents, k1, b, options, &languages);
+                    (index, stored)
+                }
+            };
+            let index = BM25Index {
+                documents: stored,
+                ids,
+                metadata,
+                synonyms,
+                fuzzy,
+                fuzzy_penalty,
+                ..index
+            };
+            index.check_tokenizer()?;
+            Ok(index)
+        }
 
-            tf.push(term_freq);
+        /// Score all documents against the query and return top-k results.
+        ///
+        /// Returns a list of (document_index, score) tuples, sorted by
+        /// score descending. Only documents with score > 0 are returned.
+        ///
+        /// Documents are scored in parallel on all CPU cores.
+        ///
+        /// The query may require words, with `+word` or `word AND word`, and
+        /// exclude them, with `-word` or `NOT word` (e.g. "transformer
+        /// -vision"); `OR` and plain words only add to the score.
+        ///
+        /// `filter` (e.g. {"source": "report.pdf"}, or {"source": ["a.pdf",
+        /// "b.pdf"]} for any of several values) limits results to documents
+        /// whose metadata has the given value for every key.
+        #[pyo3(name = "search", signature = (query, top_k=10, filter=None))]
+        fn py_search(
+            &self,
+            py: Python<'_>,
+            query: &str,
+            top_k: usize,
+            filter: Option<Filter>,
+        ) -> PyResult<Vec<(usize, f64)>> {
+            let hits = py
+                .allow_threads(|| match &filter {
+                    Some(filter) => self.search_filtered(query, top_k, filter),
+                    None => Ok(self.search(query, top_k)),
+                })
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+            self.check_tokenizer()?;
+            Ok(hits)
         }
 
-        let avg_dl = if n_docs > 0 {
-            doc_lengths.iter().sum::<usize>() as f64 / n_docs as f64
-        } else {
-            0.0
-        };
+        /// Score many queries in one call, returning the `search` results of
+        /// each, in order.
+        ///
+        /// Queries are scored in parallel on all CPU cores without returning
+        /// to Python in between, which saves per-call overhead when evaluating
+        /// hundreds of queries. `filter` applies to every query.
+        #[pyo3(name = "search_many", signature = (queries, top_k=10, filter=None))]
+        fn py_search_many(
+            &self,
+            py: Python<'_>,
+            queries: Vec<String>,
+            top_k: usize,
+            filter: Option<Filter>,
+        ) -> PyResult<Vec<Vec<(usize, f64)>>> {
+            let results = py
+                .allow_threads(|| self.search_many(&queries, top_k, filter.as_ref()))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+            self.check_tokenizer()?;
+            Ok(results)
+        }
 
-        BM25Index {
-            df,
-            tf,
-            doc_lengths,
-            avg_dl,
-            n_docs,
-            k1,
-            b,
+        /// Return the IDF of a term, or None if no document has it.
+        ///
+        /// The term is analyzed like a query (with the index-wide settings,
+        /// so "Running" is looked up as "run" when stemming) unless `analyze`
+        /// is False, for terms taken from `vocabulary` or `term_frequencies`.
+        #[pyo3(name = "idf", signature = (term, analyze=true))]
+        fn py_idf(&self, term: &str, analyze: bool) -> PyResult<Option<f64>> {
+            if !analyze {
+                return Ok(self.term_idf(term));
+            }
+            let terms = self.analyze(term);
+            self.check_tokenizer()?;
+            match terms.as_slice() {
+                [] => Ok(None),
+                [analyzed] => Ok(self.term_idf(analyzed)),
+                terms => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Expected one term, got {}: {:?}",
+                    terms.len(),
+                    terms
+                ))),
+            }
         }
-    }
 
-    /// Score all documents against the query and return top-k results.
-    ///
-    /// Returns a list of (document_index, score) tuples, sorted by
-    /// score descending. Only documents with score > 0 are returned.
-    #[pyo3(signature = (query, top_k=10))]
-    fn search(&self, query: &str, top_k: usize) -> Vec<(usize, f64)> {
-        let query_tokens = tokenizer::tokenize(query);
-        let mut scores: Vec<(usize, f64)> = Vec::new();
+        /// Return a dict of each term in the document at `doc_idx` (as
+        /// indexed: analyzed, with n-grams if any) → its frequency there,
+        /// to see why the document scored as it did.
+        #[pyo3(name = "term_frequencies")]
+        fn py_term_frequencies(
+            &self,
+            py: Python<'_>,
+            doc_idx: usize,
+        ) -> PyResult<HashMap<String, usize>> {
+            if doc_idx >= self.n_docs {
+                return Err(PyErr::new::<pyo3::exceptions::PyIndexError, _>(format!(
+                    "Document {} out of range for {} documents",
+                    doc_idx, self.n_docs
+                )));
+            }
+            Ok(py.allow_threads(|| self.term_frequencies(doc_idx)))
+        }
 
-        for (doc_idx, doc_tf) in self.tf.iter().enumerate() {
-            let doc_len = self.doc_lengths[doc_idx] as f64;
-            let mut score = 0.0;
+        /// Return a dict of every indexed term → the number of documents
+        /// containing it.
+        #[pyo3(name = "vocabulary")]
+        fn py_vocabulary(&self) -> HashMap<String, usize> {
+            self.vocabulary()
+        }
 
-            for token in &query_tokens {
-                let tf = *doc_tf.get(token).unwrap_or(&0) as f64;
-                let df = *self.df.get(token).unwrap_or(&0) as f64;
+        /// Score every document against the query.
+        ///
+        /// Returns one score per document, in index order, with 0.0 for
+        /// documents sharing no term with the query or ruled out by its
+        /// operators (see `search`): the full score vector
+        /// evaluation metrics and learned fusion need, where `search` only
+        /// returns the top k. `numpy.asarray(scores)` makes it an array.
+        #[pyo3(name = "get_scores")]
+        fn py_get_scores(&self, py: Python<'_>, query: &str) -> PyResult<Vec<f64>> {
+            let scores = py.allow_threads(|| self.get_scores(query));
+            self.check_tokenizer()?;
+            Ok(scores)
+        }
 
-                if tf == 0.0 {
-                    continue;
-                }
+        /// Return the top-k matches of the query with their documents.
+        ///
+        /// Like `search`, but returns (id, score, text) tuples: each document's
+        /// ID if the index was built with `ids` (else its index) and its text.
+        /// Needs an index built with `store_documents=True`.
+        #[pyo3(signature = (query, top_k=10, filter=None))]
+        fn search_with_docs(
+            &self,
+            py: Python<'_>,
+            query: &str,
+            top_k: usize,
+            filter: Option<Filter>,
+        ) -> PyResult<Vec<(PyObject, f64, String)>> {
+            let Some(documents) = &self.documents else {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(NOT_STORED));
+            };
+            let hits = self.py_search(py, query, top_k, filter)?;
+            Ok(hits
+                .into_iter()
+                .map(|(doc, score)| {
+                    let id = match &self.ids {
+                        Some(ids) => ids[doc].clone().into_py(py),
+                        None => doc.into_py(py),
+                    };
+                    (id, score, documents[doc].clone())
+                })
+                .collect())
+        }
 
-                // IDF: log((N - df + 0.5) / (df + 0.5) + 1)
-                let idf = ((self.n_docs as f64 - df + 0.5) / (df + 0.5) + 1.0).ln();
+        /// Return each document's BM25 term weights as a sparse vector, for a
+        /// sparse (or hybrid) Qdrant collection.
+        ///
+        /// Returns one {"indices": [...], "values": [...]} dict per document,
+        /// in index order, ready to pass as a `qdrant_client.models.
+        /// SparseVector`. Dimensions are 32-bit hashes of the terms, stable
+        /// across indexes, and values the BM25 term-frequency weights, times
+        /// the terms' IDF if `include_idf`; with `include_idf=False`, configure
+        /// the sparse vectors with `modifier=Modifier.IDF` so Qdrant applies
+        /// IDF over the whole collection instead. Search with `sparse_query`'s
+        /// vector: the dot product is the document's BM25 score.
+        #[pyo3(name = "sparse_vectors", signature = (include_idf=true))]
+        fn py_sparse_vectors<'py>(
+            &self,
+            py: Python<'py>,
+            include_idf: bool,
+        ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+            let vectors = py.allow_threads(|| self.sparse_vectors(include_idf));
+            vectors
+                .into_iter()
+                .map(|vector| vector.into_dict(py))
+                .collect()
+        }
 
-                // TF with length normalization
-                let tf_norm = (tf * (self.k1 + 1.0))
-                    / (tf + self.k1 * (1.0 - self.b + self.b * doc_len / self.avg_dl));
+        /// Return the query as a sparse vector for searching the vectors of
+        /// `sparse_vectors`: its terms (analyzed as documents were, with
+        /// synonyms) by the same hashes, valued by how often each occurs.
+        #[pyo3(name = "sparse_query")]
+        fn py_sparse_query<'py>(
+            &self,
+            py: Python<'py>,
+            query: &str,
+        ) -> PyResult<Bound<'py, PyDict>> {
+            let vector = py.allow_threads(|| self.sparse_query(query));
+            self.check_tokenizer()?;
+            vector.into_dict(py)
+        }
 
-                score += idf * tf_norm;
+        /// Return the passage of the document at `doc_idx` that best matches
+        /// the query, with the words matching it marked.
+        ///
+        /// The passage is the `window` consecutive tokens holding the most
+        /// (and rarest) distinct query terms, as written in the document, with
+        /// each matching word between `pre` and `post`; words match as in
+        /// `search`, stemmed, with synonyms and fuzzy matches. Shows a user why
+        /// a result matched. The text isn't escaped, so for HTML escape it
+        /// first or pick markers accordingly. Needs an index built with
+        /// `store_documents=True`.
+        #[pyo3(
+            name = "highlight",
+            signature = (query, doc_idx, window=30, pre="<mark>", post="</mark>"),
+        )]
+        fn py_highlight(
+            &self,
+            py: Python<'_>,
+            query: &str,
+            doc_idx: usize,
+            window: usize,
+            pre: &str,
+            post: &str,
+        ) -> PyResult<String> {
+            if doc_idx >= self.n_docs {
+                return Err(PyErr::new::<pyo3::exceptions::PyIndexError, _>(format!(
+                    "Document {} out of range for {} documents",
+                    doc_idx, self.n_docs
+                )));
             }
+            let snippet = py
+                .allow_threads(|| self.highlight(query, doc_idx, window, pre, post))
+                .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(NOT_STORED))?;
+            self.check_tokenizer()?;
+            Ok(snippet)
+        }
 
-            if score > 0.0 {
-                scores.push((doc_idx, score));
-            }
+        /// Return the top-k documents containing an exact phrase.
+        ///
+        /// Only documents with the phrase's words in a row match (stopwords
+        /// between them are skipped when the index drops stopwords), scored by
+        /// BM25 with the phrase as a single term. Returns (document_index,
+        /// score) tuples, sorted by score descending. `filter` limits results
+        /// as for `search`.
+        #[pyo3(name = "search_phrase", signature = (phrase, top_k=10, filter=None))]
+        fn py_search_phrase(
+            &self,
+            py: Python<'_>,
+            phrase: &str,
+            top_k: usize,
+            filter: Option<Filter>,
+        ) -> PyResult<Vec<(usize, f64)>> {
+            let hits = py
+                .allow_threads(|| match &filter {
+                    Some(filter) => self.search_phrase_filtered(phrase, top_k, filter),
+                    None => Ok(self.search_phrase(phrase, top_k)),
+                })
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+            self.check_tokenizer()?;
+            Ok(hits)
         }
 
-        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        scores.truncate(top_k);
-        scores
-    }
+        /// Write the index to `path` (a binary file), so later processes can
+        /// `load` it rather than rebuild it.
+        #[pyo3(name = "save")]
+        fn py_save(&self, py: Python<'_>, path: &str) -> PyResult<()> {
+            py.allow_threads(|| self.save(Path::new(path)))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+        }
 
-    /// Return the number of indexed documents.
-    fn __len__(&self) -> usize {
-        self.n_docs
-    }
+        /// Read an index written by `save`, with the settings it was built with.
+        #[staticmethod]
+        #[pyo3(name = "load")]
+        fn py_load(py: Python<'_>, path: &str) -> PyResult<Self> {
+            py.allow_threads(|| BM25Index::load(Path::new(path)))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+        }
+
+        /// The index as the bytes `save` would write, for storing it somewhere
+        /// other than a plain file.
+        fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+            let mut data = Vec::new();
+            py.allow_threads(|| self.write_to(&mut data)).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e))
+            })?;
+            Ok(PyBytes::new_bound(py, &data))
+        }
+
+        /// Read an index from the bytes `to_bytes` returned.
+        #[staticmethod]
+        fn from_bytes(py: Python<'_>, data: &[u8]) -> PyResult<Self> {
+            py.allow_threads(|| BM25Index::read_from(data, "The data"))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+        }
+
+        /// Pickle support: the index as `to_bytes` returns it.
+        fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+            self.to_bytes(py)
+        }
+
+        /// Pickle support: replace this index with the one in `state`.
+        fn __setstate__(&mut self, py: Python<'_>, state: &[u8]) -> PyResult<()> {
+            *self = Self::from_bytes(py, state)?;
+            Ok(())
+        }
+
+        /// Pickle support: unpickling builds an empty index for
+        /// `__setstate__` to fill.
+        fn __getnewargs__(&self) -> (Vec<String>,) {
+            (Vec::new(),)
+        }
+
+        /// ISO 639-1 code of the analyzer language, or None for plain tokenization.
+        #[getter]
+        pub(crate) fn language(&self) -> Option<&'static str> {
+            self.pipelines[0]
+                .analyzer
+                .as_ref()
+                .map(|a| a.language().code())
+        }
+
+        /// The (name, weight) of each field, heaviest first; empty when
+        /// documents are plain text.
+        #[getter]
+        fn fields(&self) -> Vec<(String, f64)> {
+            self.fields
+                .iter()
+                .map(|field| (field.name.clone(), field.weight))
+                .collect()
+        }
+
+        /// Return the number of indexed documents.
+        fn __len__(&self) -> usize {
+            self.n_docs
+        }
 
-    /// String representation for debugging.
-    fn __repr__(&self) -> String {
-        format!(
-            "BM25Index(n_docs={}, vocab_size={}, avg_dl={:.1}, k1={}, b={})",
-            self.n_docs,
-            self.df.len(),
-            self.avg_dl,
-            self.k1,
-            self.b
-        )
+        /// String representation for debugging.
+        fn __repr__(&self) -> String {
+            format!(
+                "BM25Index(n_docs={}, vocab_size={}, avg_dl={:.1}, k1={}, b={}, language={})",
+                self.n_docs,
+                self.terms.len(),
+                self.avg_dl,
+                self.k1,
+                self.b,
+                self.language().unwrap_or("none")
+            )
+        }
     }
 }
 
@@ -157,6 +2103,44 @@ mod tests {
         assert_eq!(index.doc_lengths, vec![6, 6, 5]);
     }
 
+    #[test]
+    fn test_postings() {
+        let docs = vec![
+            "the cat sat on the mat".to_string(),
+            "the dog sat on the log".to_string(),
+            "the cat chased the dog".to_string(),
+        ];
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        // Postings list the documents containing each term, in order, with
+        // the term's positions
+        let postings = |term: &str| -> Vec<(usize, Vec<u32>)> {
+            index
+                .term_postings(term)
+                .unwrap()
+                .iter()
+                .map(|p| (p.doc, p.positions.clone()))
+                .collect()
+        };
+        assert_eq!(
+            postings("the"),
+            vec![(0, vec![0, 4]), (1, vec![0, 4]), (2, vec![0, 3])]
+        );
+        assert_eq!(postings("cat"), vec![(0, vec![1]), (2, vec![1])]);
+        assert!(index.term_postings("bird").is_none());
+    }
+
+    #[test]
+    fn test_term_interning() {
+        let docs = vec![
+            "the cat sat on the mat".to_string(),
+            "the dog sat on the log".to_string(),
+        ];
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        // Each distinct term is interned once, its ID indexing its postings
+        assert_eq!(index.terms.len(), index.postings.len());
+        assert_eq!(index.terms["the"], 0);
+    }
+
     #[test]
     fn test_search_relevance() {
         let docs = vec![
@@ -219,4 +2203,598 @@ mod tests {
         assert!(top_indices.contains(&0));
         assert!(top_indices.contains(&2));
     }
+
+    #[test]
+    fn test_search_phrase() {
+        let docs = vec![
+            "a network of neural cells".to_string(),
+            "a neural network, and another neural network".to_string(),
+            "the neural network".to_string(),
+            "network neural".to_string(),
+        ];
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        let results = index.search_phrase("Neural network", 5);
+        let top: Vec<usize> = results.iter().map(|r| r.0).collect();
+        // The phrase twice ranks first; its words apart or swapped don't match
+        assert_eq!(top, vec![1, 2]);
+        assert_eq!(index.search_phrase("neural network", 1).len(), 1);
+        assert_eq!(index.search_phrase("network", 5).len(), 4);
+        assert!(index.search_phrase("neural cats", 5).is_empty());
+        assert!(index.search_phrase("", 5).is_empty());
+    }
+
+    #[test]
+    fn test_search_phrase_skips_stopwords() {
+        let docs = vec![
+            "the state of the art".to_string(),
+            "the art of the state".to_string(),
+        ];
+        let analyzer = resolve_analyzer(Some("en"), &docs).unwrap();
+        let index = BM25Index::with_analyzer(docs, 1.2, 0.75, analyzer);
+        let results = index.search_phrase("state of the art", 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 0);
+    }
+
+    #[test]
+    fn test_keep_top() {
+        let mut scores = vec![(0, 1.0), (1, 3.0), (2, 2.0), (3, 3.0), (4, 0.5)];
+        keep_top(&mut scores, 3);
+        assert_eq!(scores, vec![(1, 3.0), (3, 3.0), (2, 2.0)]);
+        keep_top(&mut scores, 10);
+        assert_eq!(scores.len(), 3);
+        keep_top(&mut scores, 0);
+        assert!(scores.is_empty());
+    }
+
+    fn fielded(docs: &[&[&str]]) -> Vec<Vec<String>> {
+        docs.iter()
+            .map(|fields| fields.iter().map(|f| f.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_field_weights() {
+        let fields = vec![("title".to_string(), 3.0), ("body".to_string(), 1.0)];
+        let docs = fielded(&[
+            &["Rust", "A guide to writing safe systems code"],
+            &["Python", "Calling Rust from Python, and Rust from C"],
+            &["Go", "Concurrency with goroutines and channels"],
+        ]);
+        let index = BM25Index::with_fields(docs, fields, 1.2, 0.75, TermOptions::default(), &[]);
+        assert_eq!(index.field_lengths[0], [1, 7]);
+        // One title match outweighs two body matches
+        let results = index.search("rust", 10);
+        assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), [0, 1]);
+
+        // A phrase doesn't run from one field into the next
+        assert!(index.search_phrase("rust a guide", 10).is_empty());
+        assert_eq!(index.search_phrase("a guide", 10).len(), 1);
+    }
+
+    #[test]
+    fn test_one_field_scores_as_plain_bm25() {
+        let docs = vec![
+            "the cat sat on the mat".to_string(),
+            "the dog chased the cat around".to_string(),
+            "a bird".to_string(),
+        ];
+        let plain = BM25Index::new(docs.clone(), 1.2, 0.75);
+        let fielded = BM25Index::with_fields(
+            docs.into_iter().map(|doc| vec![doc]).collect(),
+            vec![("body".to_string(), 1.0)],
+            1.2,
+            0.75,
+            TermOptions::default(),
+            &[],
+        );
+        let (plain, fielded) = (plain.search("cat", 10), fielded.search("cat", 10));
+        assert_eq!(plain.len(), fielded.len());
+        for (p, f) in plain.iter().zip(&fielded) {
+            assert_eq!(p.0, f.0);
+            assert!((p.1 - f.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_metadata_filter() {
+        let docs = vec![
+            "rust ownership rules".to_string(),
+            "rust borrow checker".to_string(),
+            "python rust bindings".to_string(),
+        ];
+        let source = |name: &str| MetadataValue::Text(name.to_string());
+        let metadata = [("a.pdf", 1), ("b.pdf", 2), ("a.pdf", 3)]
+            .into_iter()
+            .map(|(name, page)| {
+                Metadata::from([
+                    ("source".to_string(), source(name)),
+                    ("page".to_string(), MetadataValue::Int(page)),
+                ])
+            })
+            .collect();
+        let index = BM25Index {
+            metadata: Some(metadata),
+            ..BM25Index::new(docs.clone(), 1.2, 0.75)
+        };
+        let found = |filter: &Filter| -> Vec<usize> {
+            let results = index.search_filtered("rust", 10, filter).unwrap();
+            let mut docs: Vec<usize> = results.into_iter().map(|r| r.0).collect();
+            docs.sort();
+            docs
+        };
+
+        let by_source = Filter::from([("source".to_string(), FilterValue::One(source("a.pdf")))]);
+        assert_eq!(found(&by_source), [0, 2]);
+        let any_of = Filter::from([(
+            "source".to_string(),
+            FilterValue::AnyOf(vec![source("b.pdf"), source("c.pdf")]),
+        )]);
+        assert_eq!(found(&any_of), [1]);
+        // Every key must match; an integer matches an equal float
+        let page = FilterValue::One(MetadataValue::Float(3.0));
+        let both = Filter::from([
+            ("source".to_string(), FilterValue::One(source("a.pdf"))),
+            ("page".to_string(), page),
+        ]);
+        assert_eq!(found(&both), [2]);
+        let missing_key = Filter::from([("author".to_string(), FilterValue::One(source("x")))]);
+        assert!(found(&missing_key).is_empty());
+
+        // Filtering doesn't change scores
+        let all = index.search("rust", 10);
+        let filtered = index.search_filtered("rust", 10, &by_source).unwrap();
+        assert!(filtered.iter().all(|hit| all.contains(hit)));
+        let phrase = index.search_phrase_filtered("rust bindings", 10, &by_source);
+        assert_eq!(phrase.unwrap().len(), 1);
+
+        let plain = BM25Index::new(docs, 1.2, 0.75);
+        assert!(plain.search_filtered("rust", 10, &by_source).is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_matching() {
+        let docs = vec![
+            "transformer models".to_string(),
+            "recurrent networks".to_string(),
+            "transformers explained".to_string(),
+        ];
+        let exact = BM25Index::new(docs.clone(), 1.2, 0.75);
+        assert!(exact.search("tranformer", 10).is_empty());
+        let index = |fuzzy| BM25Index {
+            fuzzy,
+            fuzzy_penalty: 0.5,
+            ..BM25Index::new(docs.clone(), 1.2, 0.75)
+        };
+
+        // One edit from "transformer", two from "transformers"
+        let one_edit = index(1).search("tranformer", 10);
+        assert_eq!(one_edit.len(), 1);
+        let expected = 0.5 * exact.search("transformer", 10)[0].1;
+        assert!((one_edit[0].1 - expected).abs() < 1e-12);
+        let hits = index(2).search("tranformer", 10);
+        assert_eq!(hits.iter().map(|h| h.0).collect::<Vec<_>>(), [0, 2]);
+        // Terms the index has aren't expanded, and short ones by one edit
+        assert_eq!(index(2).search("transformer", 10).len(), 1);
+        assert_eq!(index(2).search("modls", 10)[0].0, 0);
+        assert!(index(2).search("modl", 10).is_empty());
+        assert_eq!(index(2).search("netwrks", 10)[0].0, 1);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let docs = vec![
+            "Transformers changed NLP. The transformer architecture relies on attention, \
+             not recurrence."
+                .to_string(),
+            "Recurrent networks came first.".to_string(),
+        ];
+        let index = BM25Index {
+            documents: Some(docs.clone()),
+            ..BM25Index::new(docs.clone(), 1.2, 0.75)
+        };
+        let snippet = index.highlight("transformer attention", 0, 6, "[", "]");
+        assert_eq!(
+            snippet.unwrap(),
+            "The [transformer] architecture relies on [attention]"
+        );
+        // Excluded words aren't marked; with no match, the passage leads
+        let snippet = index.highlight("attention -recurrence", 0, 3, "[", "]");
+        assert_eq!(snippet.unwrap(), "relies on [attention]");
+        let snippet = index.highlight("zebra", 1, 3, "[", "]");
+        assert_eq!(snippet.unwrap(), "Recurrent networks came");
+        assert!(BM25Index::new(docs, 1.2, 0.75)
+            .highlight("nlp", 0, 5, "[", "]")
+            .is_none());
+    }
+
+    #[test]
+    fn test_sparse_vectors() {
+        let docs = vec![
+            "sparse vectors store term weights".to_string(),
+            "dense vectors store embeddings, dense ones".to_string(),
+            "nothing shared here".to_string(),
+        ];
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        let vectors = index.sparse_vectors(true);
+        let query = index.sparse_query("dense vectors vectors");
+        assert_eq!(query.values.iter().sum::<f32>(), 3.0);
+        // The dot product of query and document is the BM25 score
+        let scores = index.get_scores("dense vectors vectors");
+        for (vector, score) in vectors.iter().zip(scores) {
+            assert!(vector.indices.windows(2).all(|w| w[0] < w[1]));
+            let dot: f32 = query
+                .indices
+                .iter()
+                .zip(&query.values)
+                .filter_map(|(i, q)| {
+                    let at = vector.indices.binary_search(i).ok()?;
+                    Some(q * vector.values[at])
+                })
+                .sum();
+            assert!((f64::from(dot) - score).abs() < 1e-4, "{dot} vs {score}");
+        }
+        // Dimensions don't depend on the index
+        assert_eq!(index.sparse_query("dense").indices, [sparse_index("dense")]);
+        let without_idf = index.sparse_vectors(false);
+        assert_eq!(without_idf[2].indices, vectors[2].indices);
+        for (&with, &without) in vectors[2].values.iter().zip(&without_idf[2].values) {
+            assert!((f64::from(with / without) - index.idf(1)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        let docs = vec![
+            "transformer models for vision".to_string(),
+            "transformer models for language".to_string(),
+            "attention is all you need".to_string(),
+        ];
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        let hits =
+            |query: &str| -> Vec<usize> { index.search(query, 10).iter().map(|h| h.0).collect() };
+        assert_eq!(hits("transformer attention"), [2, 0, 1]);
+        assert_eq!(hits("transformer -vision"), [1]);
+        assert_eq!(hits("transformer NOT vision"), [1]);
+        assert_eq!(hits("+transformer attention"), [0, 1]);
+        assert_eq!(hits("models AND language"), [1]);
+        // Scores are unchanged by operators, only which documents count
+        let scores = index.get_scores("transformer -vision");
+        assert_eq!(scores[0], 0.0);
+        assert_eq!(scores[1], index.get_scores("transformer")[1]);
+        // A required stopword rules nothing out; only exclusions match nothing
+        let stemmed = BM25Index::with_languages(
+            vec!["the cat".to_string(), "a dog".to_string()],
+            1.2,
+            0.75,
+            TermOptions {
+                analyzer: Some(Analyzer::new(Language::English)),
+                ..TermOptions::default()
+            },
+            &[],
+        );
+        assert_eq!(stemmed.search("+the dog", 10).len(), 1);
+        assert!(stemmed.search("-cat", 10).is_empty());
+    }
+
+    #[test]
+    fn test_synonyms_expand_queries() {
+        let docs = vec![
+            "the automobile was parked outside".to_string(),
+            "a car in the garage".to_string(),
+            "a bicycle".to_string(),
+        ];
+        let index = BM25Index {
+            synonyms: synonyms::builtin(),
+            ..BM25Index::new(docs.clone(), 1.2, 0.75)
+        };
+        let mut found: Vec<usize> = index.search("Car", 10).iter().map(|r| r.0).collect();
+        found.sort();
+        assert_eq!(found, [0, 1]);
+        assert_eq!(BM25Index::new(docs, 1.2, 0.75).search("car", 10).len(), 1);
+
+        // Synonyms go through the index's analysis, and count once
+        let options = TermOptions {
+            stemmer: Some(Language::English),
+            ..TermOptions::default()
+        };
+        let index = BM25Index {
+            synonyms: HashMap::from([(
+                "vehicles".to_string(),
+                vec!["automobiles".to_string(), "automobile".to_string()],
+            )]),
+            ..BM25Index::with_options(vec!["one automobile".to_string()], 1.2, 0.75, options)
+        };
+        let terms = index.query_terms(&index.pipelines[0], "vehicles");
+        assert_eq!(terms, ["vehicl", "automobil"]);
+    }
+
+    #[test]
+    fn test_search_many() {
+        let docs = vec![
+            "rust systems programming".to_string(),
+            "python scripting".to_string(),
+            "rust and python bindings".to_string(),
+        ];
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        let queries = ["rust", "python", "haskell"].map(String::from);
+        let results = index.search_many(&queries, 10, None).unwrap();
+        let each: Vec<_> = queries.iter().map(|q| index.search(q, 10)).collect();
+        assert_eq!(results, each);
+        assert!(results[2].is_empty());
+    }
+
+    #[test]
+    fn test_get_scores() {
+        let docs: Vec<String> = (0..SCORE_BATCH_DOCS + 3)
+            .map(|i| match i % 3 {
+                0 => "rust ownership".to_string(),
+                1 => "python".to_string(),
+                _ => "rust rust borrowing".to_string(),
+            })
+            .collect();
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        let scores = index.get_scores("rust");
+        assert_eq!(scores.len(), SCORE_BATCH_DOCS + 3);
+        assert_eq!(scores[1], 0.0);
+        for (doc, score) in index.search("rust", 5) {
+            assert_eq!(scores[doc], score);
+        }
+        // Documents repeat every three, in both batches
+        assert!(scores.iter().enumerate().all(|(i, &s)| s == scores[i % 3]));
+        assert!(index.get_scores("haskell").iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_term_statistics() {
+        let docs = vec![
+            "the cat sat on the cat mat".to_string(),
+            "the dog".to_string(),
+        ];
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        let vocabulary = index.vocabulary();
+        assert_eq!(vocabulary["the"], 2);
+        assert_eq!(vocabulary["cat"], 1);
+        assert_eq!(vocabulary.len(), 6);
+
+        let frequencies = index.term_frequencies(0);
+        assert_eq!(frequencies["cat"], 2);
+        assert_eq!(frequencies["the"], 2);
+        assert!(!frequencies.contains_key("dog"));
+        assert_eq!(index.term_frequencies(1).len(), 2);
+
+        // Rarer terms weigh more
+        assert!(index.term_idf("cat").unwrap() > index.term_idf("the").unwrap());
+        assert_eq!(index.term_idf("bird"), None);
+    }
+
+    /// Splits on whitespace only, keeping chemical formulas whole.
+    struct WhitespaceTokenizer;
+
+    impl CustomTokenizer for WhitespaceTokenizer {
+        fn tokenize(&self, text: &str) -> Vec<String> {
+            text.split_whitespace().map(String::from).collect()
+        }
+    }
+
+    #[test]
+    fn test_custom_tokenizer() {
+        let docs = vec!["Fe(OH)3 is rust".to_string(), "Fe is iron".to_string()];
+        let options = TermOptions {
+            tokenizer: Some(Arc::new(WhitespaceTokenizer)),
+            stopwords: HashSet::from(["is".to_string()]),
+            ..TermOptions::default()
+        };
+        let index = BM25Index::with_options(docs.clone(), 1.2, 0.75, options);
+        assert_eq!(index.pipelines[0].terms("Fe(OH)3 is"), ["Fe(OH)3"]);
+        let results = index.search("Fe(OH)3", 10);
+        assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), [0]);
+        // The built-in tokenizer splits the formula, so "fe" matches both
+        let plain = BM25Index::new(docs, 1.2, 0.75);
+        assert_eq!(plain.search("Fe(OH)3", 10).len(), 2);
+
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.bm25", std::process::id()));
+        assert!(index.save(&path).is_err());
+    }
+
+    #[test]
+    fn test_keep_case() {
+        let docs = vec!["CO emissions".to_string(), "Co alloys".to_string()];
+        let options = TermOptions {
+            words: TokenizerOptions::default().keeping_case(),
+            ..TermOptions::default()
+        };
+        let index = BM25Index::with_options(docs.clone(), 1.2, 0.75, options);
+        assert_eq!(index.search("CO", 10).len(), 1);
+        assert_eq!(index.search("co", 10).len(), 0);
+        assert_eq!(BM25Index::new(docs, 1.2, 0.75).search("CO", 10).len(), 2);
+    }
+
+    #[test]
+    fn test_search_across_batches() {
+        // Several batches of documents, tied but for the last
+        let mut docs: Vec<String> = (0..SCORE_BATCH_DOCS * 2 + 10)
+            .map(|i| format!("{} word{}", if i % 3 == 0 { "term" } else { "other" }, i))
+            .collect();
+        let last = docs.len() - 1;
+        docs[last] = "term term".to_string();
+        let index = BM25Index::new(docs, 1.2, 0.75);
+        let results = index.search("term", 4);
+        let top: Vec<usize> = results.iter().map(|r| r.0).collect();
+        assert_eq!(top, vec![last, 0, 3, 6]);
+        assert_eq!(results[1].1, results[3].1);
+        assert_eq!(index.search("term", 0), vec![]);
+    }
+
+    #[test]
+    fn test_language_analyzer_matches_inflections() {
+        let docs = vec![
+            "The runner was running through the park".to_string(),
+            "A quiet library with many books".to_string(),
+        ];
+        let analyzer = resolve_analyzer(Some("en"), &docs).unwrap();
+        let index = BM25Index::with_analyzer(docs, 1.2, 0.75, analyzer);
+        let results = index.search("runs", 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 0);
+        // Stopwords are not indexed
+        assert!(index.search("the", 5).is_empty());
+    }
+
+    #[test]
+    fn test_auto_language_detection() {
+        let docs = vec![
+            "Der Hund läuft durch den Park".to_string(),
+            "Die Katze schläft auf dem Sofa".to_string(),
+        ];
+        let analyzer = resolve_analyzer(Some("auto"), &docs).unwrap();
+        let index = BM25Index::with_analyzer(docs, 1.2, 0.75, analyzer);
+        assert_eq!(index.language(), Some("de"));
+    }
+
+    #[test]
+    fn test_document_languages() {
+        let docs = vec![
+            "The runners were running through the park".to_string(),
+            "Die Hunde laufen durch den Park".to_string(),
+            "A quiet library with many books".to_string(),
+        ];
+        let analyzer = resolve_analyzer(Some("en"), &docs).unwrap();
+        let options = TermOptions {
+            analyzer,
+            ..TermOptions::default()
+        };
+        let languages = [Some(Language::English), Some(Language::German), None];
+        let index = BM25Index::with_languages(docs, 1.2, 0.75, options, &languages);
+        assert_eq!(index.pipelines.len(), 2);
+        assert_eq!(index.doc_pipelines, vec![0, 1, 0]);
+        assert_eq!(index.language(), Some("en"));
+        // Each document matches inflections in its own language
+        assert_eq!(index.search("runner", 5)[0].0, 0);
+        assert_eq!(index.search("Hund", 5)[0].0, 1);
+        // German stopwords are dropped from German documents only
+        assert_eq!(index.doc_lengths[1], 3);
+        assert!(index.search("die", 5).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let docs = vec![
+            "The runners were running through the park".to_string(),
+            "Die Hunde laufen durch den Park".to_string(),
+        ];
+        let options = TermOptions {
+            stemmer: Some(Language::English),
+            ngrams: 2,
+            ..TermOptions::default()
+        }
+        .folding_accents();
+        let languages = [None, Some(Language::German)];
+        let index = BM25Index::with_languages(docs, 1.2, 0.75, options, &languages);
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.bm25", std::process::id()));
+        index.save(&path).unwrap();
+
+        let loaded = BM25Index::load(&path).unwrap();
+        for query in ["runs", "Hund", "through the park", "the"] {
+            assert_eq!(loaded.search(query, 5), index.search(query, 5), "{query}");
+        }
+        assert_eq!(loaded.doc_pipelines, index.doc_pipelines);
+
+        std::fs::write(&path, b"not an index").unwrap();
+        let err = BM25Index::load(&path).err().unwrap();
+        assert!(err.to_string().contains("is not a saved BM25 index"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_stopwords_shorten_documents() {
+        let docs = vec!["the cat sat on the mat".to_string(), "The Dog".to_string()];
+        let stopwords = StopwordList::Language("en".into())
+            .resolve(String::new)
+            .unwrap();
+        let options = TermOptions {
+            stopwords,
+            ..TermOptions::default()
+        };
+        let index = BM25Index::with_options(docs, 1.2, 0.75, options);
+        assert_eq!(index.doc_lengths, vec![3, 1]);
+        assert!(index.search("the", 5).is_empty());
+        // Unlike a language analyzer, words aren't stemmed
+        assert!(index.search("cats", 5).is_empty());
+
+        let custom = StopwordList::Words(vec!["CAT".into()])
+            .resolve(String::new)
+            .unwrap();
+        let options = TermOptions {
+            stopwords: custom,
+            ..TermOptions::default()
+        };
+        let index = BM25Index::with_options(vec!["cat dog".into()], 1.2, 0.75, options);
+        assert_eq!(index.doc_lengths, vec![1]);
+    }
+
+    #[test]
+    fn test_stemmer_without_stopwords() {
+        let docs = vec![
+            "The runner was running".to_string(),
+            "A quiet library".to_string(),
+        ];
+        let options = TermOptions {
+            stemmer: Some(Language::English),
+            ..TermOptions::default()
+        };
+        let index = BM25Index::with_options(docs, 1.2, 0.75, options);
+        assert_eq!(index.search("runs", 5).len(), 1);
+        // Stopwords are kept
+        assert_eq!(index.doc_lengths, vec![4, 3]);
+        assert_eq!(index.language(), None);
+    }
+
+    #[test]
+    fn test_folding_accents() {
+        let docs = vec!["Le résumé de l'été".to_string(), "Un café".to_string()];
+        let analyzer = resolve_analyzer(Some("fr"), &docs).unwrap();
+        let options = TermOptions {
+            analyzer,
+            ..TermOptions::default()
+        };
+        let index = BM25Index::with_options(docs.clone(), 1.2, 0.75, options.folding_accents());
+        assert_eq!(index.search("resume", 5).len(), 1);
+        assert_eq!(index.search("cafe", 5)[0].0, 1);
+
+        let plain = BM25Index::new(docs, 1.2, 0.75);
+        assert!(plain.search("resume", 5).is_empty());
+    }
+
+    #[test]
+    fn test_ngrams_favour_phrases() {
+        let docs = vec![
+            "the learning curve of a machine operator".to_string(),
+            "an introduction to machine learning".to_string(),
+        ];
+        let options = TermOptions {
+            ngrams: 2,
+            ..TermOptions::default()
+        };
+        let index = BM25Index::with_options(docs, 1.2, 0.75, options);
+        let results = index.search("machine learning", 5);
+        assert_eq!(results[0].0, 1);
+        assert!(results[0].1 > results[1].1 * 1.5);
+        assert_eq!(index.doc_lengths, vec![7 + 6, 5 + 4]);
+    }
+
+    #[test]
+    fn test_terms_batch() {
+        let texts: Vec<String> = (0..50).map(|i| format!("Document {} text", i)).collect();
+        let options = TermOptions::default();
+        let sequential: Vec<Vec<String>> = texts.iter().map(|t| options.terms(t)).collect();
+        assert_eq!(options.terms_batch(&texts, None).unwrap(), sequential);
+        assert_eq!(options.terms_batch(&texts, Some(2)).unwrap(), sequential);
+        assert!(options.terms_batch(&[], Some(1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(resolve_analyzer(Some("klingon"), &[]).is_err());
+    }
 }
@@ -1,12 +1,122 @@
+use anyhow::Result;
+use pyo3::prelude::*;
 use rayon::prelude::*;
+use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokenizers::Tokenizer;
+
+use crate::tokenizer::TokenizerOptions;
+
+/// A chunk of a text, with where it lies in the text.
+///
+/// Every chunker has a `*_spans` variant returning its chunks' (start,
+/// end) byte ranges; `to_chunks` turns those into `Chunk`s.
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// The chunk's text
+    pub text: String,
+    /// Byte offset (in UTF-8) of the chunk's start in the source text
+    pub start_byte: usize,
+    /// Byte offset (in UTF-8) just past the chunk's end in the source text
+    pub end_byte: usize,
+    /// Position of the chunk among the text's chunks, from 0
+    pub index: usize,
+}
+
+impl Chunk {
+    pub fn new(text: &str, (start, end): (usize, usize), index: usize) -> Chunk {
+        Chunk {
+            text: text[start..end].to_string(),
+            start_byte: start,
+            end_byte: end,
+            index,
+        }
+    }
+}
+
+// See `bindings` in lib.rs.
+#[allow(clippy::useless_conversion)]
+mod py_chunk {
+    use super::*;
+
+    #[pymethods]
+    impl Chunk {
+        fn __len__(&self) -> usize {
+            self.text.chars().count()
+        }
+
+        fn __str__(&self) -> &str {
+            &self.text
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "Chunk(index={}, start_byte={}, end_byte={}, text={:?})",
+                self.index, self.start_byte, self.end_byte, self.text
+            )
+        }
+    }
+}
+
+/// The chunks of `text` at the given byte ranges, in order.
+pub fn to_chunks(text: &str, spans: &[(usize, usize)]) -> Vec<Chunk> {
+    spans
+        .iter()
+        .enumerate()
+        .map(|(index, &span)| Chunk::new(text, span, index))
+        .collect()
+}
+
+/// The text at each of the given byte ranges, in order.
+pub fn span_texts(text: &str, spans: &[(usize, usize)]) -> Vec<String> {
+    spans
+        .iter()
+        .map(|&(start, end)| text[start..end].to_string())
+        .collect()
+}
+
+/// Merges each chunk shorter than `min_chars` characters into the chunk
+/// before it, so the fragment a chunker leaves at the end of a text or
+/// section isn't embedded on its own. A short first chunk is merged into
+/// the one after it instead. Merged chunks may exceed the chunker's
+/// maximum size.
+pub fn merge_short_spans(
+    text: &str,
+    spans: &[(usize, usize)],
+    min_chars: usize,
+) -> Vec<(usize, usize)> {
+    let is_short = |(start, end): (usize, usize)| text[start..end].chars().count() < min_chars;
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+    for &span in spans {
+        match merged.last_mut() {
+            Some(last) if is_short(span) => last.1 = last.1.max(span.1),
+            _ => merged.push(span),
+        }
+    }
+    if merged.len() > 1 && is_short(merged[0]) {
+        merged[1].0 = merged[0].0;
+        merged.remove(0);
+    }
+    merged
+}
 
 /// Splits text into overlapping chunks using a sliding window algorithm.
 ///
 /// - `chunk_size`: maximum number of characters per chunk
 /// - `overlap`: number of characters shared between adjacent chunks
 ///
+/// Sizes count Unicode characters, not bytes, so chunks never split a
+/// multi-byte character.
+///
 /// Returns a `Vec<String>` where each element is one chunk.
 pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
+    span_texts(text, &text_spans(text, chunk_size, overlap))
+}
+
+/// The (start, end) byte ranges of `chunk_text`'s chunks.
+pub fn text_spans(text: &str, chunk_size: usize, overlap: usize) -> Vec<(usize, usize)> {
     if text.is_empty() {
         return vec![];
     }
@@ -15,9 +125,18 @@ pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String>
         return vec![];
     }
 
+    // Byte offset of every character, plus the end of the text, so that
+    // character positions map to byte ranges on char boundaries
+    let offsets: Vec<usize> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([text.len()])
+        .collect();
+    let char_count = offsets.len() - 1;
+
     // If the text is shorter than or equal to chunk_size, return it as a single chunk
-    if text.len() <= chunk_size {
-        return vec![text.to_string()];
+    if char_count <= chunk_size {
+        return vec![(0, text.len())];
     }
 
     let step = if overlap >= chunk_size {
@@ -26,21 +145,21 @@ pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String>
         chunk_size - overlap
     };
 
-    let mut chunks = Vec::new();
+    let mut spans = Vec::new();
     let mut start = 0;
 
-    while start < text.len() {
-        let end = (start + chunk_size).min(text.len());
-        chunks.push(text[start..end].to_string());
+    while start < char_count {
+        let end = (start + chunk_size).min(char_count);
+        spans.push((offsets[start], offsets[end]));
 
-        if end == text.len() {
+        if end == char_count {
             break;
         }
 
         start += step;
     }
 
-    chunks
+    spans
 }
 
 /// Parallelized version of `chunk_text` using Rayon's work-stealing iterator.
@@ -55,34 +174,8 @@ pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String>
 /// Returns a `Vec<String>` where each element is one chunk, in the same
 /// order as the sequential version.
 pub fn chunk_text_parallel(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
-    if text.is_empty() || chunk_size == 0 {
-        return vec![];
-    }
-
-    if text.len() <= chunk_size {
-        return vec![text.to_string()];
-    }
-
-    let step = if overlap >= chunk_size {
-        1
-    } else {
-        chunk_size - overlap
-    };
-
     // Pre-compute chunk boundaries (lightweight, sequential)
-    let mut boundaries = Vec::new();
-    let mut start = 0;
-
-    while start < text.len() {
-        let end = (start + chunk_size).min(text.len());
-        boundaries.push((start, end));
-
-        if end == text.len() {
-            break;
-        }
-
-        start += step;
-    }
+    let boundaries = text_spans(text, chunk_size, overlap);
 
     // Extract chunks in parallel using Rayon's work-stealing scheduler
     boundaries
@@ -91,54 +184,154 @@ pub fn chunk_text_parallel(text: &str, chunk_size: usize, overlap: usize) -> Vec
         .collect()
 }
 
+/// `chunk_text_parallel` returning `Chunk`s with their offsets.
+pub fn chunk_text_parallel_offsets(text: &str, chunk_size: usize, overlap: usize) -> Vec<Chunk> {
+    text_spans(text, chunk_size, overlap)
+        .par_iter()
+        .enumerate()
+        .map(|(index, &span)| Chunk::new(text, span, index))
+        .collect()
+}
+
 /// Token-aware text chunking with overlap.
 ///
 /// Splits text into chunks where each chunk contains at most `max_tokens` words.
 /// Maintains `overlap_tokens` words of overlap between adjacent chunks.
 /// Preserves original text formatting (whitespace, punctuation) within each chunk.
+/// Words are as `words` makes them for the tokenizer.
 ///
 /// This produces chunks that align with how LLMs tokenize text, preventing
 /// mid-word splits and wasted context window space.
-pub fn chunk_by_tokens(text: &str, max_tokens: usize, overlap_tokens: usize) -> Vec<String> {
-    if text.is_empty() || max_tokens == 0 {
-        return vec![];
-    }
+pub fn chunk_by_tokens(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    words: &TokenizerOptions,
+) -> Vec<String> {
+    span_texts(text, &token_spans(text, max_tokens, overlap_tokens, words))
+}
 
-    // Find word boundaries (byte start, byte end) using same logic as tokenizer
-    let mut word_spans: Vec<(usize, usize)> = Vec::new();
-    let mut in_word = false;
-    let mut word_start = 0;
+/// The (start, end) byte ranges of `chunk_by_tokens`'s chunks.
+pub fn token_spans(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    words: &TokenizerOptions,
+) -> Vec<(usize, usize)> {
+    let step = if overlap_tokens >= max_tokens {
+        1
+    } else {
+        max_tokens - overlap_tokens
+    };
+    word_windows(text, &words.word_spans(text), max_tokens, |i, _| i + step)
+}
 
-    for (i, c) in text.char_indices() {
-        let is_word_char = c.is_alphanumeric() || c == '\'';
-        if is_word_char {
-            if !in_word {
-                word_start = i;
-                in_word = true;
-            }
-        } else if in_word {
-            word_spans.push((word_start, i));
-            in_word = false;
-        }
-    }
-    if in_word {
-        word_spans.push((word_start, text.len()));
-    }
+/// `chunk_by_tokens` on all CPU cores.
+///
+/// The word boundaries are found in one pass, then the chunks are sliced
+/// out of the text in parallel with Rayon's work-stealing scheduler.
+/// Returns the same chunks as `chunk_by_tokens`, in the same order.
+pub fn chunk_by_tokens_parallel(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    words: &TokenizerOptions,
+) -> Vec<String> {
+    token_spans(text, max_tokens, overlap_tokens, words)
+        .par_iter()
+        .map(|&(start, end)| text[start..end].to_string())
+        .collect()
+}
 
-    if word_spans.is_empty() {
-        return vec![];
-    }
+/// `chunk_by_tokens_parallel` returning `Chunk`s with their offsets.
+pub fn chunk_by_tokens_parallel_offsets(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    words: &TokenizerOptions,
+) -> Vec<Chunk> {
+    token_spans(text, max_tokens, overlap_tokens, words)
+        .par_iter()
+        .enumerate()
+        .map(|(index, &span)| Chunk::new(text, span, index))
+        .collect()
+}
 
-    if word_spans.len() <= max_tokens {
-        return vec![text.trim().to_string()];
-    }
+/// Token-aware text chunking with overlap in whole sentences.
+///
+/// Like `chunk_by_tokens`, but rather than repeating the last
+/// `overlap_tokens` words of a chunk, each chunk after the first starts
+/// `overlap_sentences` sentences (see `sentence_spans`) before the one the
+/// previous chunk was cut off in. Adjacent chunks so share complete
+/// sentences, and a sentence cut off at the end of a chunk is repeated
+/// whole at the start of the next. Where that wouldn't move past the
+/// previous chunk's start (a sentence longer than `max_tokens` words), the
+/// overlap falls back to `overlap_tokens` words.
+pub fn chunk_by_tokens_sentence_overlap(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    overlap_sentences: usize,
+    words: &TokenizerOptions,
+) -> Vec<String> {
+    let spans =
+        sentence_overlap_token_spans(text, max_tokens, overlap_tokens, overlap_sentences, words);
+    span_texts(text, &spans)
+}
 
+/// The (start, end) byte ranges of `chunk_by_tokens_sentence_overlap`'s
+/// chunks.
+pub fn sentence_overlap_token_spans(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    overlap_sentences: usize,
+    words: &TokenizerOptions,
+) -> Vec<(usize, usize)> {
+    let words = words.word_spans(text);
+    let sentence_starts: Vec<usize> = sentence_spans(text)
+        .into_iter()
+        .map(|(start, _)| start)
+        .collect();
     let step = if overlap_tokens >= max_tokens {
         1
     } else {
         max_tokens - overlap_tokens
     };
 
+    word_windows(text, &words, max_tokens, |i, end_idx| {
+        // The sentence the first word left out is in, and those before it
+        let cut = sentence_starts.partition_point(|&start| start <= words[end_idx].0);
+        let sentence = cut.saturating_sub(1 + overlap_sentences);
+        let overlap_start = sentence_starts.get(sentence).copied().unwrap_or(0);
+        let next = words.partition_point(|&(start, _)| start < overlap_start);
+        if next > i {
+            next
+        } else {
+            i + step
+        }
+    })
+}
+
+/// Chunks of up to `max_tokens` of `words` each, as byte ranges of `text`.
+/// `next(i, end)` is the index of the word after the chunk of words
+/// `i..end` to start the next chunk at.
+fn word_windows(
+    text: &str,
+    word_spans: &[(usize, usize)],
+    max_tokens: usize,
+    next: impl Fn(usize, usize) -> usize,
+) -> Vec<(usize, usize)> {
+    if word_spans.is_empty() || max_tokens == 0 {
+        return vec![];
+    }
+
+    if word_spans.len() <= max_tokens {
+        let mut spans = Vec::new();
+        push_trimmed(&mut spans, text, 0, text.len());
+        return spans;
+    }
+
     let mut chunks = Vec::new();
     let mut i = 0;
 
@@ -148,18 +341,877 @@ pub fn chunk_by_tokens(text: &str, max_tokens: usize, overlap_tokens: usize) ->
         // Extract original text span from first word start to last word end
         let chunk_start = word_spans[i].0;
         let chunk_end = word_spans[end_idx - 1].1;
-        chunks.push(text[chunk_start..chunk_end].to_string());
+        chunks.push((chunk_start, chunk_end));
 
         if end_idx == word_spans.len() {
             break;
         }
 
+        i = next(i, end_idx);
+    }
+
+    chunks
+}
+
+/// Lazily produces `chunk_by_tokens`'s chunks of a text, one at a time.
+///
+/// Only a window of `max_tokens` words is held at once, so the first
+/// chunks of a huge text are ready (e.g. for embedding) long before the
+/// text has been scanned to its end.
+/// The text is anything that derefs to a `str`: a borrowed `&str`, or an
+/// owned `String` for a stream that outlives its caller's text.
+#[derive(Debug, Clone)]
+pub struct ChunkStream<T> {
+    text: T,
+    windows: TokenWindows,
+}
+
+impl<T: AsRef<str>> ChunkStream<T> {
+    pub fn new(
+        text: T,
+        max_tokens: usize,
+        overlap_tokens: usize,
+        words: TokenizerOptions,
+    ) -> ChunkStream<T> {
+        ChunkStream {
+            text,
+            windows: TokenWindows::new(max_tokens, overlap_tokens, words),
+        }
+    }
+}
+
+impl<T: AsRef<str>> Iterator for ChunkStream<T> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        self.windows.next_chunk(self.text.as_ref())
+    }
+}
+
+/// The state of a `ChunkStream`: where it is in its text and the words of
+/// the current window.
+#[derive(Debug, Clone)]
+struct TokenWindows {
+    max_tokens: usize,
+    step: usize,
+    /// Byte offset the next word is looked for from
+    position: usize,
+    /// Words of the next chunk, as byte ranges
+    window: VecDeque<(usize, usize)>,
+    /// The word after the window, if any
+    lookahead: Option<(usize, usize)>,
+    index: usize,
+    done: bool,
+    words: TokenizerOptions,
+}
+
+impl TokenWindows {
+    fn new(max_tokens: usize, overlap_tokens: usize, words: TokenizerOptions) -> TokenWindows {
+        TokenWindows {
+            max_tokens,
+            step: max_tokens.saturating_sub(overlap_tokens).max(1),
+            position: 0,
+            window: VecDeque::new(),
+            lookahead: None,
+            index: 0,
+            done: max_tokens == 0,
+            words,
+        }
+    }
+
+    fn next_chunk(&mut self, text: &str) -> Option<Chunk> {
+        if self.done {
+            return None;
+        }
+        self.fill(text);
+        // A text that fits in one chunk is kept whole, as by
+        // `chunk_by_tokens`
+        if self.index == 0 && self.lookahead.is_none() {
+            self.done = true;
+            let mut spans = Vec::new();
+            if !self.window.is_empty() {
+                push_trimmed(&mut spans, text, 0, text.len());
+            }
+            return spans.first().map(|&span| self.emit(text, span));
+        }
+
+        let span = (self.window.front()?.0, self.window.back()?.1);
+        if self.lookahead.is_none() {
+            self.done = true;
+        } else {
+            let step = self.step.min(self.window.len());
+            self.window.drain(..step);
+        }
+        Some(self.emit(text, span))
+    }
+
+    fn emit(&mut self, text: &str, span: (usize, usize)) -> Chunk {
+        let chunk = Chunk::new(text, span, self.index);
+        self.index += 1;
+        chunk
+    }
+
+    /// Reads words until the window is full, and the word after it.
+    fn fill(&mut self, text: &str) {
+        while self.window.len() < self.max_tokens {
+            match self.lookahead.take().or_else(|| self.next_word(text)) {
+                Some(word) => self.window.push_back(word),
+                None => return,
+            }
+        }
+        if self.lookahead.is_none() {
+            self.lookahead = self.next_word(text);
+        }
+    }
+
+    /// The next word's byte range, with the same word boundaries as
+    /// `chunk_by_tokens`.
+    fn next_word(&mut self, text: &str) -> Option<(usize, usize)> {
+        let is_word_char = |c: char| self.words.is_word_char(c);
+        let rest = &text[self.position..];
+        let start = self.position + rest.find(is_word_char)?;
+        let end = text[start..]
+            .find(|c: char| !is_word_char(c))
+            .map_or(text.len(), |len| start + len);
+        self.position = end;
+        Some((start, end))
+    }
+}
+
+/// Python iterator over a text's `chunk_by_tokens` chunks, produced
+/// lazily (see `ChunkStream`).
+#[pyclass(name = "ChunkStream")]
+pub struct PyChunkStream {
+    stream: ChunkStream<String>,
+}
+
+// See `bindings` in lib.rs.
+#[allow(clippy::useless_conversion)]
+mod py_chunk_stream {
+    use super::*;
+
+    #[pymethods]
+    impl PyChunkStream {
+        #[new]
+        #[pyo3(signature = (text, max_tokens=256, overlap_tokens=32, word_chars=None))]
+        fn new(
+            text: String,
+            max_tokens: usize,
+            overlap_tokens: usize,
+            word_chars: Option<&str>,
+        ) -> PyChunkStream {
+            let words = word_chars.map(TokenizerOptions::new).unwrap_or_default();
+            PyChunkStream {
+                stream: ChunkStream::new(text, max_tokens, overlap_tokens, words),
+            }
+        }
+
+        fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+            slf
+        }
+
+        fn __next__(&mut self) -> Option<Chunk> {
+            self.stream.next()
+        }
+    }
+}
+
+/// Loads a HuggingFace `tokenizer.json` (BPE, WordPiece, Unigram, ...),
+/// caching it by path so a model's tokenizer is only parsed once.
+pub fn load_tokenizer(path: &str) -> Result<Arc<Tokenizer>> {
+    static TOKENIZERS: OnceLock<Mutex<HashMap<String, Arc<Tokenizer>>>> = OnceLock::new();
+    let cache = TOKENIZERS.get_or_init(Default::default);
+    if let Some(tokenizer) = cache.lock().unwrap().get(path) {
+        return Ok(Arc::clone(tokenizer));
+    }
+
+    if !std::path::Path::new(path).exists() {
+        anyhow::bail!("Tokenizer not found: {}", path);
+    }
+    let tokenizer = Tokenizer::from_file(path)
+        .map_err(|e| anyhow::anyhow!("Failed to load tokenizer {}: {}", path, e))?;
+    let tokenizer = Arc::new(tokenizer);
+    cache
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), Arc::clone(&tokenizer));
+    Ok(tokenizer)
+}
+
+/// A HuggingFace tokenizer (BPE, WordPiece, Unigram, ...) loaded from a
+/// `tokenizer.json`, to count text in a model's own tokens.
+///
+/// Construct from Python with:
+///     tokenizer = SubwordTokenizer("all-MiniLM-L6-v2/tokenizer.json")
+///     tokenizer.token_count("Hello, world!")
+#[pyclass(frozen)]
+pub struct SubwordTokenizer {
+    path: String,
+    tokenizer: Arc<Tokenizer>,
+}
+
+impl SubwordTokenizer {
+    pub fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+}
+
+// See `bindings` in lib.rs.
+#[allow(clippy::useless_conversion)]
+mod py_subword_tokenizer {
+    use super::*;
+
+    #[pymethods]
+    impl SubwordTokenizer {
+        /// Load the `tokenizer.json` at `path` (parsed once per path).
+        #[new]
+        fn py_new(py: Python<'_>, path: &str) -> PyResult<Self> {
+            let tokenizer = py
+                .allow_threads(|| load_tokenizer(path))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+            Ok(SubwordTokenizer {
+                path: path.to_string(),
+                tokenizer,
+            })
+        }
+
+        /// Number of the model's tokens in `text`, counting the special tokens
+        /// the model adds around an input ("[CLS]", "[SEP]", ...) if
+        /// `add_special_tokens`.
+        #[pyo3(signature = (text, add_special_tokens=false))]
+        fn token_count(
+            &self,
+            py: Python<'_>,
+            text: &str,
+            add_special_tokens: bool,
+        ) -> PyResult<usize> {
+            py.allow_threads(|| model_token_count(text, &self.tokenizer, add_special_tokens))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))
+        }
+
+        /// The model's tokens of `text`, as strings.
+        fn tokenize(&self, py: Python<'_>, text: &str) -> PyResult<Vec<String>> {
+            py.allow_threads(|| self.tokenizer.encode(text, false))
+                .map(|encoding| encoding.get_tokens().to_vec())
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Failed to tokenize text: {}",
+                        e
+                    ))
+                })
+        }
+
+        /// Path of the `tokenizer.json`.
+        #[getter]
+        fn path(&self) -> &str {
+            &self.path
+        }
+
+        /// Number of tokens in the vocabulary, added tokens included.
+        #[getter]
+        fn vocab_size(&self) -> usize {
+            self.tokenizer.get_vocab_size(true)
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "SubwordTokenizer(path={:?}, vocab_size={})",
+                self.path,
+                self.vocab_size()
+            )
+        }
+    }
+}
+
+/// Number of `tokenizer`'s tokens in `text`, with the special tokens it
+/// adds around an input if `add_special_tokens`.
+pub fn model_token_count(
+    text: &str,
+    tokenizer: &Tokenizer,
+    add_special_tokens: bool,
+) -> Result<usize> {
+    let encoding = tokenizer
+        .encode(text, add_special_tokens)
+        .map_err(|e| anyhow::anyhow!("Failed to tokenize text: {}", e))?;
+    Ok(encoding.len())
+}
+
+/// Model-token-aware text chunking with overlap.
+///
+/// Like `chunk_by_tokens`, but counts the tokens of `tokenizer` (the
+/// embedding or chat model's own) rather than words, so `max_tokens` is
+/// the true size of each chunk in the model's context window. Chunks
+/// start and end on token boundaries, in the original text.
+pub fn chunk_by_model_tokens(
+    text: &str,
+    tokenizer: &Tokenizer,
+    max_tokens: usize,
+    overlap_tokens: usize,
+) -> Result<Vec<String>> {
+    let spans = model_token_spans(text, tokenizer, max_tokens, overlap_tokens)?;
+    Ok(span_texts(text, &spans))
+}
+
+/// The (start, end) byte ranges of `chunk_by_model_tokens`'s chunks.
+pub fn model_token_spans(
+    text: &str,
+    tokenizer: &Tokenizer,
+    max_tokens: usize,
+    overlap_tokens: usize,
+) -> Result<Vec<(usize, usize)>> {
+    if text.is_empty() || max_tokens == 0 {
+        return Ok(vec![]);
+    }
+
+    let encoding = tokenizer
+        .encode(text, false)
+        .map_err(|e| anyhow::anyhow!("Failed to tokenize text: {}", e))?;
+    // Byte ranges of the tokens in `text`; tokens the normalizer inserted
+    // (and so cover no text) are left out
+    let token_spans: Vec<(usize, usize)> = encoding
+        .get_offsets()
+        .iter()
+        .copied()
+        .filter(|&(start, end)| start < end)
+        .collect();
+
+    let mut spans = Vec::new();
+    if token_spans.is_empty() {
+        return Ok(spans);
+    }
+    if token_spans.len() <= max_tokens {
+        push_trimmed(&mut spans, text, 0, text.len());
+        return Ok(spans);
+    }
+
+    let step = if overlap_tokens >= max_tokens {
+        1
+    } else {
+        max_tokens - overlap_tokens
+    };
+
+    let mut i = 0;
+    while i < token_spans.len() {
+        let end_idx = (i + max_tokens).min(token_spans.len());
+        // Byte-level BPE can split one character into several tokens that
+        // all cover it, so the end is the furthest any token reaches
+        let chunk_end = token_spans[i..end_idx]
+            .iter()
+            .map(|&(_, end)| end)
+            .max()
+            .unwrap_or(text.len());
+        push_trimmed(&mut spans, text, token_spans[i].0, chunk_end);
+
+        if end_idx == token_spans.len() {
+            break;
+        }
+
         i += step;
     }
 
+    Ok(spans)
+}
+
+/// Abbreviations (lowercase, without their final period) whose period
+/// doesn't end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "rev", "fr", "gen", "col", "lt",
+    "sgt", "capt", "gov", "sen", "rep", "hon", "vs", "cf", "viz", "al", "fig", "figs", "eq", "eqs",
+    "no", "nos", "vol", "vols", "pp", "ch", "sec", "approx", "dept", "est", "inc", "ltd", "co",
+    "corp", "jan", "feb", "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec",
+];
+
+/// Characters that end a sentence.
+const TERMINATORS: &[char] = &['.', '!', '?', '…', '。', '！', '？'];
+
+/// Closing quotes and brackets that may follow a sentence's terminator.
+const CLOSERS: &[char] = &['"', '\'', '”', '’', ')', ']'];
+
+/// Splits text into sentences, returned as (start, end) byte ranges
+/// without surrounding whitespace.
+///
+/// A sentence ends at a terminator (plus any closing quotes) followed by
+/// whitespace and a word that doesn't start in lowercase, except after an
+/// abbreviation ("Dr.", "e.g.") or an initial ("J."); a blank line, or a
+/// line break before a heading, list item, or table row, always ends one.
+pub fn sentence_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let end = if TERMINATORS.contains(&c) {
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, next)) = chars.peek() {
+                if !TERMINATORS.contains(&next) && !CLOSERS.contains(&next) {
+                    break;
+                }
+                end = j + next.len_utf8();
+                chars.next();
+            }
+            let single_period = c == '.' && end == i + 1;
+            if ends_sentence(text, end, c) && !(single_period && is_abbreviation(&text[..i])) {
+                Some(end)
+            } else {
+                None
+            }
+        } else if c == '\n' && starts_block(&text[i + 1..]) {
+            Some(i)
+        } else {
+            None
+        };
+
+        if let Some(end) = end {
+            push_trimmed(&mut spans, text, start, end);
+            start = end;
+        }
+    }
+    push_trimmed(&mut spans, text, start, text.len());
+    spans
+}
+
+/// Whether a terminator ending at `end` ends its sentence, judging by what
+/// follows it.
+fn ends_sentence(text: &str, end: usize, terminator: char) -> bool {
+    let rest = &text[end..];
+    // CJK full stops aren't followed by spaces
+    if matches!(terminator, '。' | '！' | '？') {
+        return true;
+    }
+    let next_word = rest.trim_start();
+    if next_word.is_empty() {
+        return true;
+    }
+    if next_word.len() == rest.len() {
+        return false;
+    }
+    let gap = &rest[..rest.len() - next_word.len()];
+    gap.matches('\n').count() >= 2 || !next_word.starts_with(char::is_lowercase)
+}
+
+/// Whether the word before a period is an abbreviation or an initial.
+fn is_abbreviation(before: &str) -> bool {
+    let word = before
+        .rsplit(|c: char| !(c.is_alphabetic() || c == '.'))
+        .next()
+        .unwrap_or("");
+    let mut letters = word.chars();
+    let initial = letters.next().is_some_and(char::is_uppercase) && letters.next().is_none();
+    // Dotted abbreviations such as "e.g" or "U.S"
+    let dotted = word.contains('.') && word.split('.').all(|part| part.chars().count() == 1);
+    initial || dotted || ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
+
+/// Whether a line starts a new block: a blank line, heading, list item,
+/// or table row.
+fn starts_block(line: &str) -> bool {
+    let line = line.trim_start_matches([' ', '\t']);
+    line.starts_with(['\n', '#', '|'])
+        || line.starts_with("- ")
+        || line.starts_with("* ")
+        || line.split_once(". ").is_some_and(|(number, _)| {
+            !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+fn push_trimmed(spans: &mut Vec<(usize, usize)>, text: &str, start: usize, end: usize) {
+    let segment = &text[start..end];
+    let trimmed = segment.trim_start();
+    let start = start + segment.len() - trimmed.len();
+    let end = start + trimmed.trim_end().len();
+    if start < end {
+        spans.push((start, end));
+    }
+}
+
+/// Sentence-aware text chunking with overlap.
+///
+/// Packs whole sentences (see `sentence_spans`) into chunks of at most
+/// `max_chars` characters, starting each chunk with the last
+/// `overlap_sentences` sentences of the previous one where they fit. A
+/// sentence longer than `max_chars` is split between words, and a word
+/// longer than that between characters. Chunks keep the text's original
+/// formatting.
+pub fn chunk_by_sentences(text: &str, max_chars: usize, overlap_sentences: usize) -> Vec<String> {
+    let spans = sentence_chunk_spans(text, max_chars, overlap_sentences);
+    span_texts(text, &spans)
+}
+
+/// The (start, end) byte ranges of `chunk_by_sentences`'s chunks.
+pub fn sentence_chunk_spans(
+    text: &str,
+    max_chars: usize,
+    overlap_sentences: usize,
+) -> Vec<(usize, usize)> {
+    if max_chars == 0 {
+        return vec![];
+    }
+
+    let spans: Vec<(usize, usize)> = sentence_spans(text)
+        .into_iter()
+        .flat_map(|span| split_long(text, span, max_chars))
+        .collect();
+    let fits = |first: usize, last: usize| {
+        text[spans[first].0..spans[last].1].chars().count() <= max_chars
+    };
+
+    let mut chunks = Vec::new();
+    let mut first = 0;
+    while first < spans.len() {
+        let mut last = first;
+        while last + 1 < spans.len() && fits(first, last + 1) {
+            last += 1;
+        }
+        chunks.push((spans[first].0, spans[last].1));
+
+        if last + 1 == spans.len() {
+            break;
+        }
+        // Overlap as many of this chunk's last sentences as fit alongside
+        // the next new one
+        let mut next = (last + 1).saturating_sub(overlap_sentences).max(first + 1);
+        while next <= last && !fits(next, last + 1) {
+            next += 1;
+        }
+        first = next;
+    }
     chunks
 }
 
+/// Splits a span longer than `max_chars` characters into pieces that fit,
+/// between words where possible.
+fn split_long(text: &str, (start, end): (usize, usize), max_chars: usize) -> Vec<(usize, usize)> {
+    let span = &text[start..end];
+    if span.chars().count() <= max_chars {
+        return vec![(start, end)];
+    }
+
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+    for word in span.split_whitespace() {
+        let word_start = start + (word.as_ptr() as usize - span.as_ptr() as usize);
+        let word_end = word_start + word.len();
+        match pieces.last_mut() {
+            Some(piece) if text[piece.0..word_end].chars().count() <= max_chars => {
+                piece.1 = word_end;
+            }
+            _ if word.chars().count() <= max_chars => pieces.push((word_start, word_end)),
+            _ => {
+                let mut piece_start = word_start;
+                for (n, (i, _)) in word.char_indices().enumerate() {
+                    if n > 0 && n % max_chars == 0 {
+                        pieces.push((piece_start, word_start + i));
+                        piece_start = word_start + i;
+                    }
+                }
+                pieces.push((piece_start, word_end));
+            }
+        }
+    }
+    pieces
+}
+
+/// A boundary `chunk_recursive` can split text at.
+#[derive(Debug, Clone)]
+pub enum Separator {
+    /// Between top-level blocks of source code (see `code::blocks`).
+    Code,
+    /// Between sentences (see `sentence_spans`).
+    Sentence,
+    /// At any run of whitespace.
+    Word,
+    /// Wherever the string occurs.
+    Literal(String),
+    /// Before every match of the pattern, which starts the next piece:
+    /// e.g. "§", "Q:", or a log line's timestamp.
+    Pattern(Regex),
+}
+
+/// The separators `chunk_recursive` tries by default, largest unit first.
+pub const DEFAULT_SEPARATORS: &[&str] = &["paragraph", "line", "sentence", "word"];
+
+impl Separator {
+    /// Parses a separator: "code" (function and class boundaries),
+    /// "paragraph" (a blank line), "line", "sentence", "word", or
+    /// "re:" followed by a regular expression (e.g. `re:(?m)^\d{4}-\d{2}-\d{2}`
+    /// to split a log before each dated line); any other string is split at
+    /// literally. Fails on an invalid regular expression.
+    pub fn parse(name: &str) -> Result<Separator> {
+        Ok(match name {
+            "code" => Separator::Code,
+            "paragraph" => Separator::Literal("\n\n".to_string()),
+            "line" => Separator::Literal("\n".to_string()),
+            "sentence" => Separator::Sentence,
+            "word" => Separator::Word,
+            _ => match name.strip_prefix("re:") {
+                Some(pattern) => Separator::Pattern(
+                    Regex::new(pattern)
+                        .map_err(|e| anyhow::anyhow!("Invalid separator {:?}: {}", name, e))?,
+                ),
+                None => Separator::Literal(name.to_string()),
+            },
+        })
+    }
+
+    /// Parses each of `names` (see `parse`), in order.
+    pub fn parse_all<S: AsRef<str>>(names: &[S]) -> Result<Vec<Separator>> {
+        names
+            .iter()
+            .map(|name| Separator::parse(name.as_ref()))
+            .collect()
+    }
+
+    /// The pieces `text[start..end]` splits into, as byte ranges of `text`
+    /// without surrounding whitespace.
+    fn split(&self, text: &str, (start, end): (usize, usize)) -> Vec<(usize, usize)> {
+        let span = &text[start..end];
+        let offset = |piece: &str| start + (piece.as_ptr() as usize - span.as_ptr() as usize);
+        let mut pieces = Vec::new();
+        match self {
+            Separator::Code => {
+                for (piece_start, piece_end) in crate::code::blocks(span) {
+                    pieces.push((start + piece_start, start + piece_end));
+                }
+            }
+            Separator::Sentence => {
+                for (piece_start, piece_end) in sentence_spans(span) {
+                    pieces.push((start + piece_start, start + piece_end));
+                }
+            }
+            Separator::Word => {
+                for word in span.split_whitespace() {
+                    pieces.push((offset(word), offset(word) + word.len()));
+                }
+            }
+            Separator::Literal(separator) => {
+                for piece in span.split(separator.as_str()) {
+                    let piece_start = offset(piece);
+                    push_trimmed(&mut pieces, text, piece_start, piece_start + piece.len());
+                }
+            }
+            Separator::Pattern(pattern) => {
+                let mut piece_start = start;
+                for found in pattern.find_iter(span) {
+                    let found_start = start + found.start();
+                    if found_start > piece_start {
+                        push_trimmed(&mut pieces, text, piece_start, found_start);
+                        piece_start = found_start;
+                    }
+                }
+                push_trimmed(&mut pieces, text, piece_start, end);
+            }
+        }
+        pieces
+    }
+}
+
+/// The separators `chunk_code` tries, largest unit first.
+const CODE_SEPARATORS: &[&str] = &["code", "paragraph", "line", "word"];
+
+/// Code-aware text chunking.
+///
+/// Packs whole top-level blocks of source code (functions, classes, ...;
+/// see `code::blocks`) into chunks of at most `max_chars` characters, so
+/// definitions aren't sliced apart. A block too long for a chunk is split
+/// at blank lines, then lines, as by `chunk_recursive`.
+pub fn chunk_code(text: &str, max_chars: usize) -> Vec<String> {
+    let spans = code_spans(text, max_chars);
+    span_texts(text, &spans)
+}
+
+/// The (start, end) byte ranges of `chunk_code`'s chunks.
+pub fn code_spans(text: &str, max_chars: usize) -> Vec<(usize, usize)> {
+    let separators = Separator::parse_all(CODE_SEPARATORS).expect("built-in separators");
+    recursive_spans(text, max_chars, 0, &separators)
+}
+
+/// Recursive text chunking with overlap.
+///
+/// Splits text at the first of `separators` (see `Separator::parse`),
+/// packs consecutive pieces into chunks of at most `max_chars` characters,
+/// and splits any piece too long for a chunk again at the next separator,
+/// and so on down to single characters. Chunks so break at the largest
+/// unit that fits: whole paragraphs where possible, then lines, sentences,
+/// and words with the default separators. Each chunk starts with as many
+/// of the previous chunk's last pieces as fit in `overlap_chars`
+/// characters; pieces split from a larger one are only packed together.
+/// Chunks keep the text's original formatting.
+pub fn chunk_recursive(
+    text: &str,
+    max_chars: usize,
+    overlap_chars: usize,
+    separators: &[Separator],
+) -> Vec<String> {
+    let spans = recursive_spans(text, max_chars, overlap_chars, separators);
+    span_texts(text, &spans)
+}
+
+/// The (start, end) byte ranges of `chunk_recursive`'s chunks.
+pub fn recursive_spans(
+    text: &str,
+    max_chars: usize,
+    overlap_chars: usize,
+    separators: &[Separator],
+) -> Vec<(usize, usize)> {
+    if max_chars == 0 {
+        return vec![];
+    }
+
+    let mut spans = Vec::new();
+    push_trimmed(&mut spans, text, 0, text.len());
+    let Some(&span) = spans.first() else {
+        return vec![];
+    };
+    let mut chunks = Vec::new();
+    split_recursive(
+        text,
+        span,
+        separators,
+        max_chars,
+        overlap_chars,
+        &mut chunks,
+    );
+    chunks
+}
+
+fn split_recursive(
+    text: &str,
+    span: (usize, usize),
+    separators: &[Separator],
+    max_chars: usize,
+    overlap_chars: usize,
+    chunks: &mut Vec<(usize, usize)>,
+) {
+    let fits = |(start, end): (usize, usize)| text[start..end].chars().count() <= max_chars;
+    if fits(span) {
+        chunks.push(span);
+        return;
+    }
+
+    let Some((separator, rest)) = separators.split_first() else {
+        let characters: Vec<(usize, usize)> = text[span.0..span.1]
+            .char_indices()
+            .map(|(i, c)| (span.0 + i, span.0 + i + c.len_utf8()))
+            .collect();
+        merge_pieces(text, &characters, max_chars, overlap_chars, chunks);
+        return;
+    };
+
+    let mut fitting = Vec::new();
+    for piece in separator.split(text, span) {
+        if fits(piece) {
+            fitting.push(piece);
+        } else {
+            merge_pieces(text, &fitting, max_chars, overlap_chars, chunks);
+            fitting.clear();
+            split_recursive(text, piece, rest, max_chars, overlap_chars, chunks);
+        }
+    }
+    merge_pieces(text, &fitting, max_chars, overlap_chars, chunks);
+}
+
+/// Packs consecutive pieces, each at most `max_chars` characters, into
+/// chunks, repeating up to `overlap_chars` characters of whole pieces from
+/// the end of each chunk at the start of the next.
+fn merge_pieces(
+    text: &str,
+    pieces: &[(usize, usize)],
+    max_chars: usize,
+    overlap_chars: usize,
+    chunks: &mut Vec<(usize, usize)>,
+) {
+    let chars = |first: usize, last: usize| text[pieces[first].0..pieces[last].1].chars().count();
+
+    let mut first = 0;
+    while first < pieces.len() {
+        let mut last = first;
+        while last + 1 < pieces.len() && chars(first, last + 1) <= max_chars {
+            last += 1;
+        }
+        chunks.push((pieces[first].0, pieces[last].1));
+
+        if last + 1 == pieces.len() {
+            break;
+        }
+        let mut next = last + 1;
+        while next > first + 1
+            && chars(next - 1, last) <= overlap_chars
+            && chars(next - 1, last + 1) <= max_chars
+        {
+            next -= 1;
+        }
+        first = next;
+    }
+}
+
+/// Cosine similarity of two vectors; 0 if either is all zeros.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|y| y * y).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Semantic text chunking.
+///
+/// `embeddings` holds one vector per sentence of `text`, in the order
+/// `sentence_spans` finds them. A chunk ends wherever the cosine
+/// similarity between adjacent sentences drops below `threshold`, so each
+/// chunk keeps to one topic; a run of similar sentences too long for
+/// `max_chars` characters is packed into several chunks as by
+/// `chunk_by_sentences`, without overlap. Chunks keep the text's original
+/// formatting.
+pub fn chunk_semantic(
+    text: &str,
+    embeddings: &[Vec<f32>],
+    threshold: f32,
+    max_chars: usize,
+) -> Result<Vec<String>> {
+    let spans = semantic_spans(text, embeddings, threshold, max_chars)?;
+    Ok(span_texts(text, &spans))
+}
+
+/// The (start, end) byte ranges of `chunk_semantic`'s chunks.
+pub fn semantic_spans(
+    text: &str,
+    embeddings: &[Vec<f32>],
+    threshold: f32,
+    max_chars: usize,
+) -> Result<Vec<(usize, usize)>> {
+    let spans = sentence_spans(text);
+    if embeddings.len() != spans.len() {
+        anyhow::bail!(
+            "Expected {} sentence embeddings, got {}",
+            spans.len(),
+            embeddings.len()
+        );
+    }
+    if max_chars == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut topics: Vec<Vec<(usize, usize)>> = Vec::new();
+    for (i, &span) in spans.iter().enumerate() {
+        match topics.last_mut() {
+            Some(topic) if cosine_similarity(&embeddings[i - 1], &embeddings[i]) >= threshold => {
+                topic.push(span)
+            }
+            _ => topics.push(vec![span]),
+        }
+    }
+
+    let mut chunks = Vec::new();
+    for topic in topics {
+        let pieces: Vec<(usize, usize)> = topic
+            .into_iter()
+            .flat_map(|span| split_long(text, span, max_chars))
+            .collect();
+        merge_pieces(text, &pieces, max_chars, 0, &mut chunks);
+    }
+    Ok(chunks)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,6 +1285,33 @@ mod tests {
         assert_eq!(chunks[0].len(), 1000);
     }
 
+    #[test]
+    fn test_unicode_chunking() {
+        // 1-, 2-, 3-, and 4-byte characters, so byte and char offsets differ
+        let text = "Grüße aus Köln — 東京とパリ 🚀✨. ".repeat(40);
+        let chunks = chunk_text(&text, 100, 10);
+
+        let char_count = text.chars().count();
+        assert_eq!(chunks.len(), (char_count - 10).div_ceil(90));
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 100));
+        assert_eq!(chunks[0].chars().count(), 100);
+
+        // Overlaps and the reassembled text are counted in characters
+        let tail: String = chunks[0].chars().skip(90).collect();
+        let head: String = chunks[1].chars().take(10).collect();
+        assert_eq!(tail, head);
+        let mut reconstructed = chunks[0].clone();
+        for chunk in &chunks[1..] {
+            reconstructed.extend(chunk.chars().skip(10));
+        }
+        assert_eq!(reconstructed, text);
+
+        // Multi-byte characters exactly at the chunk size aren't split
+        assert_eq!(chunk_text("🚀🚀🚀", 2, 0), ["🚀🚀", "🚀"]);
+        assert_eq!(chunk_text("ééé", 3, 1), ["ééé"]);
+        assert_eq!(text_spans("aé€🚀", 1, 0), [(0, 1), (1, 3), (3, 6), (6, 10)]);
+    }
+
     // --- Parallel chunking tests ---
 
     #[test]
@@ -266,13 +1345,42 @@ mod tests {
         assert_eq!(sequential, parallel);
     }
 
+    #[test]
+    fn test_parallel_unicode_text() {
+        let text = "Ça coûte 5 € — ünïcödé 日本語 👩‍👩‍👧 text. ".repeat(500);
+        let sequential = chunk_text(&text, 333, 33);
+        let parallel = chunk_text_parallel(&text, 333, 33);
+        assert_eq!(sequential, parallel);
+        assert_eq!(
+            chunk_text_parallel_offsets(&text, 333, 33),
+            to_chunks(&text, &text_spans(&text, 333, 33))
+        );
+    }
+
+    #[test]
+    fn test_parallel_token_chunks() {
+        let text = "Ça coûte 5 €, n'est-ce pas? The quick brown fox jumps.\n\n".repeat(2_000);
+        assert_eq!(
+            chunk_by_tokens_parallel(&text, 256, 32, &TokenizerOptions::default()),
+            chunk_by_tokens(&text, 256, 32, &TokenizerOptions::default())
+        );
+        assert_eq!(
+            chunk_by_tokens_parallel_offsets(&text, 100, 10, &TokenizerOptions::default()),
+            to_chunks(
+                &text,
+                &token_spans(&text, 100, 10, &TokenizerOptions::default())
+            )
+        );
+        assert!(chunk_by_tokens_parallel("", 10, 2, &TokenizerOptions::default()).is_empty());
+    }
+
     // --- Token-aware chunking tests ---
 
     #[test]
     fn test_token_chunk_basic() {
         // 10 words, chunk by 4 tokens with 1 overlap → should produce 3 chunks
         let text = "one two three four five six seven eight nine ten";
-        let chunks = chunk_by_tokens(text, 4, 1);
+        let chunks = chunk_by_tokens(text, 4, 1, &TokenizerOptions::default());
         assert_eq!(chunks.len(), 3);
         // First chunk should contain "one two three four"
         assert!(chunks[0].contains("one"));
@@ -282,7 +1390,7 @@ mod tests {
     #[test]
     fn test_token_chunk_preserves_formatting() {
         let text = "Hello, World!   This is   a   test.";
-        let chunks = chunk_by_tokens(text, 100, 0);
+        let chunks = chunk_by_tokens(text, 100, 0, &TokenizerOptions::default());
         // All text fits in one chunk, should preserve original spacing
         assert_eq!(chunks.len(), 1);
         assert!(chunks[0].contains("World!   This"));
@@ -290,14 +1398,428 @@ mod tests {
 
     #[test]
     fn test_token_chunk_empty() {
-        assert!(chunk_by_tokens("", 10, 2).is_empty());
-        assert!(chunk_by_tokens("hello", 0, 0).is_empty());
+        assert!(chunk_by_tokens("", 10, 2, &TokenizerOptions::default()).is_empty());
+        assert!(chunk_by_tokens("hello", 0, 0, &TokenizerOptions::default()).is_empty());
     }
 
     #[test]
     fn test_token_chunk_small_text() {
         let text = "just three words";
-        let chunks = chunk_by_tokens(text, 10, 2);
+        let chunks = chunk_by_tokens(text, 10, 2, &TokenizerOptions::default());
         assert_eq!(chunks.len(), 1);
     }
+
+    #[test]
+    fn test_token_chunk_word_chars() {
+        let text = "state-of-the-art models";
+        let default = TokenizerOptions::default();
+        assert_eq!(
+            chunk_by_tokens(text, 2, 0, &default),
+            ["state-of", "the-art", "models"]
+        );
+        // With hyphens kept, the text is two words and fits one chunk
+        let hyphens = TokenizerOptions::new("'-");
+        assert_eq!(chunk_by_tokens(text, 2, 0, &hyphens), [text]);
+        let streamed: Vec<Chunk> = ChunkStream::new(text, 1, 0, hyphens).collect();
+        assert_eq!(streamed[0].text, "state-of-the-art");
+    }
+
+    #[test]
+    fn test_token_chunk_sentence_overlap() {
+        let text = "A one two. B three four five. C six. D seven eight.";
+        // A sentence cut off is repeated whole in the next chunk
+        assert_eq!(
+            chunk_by_tokens_sentence_overlap(text, 5, 0, 0, &TokenizerOptions::default()),
+            [
+                "A one two. B three",
+                "B three four five. C",
+                "C six. D seven eight"
+            ]
+        );
+        // Overlapping the first chunk's only other sentence would start the
+        // next chunk where it started, so it falls back to words
+        assert_eq!(
+            chunk_by_tokens_sentence_overlap(text, 5, 0, 1, &TokenizerOptions::default()),
+            [
+                "A one two. B three",
+                "four five. C six. D",
+                "C six. D seven eight"
+            ]
+        );
+        assert_eq!(
+            sentence_overlap_token_spans(text, 20, 0, 1, &TokenizerOptions::default()),
+            token_spans(text, 20, 0, &TokenizerOptions::default())
+        );
+        assert!(
+            chunk_by_tokens_sentence_overlap("", 5, 0, 1, &TokenizerOptions::default()).is_empty()
+        );
+    }
+
+    // --- Sentence-aware chunking tests ---
+
+    fn sentences(text: &str) -> Vec<&str> {
+        sentence_spans(text)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect()
+    }
+
+    #[test]
+    fn test_sentence_boundaries() {
+        let text = "Dr. Smith paid $3.50 for it, e.g. Python books. Was it \"worth it?\" \
+                    He thinks so! J. R. R. Tolkien disagrees; see Fig. 2 and the U.S. \
+                    version.\n\n# Notes\nShort line\n- item one\n- item two";
+        assert_eq!(
+            sentences(text),
+            [
+                "Dr. Smith paid $3.50 for it, e.g. Python books.",
+                "Was it \"worth it?\"",
+                "He thinks so!",
+                "J. R. R. Tolkien disagrees; see Fig. 2 and the U.S. version.",
+                "# Notes\nShort line",
+                "- item one",
+                "- item two",
+            ]
+        );
+        assert_eq!(sentences("混合。文本！"), ["混合。", "文本！"]);
+        assert!(sentences("  \n ").is_empty());
+    }
+
+    #[test]
+    fn test_sentence_chunks() {
+        let text = "One fish. Two fish. Red fish. Blue fish.";
+        assert_eq!(
+            chunk_by_sentences(text, 20, 0),
+            ["One fish. Two fish.", "Red fish. Blue fish."]
+        );
+        // Each chunk starts with the previous chunk's last sentence
+        assert_eq!(
+            chunk_by_sentences(text, 20, 1),
+            [
+                "One fish. Two fish.",
+                "Two fish. Red fish.",
+                "Red fish. Blue fish."
+            ]
+        );
+        assert_eq!(chunk_by_sentences(text, 100, 1), [text]);
+        assert!(chunk_by_sentences(text, 0, 1).is_empty());
+        assert!(chunk_by_sentences("", 100, 1).is_empty());
+    }
+
+    #[test]
+    fn test_sentence_chunks_split_long_sentences() {
+        let chunks = chunk_by_sentences("Tiny. A sentence far too long to fit. End.", 12, 0);
+        assert_eq!(
+            chunks,
+            ["Tiny.", "A sentence", "far too long", "to fit. End."]
+        );
+        assert_eq!(
+            chunk_by_sentences("Supercalifragilistic", 8, 0),
+            ["Supercal", "ifragili", "stic"]
+        );
+    }
+
+    // --- Recursive chunking tests ---
+
+    fn separators(names: &[&str]) -> Vec<Separator> {
+        Separator::parse_all(names).unwrap()
+    }
+
+    #[test]
+    fn test_recursive_prefers_larger_units() {
+        let text = "First paragraph.\n\nSecond paragraph has two sentences. It is long.\n\nEnd.";
+        let default = separators(DEFAULT_SEPARATORS);
+        assert_eq!(chunk_recursive(text, 200, 0, &default), [text]);
+        // The long paragraph is split into sentences, packed on their own
+        assert_eq!(
+            chunk_recursive(text, 40, 0, &default),
+            [
+                "First paragraph.",
+                "Second paragraph has two sentences.",
+                "It is long.",
+                "End.",
+            ]
+        );
+        // Short paragraphs are packed together
+        assert_eq!(
+            chunk_recursive("A.\n\nB.\n\nC.", 6, 0, &default),
+            ["A.\n\nB.", "C."]
+        );
+    }
+
+    #[test]
+    fn test_recursive_words_and_characters() {
+        let default = separators(DEFAULT_SEPARATORS);
+        assert_eq!(
+            chunk_recursive("alpha beta gamma delta", 11, 0, &default),
+            ["alpha beta", "gamma delta"]
+        );
+        assert_eq!(
+            chunk_recursive("abcdefghij", 4, 0, &default),
+            ["abcd", "efgh", "ij"]
+        );
+        // Custom separators
+        assert_eq!(
+            chunk_recursive("a; b; c", 4, 0, &separators(&["; "])),
+            ["a; b", "c"]
+        );
+        assert!(chunk_recursive("  ", 10, 0, &default).is_empty());
+        assert!(chunk_recursive("text", 0, 0, &default).is_empty());
+    }
+
+    #[test]
+    fn test_recursive_pattern_separators() {
+        // A pattern's match starts the next piece, rather than being dropped
+        let legal = "§ 1 Scope. Applies here. § 2 Terms. Defined below.";
+        assert_eq!(
+            chunk_recursive(legal, 30, 0, &separators(&["re:§", "sentence"])),
+            ["§ 1 Scope. Applies here.", "§ 2 Terms. Defined below."]
+        );
+        let faq = "Q: Why?\nA: Because.\nQ: How?\nA: Like so.";
+        assert_eq!(
+            chunk_recursive(faq, 25, 0, &separators(&["re:(?m)^Q:", "line"])),
+            ["Q: Why?\nA: Because.", "Q: How?\nA: Like so."]
+        );
+        let log = "2024-05-01 10:00 start\n  detail\n2024-05-01 10:05 stop\n";
+        let dated_lines = separators(&[r"re:(?m)^\d{4}-\d{2}-\d{2}", "word"]);
+        assert_eq!(
+            chunk_recursive(log, 32, 0, &dated_lines),
+            ["2024-05-01 10:00 start\n  detail", "2024-05-01 10:05 stop"]
+        );
+
+        let err = Separator::parse("re:(").unwrap_err();
+        assert!(err.to_string().contains("Invalid separator"));
+    }
+
+    #[test]
+    fn test_recursive_overlap() {
+        let chunks = chunk_recursive("one two three four five", 13, 5, &[Separator::Word]);
+        assert_eq!(chunks, ["one two three", "three four", "four five"]);
+    }
+
+    // --- Semantic chunking tests ---
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]), 1.0);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_semantic_chunks() {
+        let text = "Cats purr. Cats nap a lot. Rust has traits. Traits are interfaces.";
+        let embeddings = vec![
+            vec![1.0, 0.1],
+            vec![0.9, 0.2],
+            vec![0.1, 1.0],
+            vec![0.2, 0.9],
+        ];
+        assert_eq!(
+            chunk_semantic(text, &embeddings, 0.5, 1000).unwrap(),
+            [
+                "Cats purr. Cats nap a lot.",
+                "Rust has traits. Traits are interfaces."
+            ]
+        );
+        // A topic too long for one chunk is split between sentences
+        assert_eq!(
+            chunk_semantic(text, &embeddings, 0.5, 20).unwrap(),
+            [
+                "Cats purr.",
+                "Cats nap a lot.",
+                "Rust has traits.",
+                "Traits are",
+                "interfaces."
+            ]
+        );
+        assert_eq!(
+            chunk_semantic(text, &embeddings, -1.0, 1000).unwrap(),
+            [text]
+        );
+        assert!(chunk_semantic(text, &embeddings[..2], 0.5, 1000).is_err());
+        assert!(chunk_semantic("", &[], 0.5, 1000).unwrap().is_empty());
+    }
+
+    // --- Chunk offsets tests ---
+
+    #[test]
+    fn test_chunk_offsets() {
+        let text = "  Grüße aus Köln. Bis bald!  ";
+        let chunks = to_chunks(text, &sentence_chunk_spans(text, 17, 0));
+        assert_eq!(chunks.len(), 2);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+            assert_eq!(&text[chunk.start_byte..chunk.end_byte], chunk.text);
+        }
+        assert_eq!(chunks[1].text, "Bis bald!");
+
+        // The spans match the plain chunkers' output
+        let text = "one two three four five six";
+        assert_eq!(
+            span_texts(text, &token_spans(text, 4, 1, &TokenizerOptions::default())),
+            chunk_by_tokens(text, 4, 1, &TokenizerOptions::default())
+        );
+        assert_eq!(
+            chunk_text_parallel_offsets(text, 10, 2),
+            to_chunks(text, &text_spans(text, 10, 2))
+        );
+    }
+
+    #[test]
+    fn test_merge_short_spans() {
+        let text = "aaaa bb cccc d";
+        let spans = [(0, 4), (5, 7), (8, 12), (13, 14)];
+        assert_eq!(merge_short_spans(text, &spans, 3), [(0, 7), (8, 14)]);
+        assert_eq!(merge_short_spans(text, &spans, 0), spans);
+        // A short first chunk joins the next one
+        assert_eq!(merge_short_spans(text, &[(5, 7), (8, 12)], 3), [(5, 12)]);
+        assert_eq!(merge_short_spans(text, &[(5, 7)], 3), [(5, 7)]);
+        // Overlapping chunks merge into their union
+        assert_eq!(merge_short_spans(text, &[(0, 10), (6, 12)], 7), [(0, 12)]);
+
+        let source = "fn one() {\n    1\n}\n\nfn two() {\n    2\n}\n\nfn three() {\n    3\n}\n";
+        let spans = merge_short_spans(source, &code_spans(source, 40), 25);
+        assert_eq!(span_texts(source, &spans), [source.trim_end()]);
+    }
+
+    // --- Code chunking tests ---
+
+    #[test]
+    fn test_code_chunks_keep_functions_whole() {
+        let source = "fn one() {\n    1\n}\n\nfn two() {\n    2\n}\n\nfn three() {\n    3\n}\n";
+        assert_eq!(
+            chunk_code(source, 40),
+            [
+                "fn one() {\n    1\n}\n\nfn two() {\n    2\n}",
+                "fn three() {\n    3\n}"
+            ]
+        );
+        // A function too long for a chunk is split between its lines
+        let long = "def f():\n    a = 1\n    b = 2\n";
+        assert_eq!(chunk_code(long, 20), ["def f():\n    a = 1", "b = 2"]);
+    }
+
+    // --- Model-token chunking tests ---
+
+    /// A word-level tokenizer.json in which punctuation marks are tokens of
+    /// their own, so model tokens outnumber words.
+    fn word_level_tokenizer() -> Tokenizer {
+        let json = r#"{
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": {"type": "Lowercase"},
+            "pre_tokenizer": {"type": "Whitespace"},
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": {"[UNK]": 0, "hello": 1, ",": 2, "world": 3, "!": 4},
+                "unk_token": "[UNK]"
+            }
+        }"#;
+        json.parse().unwrap()
+    }
+
+    #[test]
+    fn test_chunk_by_model_tokens() {
+        let tokenizer = word_level_tokenizer();
+        let text = "Hello, world! Hello, world!";
+        // 8 model tokens, though only 4 words
+        assert_eq!(
+            chunk_by_tokens(text, 4, 0, &TokenizerOptions::default()),
+            [text]
+        );
+        let chunks = chunk_by_model_tokens(text, &tokenizer, 4, 0).unwrap();
+        assert_eq!(chunks, ["Hello, world!", "Hello, world!"]);
+        assert_eq!(
+            chunk_by_model_tokens(text, &tokenizer, 3, 1).unwrap(),
+            ["Hello, world", "world! Hello", "Hello, world", "world!"]
+        );
+        assert_eq!(
+            model_token_spans(text, &tokenizer, 4, 0).unwrap(),
+            [(0, 13), (14, 27)]
+        );
+
+        // Offsets point into the original, not the lowercased, text
+        let unicode = "  Grüße, Köln!  ";
+        assert_eq!(
+            chunk_by_model_tokens(unicode, &tokenizer, 2, 0).unwrap(),
+            ["Grüße,", "Köln!"]
+        );
+        assert_eq!(
+            chunk_by_model_tokens(unicode, &tokenizer, 10, 2).unwrap(),
+            ["Grüße, Köln!"]
+        );
+        assert!(chunk_by_model_tokens("", &tokenizer, 4, 0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_model_token_count() {
+        let tokenizer = word_level_tokenizer();
+        let count = |text: &str| model_token_count(text, &tokenizer, false).unwrap();
+        assert_eq!(count("Hello, world!"), 4);
+        // Unknown words are one token each
+        assert_eq!(count("Goodbye, moon"), 3);
+        assert_eq!(count(""), 0);
+    }
+
+    #[test]
+    fn test_load_tokenizer() {
+        let path = std::env::temp_dir().join("rusty_rag_test_tokenizer.json");
+        std::fs::write(&path, word_level_tokenizer().to_string(false).unwrap()).unwrap();
+        let path = path.to_str().unwrap();
+        let first = load_tokenizer(path).unwrap();
+        assert!(Arc::ptr_eq(&first, &load_tokenizer(path).unwrap()));
+        assert_eq!(first.get_vocab_size(false), 5);
+
+        let err = load_tokenizer("/nonexistent/tokenizer.json").unwrap_err();
+        assert!(err.to_string().contains("Tokenizer not found"));
+    }
+
+    // --- Streaming chunker tests ---
+
+    #[test]
+    fn test_stream_matches_chunk_by_tokens() {
+        let texts = [
+            "one two three four five six seven eight nine ten",
+            "  Short text, kept whole!  ",
+            "Grüße, Welt! Ein Text über Köln — mit Umlauten.",
+            "",
+            "... ---",
+        ];
+        for text in texts {
+            for (max_tokens, overlap) in [(4, 1), (3, 0), (2, 5), (100, 10), (0, 0)] {
+                let streamed: Vec<Chunk> =
+                    ChunkStream::new(text, max_tokens, overlap, TokenizerOptions::default())
+                        .collect();
+                let texts: Vec<&str> = streamed.iter().map(|c| c.text.as_str()).collect();
+                assert_eq!(
+                    texts,
+                    chunk_by_tokens(text, max_tokens, overlap, &TokenizerOptions::default()),
+                    "{text:?}"
+                );
+                assert_eq!(
+                    streamed,
+                    to_chunks(
+                        text,
+                        &token_spans(text, max_tokens, overlap, &TokenizerOptions::default())
+                    )
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_is_lazy() {
+        let mut stream = ChunkStream::new("a b c d e f", 2, 0, TokenizerOptions::default());
+        assert_eq!(stream.next().unwrap().text, "a b");
+        // Only the first window and the word after it have been read
+        assert_eq!(stream.windows.position, "a b c".len());
+        assert_eq!(stream.count(), 2);
+    }
 }
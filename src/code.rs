@@ -0,0 +1,142 @@
+//! Heuristic splitting of source code into top-level blocks (functions,
+//! classes, impls, ...) without parsing a particular language.
+//!
+//! A block starts at a line that is neither indented nor inside brackets
+//! left open by earlier lines, so function bodies in brace languages and
+//! indented bodies in Python alike stay with their definition.
+
+/// First words of lines that continue the block above them: the later
+/// clauses of compound statements and block terminators.
+const CONTINUATIONS: &[&str] = &[
+    "else", "elif", "elsif", "except", "finally", "catch", "rescue", "ensure", "end", "fi", "done",
+    "esac", "until", "where",
+];
+
+/// Starts of lines that belong to the block below them: comments, doc
+/// comments, decorators, and attributes.
+const PREFIXES: &[&str] = &["//", "/*", "#", "@", "--", ";", "%"];
+
+/// Splits source code into its top-level blocks, returned as (start, end)
+/// byte ranges without trailing whitespace.
+///
+/// Comments, decorators, and attributes directly above a definition are
+/// part of its block, as are closing brackets and clauses such as `else:`
+/// at the top level.
+pub fn blocks(source: &str) -> Vec<(usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut block: Option<(usize, usize)> = None;
+    // Whether the current block so far holds only comments and attributes
+    let mut prefix_only = false;
+    let mut depth = 0usize;
+    let mut offset = 0;
+
+    for line in source.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let content = line.trim_end();
+        if content.trim_start().is_empty() {
+            continue;
+        }
+
+        let top_level = depth == 0 && !content.starts_with([' ', '\t']);
+        let is_prefix = PREFIXES.iter().any(|prefix| content.starts_with(prefix));
+        match &mut block {
+            Some(current) if !top_level || prefix_only || continues(content) => {
+                current.1 = line_start + content.len();
+                prefix_only &= is_prefix;
+            }
+            _ => {
+                blocks.extend(block);
+                block = Some((line_start, line_start + content.len()));
+                prefix_only = is_prefix;
+            }
+        }
+        depth = depth.saturating_add_signed(bracket_balance(content));
+    }
+    blocks.extend(block);
+    blocks
+}
+
+/// Whether a top-level line continues the block above it.
+fn continues(line: &str) -> bool {
+    let word: &str = line
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or("");
+    line.starts_with([')', ']', '}']) || CONTINUATIONS.contains(&word)
+}
+
+/// Opening minus closing brackets on a line, outside double-quoted
+/// strings and line comments.
+fn bracket_balance(line: &str) -> isize {
+    let mut balance = 0;
+    let mut in_string = false;
+    let mut chars = line.chars().peekable();
+    let mut previous = ' ';
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => balance += 1,
+            ')' | ']' | '}' => balance -= 1,
+            '/' if chars.peek() == Some(&'/') => break,
+            // A "#" comment, as opposed to a "#[attribute]" or "#include"
+            '#' if previous.is_whitespace() && chars.peek().is_none_or(|c| *c == ' ') => break,
+            _ => {}
+        }
+        previous = c;
+    }
+    balance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(source: &str) -> Vec<&str> {
+        blocks(source)
+            .into_iter()
+            .map(|(start, end)| &source[start..end])
+            .collect()
+    }
+
+    #[test]
+    fn test_brace_language_blocks() {
+        let source = "use std::fmt;\n\n/// Adds.\n#[inline]\nfn add(a: i32, b: i32) -> i32 {\n\
+                      \x20   let s = \"}\"; // }\n\n    a + b\n}\n\nstruct P {\n    x: i32,\n}\n";
+        assert_eq!(
+            texts(source),
+            [
+                "use std::fmt;",
+                "/// Adds.\n#[inline]\nfn add(a: i32, b: i32) -> i32 {\n    let s = \"}\"; // }\n\n    a + b\n}",
+                "struct P {\n    x: i32,\n}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indented_language_blocks() {
+        let source = "import os\n\n\n@cache\ndef f(x):\n    if x:\n        return 1\n\n\
+                      \x20   return 2\n\ntry:\n    f(1)\nexcept ValueError:\n    pass\n\n\
+                      CONFIG = {\n\"a\": 1,\n}\n";
+        assert_eq!(
+            texts(source),
+            [
+                "import os",
+                "@cache\ndef f(x):\n    if x:\n        return 1\n\n    return 2",
+                "try:\n    f(1)\nexcept ValueError:\n    pass",
+                "CONFIG = {\n\"a\": 1,\n}",
+            ]
+        );
+        assert!(blocks("\n  \n").is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+//! Passphrase-based encryption for data written to disk.
+//!
+//! Used for the local chunk/embedding caches and exported files so that
+//! document contents stay private even on a shared machine.
+//!
+//! Format: MAGIC (6 bytes) | salt (16 bytes) | nonce (12 bytes) | AES-256-GCM ciphertext.
+//! The key is derived from the passphrase with Argon2id and the per-file salt.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use argon2::Argon2;
+
+const MAGIC: &[u8; 6] = b"RRAG\x00\x01";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + NONCE_LEN;
+
+/// Derive a 256-bit key from the passphrase and salt using Argon2id.
+fn derive_key(passphrase: &[u8], salt: &[u8]) -> Result<Key<Aes256Gcm>> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase, salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypt `data` with a key derived from `passphrase`.
+///
+/// A fresh random salt and nonce are generated for every call.
+pub fn encrypt(data: &[u8], passphrase: &[u8]) -> Result<Vec<u8>> {
+    if passphrase.is_empty() {
+        anyhow::bail!("Passphrase must not be empty");
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt).context("Failed to generate salt")?;
+    getrandom::getrandom(&mut nonce).context("Failed to generate nonce")?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let ciphertext = Aes256Gcm::new(&key)
+        .encrypt(Nonce::from_slice(&nonce), data)
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt data produced by [`encrypt`].
+///
+/// Fails if the header is missing, the passphrase is wrong, or the data
+/// has been tampered with.
+pub fn decrypt(data: &[u8], passphrase: &[u8]) -> Result<Vec<u8>> {
+    if !is_encrypted(data) {
+        anyhow::bail!("Data is not in the RustyRAG encrypted format");
+    }
+
+    let salt = &data[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = &data[MAGIC.len() + SALT_LEN..HEADER_LEN];
+    let key = derive_key(passphrase, salt)?;
+
+    Aes256Gcm::new(&key)
+        .decrypt(Nonce::from_slice(nonce), &data[HEADER_LEN..])
+        .map_err(|_| anyhow::anyhow!("Decryption failed: wrong passphrase or corrupted data"))
+}
+
+/// Check whether `data` starts with the encrypted-file header.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN && data.starts_with(MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let data = b"confidential chunk text";
+        let encrypted = encrypt(data, b"hunter2").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert_ne!(&encrypted[HEADER_LEN..], data);
+        assert_eq!(decrypt(&encrypted, b"hunter2").unwrap(), data);
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let encrypted = encrypt(b"secret", b"right").unwrap();
+        assert!(decrypt(&encrypted, b"wrong").is_err());
+    }
+
+    #[test]
+    fn test_tampered_data() {
+        let mut encrypted = encrypt(b"secret", b"pass").unwrap();
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 0xff;
+        assert!(decrypt(&encrypted, b"pass").is_err());
+    }
+
+    #[test]
+    fn test_fresh_salt_per_call() {
+        let a = encrypt(b"same", b"pass").unwrap();
+        let b = encrypt(b"same", b"pass").unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_plaintext_rejected() {
+        assert!(!is_encrypted(b"[\"plain json\"]"));
+        assert!(decrypt(b"[\"plain json\"]", b"pass").is_err());
+    }
+
+    #[test]
+    fn test_empty_passphrase() {
+        assert!(encrypt(b"data", b"").is_err());
+    }
+}
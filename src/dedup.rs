@@ -0,0 +1,134 @@
+//! Near-duplicate detection for chunks, with MinHash and locality-sensitive
+//! hashing.
+//!
+//! Each chunk is reduced to its set of word shingles (runs of
+//! `SHINGLE_WORDS` lowercase words). Chunks whose MinHash signatures agree
+//! on a whole band are candidates, confirmed by the exact Jaccard
+//! similarity of their shingle sets, so boilerplate repeated with small
+//! changes (page numbers, dates) is caught without comparing every pair.
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 3;
+/// Bands the signature is split into for candidate lookup.
+const BANDS: usize = 32;
+/// Signature values per band; at a similarity of 0.95, two chunks share a
+/// band with near certainty, while dissimilar ones rarely do.
+const ROWS: usize = 4;
+
+/// For each chunk, the index of an earlier chunk it nearly duplicates (the
+/// Jaccard similarity of their word shingles is at least `threshold`), or
+/// `None` for chunks to keep. A chunk is only compared with chunks kept
+/// before it; chunks without words are always kept.
+pub fn near_duplicates(chunks: &[String], threshold: f64) -> Vec<Option<usize>> {
+    let shingle_sets: Vec<HashSet<u64>> = chunks.par_iter().map(|c| shingles(c)).collect();
+    let signatures: Vec<[u64; BANDS * ROWS]> = shingle_sets.par_iter().map(signature).collect();
+
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    let mut duplicates = Vec::with_capacity(chunks.len());
+    for (i, shingles) in shingle_sets.iter().enumerate() {
+        if shingles.is_empty() {
+            duplicates.push(None);
+            continue;
+        }
+        let bands: Vec<(usize, u64)> = signatures[i]
+            .chunks(ROWS)
+            .enumerate()
+            .map(|(band, rows)| (band, fnv1a(rows.iter().flat_map(|row| row.to_le_bytes()))))
+            .collect();
+        let original = bands
+            .iter()
+            .filter_map(|band| buckets.get(band))
+            .flatten()
+            .copied()
+            .find(|&j| jaccard(shingles, &shingle_sets[j]) >= threshold);
+        if original.is_none() {
+            for band in bands {
+                buckets.entry(band).or_default().push(i);
+            }
+        }
+        duplicates.push(original);
+    }
+    duplicates
+}
+
+/// Hashes of a text's shingles; a text shorter than a shingle is one.
+fn shingles(text: &str) -> HashSet<u64> {
+    let words = crate::tokenizer::tokenize(text);
+    words
+        .windows(SHINGLE_WORDS.min(words.len()).max(1))
+        .map(|window| fnv1a(window.join(" ").bytes()))
+        .collect()
+}
+
+/// MinHash signature: for each of `BANDS * ROWS` hash functions, the
+/// smallest hash of any shingle.
+fn signature(shingles: &HashSet<u64>) -> [u64; BANDS * ROWS] {
+    let mut signature = [u64::MAX; BANDS * ROWS];
+    for &shingle in shingles {
+        for (i, min) in signature.iter_mut().enumerate() {
+            let seed = (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            *min = (*min).min(mix(shingle ^ seed));
+        }
+    }
+    signature
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// 64-bit FNV-1a, stable across runs and platforms.
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// The SplitMix64 finalizer, to derive independent hash functions.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_near_duplicates() {
+        let boilerplate = "This document is confidential and intended solely for the \
+                           use of the individual to whom it is addressed. If you have \
+                           received it in error, notify the sender and delete it. \
+                           Unauthorized copying or distribution is strictly prohibited \
+                           and may be unlawful under applicable data protection laws.";
+        let chunks: Vec<String> = vec![
+            format!("{} Page 1", boilerplate),
+            "Quarterly revenue grew by twelve percent.".to_string(),
+            format!("{} Page 2", boilerplate),
+            // Same words, different spacing and case
+            boilerplate.to_uppercase().replace(' ', "  "),
+            // A word changed, so three shingles differ
+            boilerplate.replace("sender", "author"),
+            String::new(),
+            String::new(),
+        ];
+        assert_eq!(
+            near_duplicates(&chunks, 0.95),
+            [None, None, Some(0), Some(0), None, None, None]
+        );
+        // A looser threshold catches the changed word too
+        assert_eq!(near_duplicates(&chunks, 0.8)[4], Some(0));
+    }
+
+    #[test]
+    fn test_short_texts() {
+        let chunks = vec!["Hello".to_string(), "hello!".to_string(), "Bye".to_string()];
+        assert_eq!(near_duplicates(&chunks, 0.95), [None, Some(0), None]);
+        assert!(near_duplicates(&[], 0.95).is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::archive::ZipArchive;
+use crate::xml::{attribute, local_name, XmlEvent, XmlReader};
+
+/// Extracts the text of a Word (.docx) document.
+///
+/// Paragraphs become lines, headings are marked up as markdown (`#`,
+/// `##`, ...), list items get a `- ` bullet, and tables are rendered as
+/// markdown tables, matching what PDF extraction produces.
+pub fn extract_text(path: &str) -> Result<String> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    if file_path
+        .extension()
+        .is_none_or(|ext| !ext.eq_ignore_ascii_case("docx"))
+    {
+        anyhow::bail!("File is not a DOCX: {}", path);
+    }
+
+    let data =
+        std::fs::read(file_path).with_context(|| format!("Failed to open file: {}", path))?;
+    let text = ZipArchive::new(&data)
+        .and_then(|archive| archive.read_string("word/document.xml"))
+        .map(|xml| document_text(&xml))
+        .with_context(|| format!("Failed to read DOCX: {}", path))?;
+
+    if text.is_empty() {
+        anyhow::bail!("No text could be extracted from the DOCX: {}", path);
+    }
+    Ok(text)
+}
+
+/// Paragraph being assembled from its runs.
+#[derive(Default)]
+struct Paragraph {
+    text: String,
+    heading: Option<usize>,
+    list_item: bool,
+}
+
+/// Walk `word/document.xml`, collecting one block per paragraph or table.
+fn document_text(xml: &str) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut para = Paragraph::default();
+    // Open tables, innermost last; each is a list of rows of cells
+    let mut tables: Vec<Vec<Vec<String>>> = Vec::new();
+    let mut in_run = false;
+    let mut in_text = false;
+
+    for event in XmlReader::new(xml) {
+        match event {
+            XmlEvent::Start {
+                name,
+                attrs,
+                self_closing,
+            } => match local_name(name) {
+                "p" => para = Paragraph::default(),
+                "pStyle" => {
+                    para.heading = attribute(attrs, "val").and_then(|s| heading_level(&s));
+                }
+                "numPr" => para.list_item = true,
+                "r" => in_run = !self_closing,
+                "t" => in_text = !self_closing,
+                // Tab stops are also <w:tab> elements, but outside any run
+                "tab" if in_run => para.text.push('\t'),
+                "br" | "cr" if in_run => para.text.push('\n'),
+                "tbl" => tables.push(Vec::new()),
+                "tr" => {
+                    if let Some(table) = tables.last_mut() {
+                        table.push(Vec::new());
+                    }
+                }
+                "tc" => {
+                    if let Some(row) = tables.last_mut().and_then(|t| t.last_mut()) {
+                        row.push(String::new());
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::End { name } => match local_name(name) {
+                "r" => in_run = false,
+                "t" => in_text = false,
+                "p" => {
+                    let para = std::mem::take(&mut para);
+                    let text = para.text.trim();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    match tables
+                        .last_mut()
+                        .and_then(|t| t.last_mut())
+                        .and_then(|r| r.last_mut())
+                    {
+                        Some(cell) => append(cell, text),
+                        None => blocks.push(format_paragraph(text, &para)),
+                    }
+                }
+                "tbl" => {
+                    let Some(rows) = tables.pop() else { continue };
+                    if rows.iter().all(|row| row.iter().all(|c| c.is_empty())) {
+                        continue;
+                    }
+                    // A nested table is flattened into the enclosing cell
+                    match tables
+                        .last_mut()
+                        .and_then(|t| t.last_mut())
+                        .and_then(|r| r.last_mut())
+                    {
+                        Some(cell) => {
+                            for text in rows.iter().flatten() {
+                                append(cell, text);
+                            }
+                        }
+                        None => blocks.push(crate::markdown::table(&rows)),
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::Text(text) if in_text => para.text.push_str(&text),
+            XmlEvent::Text(_) => {}
+        }
+    }
+    blocks.join("\n")
+}
+
+/// Heading level for a paragraph style: "Title" is 1, "Heading2" is 2.
+fn heading_level(style: &str) -> Option<usize> {
+    if style.eq_ignore_ascii_case("title") {
+        return Some(1);
+    }
+    let lower = style.to_ascii_lowercase();
+    let level = lower
+        .strip_prefix("heading")?
+        .trim()
+        .parse::<usize>()
+        .ok()?;
+    (1..=6).contains(&level).then_some(level)
+}
+
+fn format_paragraph(text: &str, para: &Paragraph) -> String {
+    match (para.heading, para.list_item) {
+        (Some(level), _) => format!("{} {}", "#".repeat(level), text),
+        (None, true) => format!("- {}", text),
+        (None, false) => text.to_string(),
+    }
+}
+
+/// Add text to a table cell, separating it from what's already there.
+fn append(cell: &mut String, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if !cell.is_empty() {
+        cell.push(' ');
+    }
+    cell.push_str(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::build_zip;
+
+    fn body(inner: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            inner
+        )
+    }
+
+    fn para(text: &str) -> String {
+        format!("<w:p><w:r><w:t>{}</w:t></w:r></w:p>", text)
+    }
+
+    #[test]
+    fn test_paragraphs_headings_and_lists() {
+        let xml = body(&format!(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr><w:r><w:t>Install</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Run the </w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>installer</w:t></w:r><w:r><w:tab/><w:t>&amp; reboot.</w:t></w:r></w:p>
+            <w:p/>
+            <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Step one</w:t></w:r></w:p>{}"#,
+            para("Done.")
+        ));
+        assert_eq!(
+            document_text(&xml),
+            "# Install\nRun the installer\t& reboot.\n- Step one\nDone."
+        );
+    }
+
+    #[test]
+    fn test_tables() {
+        let cell = |text: &str| format!("<w:tc>{}</w:tc>", para(text));
+        let nested = format!("<w:tbl><w:tr>{}{}</w:tr></w:tbl>", cell("x"), cell("y"));
+        let xml = body(&format!(
+            "{}<w:tbl><w:tr>{}{}</w:tr><w:tr>{}<w:tc>{}{}</w:tc></w:tr></w:tbl>{}",
+            para("Before"),
+            cell("Name"),
+            cell("Notes"),
+            cell("Ada"),
+            para("first"),
+            nested,
+            para("After")
+        ));
+        assert_eq!(
+            document_text(&xml),
+            "Before\n| Name | Notes |\n| --- | --- |\n| Ada | first x y |\nAfter"
+        );
+    }
+
+    #[test]
+    fn test_heading_level() {
+        assert_eq!(heading_level("Heading3"), Some(3));
+        assert_eq!(heading_level("heading 2"), Some(2));
+        assert_eq!(heading_level("Title"), Some(1));
+        assert_eq!(heading_level("Normal"), None);
+        assert_eq!(heading_level("Heading9"), None);
+    }
+
+    #[test]
+    fn test_extract_text_from_file() {
+        let xml = body(&para("Hello from Word"));
+        let data = build_zip(&[("word/document.xml", xml.as_bytes())], false);
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.docx", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        assert_eq!(
+            extract_text(path.to_str().unwrap()).unwrap(),
+            "Hello from Word"
+        );
+
+        std::fs::write(&path, b"not a zip").unwrap();
+        let err = extract_text(path.to_str().unwrap()).unwrap_err();
+        assert!(format!("{:#}", err).contains("Failed to read DOCX"));
+        std::fs::remove_file(path).unwrap();
+
+        let err = extract_text("/nonexistent/file.docx").unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+    }
+}
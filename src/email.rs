@@ -0,0 +1,515 @@
+use anyhow::{Context, Result};
+use encoding_rs::Encoding;
+use std::path::Path;
+
+/// Nesting depth of multipart bodies followed before giving up.
+const MAX_DEPTH: usize = 8;
+
+/// A message read from an .eml file or an mbox archive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Email {
+    pub subject: Option<String>,
+    /// The From header, e.g. "Ada Lovelace <ada@example.com>"
+    pub sender: Option<String>,
+    /// The Date header in ISO 8601, if it parses
+    pub date: Option<String>,
+    /// Body text, without quoted replies or the signature
+    pub text: String,
+}
+
+/// Reads the messages of an email file: a single .eml message, or every
+/// message of an .mbox archive.
+///
+/// The body is the message's plain-text part, or its HTML part converted
+/// to text; attachments are skipped. Quoted replies ("> ..." lines and the
+/// "On ... wrote:" line introducing them), forwarded "Original Message"
+/// blocks, and the signature below a "-- " line are stripped, so each
+/// message contributes only what its sender wrote.
+pub fn extract_messages(path: &str) -> Result<Vec<Email>> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    let mbox = match file_path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("mbox") => true,
+        Some(ext) if ext.eq_ignore_ascii_case("eml") => false,
+        _ => anyhow::bail!("File is not an email (.eml or .mbox): {}", path),
+    };
+
+    let data =
+        std::fs::read(file_path).with_context(|| format!("Failed to open file: {}", path))?;
+    // Read bytes as Latin-1 so 8-bit bodies survive until their charset
+    // is known; `raw_bytes` turns them back into bytes
+    let data: String = data.iter().map(|&b| char::from(b)).collect();
+    let data = data.replace("\r\n", "\n");
+    let raw = if mbox { split_mbox(&data) } else { vec![data] };
+
+    let messages: Vec<Email> = raw
+        .iter()
+        .map(|message| parse_message(message))
+        .filter(|email| !email.text.is_empty() || email.subject.is_some())
+        .collect();
+    if messages.is_empty() {
+        anyhow::bail!("No text could be extracted from the email: {}", path);
+    }
+    Ok(messages)
+}
+
+/// Split an mbox archive at its "From " separator lines, undoing the
+/// ">From " quoting of body lines.
+fn split_mbox(data: &str) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current: Option<String> = None;
+    let mut previous_blank = true;
+    for line in data.lines() {
+        if line.starts_with("From ") && previous_blank {
+            messages.extend(current.take());
+            current = Some(String::new());
+        } else if let Some(message) = &mut current {
+            let quoted = line.trim_start_matches('>');
+            if quoted.starts_with("From ") && quoted.len() < line.len() {
+                message.push_str(&line[1..]);
+            } else {
+                message.push_str(line);
+            }
+            message.push('\n');
+        }
+        previous_blank = line.is_empty();
+    }
+    messages.extend(current);
+    messages
+}
+
+fn parse_message(message: &str) -> Email {
+    let (headers, body) = split_entity(message);
+    Email {
+        subject: header(&headers, "subject").filter(|s| !s.is_empty()),
+        sender: header(&headers, "from").filter(|s| !s.is_empty()),
+        date: header(&headers, "date").and_then(|d| parse_date(&d)),
+        text: strip_replies(&entity_text(&headers, body, 0).unwrap_or_default()),
+    }
+}
+
+/// Split a message or MIME part into its unfolded headers (lowercase
+/// names) and its body.
+fn split_entity(entity: &str) -> (Vec<(String, String)>, &str) {
+    let (head, body) = match entity.find("\n\n") {
+        Some(i) => (&entity[..i], &entity[i + 2..]),
+        None if entity.starts_with('\n') => ("", &entity[1..]),
+        None => (entity, ""),
+    };
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+/// A header's value with encoded words decoded.
+fn header(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| decode_words(&decode_charset(&raw_bytes(value), None)))
+}
+
+/// The bytes of text read as Latin-1.
+fn raw_bytes(text: &str) -> Vec<u8> {
+    text.chars().map(|c| c as u32 as u8).collect()
+}
+
+/// A header's value before its parameters ("text/plain; charset=utf-8" →
+/// "text/plain"), lowercased, and the named parameter's value.
+fn header_param(headers: &[(String, String)], name: &str, param: &str) -> (String, Option<String>) {
+    let Some((_, value)) = headers.iter().find(|(key, _)| key == name) else {
+        return (String::new(), None);
+    };
+    let mut parts = value.split(';');
+    let main = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let found = parts.find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(param)
+            .then(|| value.trim().trim_matches('"').to_string())
+    });
+    (main, found)
+}
+
+/// Text of a MIME entity: a text/plain part as is, a text/html part
+/// converted to text, and a multipart's text parts (only the preferred one
+/// of alternatives). Attachments and other types yield None.
+fn entity_text(headers: &[(String, String)], body: &str, depth: usize) -> Option<String> {
+    let (disposition, _) = header_param(headers, "content-disposition", "filename");
+    if disposition == "attachment" || depth > MAX_DEPTH {
+        return None;
+    }
+    let (content_type, boundary) = header_param(headers, "content-type", "boundary");
+    let content_type = if content_type.is_empty() {
+        "text/plain".to_string()
+    } else {
+        content_type
+    };
+
+    if let Some(subtype) = content_type.strip_prefix("multipart/") {
+        let boundary = boundary?;
+        let parts = multipart_parts(body, &boundary);
+        let texts = parts.iter().filter_map(|part| {
+            let (headers, body) = split_entity(part);
+            let (kind, _) = header_param(&headers, "content-type", "charset");
+            Some((kind, entity_text(&headers, body, depth + 1)?))
+        });
+        if subtype == "alternative" {
+            let texts: Vec<(String, String)> = texts.collect();
+            return texts
+                .iter()
+                .find(|(kind, _)| kind.is_empty() || kind == "text/plain")
+                .or(texts.first())
+                .map(|(_, text)| text.clone());
+        }
+        let joined: Vec<String> = texts.map(|(_, text)| text).collect();
+        return (!joined.is_empty()).then(|| joined.join("\n\n"));
+    }
+
+    if content_type != "text/plain" && content_type != "text/html" {
+        return None;
+    }
+    let (_, charset) = header_param(headers, "content-type", "charset");
+    let (encoding, _) = header_param(headers, "content-transfer-encoding", "");
+    let bytes = match encoding.as_str() {
+        "base64" => decode_base64(&raw_bytes(body)),
+        "quoted-printable" => decode_quoted_printable(&raw_bytes(body)),
+        _ => raw_bytes(body),
+    };
+    let text = decode_charset(&bytes, charset.as_deref());
+    if content_type == "text/html" {
+        Some(crate::html::document_text(&text))
+    } else {
+        Some(text)
+    }
+}
+
+/// The parts of a multipart body, between its boundary lines.
+fn multipart_parts(body: &str, boundary: &str) -> Vec<String> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut current: Option<String> = None;
+    for line in body.lines() {
+        if let Some(rest) = line.strip_prefix(&delimiter) {
+            parts.extend(current.take());
+            if rest.starts_with("--") {
+                break;
+            }
+            current = Some(String::new());
+        } else if let Some(part) = &mut current {
+            part.push_str(line);
+            part.push('\n');
+        }
+    }
+    parts.extend(current);
+    parts
+}
+
+/// Decode text in the given charset, or sniff it when none is declared.
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    match charset.and_then(|label| Encoding::for_label(label.as_bytes())) {
+        Some(encoding) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+        None => crate::text::decode(bytes)
+            .unwrap_or_else(|_| String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
+
+/// Decode RFC 2047 encoded words ("=?utf-8?B?...?=" and "=?...?Q?...?=").
+/// Whitespace between two encoded words is dropped.
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(4, '?').collect::<Vec<_>>();
+        let [charset, kind, text, tail] = decoded[..] else {
+            break;
+        };
+        let Some(tail) = tail.strip_prefix('=') else {
+            break;
+        };
+        let bytes = match kind {
+            "B" | "b" => decode_base64(text.as_bytes()),
+            "Q" | "q" => decode_quoted_printable(text.replace('_', " ").as_bytes()),
+            _ => break,
+        };
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        // RFC 2231 allows a language after the charset: "utf-8*en"
+        let charset = charset.split('*').next().unwrap_or(charset);
+        out.push_str(&decode_charset(&bytes, Some(charset)));
+        after_word = true;
+        rest = tail;
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_base64(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in data {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => continue,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    out
+}
+
+fn decode_quoted_printable(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            // A soft line break joins two lines
+            if bytes.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            if let Some(byte) = hex {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Remove quoted replies, forwarded originals, and the signature.
+fn strip_replies(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if line == "-- " || trimmed.starts_with("-----Original Message-----") {
+            break;
+        }
+        if trimmed.starts_with('>') {
+            continue;
+        }
+        if trimmed.ends_with("wrote:") {
+            // The attribution may wrap: "On Mon, ... Ada <\nada@x> wrote:"
+            if !trimmed.starts_with("On ") && lines.last().is_some_and(|l| l.starts_with("On ")) {
+                lines.pop();
+            }
+            continue;
+        }
+        lines.push(trimmed);
+    }
+
+    // Collapse runs of blank lines left behind
+    let mut out = String::new();
+    for line in lines {
+        if line.is_empty() && (out.is_empty() || out.ends_with("\n\n")) {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// Convert an RFC 5322 date ("Tue, 1 Jul 2003 10:52:37 +0200") to ISO
+/// 8601. Returns None if it can't be parsed.
+fn parse_date(raw: &str) -> Option<String> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    // Drop the day of the week and any trailing comment like "(UTC)"
+    let raw = raw.split_once(',').map_or(raw, |(_, rest)| rest);
+    let raw = raw.split('(').next().unwrap_or(raw);
+    let mut tokens = raw.split_whitespace();
+
+    let day: u32 = tokens.next()?.parse().ok()?;
+    let month_name = tokens.next()?.to_ascii_lowercase();
+    let month = MONTHS.iter().position(|m| month_name.starts_with(m))? + 1;
+    let mut year: u32 = tokens.next()?.parse().ok()?;
+    if year < 50 {
+        year += 2000;
+    } else if year < 1000 {
+        year += 1900;
+    }
+    let mut time = tokens.next().unwrap_or("00:00:00").split(':');
+    let hour: u32 = time.next()?.parse().ok()?;
+    let minute: u32 = time.next()?.parse().ok()?;
+    let second: u32 = time.next().map_or(Some(0), |s| s.parse().ok())?;
+    if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut iso = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    );
+    match tokens.next() {
+        Some("GMT" | "UT" | "UTC" | "Z") => iso.push('Z'),
+        Some(zone)
+            if zone.len() == 5
+                && zone.starts_with(['+', '-'])
+                && zone[1..].bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            iso.push_str(&format!("{}:{}", &zone[..3], &zone[3..]));
+        }
+        _ => {}
+    }
+    Some(iso)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "From: =?UTF-8?B?QWRhIEzDtnZlbGFjZQ==?= <ada@example.com>\n\
+To: team@example.com\n\
+Subject: =?utf-8?Q?Q3_r=C3=A9sum=C3=A9?=\n =?utf-8?Q?_draft?=\n\
+Date: Tue, 1 Jul 2003 10:52:37 +0200\n\
+MIME-Version: 1.0\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\n\
+\n\
+--outer\n\
+Content-Type: multipart/alternative; boundary=inner\n\
+\n\
+--inner\n\
+Content-Type: text/plain; charset=utf-8\n\
+Content-Transfer-Encoding: quoted-printable\n\
+\n\
+The numbers look good =E2=80=94 let's ship a long line that was soft-wr=\n\
+apped.\n\
+\n\
+On Mon, 30 Jun 2003 at 09:00, Bob <bob@example.com>\n\
+wrote:\n\
+> Can you check the numbers?\n\
+> Thanks\n\
+\n\
+-- \n\
+Ada\n\
+--inner\n\
+Content-Type: text/html\n\
+\n\
+<p>HTML version</p>\n\
+--inner--\n\
+--outer\n\
+Content-Type: text/plain\n\
+Content-Disposition: attachment; filename=\"data.csv\"\n\
+\n\
+a,b\n\
+--outer--\n";
+
+    #[test]
+    fn test_parse_message() {
+        let email = parse_message(MESSAGE);
+        assert_eq!(email.subject.as_deref(), Some("Q3 résumé draft"));
+        assert_eq!(
+            email.sender.as_deref(),
+            Some("Ada Lövelace <ada@example.com>")
+        );
+        assert_eq!(email.date.as_deref(), Some("2003-07-01T10:52:37+02:00"));
+        assert_eq!(
+            email.text,
+            "The numbers look good — let's ship a long line that was soft-wrapped."
+        );
+    }
+
+    #[test]
+    fn test_html_only_and_base64() {
+        let message = "Subject: Notice\n\
+Content-Type: text/html; charset=iso-8859-1\n\
+Content-Transfer-Encoding: base64\n\
+\n\
+PHA+Q2Fm6SBvcGVucyBhdCA5PC9wPg==\n";
+        assert_eq!(parse_message(message).text, "Café opens at 9");
+
+        // 8-bit bodies are decoded in their declared charset
+        let message =
+            "Subject: Caf\u{e9}\nContent-Type: text/plain; charset=windows-1252\n\n\u{80}5\n";
+        let email = parse_message(message);
+        assert_eq!(
+            (email.subject.unwrap(), email.text),
+            ("Café".into(), "€5".into())
+        );
+    }
+
+    #[test]
+    fn test_strip_replies() {
+        let text = "Sounds good.\n\n\n-----Original Message-----\nFrom: Bob\nOld text";
+        assert_eq!(strip_replies(text), "Sounds good.");
+        let inline = "> question one?\nAnswer one.\n> question two?\nAnswer two.";
+        assert_eq!(strip_replies(inline), "Answer one.\nAnswer two.");
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date("Thu, 13 Feb 2020 08:05 GMT").as_deref(),
+            Some("2020-02-13T08:05:00Z")
+        );
+        assert_eq!(
+            parse_date("5 Mar 99 23:59:59 -0500 (EST)").as_deref(),
+            Some("1999-03-05T23:59:59-05:00")
+        );
+        assert_eq!(parse_date("yesterday"), None);
+        // A zone that isn't +hhmm is left off, even if not ASCII
+        assert_eq!(
+            parse_date("5 Mar 1999 23:59 +1é2").as_deref(),
+            Some("1999-03-05T23:59:00")
+        );
+    }
+
+    #[test]
+    fn test_extract_mbox() {
+        let mbox = "From ada@example.com Tue Jul  1 10:52:37 2003\n\
+Subject: First\n\
+\n\
+Hello.\n\
+>From the archive.\n\
+\n\
+From bob@example.com Wed Jul  2 08:00:00 2003\n\
+Subject: Second\n\
+\n\
+Reply.\n";
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.mbox", std::process::id()));
+        std::fs::write(&path, mbox).unwrap();
+        let messages = extract_messages(path.to_str().unwrap()).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].text, "Hello.\nFrom the archive.");
+        assert_eq!(messages[1].subject.as_deref(), Some("Second"));
+        std::fs::remove_file(path).unwrap();
+
+        let err = extract_messages("/nonexistent/mail.eml").unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+    }
+}
@@ -0,0 +1,312 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::archive::ZipArchive;
+use crate::xml::{attribute, local_name, XmlEvent, XmlReader};
+
+/// A book read from an EPUB file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Book {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub language: Option<String>,
+    /// Chapters in reading (spine) order; chapters without text are skipped
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    /// The chapter's first heading, if it has one
+    pub title: Option<String>,
+    /// Chapter text, with headings, lists, and tables as markdown
+    pub text: String,
+}
+
+/// Reads an EPUB's metadata and the text of each chapter in spine order.
+pub fn extract(path: &str) -> Result<Book> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    if file_path
+        .extension()
+        .is_none_or(|ext| !ext.eq_ignore_ascii_case("epub"))
+    {
+        anyhow::bail!("File is not an EPUB: {}", path);
+    }
+
+    let data =
+        std::fs::read(file_path).with_context(|| format!("Failed to open file: {}", path))?;
+    let book = read_book(&data).with_context(|| format!("Failed to read EPUB: {}", path))?;
+
+    if book.chapters.is_empty() {
+        anyhow::bail!("No text could be extracted from the EPUB: {}", path);
+    }
+    Ok(book)
+}
+
+fn read_book(data: &[u8]) -> Result<Book> {
+    let archive = ZipArchive::new(data)?;
+
+    // META-INF/container.xml points at the package (.opf) document
+    let container = archive.read_string("META-INF/container.xml")?;
+    let opf_path = XmlReader::new(&container)
+        .find_map(|event| match event {
+            XmlEvent::Start { name, attrs, .. } if local_name(name) == "rootfile" => {
+                attribute(attrs, "full-path").map(|p| p.into_owned())
+            }
+            _ => None,
+        })
+        .context("EPUB container lists no package document")?;
+    let opf = archive.read_string(&opf_path)?;
+    let package = parse_package(&opf);
+
+    let base = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut chapters = Vec::new();
+    for idref in &package.spine {
+        let Some(href) = package.manifest.get(idref) else {
+            continue;
+        };
+        let xhtml = archive.read(&resolve(base, href))?;
+        let text = crate::html::document_text(&String::from_utf8_lossy(&xhtml));
+        if text.is_empty() {
+            continue;
+        }
+        let title = crate::markdown::sections(&text)
+            .into_iter()
+            .find_map(|section| section.path.into_iter().next());
+        chapters.push(Chapter { title, text });
+    }
+
+    Ok(Book {
+        title: package.title,
+        author: package.author,
+        language: package.language,
+        chapters,
+    })
+}
+
+/// The parts of an OPF package document we need.
+#[derive(Default)]
+struct Package {
+    title: Option<String>,
+    author: Option<String>,
+    language: Option<String>,
+    /// Manifest item id → href, for content documents only
+    manifest: HashMap<String, String>,
+    /// Item ids in reading order
+    spine: Vec<String>,
+}
+
+fn parse_package(opf: &str) -> Package {
+    let mut package = Package::default();
+    // Dublin Core element whose text is being read
+    let mut field: Option<&str> = None;
+    let mut text = String::new();
+
+    for event in XmlReader::new(opf) {
+        match event {
+            XmlEvent::Start {
+                name,
+                attrs,
+                self_closing,
+            } => match local_name(name) {
+                "item" => {
+                    let media_type = attribute(attrs, "media-type").unwrap_or_default();
+                    if media_type.contains("html") {
+                        if let (Some(id), Some(href)) =
+                            (attribute(attrs, "id"), attribute(attrs, "href"))
+                        {
+                            package.manifest.insert(id.into_owned(), href.into_owned());
+                        }
+                    }
+                }
+                "itemref" => {
+                    // Non-linear items (pop-up notes etc.) aren't part of the text
+                    let linear = attribute(attrs, "linear").is_none_or(|l| l != "no");
+                    if let (Some(id), true) = (attribute(attrs, "idref"), linear) {
+                        package.spine.push(id.into_owned());
+                    }
+                }
+                name @ ("title" | "creator" | "language") if !self_closing => {
+                    field = Some(name);
+                    text.clear();
+                }
+                _ => {}
+            },
+            XmlEvent::End { name } if Some(local_name(name)) == field => {
+                let value = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                let slot = match field {
+                    Some("title") => &mut package.title,
+                    Some("creator") => &mut package.author,
+                    _ => &mut package.language,
+                };
+                // The first of repeated elements is the primary one
+                if slot.is_none() && !value.is_empty() {
+                    *slot = Some(value);
+                }
+                field = None;
+            }
+            XmlEvent::Text(t) if field.is_some() => text.push_str(&t),
+            _ => {}
+        }
+    }
+    package
+}
+
+/// Resolve a manifest href against the package document's directory.
+pub(crate) fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href);
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    percent_decode(&parts.join("/"))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = s
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::build_zip;
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#;
+
+    const OPF: &str = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>The Rust Book</dc:title>
+    <dc:creator>Steve Klabnik</dc:creator>
+    <dc:creator>Carol Nichols</dc:creator>
+    <dc:language>en</dc:language>
+  </metadata>
+  <manifest>
+    <item id="css" href="style.css" media-type="text/css"/>
+    <item id="ch1" href="text/ch%201.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch2" href="text/ch2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="blank" href="text/blank.xhtml" media-type="application/xhtml+xml"/>
+    <item id="notes" href="text/notes.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="ch2"/><itemref idref="blank"/><itemref idref="ch1"/><itemref idref="notes" linear="no"/></spine>
+</package>"#;
+
+    fn chapter(body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?><html xmlns="http://www.w3.org/1999/xhtml"><head><title>x</title></head><body>{}</body></html>"#,
+            body
+        )
+    }
+
+    fn write_epub(name: &str) -> std::path::PathBuf {
+        let ch1 = chapter(
+            "<section><header><h1>Ownership</h1></header><p>Each value has an owner.</p></section>",
+        );
+        let ch2 = chapter("<h2>Getting Started</h2><p>Install rustup.</p>");
+        let blank = chapter("<div><img src=\"cover.png\"/></div>");
+        let notes = chapter("<p>Footnotes</p>");
+        let data = build_zip(
+            &[
+                ("mimetype", b"application/epub+zip"),
+                ("META-INF/container.xml", CONTAINER.as_bytes()),
+                ("OEBPS/content.opf", OPF.as_bytes()),
+                ("OEBPS/text/ch 1.xhtml", ch1.as_bytes()),
+                ("OEBPS/text/ch2.xhtml", ch2.as_bytes()),
+                ("OEBPS/text/blank.xhtml", blank.as_bytes()),
+                ("OEBPS/text/notes.xhtml", notes.as_bytes()),
+            ],
+            false,
+        );
+        let path = std::env::temp_dir().join(format!(
+            "rusty_rag_test_{}_{}.epub",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_extract_book() {
+        let path = write_epub("book");
+        let book = extract(path.to_str().unwrap()).unwrap();
+        assert_eq!(book.title.as_deref(), Some("The Rust Book"));
+        assert_eq!(book.author.as_deref(), Some("Steve Klabnik"));
+        assert_eq!(book.language.as_deref(), Some("en"));
+        assert_eq!(
+            book.chapters,
+            vec![
+                Chapter {
+                    title: Some("Getting Started".into()),
+                    text: "## Getting Started\nInstall rustup.".into(),
+                },
+                Chapter {
+                    title: Some("Ownership".into()),
+                    text: "# Ownership\nEach value has an owner.".into(),
+                },
+            ]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = extract("/nonexistent/book.epub").unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.epub", std::process::id()));
+        std::fs::write(
+            &path,
+            build_zip(&[("mimetype", b"application/epub+zip")], true),
+        )
+        .unwrap();
+        let err = extract(path.to_str().unwrap()).unwrap_err();
+        assert!(format!("{:#}", err).contains("META-INF/container.xml"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            resolve("OEBPS", "text/ch1.xhtml#top"),
+            "OEBPS/text/ch1.xhtml"
+        );
+        assert_eq!(
+            resolve("OEBPS/text", "../images/a%20b.png"),
+            "OEBPS/images/a b.png"
+        );
+        assert_eq!(resolve("", "ch1.xhtml"), "ch1.xhtml");
+    }
+}
@@ -0,0 +1,463 @@
+use anyhow::Result;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::{docx, email, epub, html, latex, markdown, pdf, pptx, spreadsheet, subtitles, text};
+
+/// A stretch of a document's text and where it sits in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    /// 1-based page (or slide) number, for paginated formats
+    pub page_number: Option<u32>,
+    /// Heading path or title of the enclosing section, e.g.
+    /// "Installation > Linux"
+    pub section: Option<String>,
+    pub text: String,
+}
+
+/// Turns files of one document format into text spans.
+///
+/// Implementations are registered with a [`Registry`], which picks one by
+/// file extension or MIME type, so a new format only needs an extractor
+/// and a `register` call.
+pub trait DocumentExtractor: Send + Sync {
+    /// Short name of the format, e.g. "pdf"
+    fn name(&self) -> &str;
+    /// File extensions handled, lowercase and without the dot
+    fn extensions(&self) -> &[&str];
+    /// MIME types handled, e.g. "application/pdf"
+    fn mime_types(&self) -> &[&str] {
+        &[]
+    }
+    /// Extract the file's text, in document order. Fails if the file holds
+    /// no text.
+    fn extract(&self, path: &str) -> Result<Vec<Span>>;
+}
+
+/// Extractors by file extension and MIME type.
+#[derive(Default)]
+pub struct Registry {
+    extractors: Vec<Box<dyn DocumentExtractor>>,
+}
+
+impl Registry {
+    /// A registry of the formats the core reads: PDF, Word, HTML, LaTeX,
+    /// Markdown, EPUB, PowerPoint, email, subtitles, spreadsheets, and
+    /// plain text.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::default();
+        registry.register(Box::new(PdfExtractor));
+        registry.register(Box::new(EpubExtractor));
+        registry.register(Box::new(PptxExtractor));
+        registry.register(Box::new(EmailExtractor));
+        registry.register(Box::new(SubtitleExtractor));
+        for markup in MARKUP_FORMATS {
+            registry.register(Box::new(markup));
+        }
+        registry
+    }
+
+    /// Add an extractor. It takes precedence over those registered
+    /// earlier for the same extensions or MIME types, so built-in formats
+    /// can be overridden.
+    pub fn register(&mut self, extractor: Box<dyn DocumentExtractor>) {
+        self.extractors.push(extractor);
+    }
+
+    /// The extractor for files with this extension (with or without the
+    /// dot, in any case).
+    pub fn for_extension(&self, extension: &str) -> Option<&dyn DocumentExtractor> {
+        let extension = extension.trim_start_matches('.');
+        self.find(|extractor| {
+            extractor
+                .extensions()
+                .iter()
+                .any(|ext| ext.eq_ignore_ascii_case(extension))
+        })
+    }
+
+    /// The extractor for this MIME type, ignoring any parameters such as
+    /// "; charset=utf-8".
+    pub fn for_mime_type(&self, mime_type: &str) -> Option<&dyn DocumentExtractor> {
+        let mime_type = mime_type.split(';').next().unwrap_or_default().trim();
+        self.find(|extractor| {
+            extractor
+                .mime_types()
+                .iter()
+                .any(|mime| mime.eq_ignore_ascii_case(mime_type))
+        })
+    }
+
+    /// Extract a file with the extractor for its MIME type if one is
+    /// given, or else for its extension.
+    pub fn extract(&self, path: &str, mime_type: Option<&str>) -> Result<Vec<Span>> {
+        let extension = Path::new(path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let extractor = match mime_type {
+            Some(mime_type) => self.for_mime_type(mime_type),
+            None => self.for_extension(&extension),
+        };
+        match extractor {
+            Some(extractor) => extractor.extract(path),
+            None => match mime_type {
+                Some(mime_type) => anyhow::bail!("Unsupported MIME type '{}': {}", mime_type, path),
+                None => anyhow::bail!("Unsupported file type '.{}': {}", extension, path),
+            },
+        }
+    }
+
+    /// The registered extractors, earliest first.
+    pub fn extractors(&self) -> impl Iterator<Item = &dyn DocumentExtractor> {
+        self.extractors.iter().map(|extractor| extractor.as_ref())
+    }
+
+    fn find(
+        &self,
+        matches: impl Fn(&dyn DocumentExtractor) -> bool,
+    ) -> Option<&dyn DocumentExtractor> {
+        self.extractors
+            .iter()
+            .rev()
+            .map(|extractor| extractor.as_ref())
+            .find(|&extractor| matches(extractor))
+    }
+}
+
+/// The registry of built-in extractors, built on first use.
+pub fn builtins() -> &'static Registry {
+    static BUILTINS: OnceLock<Registry> = OnceLock::new();
+    BUILTINS.get_or_init(Registry::with_builtins)
+}
+
+/// PDFs: a span per page, split where an outline (bookmark) section starts.
+struct PdfExtractor;
+
+impl DocumentExtractor for PdfExtractor {
+    fn name(&self) -> &str {
+        "pdf"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pdf"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/pdf"]
+    }
+
+    fn extract(&self, path: &str) -> Result<Vec<Span>> {
+        let sections = pdf::extract_sections(path, None, pdf::Cleanup::default())?;
+        Ok(sections
+            .into_iter()
+            .map(|section| Span {
+                page_number: Some(section.page_number),
+                section: heading_path(&section.path),
+                text: section.text,
+            })
+            .collect())
+    }
+}
+
+/// EPUB books: their chapters split at headings, each section outside a
+/// heading filed under its chapter's title.
+struct EpubExtractor;
+
+impl DocumentExtractor for EpubExtractor {
+    fn name(&self) -> &str {
+        "epub"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["epub"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/epub+zip"]
+    }
+
+    fn extract(&self, path: &str) -> Result<Vec<Span>> {
+        let book = epub::extract(path)?;
+        let mut spans = Vec::new();
+        for (n, chapter) in book.chapters.into_iter().enumerate() {
+            let title = chapter
+                .title
+                .unwrap_or_else(|| format!("Chapter {}", n + 1));
+            for section in markdown::sections(&chapter.text) {
+                spans.push(Span {
+                    page_number: None,
+                    section: heading_path(&section.path).or_else(|| Some(title.clone())),
+                    text: section.text,
+                });
+            }
+        }
+        Ok(spans)
+    }
+}
+
+/// PowerPoint decks: a span per slide, numbered like pages and titled by
+/// the slide title.
+struct PptxExtractor;
+
+impl DocumentExtractor for PptxExtractor {
+    fn name(&self) -> &str {
+        "pptx"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pptx"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/vnd.openxmlformats-officedocument.presentationml.presentation"]
+    }
+
+    fn extract(&self, path: &str) -> Result<Vec<Span>> {
+        Ok(pptx::extract_slides(path)?
+            .into_iter()
+            .map(|slide| Span {
+                page_number: Some(slide.number),
+                section: slide.title,
+                text: slide.text,
+            })
+            .collect())
+    }
+}
+
+/// Email: a span per message, headed by its subject, sender, and date.
+struct EmailExtractor;
+
+impl DocumentExtractor for EmailExtractor {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["eml", "mbox"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["message/rfc822", "application/mbox"]
+    }
+
+    fn extract(&self, path: &str) -> Result<Vec<Span>> {
+        Ok(email::extract_messages(path)?
+            .into_iter()
+            .map(|message| {
+                let headers = [
+                    ("Subject", &message.subject),
+                    ("From", &message.sender),
+                    ("Date", &message.date),
+                ];
+                let mut text: String = headers
+                    .iter()
+                    .filter_map(|(label, value)| Some(format!("{}: {}\n", label, value.as_ref()?)))
+                    .collect();
+                text.push('\n');
+                text.push_str(&message.text);
+                Span {
+                    page_number: None,
+                    section: message.subject,
+                    text: text.trim().to_string(),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Subtitles and transcripts: a span per paragraph of cues, in a section
+/// named by its time range (e.g. "00:01:05 – 00:01:40").
+struct SubtitleExtractor;
+
+impl DocumentExtractor for SubtitleExtractor {
+    fn name(&self) -> &str {
+        "subtitles"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["srt", "vtt"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/x-subrip", "text/vtt"]
+    }
+
+    fn extract(&self, path: &str) -> Result<Vec<Span>> {
+        Ok(subtitles::extract_paragraphs(path)?
+            .into_iter()
+            .map(|paragraph| Span {
+                page_number: None,
+                section: Some(format!(
+                    "{} – {}",
+                    subtitles::format_time(paragraph.start_ms),
+                    subtitles::format_time(paragraph.end_ms)
+                )),
+                text: paragraph.labeled_text(),
+            })
+            .collect())
+    }
+}
+
+/// Formats read as a single markdown text, split into spans at its
+/// headings.
+struct MarkupExtractor {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    mime_types: &'static [&'static str],
+    read: fn(&str) -> Result<String>,
+}
+
+const MARKUP_FORMATS: [MarkupExtractor; 6] = [
+    MarkupExtractor {
+        name: "docx",
+        extensions: &["docx"],
+        mime_types: &["application/vnd.openxmlformats-officedocument.wordprocessingml.document"],
+        read: docx::extract_text,
+    },
+    MarkupExtractor {
+        name: "html",
+        extensions: &["html", "htm"],
+        mime_types: &["text/html", "application/xhtml+xml"],
+        read: html::extract_text,
+    },
+    MarkupExtractor {
+        name: "latex",
+        extensions: &["tex", "latex"],
+        mime_types: &["application/x-tex", "text/x-tex"],
+        read: latex::extract_text,
+    },
+    MarkupExtractor {
+        name: "markdown",
+        extensions: &["md", "markdown"],
+        mime_types: &["text/markdown"],
+        read: text::read_file,
+    },
+    MarkupExtractor {
+        name: "spreadsheet",
+        extensions: &["csv", "tsv", "xlsx"],
+        mime_types: &[
+            "text/csv",
+            "text/tab-separated-values",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        ],
+        read: spreadsheet::extract_text,
+    },
+    MarkupExtractor {
+        name: "text",
+        extensions: &["txt", "text"],
+        mime_types: &["text/plain"],
+        read: text::read_file,
+    },
+];
+
+impl DocumentExtractor for MarkupExtractor {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        self.mime_types
+    }
+
+    fn extract(&self, path: &str) -> Result<Vec<Span>> {
+        let spans: Vec<Span> = markdown::sections(&(self.read)(path)?)
+            .into_iter()
+            .map(|section| Span {
+                page_number: None,
+                section: heading_path(&section.path),
+                text: section.text,
+            })
+            .collect();
+        if spans.is_empty() {
+            anyhow::bail!("No text could be extracted from: {}", path);
+        }
+        Ok(spans)
+    }
+}
+
+/// Join a heading path into one section label, or None if it is empty.
+fn heading_path(path: &[String]) -> Option<String> {
+    (!path.is_empty()).then(|| path.join(" > "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Reverser;
+
+    impl DocumentExtractor for Reverser {
+        fn name(&self) -> &str {
+            "reverser"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["md", "rev"]
+        }
+
+        fn extract(&self, path: &str) -> Result<Vec<Span>> {
+            Ok(vec![Span {
+                page_number: None,
+                section: None,
+                text: text::read_file(path)?.chars().rev().collect(),
+            }])
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let registry = Registry::with_builtins();
+        let name =
+            |extractor: Option<&dyn DocumentExtractor>| extractor.map(|e| e.name().to_string());
+        assert_eq!(name(registry.for_extension("PDF")), Some("pdf".into()));
+        assert_eq!(name(registry.for_extension(".htm")), Some("html".into()));
+        assert_eq!(name(registry.for_extension("mbox")), Some("email".into()));
+        assert_eq!(name(registry.for_extension("rev")), None);
+        assert_eq!(
+            name(registry.for_mime_type("text/html; charset=utf-8")),
+            Some("html".into())
+        );
+        assert_eq!(name(registry.for_mime_type("image/png")), None);
+    }
+
+    #[test]
+    fn test_extract_markup_sections() {
+        let path =
+            std::env::temp_dir().join(format!("rusty_rag_test_{}_registry.md", std::process::id()));
+        std::fs::write(
+            &path,
+            "Intro text.\n\n# Setup\n\nInstall it.\n\n## Linux\n\nUse apt.\n",
+        )
+        .unwrap();
+        let path_str = path.to_str().unwrap();
+
+        let spans = Registry::with_builtins().extract(path_str, None).unwrap();
+        let sections: Vec<Option<&str>> =
+            spans.iter().map(|span| span.section.as_deref()).collect();
+        assert_eq!(sections, [None, Some("Setup"), Some("Setup > Linux")]);
+        assert!(spans[2].text.contains("Use apt."));
+
+        // A registered extractor overrides the built-in one for its extensions
+        let mut registry = Registry::with_builtins();
+        registry.register(Box::new(Reverser));
+        let spans = registry.extract(path_str, None).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert!(spans[0].text.starts_with("\n.tpa esU"));
+        assert_eq!(
+            registry
+                .extract(path_str, Some("text/html"))
+                .unwrap_err()
+                .to_string(),
+            format!("File is not an HTML file: {}", path_str)
+        );
+        std::fs::remove_file(path).unwrap();
+
+        let err = registry.extract("notes.xyz", None).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported file type '.xyz': notes.xyz");
+    }
+}
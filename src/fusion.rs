@@ -0,0 +1,95 @@
+//! Reciprocal Rank Fusion (RRF) of ranked result lists.
+//!
+//! Hybrid retrieval ranks the same documents several ways (BM25 keyword
+//! scores, vector similarities) whose scores don't compare. RRF merges the
+//! rankings by position alone:
+//!   RRF(d) = Σ w_i / (k + rank_i(d))
+//! where rank_i(d) is d's 1-based position in ranking i, w_i that ranking's
+//! weight (1 for plain RRF), and k (60 by convention) damps the lead of the
+//! very top ranks, so a document ranked well by several lists beats one
+//! ranked first by a single list.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use pyo3::prelude::*;
+
+/// A document ID passed from Python: an index or a string key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, FromPyObject)]
+pub enum DocId {
+    Index(i64),
+    Key(String),
+}
+
+impl IntoPy<PyObject> for DocId {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            DocId::Index(index) => index.into_py(py),
+            DocId::Key(key) => key.into_py(py),
+        }
+    }
+}
+
+/// Merge `rankings` (each best first) into one, best first, scoring each
+/// document by RRF with ranking i weighted by `weights[i]` (1 where
+/// missing). Rankings of weight 0 are skipped; ties keep the order in
+/// which documents were first seen.
+pub fn rrf_fuse<T: Eq + Hash + Clone>(
+    rankings: &[Vec<T>],
+    weights: &[f64],
+    k: f64,
+) -> Vec<(T, f64)> {
+    let mut fused: Vec<(T, f64)> = Vec::new();
+    let mut positions: HashMap<T, usize> = HashMap::new();
+    for (i, ranking) in rankings.iter().enumerate() {
+        let weight = weights.get(i).copied().unwrap_or(1.0);
+        if weight == 0.0 {
+            continue;
+        }
+        for (rank, doc) in (1..).zip(ranking) {
+            let score = weight / (k + rank as f64);
+            match positions.get(doc) {
+                Some(&position) => fused[position].1 += score,
+                None => {
+                    positions.insert(doc.clone(), fused.len());
+                    fused.push((doc.clone(), score));
+                }
+            }
+        }
+    }
+    // A stable sort, so ties stay in first-seen order
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agreement_beats_one_top_rank() {
+        let bm25 = vec!["a", "b", "c"];
+        let vector = vec!["d", "b", "a"];
+        let fused = rrf_fuse(&[bm25, vector], &[], 60.0);
+        let order: Vec<&str> = fused.iter().map(|&(doc, _)| doc).collect();
+        assert_eq!(order, ["a", "b", "d", "c"]);
+        assert!((fused[0].1 - (1.0 / 61.0 + 1.0 / 63.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_weights() {
+        let fused = rrf_fuse(&[vec![1, 2], vec![2, 1]], &[3.0, 1.0], 60.0);
+        assert_eq!(fused[0].0, 1);
+        // A ranking of weight 0 contributes nothing, not even its documents
+        let fused = rrf_fuse(&[vec![1], vec![2]], &[1.0, 0.0], 60.0);
+        assert_eq!(fused, [(1, 1.0 / 61.0)]);
+    }
+
+    #[test]
+    fn test_ties_keep_first_seen_order() {
+        let fused = rrf_fuse(&[vec!["x"], vec!["y"]], &[], 60.0);
+        assert_eq!(fused[0].0, "x");
+        assert_eq!(fused[1].0, "y");
+        assert!(rrf_fuse::<u32>(&[], &[], 60.0).is_empty());
+    }
+}
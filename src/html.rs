@@ -0,0 +1,551 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::xml::{attribute, XmlEvent, XmlReader};
+
+/// Extracts the main content of an HTML page as text.
+///
+/// Navigation, headers, footers, sidebars, and scripts are dropped, and the
+/// element holding the article body is picked readability-style: `<main>`
+/// or `<article>` if present, otherwise the container whose paragraphs
+/// score highest. Headings, list items, and tables are kept as markdown.
+pub fn extract_text(path: &str) -> Result<String> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    if file_path
+        .extension()
+        .is_none_or(|ext| !(ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm")))
+    {
+        anyhow::bail!("File is not an HTML file: {}", path);
+    }
+
+    let data =
+        std::fs::read(file_path).with_context(|| format!("Failed to open file: {}", path))?;
+    let html =
+        crate::text::decode(&data).with_context(|| format!("Failed to read HTML: {}", path))?;
+    let text = html_to_text(&html);
+
+    if text.is_empty() {
+        anyhow::bail!("No text could be extracted from the HTML file: {}", path);
+    }
+    Ok(text)
+}
+
+/// Main-content text of an HTML document.
+pub fn html_to_text(html: &str) -> String {
+    let mut root = parse(html);
+    prune(&mut root, false);
+    let mut renderer = Renderer::default();
+    renderer.render(main_content(&root));
+    renderer.finish()
+}
+
+/// Text of a whole HTML document that is all content (e.g. an EPUB
+/// chapter): boilerplate is still pruned, but no main element is picked.
+pub fn document_text(html: &str) -> String {
+    let mut root = parse(html);
+    prune(&mut root, false);
+    let mut renderer = Renderer::default();
+    renderer.render(&root);
+    renderer.finish()
+}
+
+/// Elements that never have children.
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content is not text worth indexing; dropped with all
+/// their children.
+const DROPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "head", "nav", "header", "footer",
+    "aside", "form", "button", "select", "textarea", "dialog",
+];
+
+/// Elements whose content is raw text rather than markup.
+const RAW_TEXT: &[&str] = &["script", "style", "textarea", "template"];
+
+/// Class/id fragments of page furniture rather than content.
+const BOILERPLATE_HINTS: &[&str] = &[
+    "nav",
+    "menu",
+    "footer",
+    "sidebar",
+    "comment",
+    "cookie",
+    "banner",
+    "share",
+    "social",
+    "related",
+    "advert",
+    "promo",
+    "breadcrumb",
+    "popup",
+    "modal",
+    "subscribe",
+    "newsletter",
+];
+
+/// Class/id fragments that mark content even when a boilerplate hint matches
+/// too (e.g. "main-nav-content").
+const CONTENT_HINTS: &[&str] = &["article", "content", "main", "post", "entry", "story"];
+
+/// Elements that start a new line of text.
+const BLOCK: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "body",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// Minimum text length for `<main>`/`<article>` to be trusted as the content.
+const MIN_SEMANTIC_CHARS: usize = 140;
+/// Paragraphs shorter than this don't count towards a container's score.
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug)]
+struct Element {
+    /// Lowercase tag name
+    name: String,
+    /// Lowercase id, class, and role, for boilerplate detection
+    hints: String,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str, attrs: &str) -> Self {
+        let hints = ["id", "class", "role"]
+            .iter()
+            .filter_map(|key| attribute(attrs, key))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        Self {
+            name: name.to_ascii_lowercase(),
+            hints,
+            children: Vec::new(),
+        }
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(el) => Some(el),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// Length of all text inside, ignoring whitespace runs.
+    fn text_len(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| match child {
+                Node::Element(el) => el.text_len(),
+                Node::Text(text) => text.split_whitespace().map(|w| w.len() + 1).sum(),
+            })
+            .sum()
+    }
+
+    /// Length of text inside links.
+    fn link_text_len(&self) -> usize {
+        if self.name == "a" {
+            return self.text_len();
+        }
+        self.elements().map(Element::link_text_len).sum()
+    }
+
+    fn text(&self) -> String {
+        let mut out = String::new();
+        self.collect_text(&mut out);
+        out.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn collect_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                Node::Element(el) => {
+                    // Keep words in separate blocks apart
+                    let block = el.name == "br" || BLOCK.contains(&el.name.as_str());
+                    if block {
+                        out.push(' ');
+                    }
+                    el.collect_text(out);
+                    if block {
+                        out.push(' ');
+                    }
+                }
+                Node::Text(text) => out.push_str(text),
+            }
+        }
+    }
+}
+
+/// Build an element tree, tolerating the unclosed and stray tags real
+/// pages are full of.
+fn parse(html: &str) -> Element {
+    let mut stack = vec![Element::new("#root", "")];
+    let mut reader = XmlReader::new(html);
+
+    while let Some(event) = reader.next() {
+        match event {
+            XmlEvent::Start {
+                name,
+                attrs,
+                self_closing,
+            } => {
+                let el = Element::new(name, attrs);
+                if RAW_TEXT.contains(&el.name.as_str()) {
+                    if !self_closing {
+                        reader.skip_to_end(&el.name);
+                    }
+                    continue;
+                }
+                close_implied(&mut stack, &el.name);
+                if self_closing || VOID.contains(&el.name.as_str()) {
+                    stack.last_mut().unwrap().children.push(Node::Element(el));
+                } else {
+                    stack.push(el);
+                }
+            }
+            XmlEvent::End { name } => {
+                let name = name.to_ascii_lowercase();
+                // Stray end tags are ignored; others close everything above
+                if let Some(pos) = stack.iter().rposition(|el| el.name == name) {
+                    if pos > 0 {
+                        close_to(&mut stack, pos);
+                    }
+                }
+            }
+            XmlEvent::Text(text) => {
+                let top = stack.last_mut().unwrap();
+                top.children.push(Node::Text(text.into_owned()));
+            }
+        }
+    }
+    close_to(&mut stack, 1);
+    stack.pop().unwrap()
+}
+
+/// Pop elements until `stack` has `len` left, attaching each to its parent.
+fn close_to(stack: &mut Vec<Element>, len: usize) {
+    while stack.len() > len {
+        let el = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(Node::Element(el));
+    }
+}
+
+/// Close elements whose end tag HTML lets authors omit, e.g. an open `<p>`
+/// when a block starts, or the previous `<li>` when the next one does.
+fn close_implied(stack: &mut Vec<Element>, name: &str) {
+    let closes: &[&str] = match name {
+        "li" => &["li", "p"],
+        "dt" | "dd" => &["dt", "dd", "p"],
+        "td" | "th" => &["td", "th"],
+        "tr" => &["td", "th", "tr"],
+        "option" => &["option"],
+        _ if BLOCK.contains(&name) => &["p"],
+        _ => &[],
+    };
+    while stack.len() > 1 && closes.contains(&stack.last().unwrap().name.as_str()) {
+        close_to(stack, stack.len() - 1);
+    }
+}
+
+/// Remove page furniture: dropped elements and boilerplate-looking blocks.
+///
+/// A `<header>` inside an article or section usually holds its title, so
+/// it is only dropped at page level.
+fn prune(el: &mut Element, in_content: bool) {
+    let in_content = in_content || matches!(el.name.as_str(), "article" | "main" | "section");
+    el.children.retain(|child| match child {
+        Node::Element(child) => !is_boilerplate(child, in_content),
+        Node::Text(_) => true,
+    });
+    for child in el.children.iter_mut() {
+        if let Node::Element(child) = child {
+            prune(child, in_content);
+        }
+    }
+}
+
+fn is_boilerplate(el: &Element, in_content: bool) -> bool {
+    if in_content && el.name == "header" {
+        return false;
+    }
+    if DROPPED.contains(&el.name.as_str()) {
+        return true;
+    }
+    if matches!(el.name.as_str(), "html" | "body" | "main" | "article") {
+        return false;
+    }
+    BOILERPLATE_HINTS.iter().any(|hint| el.hints.contains(hint))
+        && !CONTENT_HINTS.iter().any(|hint| el.hints.contains(hint))
+}
+
+/// Pick the element holding the page's main content.
+fn main_content(root: &Element) -> &Element {
+    let mut semantic = Vec::new();
+    collect(root, &mut semantic, &|el| {
+        matches!(el.name.as_str(), "main" | "article") || el.hints.split(' ').any(|h| h == "main")
+    });
+    if let Some(best) = semantic.into_iter().max_by_key(|el| el.text_len()) {
+        if best.text_len() >= MIN_SEMANTIC_CHARS {
+            return best;
+        }
+    }
+
+    // Readability-style scoring: each paragraph scores its parent fully
+    // and its grandparent half, favouring long, comma-rich prose
+    let mut scores: Vec<(&Element, f64)> = Vec::new();
+    score_paragraphs(root, &mut Vec::new(), &mut scores);
+    scores
+        .into_iter()
+        .map(|(el, score)| {
+            let link_density = el.link_text_len() as f64 / el.text_len().max(1) as f64;
+            (el, score * (1.0 - link_density))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(el, _)| el)
+        .unwrap_or(root)
+}
+
+fn collect<'a>(el: &'a Element, out: &mut Vec<&'a Element>, pred: &dyn Fn(&Element) -> bool) {
+    for child in el.elements() {
+        if pred(child) {
+            out.push(child);
+        }
+        collect(child, out, pred);
+    }
+}
+
+fn score_paragraphs<'a>(
+    el: &'a Element,
+    ancestors: &mut Vec<&'a Element>,
+    scores: &mut Vec<(&'a Element, f64)>,
+) {
+    if matches!(el.name.as_str(), "p" | "pre" | "td" | "blockquote") {
+        let text = el.text();
+        if text.len() >= MIN_PARAGRAPH_CHARS {
+            let score =
+                1.0 + text.matches(',').count() as f64 + (text.len() as f64 / 100.0).min(3.0);
+            for (depth, ancestor) in ancestors.iter().rev().take(2).enumerate() {
+                let share = if depth == 0 { score } else { score / 2.0 };
+                match scores
+                    .iter_mut()
+                    .find(|(el, _)| std::ptr::eq(*el, *ancestor))
+                {
+                    Some((_, total)) => *total += share,
+                    None => scores.push((ancestor, share)),
+                }
+            }
+        }
+    }
+    ancestors.push(el);
+    for child in el.elements() {
+        score_paragraphs(child, ancestors, scores);
+    }
+    ancestors.pop();
+}
+
+/// Turns an element tree into lines of text with light markdown.
+#[derive(Default)]
+struct Renderer {
+    lines: Vec<String>,
+    current: String,
+    prefix: String,
+}
+
+impl Renderer {
+    fn render(&mut self, el: &Element) {
+        let name = el.name.as_str();
+        match name {
+            "br" => return self.flush(),
+            "table" => {
+                self.flush();
+                let mut rows = Vec::new();
+                collect(el, &mut rows, &|el| el.name == "tr");
+                let rows: Vec<Vec<String>> = rows
+                    .into_iter()
+                    .map(|tr| {
+                        tr.elements()
+                            .filter(|c| matches!(c.name.as_str(), "td" | "th"))
+                            .map(Element::text)
+                            .collect::<Vec<_>>()
+                    })
+                    .filter(|row: &Vec<String>| row.iter().any(|c| !c.is_empty()))
+                    .collect();
+                if !rows.is_empty() {
+                    self.lines.push(crate::markdown::table(&rows));
+                }
+                return;
+            }
+            "pre" => {
+                self.flush();
+                let mut text = String::new();
+                el.collect_text(&mut text);
+                self.lines.extend(
+                    text.lines()
+                        .map(|l| l.trim_end().to_string())
+                        .filter(|l| !l.is_empty()),
+                );
+                return;
+            }
+            _ => {}
+        }
+
+        let block = BLOCK.contains(&name);
+        if block {
+            self.flush();
+            if let Some(level) = name.strip_prefix('h').and_then(|n| n.parse::<usize>().ok()) {
+                self.prefix = format!("{} ", "#".repeat(level));
+            } else if name == "li" {
+                self.prefix = "- ".to_string();
+            }
+        }
+        for child in &el.children {
+            match child {
+                Node::Element(child) => self.render(child),
+                Node::Text(text) => {
+                    self.current.push_str(text);
+                }
+            }
+        }
+        if block {
+            self.flush();
+        }
+    }
+
+    /// End the current line.
+    fn flush(&mut self) {
+        let text = self
+            .current
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !text.is_empty() {
+            self.lines.push(format!("{}{}", self.prefix, text));
+        }
+        self.current.clear();
+        self.prefix.clear();
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROSE: &str = "Retrieval-augmented generation grounds a language model in documents, \
+                         which keeps answers specific, current, and verifiable.";
+
+    #[test]
+    fn test_boilerplate_removed() {
+        let html = format!(
+            r#"<!DOCTYPE html><html><head><title>Guide</title><style>p {{ color: red }}</style></head>
+            <body>
+              <header><a href="/">Home</a></header>
+              <nav><ul><li><a href="/a">A</a><li><a href="/b">B</a></ul></nav>
+              <div class="content">
+                <h1>Getting started</h1>
+                <p>{PROSE}
+                <p>Install it with <code>pip</code>.<br>Then run it.</p>
+                <ul><li>Fast<li>Local</ul>
+                <div class="share-buttons">Tweet this</div>
+              </div>
+              <script>var x = "<p>not text</p>";</script>
+              <footer>© 2024</footer>
+            </body></html>"#
+        );
+        assert_eq!(
+            html_to_text(&html),
+            format!(
+                "# Getting started\n{}\nInstall it with pip.\nThen run it.\n- Fast\n- Local",
+                PROSE
+            )
+        );
+    }
+
+    #[test]
+    fn test_article_preferred() {
+        let html = format!(
+            "<body><div><p>Sign up for our newsletter today, it is great.</p></div>\
+             <article><h2>Story</h2><p>{PROSE}</p></article></body>"
+        );
+        assert_eq!(html_to_text(&html), format!("## Story\n{}", PROSE));
+    }
+
+    #[test]
+    fn test_highest_scoring_container() {
+        let links = "<a href=\"/1\">One link, with a long title, that goes on and on</a>";
+        let html = format!(
+            "<body><div id=\"links\"><p>{links}</p><p>{links}</p></div>\
+             <div id=\"body\"><p>{PROSE}</p><p>{PROSE}</p></div></body>"
+        );
+        assert_eq!(html_to_text(&html), format!("{}\n{}", PROSE, PROSE));
+    }
+
+    #[test]
+    fn test_document_text_keeps_article_headers() {
+        let html = "<body><header>Site</header><section><header><h1>Chapter 1</h1></header>\
+                    <p>Short.</p></section><p>Also short.</p></body>";
+        assert_eq!(document_text(html), "# Chapter 1\nShort.\nAlso short.");
+    }
+
+    #[test]
+    fn test_tables_and_pre() {
+        let html = "<table><tr><th>Name<th>Score<tr><td>Ada<td>97</table>\
+                    <pre>fn main() {\n    run();\n}</pre>";
+        assert_eq!(
+            html_to_text(html),
+            "| Name | Score |\n| --- | --- |\n| Ada | 97 |\nfn main() {\n    run();\n}"
+        );
+    }
+
+    #[test]
+    fn test_extract_text_from_file() {
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.html", std::process::id()));
+        std::fs::write(&path, format!("<p>{}</p>", PROSE)).unwrap();
+        assert_eq!(extract_text(path.to_str().unwrap()).unwrap(), PROSE);
+        std::fs::remove_file(path).unwrap();
+
+        let err = extract_text("/nonexistent/page.html").unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+    }
+}
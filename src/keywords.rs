@@ -0,0 +1,131 @@
+//! Keyword extraction for chunks, by TF-IDF over the chunks of a document.
+//!
+//! A chunk's keywords are the words it uses often that the document's other
+//! chunks don't, so in a manual about a database, the chunk on installing it
+//! is tagged "docker" ahead of "database". Words sharing a stem count as one
+//! term, shown as its most frequent spelling.
+
+use crate::analyzer::{Analyzer, Language};
+use crate::tokenizer;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Words shorter than this (in characters) are never keywords.
+const MIN_KEYWORD_CHARS: usize = 3;
+
+/// Up to `top_k` keywords of each chunk, best first.
+///
+/// Stopwords of `language`, numbers, and very short words are skipped; a
+/// term's score is its count in the chunk times its inverse document
+/// frequency among `chunks`, ties going to the term seen first.
+pub fn chunk_keywords(chunks: &[String], top_k: usize, language: Language) -> Vec<Vec<String>> {
+    let analyzer = Analyzer::new(language);
+    // (stem, word) pairs of each chunk's candidate words, in order
+    let terms: Vec<Vec<(String, String)>> = chunks
+        .par_iter()
+        .map(|chunk| candidates(chunk, &analyzer))
+        .collect();
+
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    // Each stem's spellings, with their counts and order of appearance
+    let mut spellings: HashMap<&str, HashMap<&str, (usize, usize)>> = HashMap::new();
+    for (position, (stem, word)) in terms.iter().flatten().enumerate() {
+        let entry = spellings.entry(stem).or_default();
+        entry.entry(word).or_insert((0, position)).0 += 1;
+    }
+    for chunk_terms in &terms {
+        let stems: HashSet<&str> = chunk_terms.iter().map(|(stem, _)| stem.as_str()).collect();
+        for stem in stems {
+            *document_frequency.entry(stem).or_default() += 1;
+        }
+    }
+    // The most frequent spelling of each stem, the first seen on a tie
+    let spelling: HashMap<&str, &str> = spellings
+        .iter()
+        .map(|(&stem, words)| {
+            let (word, _) = words
+                .iter()
+                .max_by_key(|&(_, &(count, position))| (count, Reverse(position)))
+                .expect("every stem has a spelling");
+            (stem, *word)
+        })
+        .collect();
+
+    let n = chunks.len() as f64;
+    terms
+        .par_iter()
+        .map(|chunk_terms| {
+            // Stems in order of first appearance, with their counts
+            let mut order: Vec<&str> = Vec::new();
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for (stem, _) in chunk_terms {
+                let count = counts.entry(stem).or_insert_with(|| {
+                    order.push(stem);
+                    0
+                });
+                *count += 1;
+            }
+            // Smoothed inverse document frequency, as in scikit-learn
+            let score = |stem: &str| {
+                let df = document_frequency[stem] as f64;
+                counts[stem] as f64 * (((1.0 + n) / (1.0 + df)).ln() + 1.0)
+            };
+            let mut ranked: Vec<(f64, &str)> = order.iter().map(|&s| (score(s), s)).collect();
+            // A stable sort keeps ties in order of appearance
+            ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+            ranked
+                .into_iter()
+                .take(top_k)
+                .map(|(_, stem)| spelling[stem].to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// A chunk's candidate keywords as (stem, word) pairs, in order.
+fn candidates(chunk: &str, analyzer: &Analyzer) -> Vec<(String, String)> {
+    tokenizer::tokenize(chunk)
+        .into_iter()
+        .map(|word| word.trim_matches('\'').to_string())
+        .filter(|word| {
+            word.chars().count() >= MIN_KEYWORD_CHARS
+                && !word.chars().all(|c| c.is_numeric())
+                && !analyzer.is_stopword(word)
+        })
+        .map(|word| (analyzer.stem(&word), word))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_keywords() {
+        let chunks: Vec<String> = [
+            "The database stores rows. Install the database with Docker; Docker \
+             volumes keep the data.",
+            "The database indexes rows. Indexes speed up queries, and an index \
+             is rebuilt nightly.",
+            "",
+        ]
+        .map(String::from)
+        .to_vec();
+        let keywords = chunk_keywords(&chunks, 2, Language::English);
+        assert_eq!(keywords[0], ["docker", "database"]);
+        // "indexes" and "index" are one term, spelled as most often written
+        assert_eq!(keywords[1], ["indexes", "speed"]);
+        assert!(keywords[2].is_empty());
+    }
+
+    #[test]
+    fn test_skips_numbers_and_short_words() {
+        let chunks = vec!["In 2024 an AI ran 3 jobs: jobs, jobs.".to_string()];
+        assert_eq!(
+            chunk_keywords(&chunks, 5, Language::English),
+            [["jobs", "ran"]]
+        );
+        assert!(chunk_keywords(&[], 5, Language::English).is_empty());
+    }
+}
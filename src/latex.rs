@@ -0,0 +1,863 @@
+use anyhow::Result;
+use std::path::{Component, Path};
+use unicode_normalization::UnicodeNormalization;
+
+/// Deepest chain of `\input` files followed.
+const MAX_INPUT_DEPTH: usize = 8;
+
+/// Sectioning commands, outermost first.
+const SECTIONS: &[&str] = &[
+    "part",
+    "chapter",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// Index in SECTIONS of `\section`, the outermost level of most papers.
+const SECTION: usize = 2;
+
+/// Environments whose body is math, kept as its LaTeX source.
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation",
+    "align",
+    "alignat",
+    "flalign",
+    "gather",
+    "multline",
+    "eqnarray",
+    "displaymath",
+    "math",
+];
+
+/// Environments whose body is kept verbatim.
+const VERBATIM_ENVIRONMENTS: &[&str] = &["verbatim", "Verbatim", "lstlisting", "minted"];
+
+/// Commands dropped along with their arguments: cross-references,
+/// citations, graphics, spacing, and preamble setup.
+const DROPPED: &[&str] = &[
+    "label",
+    "ref",
+    "eqref",
+    "autoref",
+    "cref",
+    "Cref",
+    "pageref",
+    "nameref",
+    "cite",
+    "citep",
+    "citet",
+    "citealp",
+    "citeauthor",
+    "citeyear",
+    "nocite",
+    "includegraphics",
+    "vspace",
+    "hspace",
+    "bibliographystyle",
+    "bibliography",
+    "addbibresource",
+    "documentclass",
+    "usepackage",
+    "newcommand",
+    "renewcommand",
+    "providecommand",
+    "newenvironment",
+    "renewenvironment",
+    "DeclareMathOperator",
+    "newtheorem",
+    "setlength",
+    "addtolength",
+    "setcounter",
+    "addtocounter",
+    "pagestyle",
+    "thispagestyle",
+    "graphicspath",
+    "hypersetup",
+    "definecolor",
+    "color",
+    "captionsetup",
+    "title",
+    "author",
+    "date",
+    "thanks",
+];
+
+/// Commands standing for a symbol.
+const SYMBOLS: &[(&str, &str)] = &[
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("LaTeX", "LaTeX"),
+    ("TeX", "TeX"),
+    ("S", "§"),
+    ("P", "¶"),
+    ("copyright", "©"),
+    ("textbackslash", "\\"),
+    ("textasciitilde", "~"),
+    ("textendash", "–"),
+    ("textemdash", "—"),
+    ("textbullet", "•"),
+    ("ss", "ß"),
+    ("ae", "æ"),
+    ("AE", "Æ"),
+    ("oe", "œ"),
+    ("OE", "Œ"),
+    ("o", "ø"),
+    ("O", "Ø"),
+    ("aa", "å"),
+    ("AA", "Å"),
+    ("quad", " "),
+    ("qquad", " "),
+];
+
+/// Accent commands and the combining marks they add to the next letter.
+const ACCENTS: &[(&str, char)] = &[
+    ("'", '\u{301}'),
+    ("`", '\u{300}'),
+    ("^", '\u{302}'),
+    ("\"", '\u{308}'),
+    ("~", '\u{303}'),
+    ("=", '\u{304}'),
+    (".", '\u{307}'),
+    ("c", '\u{327}'),
+    ("v", '\u{30C}'),
+    ("u", '\u{306}'),
+    ("H", '\u{30B}'),
+    ("r", '\u{30A}'),
+    ("k", '\u{328}'),
+];
+
+/// Extracts the text of a LaTeX source file, e.g. an arXiv paper's.
+///
+/// Markup is stripped, but section titles are kept as markdown headings,
+/// math as its LaTeX source, and figure and table captions as
+/// "Figure: ..." lines. Files pulled in with `\input` or `\include` are
+/// read from the main file's directory (never from outside it).
+pub fn extract_text(path: &str) -> Result<String> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    let source = strip_comments(&crate::text::read_file(path)?);
+    let dir = file_path.parent().unwrap_or(Path::new(""));
+    let text = latex_to_text(&expand_inputs(&source, dir, 0));
+
+    if text.is_empty() {
+        anyhow::bail!("No text could be extracted from the LaTeX file: {}", path);
+    }
+    Ok(text)
+}
+
+/// Text of a LaTeX document: its title, then the body of its `document`
+/// environment (or all of it, for a fragment without one).
+pub fn latex_to_text(source: &str) -> String {
+    let body = match source.find("\\begin{document}") {
+        Some(start) => {
+            let body = &source[start + "\\begin{document}".len()..];
+            &body[..body.find("\\end{document}").unwrap_or(body.len())]
+        }
+        None => source,
+    };
+
+    let mut converter = Converter::new(body, section_offset(body));
+    if let Some(title) = title(source) {
+        converter.out.push_str(&title);
+        converter.paragraph();
+    }
+    converter.run()
+}
+
+/// Drops `%` comments, and the lines holding nothing else. As in TeX, a
+/// comment also swallows the line break after it.
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    for line in source.lines() {
+        match comment_start(line) {
+            Some(start) if line[..start].trim().is_empty() => {}
+            Some(start) => out.push_str(&line[..start]),
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Byte offset of the `%` starting a line's comment, if it has one.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut backslashes = 0;
+    for (i, b) in line.bytes().enumerate() {
+        match b {
+            b'\\' => backslashes += 1,
+            b'%' if backslashes % 2 == 0 => return Some(i),
+            _ => backslashes = 0,
+        }
+    }
+    None
+}
+
+/// Replaces `\input{file}` and `\include{file}` with the file's source.
+///
+/// Files are looked up relative to `dir`, adding a .tex extension if
+/// needed; absolute paths, paths leaving `dir`, and missing files are
+/// dropped.
+fn expand_inputs(source: &str, dir: &Path, depth: usize) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find('\\') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name_len = after
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+
+        if matches!(name, "input" | "include" | "subfile") {
+            let arg = after[name_len..].trim_start().strip_prefix('{');
+            if let Some((file, tail)) = arg.and_then(|arg| arg.split_once('}')) {
+                if depth < MAX_INPUT_DEPTH {
+                    if let Some(included) = read_input(dir, file.trim()) {
+                        out.push_str(&expand_inputs(&strip_comments(&included), dir, depth + 1));
+                    }
+                }
+                rest = tail;
+                continue;
+            }
+        }
+
+        // Copy the command, or the escaped character, as it is
+        let len = if name_len == 0 {
+            after.chars().next().map_or(0, char::len_utf8)
+        } else {
+            name_len
+        };
+        out.push_str(&rest[start..start + 1 + len]);
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Source of an `\input` file, if it lies within `dir` and can be read.
+fn read_input(dir: &Path, name: &str) -> Option<String> {
+    let relative = Path::new(name);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    let path = dir.join(relative);
+    let path = if path.is_file() {
+        path
+    } else {
+        dir.join(format!("{}.tex", name))
+    };
+    crate::text::read_file(path.to_str()?).ok()
+}
+
+/// Index in SECTIONS of the outermost sectioning command a document uses,
+/// which becomes a level 1 heading.
+fn section_offset(source: &str) -> usize {
+    SECTIONS
+        .iter()
+        .position(|name| uses_command(source, name))
+        .unwrap_or(SECTION)
+}
+
+fn uses_command(source: &str, name: &str) -> bool {
+    let command = format!("\\{}", name);
+    source
+        .match_indices(&command)
+        .any(|(i, _)| !source[i + command.len()..].starts_with(|c: char| c.is_ascii_alphabetic()))
+}
+
+/// The document's `\title`, as text.
+fn title(source: &str) -> Option<String> {
+    let start = source
+        .match_indices("\\title")
+        .map(|(i, _)| i + "\\title".len())
+        .find(|&end| !source[end..].starts_with(|c: char| c.is_ascii_alphabetic()))?;
+    let mut converter = Converter::new(&source[start..], SECTION);
+    converter.skip_optional();
+    let title = converter.group_text();
+    (!title.is_empty()).then_some(title)
+}
+
+/// Index of the delimiter closing the group opened at `open`, or the end
+/// of `source` if it is never closed. Escaped delimiters don't count.
+fn closing(source: &str, open: usize, left: u8, right: u8) -> usize {
+    let bytes = source.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == left => depth += 1,
+            b if b == right => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    source.len()
+}
+
+/// Math source with labels and numbering switches removed and its
+/// whitespace collapsed.
+fn math_text(math: &str) -> String {
+    let mut out = String::with_capacity(math.len());
+    let mut rest = math;
+    while let Some(start) = rest.find("\\label{") {
+        out.push_str(&rest[..start]);
+        let end = closing(rest, start + "\\label".len(), b'{', b'}');
+        rest = &rest[(end + 1).min(rest.len())..];
+    }
+    out.push_str(rest);
+    out.replace("\\nonumber", "")
+        .replace("\\notag", "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renders LaTeX markup as text, with headings and list items as markdown.
+struct Converter<'a> {
+    source: &'a str,
+    pos: usize,
+    /// Index in SECTIONS of the level 1 heading command.
+    section_offset: usize,
+    /// Open environments, innermost last, without any trailing `*`.
+    environments: Vec<&'a str>,
+    out: String,
+}
+
+impl<'a> Converter<'a> {
+    fn new(source: &'a str, section_offset: usize) -> Self {
+        Converter {
+            source,
+            pos: 0,
+            section_offset,
+            environments: Vec::new(),
+            out: String::new(),
+        }
+    }
+
+    fn run(mut self) -> String {
+        while let Some(c) = self.next_char() {
+            match c {
+                '\\' => self.command(),
+                '$' => {
+                    if self.eat("$") {
+                        self.math("$$", true)
+                    } else {
+                        self.math("$", false)
+                    }
+                }
+                '{' | '}' => {}
+                '~' => self.space(),
+                // Outside math, only table rows have column separators
+                '&' => {
+                    self.space();
+                    self.out.push_str("| ");
+                }
+                '\n' if self.blank_line_follows() => self.paragraph(),
+                c if c.is_whitespace() => self.space(),
+                '`' if self.eat("`") => self.out.push('"'),
+                '\'' if self.eat("'") => self.out.push('"'),
+                '`' => self.out.push('\''),
+                '-' if self.eat("--") => self.out.push('—'),
+                '-' if self.eat("-") => self.out.push('–'),
+                c => self.out.push(c),
+            }
+        }
+        finish(&self.out)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.rest().chars().next()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    /// Position and character of the next token, skipping spaces and at
+    /// most one line break (as TeX does before an argument).
+    fn peek_token(&self) -> Option<(usize, char)> {
+        let mut newline = false;
+        for (i, c) in self.rest().char_indices() {
+            match c {
+                '\n' if newline => return None,
+                '\n' => newline = true,
+                c if c.is_whitespace() => {}
+                c => return Some((self.pos + i, c)),
+            }
+        }
+        None
+    }
+
+    fn blank_line_follows(&self) -> bool {
+        let rest = self.rest();
+        rest.trim_start_matches([' ', '\t', '\r']).starts_with('\n')
+    }
+
+    fn space(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    /// Starts a new line.
+    fn line(&mut self) {
+        self.out
+            .truncate(self.out.trim_end_matches([' ', '\t']).len());
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// Starts a new paragraph.
+    fn paragraph(&mut self) {
+        self.out.truncate(self.out.trim_end().len());
+        if !self.out.is_empty() {
+            self.out.push_str("\n\n");
+        }
+    }
+
+    /// Name of the command after a backslash: its letters, or the single
+    /// other character it escapes.
+    fn command_name(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = match rest.find(|c: char| !c.is_ascii_alphabetic()) {
+            Some(0) => rest.chars().next().map_or(0, char::len_utf8),
+            Some(len) => len,
+            None => rest.len(),
+        };
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn command(&mut self) {
+        let name = self.command_name();
+        if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            self.eat("*");
+        }
+        if let Some(&(_, mark)) = ACCENTS.iter().find(|(accent, _)| *accent == name) {
+            let letter = match self.group_raw() {
+                "\\i" => "i",
+                "\\j" => "j",
+                letter => letter,
+            };
+            self.out.push_str(letter);
+            self.out.push(mark);
+            return;
+        }
+        if let Some(&(_, symbol)) = SYMBOLS.iter().find(|(command, _)| *command == name) {
+            self.out.push_str(symbol);
+            return;
+        }
+        if let Some(index) = SECTIONS.iter().position(|section| *section == name) {
+            self.skip_optional();
+            let title = self.group_text();
+            self.heading(index, &title);
+            return;
+        }
+        if DROPPED.contains(&name) {
+            self.skip_arguments();
+            // "as in~\cite{x}." leaves no space before the period
+            if self.rest().starts_with(['.', ',', ';', ':', ')']) {
+                self.out.truncate(self.out.trim_end_matches(' ').len());
+            }
+            return;
+        }
+
+        match name {
+            "\\" | "newline" | "linebreak" => {
+                self.skip_optional();
+                self.line();
+            }
+            "par" => self.paragraph(),
+            "%" | "&" | "$" | "#" | "_" | "{" | "}" => self.out.push_str(name),
+            " " | "\n" | "," | ";" | ":" => self.space(),
+            "(" => self.math("\\)", false),
+            "[" => self.math("\\]", true),
+            "begin" => self.begin_environment(),
+            "end" => self.end_environment(),
+            "caption" => self.caption(),
+            "item" => self.item(),
+            "bibitem" => {
+                self.skip_optional();
+                self.group_raw();
+                self.line();
+                self.out.push_str("- ");
+            }
+            "footnote" => {
+                self.skip_optional();
+                let note = self.group_text();
+                if !note.is_empty() {
+                    self.space();
+                    self.out.push_str(&format!("({})", note));
+                }
+            }
+            "url" => {
+                let url = self.group_raw();
+                self.out.push_str(url);
+            }
+            // The link target, or the color, of the text that follows
+            "href" | "textcolor" | "colorbox" => {
+                self.group_raw();
+            }
+            "verb" => {
+                if let Some(delimiter) = self.next_char() {
+                    let rest = self.rest();
+                    let end = rest.find(delimiter).unwrap_or(rest.len());
+                    self.out.push_str(&rest[..end]);
+                    self.pos += (end + delimiter.len_utf8()).min(rest.len());
+                }
+            }
+            "def" => {
+                // \def\name#1{body}
+                let rest = self.rest();
+                match rest.find('{') {
+                    Some(open) => {
+                        self.pos += closing(rest, open, b'{', b'}') + 1;
+                        self.pos = self.pos.min(self.source.len());
+                    }
+                    None => self.pos = self.source.len(),
+                }
+            }
+            // Formatting commands (\textbf, \emph, ...) keep their text,
+            // and the rest are dropped
+            _ => {}
+        }
+    }
+
+    fn heading(&mut self, index: usize, title: &str) {
+        let level = (index.saturating_sub(self.section_offset) + 1).min(6);
+        self.paragraph();
+        self.out.push_str(&"#".repeat(level));
+        self.out.push(' ');
+        self.out.push_str(title);
+        self.paragraph();
+    }
+
+    /// Math up to `end`, inline or on a line of its own.
+    fn math(&mut self, end: &str, display: bool) {
+        let math = self.until(end);
+        let math = math_text(math);
+        if display {
+            self.line();
+            self.out.push_str(&math);
+            self.line();
+        } else {
+            self.out.push_str(&math);
+        }
+    }
+
+    /// Source up to `end`, skipping past it.
+    fn until(&mut self, end: &str) -> &'a str {
+        let rest = self.rest();
+        match rest.find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                &rest[..i]
+            }
+            None => {
+                self.pos = self.source.len();
+                rest
+            }
+        }
+    }
+
+    fn begin_environment(&mut self) {
+        let name = self.group_raw().trim();
+        let base = name.trim_end_matches('*');
+        let end = format!("\\end{{{}}}", name);
+
+        if MATH_ENVIRONMENTS.contains(&base) {
+            self.math(&end, true);
+            return;
+        }
+        if VERBATIM_ENVIRONMENTS.contains(&base) {
+            self.skip_optional();
+            if base == "minted" {
+                self.group_raw();
+            }
+            let code = self.until(&end).trim_matches('\n');
+            self.line();
+            self.out.push_str(code);
+            self.line();
+            return;
+        }
+        if base == "comment" {
+            self.until(&end);
+            return;
+        }
+
+        match base {
+            "tabular" | "tabularx" | "longtable" => {
+                if name == "tabular*" || base == "tabularx" {
+                    self.group_raw();
+                }
+                self.skip_optional();
+                self.group_raw();
+            }
+            "wrapfigure" | "wraptable" => {
+                self.skip_optional();
+                self.group_raw();
+                self.group_raw();
+            }
+            "minipage" | "subfigure" => {
+                self.skip_optional();
+                self.group_raw();
+            }
+            "abstract" => self.heading(SECTION, "Abstract"),
+            "thebibliography" => {
+                self.group_raw();
+                self.heading(SECTION, "References");
+            }
+            _ => self.skip_optional(),
+        }
+        self.line();
+        self.environments.push(base);
+    }
+
+    fn end_environment(&mut self) {
+        let name = self.group_raw().trim().trim_end_matches('*');
+        if let Some(i) = self.environments.iter().rposition(|env| *env == name) {
+            self.environments.truncate(i);
+        }
+        self.line();
+    }
+
+    fn caption(&mut self) {
+        self.skip_optional();
+        let caption = self.group_text();
+        let kind = self
+            .environments
+            .iter()
+            .rev()
+            .find_map(|env| match *env {
+                "figure" | "wrapfigure" | "subfigure" => Some("Figure"),
+                "table" | "wraptable" => Some("Table"),
+                _ => None,
+            })
+            .unwrap_or("Caption");
+        self.line();
+        self.out.push_str(&format!("{}: {}", kind, caption));
+        self.line();
+    }
+
+    fn item(&mut self) {
+        let label = self.optional_text();
+        self.line();
+        self.out.push_str("- ");
+        if let Some(label) = label.filter(|label| !label.is_empty()) {
+            self.out.push_str(&label);
+            self.out.push_str(": ");
+        }
+    }
+
+    /// The next argument's source: a braced group's contents, a command,
+    /// or a single character.
+    fn group_raw(&mut self) -> &'a str {
+        let Some((start, c)) = self.peek_token() else {
+            return "";
+        };
+        match c {
+            '}' => "",
+            '{' => {
+                let end = closing(self.source, start, b'{', b'}');
+                self.pos = (end + 1).min(self.source.len());
+                &self.source[start + 1..end]
+            }
+            '\\' => {
+                self.pos = start + 1;
+                self.command_name();
+                &self.source[start..self.pos]
+            }
+            c => {
+                self.pos = start + c.len_utf8();
+                &self.source[start..self.pos]
+            }
+        }
+    }
+
+    /// The next argument, rendered as a line of text.
+    fn group_text(&mut self) -> String {
+        let raw = self.group_raw();
+        inline_text(raw, self.section_offset)
+    }
+
+    /// A following optional `[argument]`, rendered as a line of text.
+    fn optional_text(&mut self) -> Option<String> {
+        let (start, '[') = self.peek_token()? else {
+            return None;
+        };
+        let end = closing(self.source, start, b'[', b']');
+        self.pos = (end + 1).min(self.source.len());
+        Some(inline_text(
+            &self.source[start + 1..end],
+            self.section_offset,
+        ))
+    }
+
+    fn skip_optional(&mut self) {
+        self.optional_text();
+    }
+
+    /// Skips any arguments that follow, optional or not.
+    fn skip_arguments(&mut self) {
+        while let Some((_, c)) = self.peek_token() {
+            match c {
+                '[' => self.skip_optional(),
+                '{' => {
+                    self.group_raw();
+                }
+                _ => break,
+            }
+        }
+    }
+}
+
+/// A fragment of markup rendered on one line, e.g. a heading's title.
+fn inline_text(source: &str, section_offset: usize) -> String {
+    Converter::new(source, section_offset)
+        .run()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Trims each line's trailing whitespace, keeps at most one blank line
+/// between blocks, and composes accented letters.
+fn finish(out: &str) -> String {
+    let mut text = String::with_capacity(out.len());
+    let mut blank = false;
+    for line in out.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank = true;
+            continue;
+        }
+        if !text.is_empty() {
+            text.push_str(if blank { "\n\n" } else { "\n" });
+        }
+        text.push_str(line);
+        blank = false;
+    }
+    text.nfc().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections_math_and_captions() {
+        let source = r"\documentclass{article}
+\usepackage{amsmath} % for align
+\title{Attention Is \emph{All} You Need}
+\begin{document}
+\maketitle
+\begin{abstract}
+We propose the Transformer~\cite{vaswani}.
+\end{abstract}
+
+\section{Introduction}\label{sec:intro}
+Recurrent models compute $h_t = f(h_{t-1}, x_t)$ step by step,
+% a comment line
+which precludes parallelization.\footnote{See \S2.}
+
+\subsection*{Scaled Dot-Product}
+\begin{equation}
+  \mathrm{Attention}(Q, K, V) = \mathrm{softmax}(QK^T) V \label{eq:attn}
+\end{equation}
+\begin{figure}[t]
+  \centering
+  \includegraphics[width=\linewidth]{arch.pdf}
+  \caption{The Transformer --- model architecture.}
+\end{figure}
+\begin{itemize}
+  \item Caf\'e 50\% faster
+  \item[Cost] ``low''
+\end{itemize}
+\end{document}";
+        assert_eq!(
+            latex_to_text(&strip_comments(source)),
+            "Attention Is All You Need\n\n\
+             # Abstract\n\n\
+             We propose the Transformer.\n\n\
+             # Introduction\n\n\
+             Recurrent models compute h_t = f(h_{t-1}, x_t) step by step, which \
+             precludes parallelization. (See §2.)\n\n\
+             ## Scaled Dot-Product\n\n\
+             \\mathrm{Attention}(Q, K, V) = \\mathrm{softmax}(QK^T) V\n\
+             Figure: The Transformer — model architecture.\n\
+             - Café 50% faster\n\
+             - Cost: \"low\""
+        );
+    }
+
+    #[test]
+    fn test_tables_and_verbatim() {
+        let source = r"\begin{table}
+\caption{Results}
+\begin{tabular}{lr}
+\hline
+Model & BLEU \\
+Ours & 28.4 \\
+\end{tabular}
+\end{table}
+\begin{verbatim}
+  x = 1 % 2
+\end{verbatim}";
+        assert_eq!(
+            latex_to_text(source),
+            "Table: Results\nModel | BLEU\nOurs | 28.4\n  x = 1 % 2"
+        );
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(
+            strip_comments("50\\% off % sale\n% note\nend%\nline\n"),
+            "50\\% off endline\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_inputs() {
+        let dir = std::env::temp_dir().join("rusty_rag_latex_inputs");
+        std::fs::create_dir_all(dir.join("sections")).unwrap();
+        std::fs::write(dir.join("sections/intro.tex"), "\\section{Intro}\nHello.\n").unwrap();
+        let main = dir.join("main.tex");
+        std::fs::write(
+            &main,
+            "\\begin{document}\n\\input{sections/intro}\n\\input{../secret}\n\\input{/etc/passwd}\n\\end{document}\n",
+        )
+        .unwrap();
+        let text = extract_text(main.to_str().unwrap()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(text, "# Intro\n\nHello.");
+    }
+}
@@ -0,0 +1,312 @@
+//! Lookup-table English lemmatization for BM25.
+//!
+//! Where a stemmer cuts suffixes off ("studies" → "studi"), a lemmatizer
+//! maps a word to its dictionary form, which stemming can't reach for
+//! irregular words: "better" → "good", "ran" → "run", "mice" → "mouse". So a
+//! question in plain words ("which children ran better?") matches formal
+//! text ("the best-performing child runs").
+//!
+//! Irregular forms are looked up in a bundled table; regular plurals and
+//! third-person verbs lose their "-s" or "-es" by rule. Other regular
+//! inflections ("-ed", "-ing") are left to the stemmer, which lemmas can
+//! still go through.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Irregular forms, by lemma. A lemma listed with no forms is a word ending
+/// in "s" that isn't a plural ("news", "lens"), kept as it is.
+const IRREGULAR: &[(&str, &[&str])] = &[
+    // Verbs
+    ("be", &["am", "is", "are", "was", "were", "been", "being"]),
+    ("have", &["has", "had", "having"]),
+    ("do", &["does", "did", "done", "doing"]),
+    ("go", &["goes", "went", "gone", "going"]),
+    ("say", &["said"]),
+    ("make", &["made", "making"]),
+    ("take", &["took", "taken", "taking"]),
+    ("get", &["got", "gotten", "getting"]),
+    ("see", &["saw", "seen", "seeing"]),
+    ("come", &["came", "coming"]),
+    ("know", &["knew", "known"]),
+    ("think", &["thought"]),
+    ("give", &["gave", "given", "giving"]),
+    ("find", &["found"]),
+    ("tell", &["told"]),
+    ("become", &["became", "becoming"]),
+    ("leave", &["left", "leaving"]),
+    ("feel", &["felt"]),
+    ("bring", &["brought"]),
+    ("begin", &["began", "begun", "beginning"]),
+    ("keep", &["kept"]),
+    ("hold", &["held"]),
+    ("write", &["wrote", "written", "writing"]),
+    ("rewrite", &["rewrote", "rewritten", "rewriting"]),
+    ("stand", &["stood"]),
+    ("understand", &["understood"]),
+    ("hear", &["heard"]),
+    ("mean", &["meant"]),
+    ("meet", &["met"]),
+    ("run", &["ran", "running"]),
+    ("pay", &["paid"]),
+    ("sit", &["sat", "sitting"]),
+    ("speak", &["spoke", "spoken"]),
+    ("lead", &["led"]),
+    ("grow", &["grew", "grown"]),
+    ("lose", &["lost", "losing"]),
+    ("fall", &["fell", "fallen"]),
+    ("send", &["sent"]),
+    ("build", &["built"]),
+    ("draw", &["drew", "drawn"]),
+    ("withdraw", &["withdrew", "withdrawn"]),
+    ("break", &["broke", "broken"]),
+    ("spend", &["spent"]),
+    ("lend", &["lent"]),
+    ("rise", &["rose", "risen", "rising"]),
+    ("arise", &["arose", "arisen", "arising"]),
+    ("drive", &["drove", "driven", "driving"]),
+    ("ride", &["rode", "ridden", "riding"]),
+    ("override", &["overrode", "overridden", "overriding"]),
+    ("buy", &["bought"]),
+    ("wear", &["wore", "worn"]),
+    ("tear", &["tore", "torn"]),
+    ("bear", &["bore", "borne"]),
+    ("choose", &["chose", "chosen", "choosing"]),
+    ("seek", &["sought"]),
+    ("throw", &["threw", "thrown"]),
+    ("blow", &["blew", "blown"]),
+    ("fly", &["flew", "flown"]),
+    ("catch", &["caught"]),
+    ("teach", &["taught"]),
+    ("fight", &["fought"]),
+    ("deal", &["dealt"]),
+    ("win", &["won", "winning"]),
+    ("sell", &["sold"]),
+    ("eat", &["ate", "eaten", "eating"]),
+    ("forget", &["forgot", "forgotten", "forgetting"]),
+    ("forgive", &["forgave", "forgiven"]),
+    ("forbid", &["forbade", "forbidden"]),
+    ("hide", &["hid", "hidden", "hiding"]),
+    ("shake", &["shook", "shaken", "shaking"]),
+    ("mistake", &["mistook", "mistaken"]),
+    ("undertake", &["undertook", "undertaken"]),
+    ("freeze", &["froze", "frozen"]),
+    ("steal", &["stole", "stolen"]),
+    ("wake", &["woke", "woken", "waking"]),
+    ("weave", &["wove", "woven"]),
+    ("sing", &["sang", "sung"]),
+    ("swim", &["swam", "swum", "swimming"]),
+    ("drink", &["drank", "drunk"]),
+    ("ring", &["rang", "rung"]),
+    ("sink", &["sank", "sunk"]),
+    ("shine", &["shone"]),
+    ("shoot", &["shot"]),
+    ("sleep", &["slept"]),
+    ("sweep", &["swept"]),
+    ("dig", &["dug", "digging"]),
+    ("hang", &["hung"]),
+    ("stick", &["stuck"]),
+    ("strike", &["struck"]),
+    ("swing", &["swung"]),
+    ("spin", &["spun", "spinning"]),
+    ("feed", &["fed"]),
+    ("flee", &["fled"]),
+    ("slide", &["slid", "sliding"]),
+    ("bend", &["bent"]),
+    ("set", &["setting"]),
+    ("let", &["letting"]),
+    ("put", &["putting"]),
+    ("cut", &["cutting"]),
+    ("shut", &["shutting"]),
+    ("split", &["splitting"]),
+    ("stop", &["stopped", "stopping"]),
+    // Adjectives and adverbs
+    ("good", &["better", "best"]),
+    ("bad", &["worse", "worst"]),
+    ("far", &["further", "furthest", "farther", "farthest"]),
+    ("little", &["less", "least"]),
+    ("much", &["more", "most"]),
+    // Nouns
+    ("man", &["men"]),
+    ("woman", &["women"]),
+    ("child", &["children"]),
+    ("person", &["people"]),
+    ("foot", &["feet"]),
+    ("tooth", &["teeth"]),
+    ("goose", &["geese"]),
+    ("mouse", &["mice"]),
+    ("ox", &["oxen"]),
+    ("criterion", &["criteria"]),
+    ("phenomenon", &["phenomena"]),
+    ("analysis", &["analyses"]),
+    ("crisis", &["crises"]),
+    ("thesis", &["theses"]),
+    ("hypothesis", &["hypotheses"]),
+    ("diagnosis", &["diagnoses"]),
+    ("synthesis", &["syntheses"]),
+    ("parenthesis", &["parentheses"]),
+    ("index", &["indices"]),
+    ("matrix", &["matrices"]),
+    ("vertex", &["vertices"]),
+    ("appendix", &["appendices"]),
+    ("radius", &["radii"]),
+    ("nucleus", &["nuclei"]),
+    ("stimulus", &["stimuli"]),
+    ("fungus", &["fungi"]),
+    ("curriculum", &["curricula"]),
+    ("bacterium", &["bacteria"]),
+    ("wolf", &["wolves"]),
+    ("half", &["halves"]),
+    ("knife", &["knives"]),
+    ("wife", &["wives"]),
+    ("life", &["lives"]),
+    ("shelf", &["shelves"]),
+    ("thief", &["thieves"]),
+    ("leaf", &["leaves"]),
+    ("loaf", &["loaves"]),
+    ("calf", &["calves"]),
+    ("hero", &["heroes"]),
+    ("potato", &["potatoes"]),
+    ("tomato", &["tomatoes"]),
+    ("echo", &["echoes"]),
+    ("quiz", &["quizzes"]),
+    ("movie", &["movies"]),
+    ("cookie", &["cookies"]),
+    ("calorie", &["calories"]),
+    ("cache", &["caches"]),
+    ("niche", &["niches"]),
+    ("ache", &["aches"]),
+    ("headache", &["headaches"]),
+    // Not plurals
+    ("news", &[]),
+    ("series", &[]),
+    ("species", &[]),
+    ("means", &[]),
+    ("lens", &[]),
+    ("physics", &[]),
+    ("mathematics", &[]),
+    ("economics", &[]),
+    ("politics", &[]),
+    ("ethics", &[]),
+    ("always", &[]),
+    ("perhaps", &[]),
+    ("thus", &[]),
+    ("this", &[]),
+    ("its", &[]),
+    ("his", &[]),
+    ("hers", &[]),
+    ("ours", &[]),
+    ("yours", &[]),
+    ("theirs", &[]),
+    ("yes", &[]),
+    ("whereas", &[]),
+    ("towards", &[]),
+    ("afterwards", &[]),
+    ("sometimes", &[]),
+    ("besides", &[]),
+    ("bias", &[]),
+    ("alias", &[]),
+    ("atlas", &[]),
+    ("canvas", &[]),
+    ("chaos", &[]),
+];
+
+/// Words this short are never stripped of an "s".
+const MIN_PLURAL_CHARS: usize = 4;
+
+/// Irregular form (or lemma) → lemma.
+fn lemmas() -> &'static HashMap<&'static str, &'static str> {
+    static LEMMAS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    LEMMAS.get_or_init(|| {
+        let mut lemmas = HashMap::new();
+        for &(lemma, forms) in IRREGULAR {
+            lemmas.insert(lemma, lemma);
+            for &form in forms {
+                lemmas.insert(form, lemma);
+            }
+        }
+        lemmas
+    })
+}
+
+/// The lemma of a lowercase English word: its irregular form's lemma, or
+/// the word without a possessive ("'s") or plural ending.
+pub fn lemmatize(word: &str) -> String {
+    let word = word
+        .strip_suffix("'s")
+        .or_else(|| word.strip_suffix('\''))
+        .unwrap_or(word);
+    if let Some(lemma) = lemmas().get(word) {
+        return lemma.to_string();
+    }
+    if word.chars().count() < MIN_PLURAL_CHARS
+        || !word.ends_with('s')
+        || ["ss", "us", "is"].iter().any(|end| word.ends_with(end))
+    {
+        return word.to_string();
+    }
+
+    let stem = &word[..word.len() - 1];
+    if let Some(base) = word.strip_suffix("ies") {
+        // "studies" → "study", but "ties" → "tie"
+        if base.chars().count() > 1 {
+            return format!("{}y", base);
+        }
+    } else if let Some(base) = word.strip_suffix("es") {
+        // "classes" → "class", "boxes" → "box", "churches" → "church"
+        if ["ss", "x", "zz", "ch", "sh"]
+            .iter()
+            .any(|end| base.ends_with(end))
+        {
+            return base.to_string();
+        }
+    }
+    stem.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_irregular_forms() {
+        let lemmas: Vec<String> = [
+            "better", "best", "ran", "mice", "was", "children", "criteria",
+        ]
+        .iter()
+        .map(|w| lemmatize(w))
+        .collect();
+        assert_eq!(
+            lemmas,
+            ["good", "good", "run", "mouse", "be", "child", "criterion"]
+        );
+        assert_eq!(lemmatize("good"), "good");
+    }
+
+    #[test]
+    fn test_regular_plurals() {
+        for (word, lemma) in [
+            ("documents", "document"),
+            ("studies", "study"),
+            ("ties", "tie"),
+            ("classes", "class"),
+            ("boxes", "box"),
+            ("churches", "church"),
+            ("databases", "database"),
+            ("runs", "run"),
+            ("company's", "company"),
+            ("companies'", "company"),
+        ] {
+            assert_eq!(lemmatize(word), lemma, "{word}");
+        }
+    }
+
+    #[test]
+    fn test_words_ending_in_s_kept() {
+        for word in [
+            "class", "status", "analysis", "news", "series", "gas", "this",
+        ] {
+            assert_eq!(lemmatize(word), word);
+        }
+    }
+}
@@ -1,79 +1,1395 @@
-use pyo3::prelude::*;
-
+mod analyzer;
+mod archive;
 mod bm25;
 mod chunker;
+mod code;
+mod crypto;
+mod dedup;
+mod docx;
+mod email;
+mod epub;
+mod extractor;
+mod fusion;
+mod html;
+mod keywords;
+mod latex;
+mod lemmatizer;
+mod markdown;
+#[cfg(feature = "ocr")]
+mod ocr;
 mod pdf;
+mod pptx;
+mod quality;
+mod query;
+mod spell;
+mod spreadsheet;
+mod stats;
+mod stopwords;
+mod subtitles;
+mod synonyms;
+mod text;
+mod tfidf;
 mod tokenizer;
+mod xml;
 
-/// Extract all text from a PDF file using memory-mapped I/O.
-///
-/// Returns the full text as a single string with normalized whitespace.
-/// Uses mmap under the hood so it can handle files larger than available RAM.
-#[pyfunction]
-fn extract_pdf_text(path: &str) -> PyResult<String> {
-    pdf::extract_text(path)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
-}
+// pyo3 0.22 emits each binding's wrapper beside it, converting a `PyResult`
+// error with an `.into()` that newer clippy flags as useless. The bindings
+// live in this module so the lint is allowed for them alone.
+#[allow(clippy::useless_conversion)]
+mod bindings {
+    use pyo3::prelude::*;
+    use pyo3::types::{PyBytes, PyDict, PyString};
 
-/// Split text into overlapping chunks using a parallel sliding window algorithm.
-///
-/// Uses Rayon's work-stealing scheduler to extract chunks across all CPU cores.
-/// Returns chunks in the same order as sequential processing.
-#[pyfunction]
-#[pyo3(signature = (text, chunk_size=1000, overlap=100))]
-fn chunk_text_parallel(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
-    chunker::chunk_text_parallel(text, chunk_size, overlap)
-}
+    use super::*;
 
-/// Split text into overlapping chunks using a sequential sliding window algorithm.
-///
-/// Single-threaded version, useful for small texts or debugging.
-#[pyfunction]
-#[pyo3(signature = (text, chunk_size=1000, overlap=100))]
-fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
-    chunker::chunk_text(text, chunk_size, overlap)
-}
+    /// Extract all text from a PDF file using memory-mapped I/O.
+    ///
+    /// Returns the full text as a single string with normalized whitespace;
+    /// tables are rendered inline as markdown. Uses mmap under the hood so it
+    /// can handle files larger than available RAM.
+    /// Words hyphenated across line breaks are rejoined. Pass `password` to
+    /// open encrypted PDFs. Running headers, footers, and page numbers are
+    /// stripped unless `strip_furniture` is False.
+    ///
+    /// Paragraphs are separated by a blank line unless `preserve_paragraphs` is
+    /// False; `join_lines` joins the lines within each paragraph, and `nfc`
+    /// composes the text to Unicode NFC.
+    #[pyfunction]
+    #[pyo3(signature = (
+        path,
+        password=None,
+        strip_furniture=true,
+        preserve_paragraphs=true,
+        join_lines=false,
+        nfc=true,
+    ))]
+    fn extract_pdf_text(
+        py: Python<'_>,
+        path: &str,
+        password: Option<&str>,
+        strip_furniture: bool,
+        preserve_paragraphs: bool,
+        join_lines: bool,
+        nfc: bool,
+    ) -> PyResult<String> {
+        let cleanup = pdf::Cleanup {
+            strip_furniture,
+            preserve_paragraphs,
+            join_lines,
+            nfc,
+            ..Default::default()
+        };
+        py.allow_threads(|| pdf::extract_text(path, password, cleanup))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
 
-/// Token-aware text chunking with overlap.
-///
-/// Splits text into chunks where each chunk contains at most `max_tokens` words.
-/// Preserves original formatting. Aligns with how LLMs tokenize text.
-#[pyfunction]
-#[pyo3(signature = (text, max_tokens=256, overlap_tokens=32))]
-fn chunk_by_tokens(text: &str, max_tokens: usize, overlap_tokens: usize) -> Vec<String> {
-    chunker::chunk_by_tokens(text, max_tokens, overlap_tokens)
-}
+    /// Extract text from a PDF page by page.
+    ///
+    /// Returns (page_number, text) pairs with 1-based page numbers, skipping
+    /// pages without text, so chunks can cite the page they came from.
+    /// Pass `password` to open encrypted PDFs; `strip_furniture` and the
+    /// normalization options as for `extract_pdf_text`.
+    #[pyfunction]
+    #[pyo3(signature = (
+        path,
+        password=None,
+        strip_furniture=true,
+        preserve_paragraphs=true,
+        join_lines=false,
+        nfc=true,
+    ))]
+    fn extract_pdf_pages(
+        py: Python<'_>,
+        path: &str,
+        password: Option<&str>,
+        strip_furniture: bool,
+        preserve_paragraphs: bool,
+        join_lines: bool,
+        nfc: bool,
+    ) -> PyResult<Vec<(u32, String)>> {
+        let cleanup = pdf::Cleanup {
+            strip_furniture,
+            preserve_paragraphs,
+            join_lines,
+            nfc,
+            ..Default::default()
+        };
+        py.allow_threads(|| pdf::extract_pages(path, password, cleanup))
+            .map(|pages| {
+                pages
+                    .into_iter()
+                    .map(|page| (page.page_number, page.text))
+                    .collect()
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
 
-/// Tokenize text into lowercase word tokens.
-///
-/// Splits on non-alphanumeric characters (preserving apostrophes).
-#[pyfunction]
-fn tokenize(text: &str) -> Vec<String> {
-    tokenizer::tokenize(text)
-}
+    /// Extract text from a PDF page by page, split into sections by its outline.
+    ///
+    /// Returns (page_number, section_path, text) triples, where section_path is
+    /// the title path of the bookmark the text falls under (e.g.
+    /// ["2 Methods", "2.1 Data"]) and empty before the first bookmark or when
+    /// the PDF has no outline. Pages holding a section start are split there.
+    /// `password`, `strip_furniture`, and the normalization options as for
+    /// `extract_pdf_text`.
+    #[pyfunction]
+    #[pyo3(signature = (
+        path,
+        password=None,
+        strip_furniture=true,
+        preserve_paragraphs=true,
+        join_lines=false,
+        nfc=true,
+    ))]
+    fn extract_pdf_sections(
+        py: Python<'_>,
+        path: &str,
+        password: Option<&str>,
+        strip_furniture: bool,
+        preserve_paragraphs: bool,
+        join_lines: bool,
+        nfc: bool,
+    ) -> PyResult<Vec<(u32, Vec<String>, String)>> {
+        let cleanup = pdf::Cleanup {
+            strip_furniture,
+            preserve_paragraphs,
+            join_lines,
+            nfc,
+            ..Default::default()
+        };
+        py.allow_threads(|| pdf::extract_sections(path, password, cleanup))
+            .map(|sections| {
+                sections
+                    .into_iter()
+                    .map(|section| (section.page_number, section.path, section.text))
+                    .collect()
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
 
-/// Count the number of word tokens in text.
-#[pyfunction]
-fn token_count(text: &str) -> usize {
-    tokenizer::token_count(text)
-}
+    /// Read a PDF's document information (title, author, dates, ...).
+    ///
+    /// Returns a dict with keys title, author, subject, keywords, creator,
+    /// producer, creation_date and modification_date (ISO 8601), each None if
+    /// absent, plus page_count.
+    #[pyfunction]
+    #[pyo3(signature = (path, password=None))]
+    fn extract_pdf_metadata<'py>(
+        py: Python<'py>,
+        path: &str,
+        password: Option<&str>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let meta = py
+            .allow_threads(|| pdf::extract_metadata(path, password))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))?;
+        let dict = PyDict::new_bound(py);
+        dict.set_item("title", meta.title)?;
+        dict.set_item("author", meta.author)?;
+        dict.set_item("subject", meta.subject)?;
+        dict.set_item("keywords", meta.keywords)?;
+        dict.set_item("creator", meta.creator)?;
+        dict.set_item("producer", meta.producer)?;
+        dict.set_item("creation_date", meta.creation_date)?;
+        dict.set_item("modification_date", meta.modification_date)?;
+        dict.set_item("page_count", meta.page_count)?;
+        Ok(dict)
+    }
+
+    /// Read the filled-in fields of a PDF form (AcroForm).
+    ///
+    /// Returns (page_number, label, value) triples in form order, where
+    /// page_number is None if the field isn't shown on a page and label is the
+    /// field's tooltip or full name. Checkboxes read "Yes" or "No"; empty
+    /// fields and buttons are skipped.
+    #[pyfunction]
+    #[pyo3(signature = (path, password=None))]
+    fn extract_pdf_form_fields(
+        py: Python<'_>,
+        path: &str,
+        password: Option<&str>,
+    ) -> PyResult<Vec<(Option<u32>, String, String)>> {
+        py.allow_threads(|| pdf::extract_form_fields(path, password))
+            .map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|field| (field.page_number, field.label, field.value))
+                    .collect()
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Report which pages of a PDF have a text layer.
+    ///
+    /// Returns a dict with page_count and the 1-based page numbers of
+    /// text_pages, image_pages (images but no text, i.e. scans) and
+    /// blank_pages, plus ocr, whether this build can OCR the image-only pages.
+    #[pyfunction]
+    #[pyo3(signature = (path, password=None))]
+    fn pdf_text_coverage<'py>(
+        py: Python<'py>,
+        path: &str,
+        password: Option<&str>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let coverage = py
+            .allow_threads(|| pdf::text_coverage(path, password))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))?;
+        let dict = PyDict::new_bound(py);
+        dict.set_item("page_count", coverage.page_count)?;
+        dict.set_item("text_pages", coverage.text_pages)?;
+        dict.set_item("image_pages", coverage.image_pages)?;
+        dict.set_item("blank_pages", coverage.blank_pages)?;
+        dict.set_item("ocr", cfg!(feature = "ocr"))?;
+        Ok(dict)
+    }
+
+    /// Extract the images embedded in a PDF, for captioning figures.
+    ///
+    /// Returns (page_number, image_bytes) pairs, each image a JPEG or PNG file,
+    /// skipping images smaller than `min_size` pixels on either side and
+    /// images in encodings that can't be passed on (JPEG 2000, JBIG2, ...).
+    /// An image shown on several pages is returned once, for its first page.
+    #[pyfunction]
+    #[pyo3(signature = (path, password=None, min_size=100))]
+    fn extract_pdf_images<'py>(
+        py: Python<'py>,
+        path: &str,
+        password: Option<&str>,
+        min_size: u32,
+    ) -> PyResult<Vec<(u32, Bound<'py, PyBytes>)>> {
+        let images = py
+            .allow_threads(|| pdf::extract_images(path, password, min_size))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))?;
+        Ok(images
+            .into_iter()
+            .map(|image| (image.page_number, PyBytes::new_bound(py, &image.data)))
+            .collect())
+    }
+
+    /// Extract the text of a Word (.docx) document.
+    ///
+    /// Headings and list items are marked up as markdown and tables are
+    /// rendered as markdown tables.
+    #[pyfunction]
+    fn extract_docx_text(py: Python<'_>, path: &str) -> PyResult<String> {
+        py.allow_threads(|| docx::extract_text(path))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Extract the main content of an HTML page.
+    ///
+    /// Navigation, headers, footers, and scripts are stripped before the text
+    /// is returned; headings, lists, and tables are kept as markdown.
+    #[pyfunction]
+    fn extract_html_text(py: Python<'_>, path: &str) -> PyResult<String> {
+        py.allow_threads(|| html::extract_text(path))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Extract the text of a LaTeX source file, e.g. an arXiv paper's.
+    ///
+    /// Commands are stripped; section titles are kept as markdown headings,
+    /// math as its LaTeX source, and figure and table captions as
+    /// "Figure: ..." / "Table: ..." lines. `\input` files next to it are
+    /// followed.
+    #[pyfunction]
+    fn extract_latex_text(py: Python<'_>, path: &str) -> PyResult<String> {
+        py.allow_threads(|| latex::extract_text(path))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Extract the transcript of a subtitle file (.srt or .vtt).
+    ///
+    /// Cues are merged into paragraphs at pauses, changes of speaker, and
+    /// sentence ends, and returned as (start_seconds, end_seconds, text)
+    /// triples; a WebVTT speaker heads the text as "Name: ...".
+    #[pyfunction]
+    fn extract_subtitles(py: Python<'_>, path: &str) -> PyResult<Vec<(f64, f64, String)>> {
+        py.allow_threads(|| subtitles::extract_paragraphs(path))
+            .map(|paragraphs| {
+                paragraphs
+                    .iter()
+                    .map(|p| {
+                        let seconds = |ms: u64| ms as f64 / 1000.0;
+                        (seconds(p.start_ms), seconds(p.end_ms), p.labeled_text())
+                    })
+                    .collect()
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Read a plain-text or source-code file, detecting its encoding.
+    ///
+    /// Handles UTF-8, UTF-16 (with or without a byte-order mark), and legacy
+    /// Windows-1252/Latin-1 text. Raises RuntimeError for binary files.
+    #[pyfunction]
+    fn read_text_file(py: Python<'_>, path: &str) -> PyResult<String> {
+        py.allow_threads(|| text::read_file(path))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Extract a spreadsheet (.csv, .tsv, or .xlsx) as text records.
+    ///
+    /// Each row becomes a block of "column: value" lines named after the header
+    /// row, separated by blank lines; each worksheet of a workbook starts with
+    /// a "# Sheet name" heading.
+    #[pyfunction]
+    fn extract_spreadsheet_text(py: Python<'_>, path: &str) -> PyResult<String> {
+        py.allow_threads(|| spreadsheet::extract_text(path))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Read an EPUB book: its metadata and the text of each chapter.
+    ///
+    /// Returns a dict with keys title, author, language (each None if absent)
+    /// and chapters, a list of (chapter_title, text) pairs in reading order.
+    /// A chapter's title is its first heading, or None.
+    #[pyfunction]
+    fn extract_epub<'py>(py: Python<'py>, path: &str) -> PyResult<Bound<'py, PyDict>> {
+        let book = py
+            .allow_threads(|| epub::extract(path))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))?;
+        let chapters: Vec<(Option<String>, String)> = book
+            .chapters
+            .into_iter()
+            .map(|chapter| (chapter.title, chapter.text))
+            .collect();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("title", book.title)?;
+        dict.set_item("author", book.author)?;
+        dict.set_item("language", book.language)?;
+        dict.set_item("chapters", chapters)?;
+        Ok(dict)
+    }
+
+    /// Extract the slides of a PowerPoint (.pptx) deck.
+    ///
+    /// Returns (slide_number, title, text) triples in presentation order, where
+    /// title is None for slides without a title placeholder and text includes
+    /// the title, body, tables (as markdown), and speaker notes.
+    #[pyfunction]
+    fn extract_pptx(py: Python<'_>, path: &str) -> PyResult<Vec<(u32, Option<String>, String)>> {
+        py.allow_threads(|| pptx::extract_slides(path))
+            .map(|slides| {
+                slides
+                    .into_iter()
+                    .map(|slide| (slide.number, slide.title, slide.text))
+                    .collect()
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// Read the messages of an email file (.eml, or an .mbox archive).
+    ///
+    /// Returns a list of dicts with keys subject, sender, date (ISO 8601; each
+    /// None if absent) and text, the body without quoted replies or signature.
+    #[pyfunction]
+    fn extract_email<'py>(py: Python<'py>, path: &str) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let messages = py
+            .allow_threads(|| email::extract_messages(path))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))?;
+        messages
+            .into_iter()
+            .map(|message| {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("subject", message.subject)?;
+                dict.set_item("sender", message.sender)?;
+                dict.set_item("date", message.date)?;
+                dict.set_item("text", message.text)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    /// A span of `extract_document`'s output: (page_number, section, text).
+    type SpanTuple = (Option<u32>, Option<String>, String);
+
+    /// Extract any supported document, picking the extractor by MIME type if
+    /// `mime_type` is given and by file extension otherwise.
+    ///
+    /// Returns (page_number, section, text) triples in document order, where
+    /// page_number is set for paginated formats (PDF pages, slides) and section
+    /// is the heading path or title of the enclosing section, each None where
+    /// it doesn't apply.
+    #[pyfunction]
+    #[pyo3(signature = (path, mime_type=None))]
+    fn extract_document(
+        py: Python<'_>,
+        path: &str,
+        mime_type: Option<&str>,
+    ) -> PyResult<Vec<SpanTuple>> {
+        py.allow_threads(|| extractor::builtins().extract(path, mime_type))
+            .map(|spans| {
+                spans
+                    .into_iter()
+                    .map(|span| (span.page_number, span.section, span.text))
+                    .collect()
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:#}", e)))
+    }
+
+    /// List the formats `extract_document` reads.
+    ///
+    /// Returns a dict per format with keys name, extensions (with the leading
+    /// dot, e.g. ".pdf") and mime_types.
+    #[pyfunction]
+    fn document_formats(py: Python<'_>) -> PyResult<Vec<Bound<'_, PyDict>>> {
+        extractor::builtins()
+            .extractors()
+            .map(|extractor| {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("name", extractor.name())?;
+                let extensions: Vec<String> = extractor
+                    .extensions()
+                    .iter()
+                    .map(|ext| format!(".{}", ext))
+                    .collect();
+                dict.set_item("extensions", extensions)?;
+                dict.set_item("mime_types", extractor.mime_types().to_vec())?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    /// Split markdown into sections at its headings.
+    ///
+    /// Returns (heading_path, text) pairs, where heading_path lists the titles
+    /// of the enclosing headings outermost first (empty before the first
+    /// heading), so chunks can be tagged with e.g. "Installation > Linux".
+    #[pyfunction]
+    fn markdown_sections(text: &str) -> Vec<(Vec<String>, String)> {
+        markdown::sections(text)
+            .into_iter()
+            .map(|section| (section.path, section.text))
+            .collect()
+    }
+
+    /// The chunks of `text` at `spans` for Python, after merging those shorter
+    /// than `min_chunk_size` characters into their neighbours: `Chunk`s if
+    /// `offsets`, otherwise strings.
+    fn spans_to_py(
+        py: Python<'_>,
+        text: &str,
+        spans: &[(usize, usize)],
+        min_chunk_size: usize,
+        offsets: bool,
+    ) -> PyObject {
+        let spans = chunker::merge_short_spans(text, spans, min_chunk_size);
+        if offsets {
+            chunker::to_chunks(text, &spans).into_py(py)
+        } else {
+            chunker::span_texts(text, &spans).into_py(py)
+        }
+    }
+
+    /// Split text into overlapping chunks using a parallel sliding window algorithm.
+    ///
+    /// Uses Rayon's work-stealing scheduler to extract chunks across all CPU cores.
+    /// Returns chunks in the same order as sequential processing.
+    ///
+    /// Every chunker takes `min_chunk_size`: chunks shorter than that many
+    /// characters, such as the fragment left at the end of a text, are merged
+    /// into the chunk before them.
+    #[pyfunction]
+    #[pyo3(signature = (text, chunk_size=1000, overlap=100, offsets=false, min_chunk_size=0))]
+    fn chunk_text_parallel(
+        py: Python<'_>,
+        text: &str,
+        chunk_size: usize,
+        overlap: usize,
+        offsets: bool,
+        min_chunk_size: usize,
+    ) -> PyObject {
+        if min_chunk_size > 0 {
+            let spans = chunker::text_spans(text, chunk_size, overlap);
+            spans_to_py(py, text, &spans, min_chunk_size, offsets)
+        } else if offsets {
+            chunker::chunk_text_parallel_offsets(text, chunk_size, overlap).into_py(py)
+        } else {
+            chunker::chunk_text_parallel(text, chunk_size, overlap).into_py(py)
+        }
+    }
+
+    /// Split text into overlapping chunks using a sequential sliding window algorithm.
+    ///
+    /// Single-threaded version, useful for small texts or debugging.
+    #[pyfunction]
+    #[pyo3(signature = (text, chunk_size=1000, overlap=100, offsets=false, min_chunk_size=0))]
+    fn chunk_text(
+        py: Python<'_>,
+        text: &str,
+        chunk_size: usize,
+        overlap: usize,
+        offsets: bool,
+        min_chunk_size: usize,
+    ) -> PyObject {
+        if offsets || min_chunk_size > 0 {
+            let spans = chunker::text_spans(text, chunk_size, overlap);
+            spans_to_py(py, text, &spans, min_chunk_size, offsets)
+        } else {
+            chunker::chunk_text(text, chunk_size, overlap).into_py(py)
+        }
+    }
+
+    /// Token-aware text chunking with overlap.
+    ///
+    /// Splits text into chunks where each chunk contains at most `max_tokens` words.
+    /// Preserves original formatting. Aligns with how LLMs tokenize text.
+    ///
+    /// With `overlap_sentences`, adjacent chunks overlap in whole sentences
+    /// rather than `overlap_tokens` words: each chunk starts that many
+    /// sentences before the one the previous chunk was cut off in.
+    ///
+    /// With a `SubwordTokenizer`, tokens are the model's own rather than words
+    /// (as with `chunk_by_model_tokens`). `word_chars` sets the characters kept
+    /// in words besides letters and digits, as for `tokenize`.
+    #[pyfunction]
+    #[pyo3(signature = (
+        text, max_tokens=256, overlap_tokens=32, overlap_sentences=None, offsets=false,
+        min_chunk_size=0, tokenizer=None, word_chars=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn chunk_by_tokens(
+        py: Python<'_>,
+        text: &str,
+        max_tokens: usize,
+        overlap_tokens: usize,
+        overlap_sentences: Option<usize>,
+        offsets: bool,
+        min_chunk_size: usize,
+        tokenizer: Option<PyRef<'_, chunker::SubwordTokenizer>>,
+        word_chars: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let words = word_options(word_chars);
+        if let Some(tokenizer) = tokenizer {
+            if overlap_sentences.is_some() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "overlap_sentences isn't supported with a tokenizer",
+                ));
+            }
+            let tokenizer = tokenizer.tokenizer();
+            let spans = py
+                .allow_threads(|| {
+                    chunker::model_token_spans(text, tokenizer, max_tokens, overlap_tokens)
+                })
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+            if offsets || min_chunk_size > 0 {
+                return Ok(spans_to_py(py, text, &spans, min_chunk_size, offsets));
+            }
+            return Ok(chunker::span_texts(text, &spans).into_py(py));
+        }
+        Ok(if let Some(overlap_sentences) = overlap_sentences {
+            let spans = chunker::sentence_overlap_token_spans(
+                text,
+                max_tokens,
+                overlap_tokens,
+                overlap_sentences,
+                &words,
+            );
+            if offsets || min_chunk_size > 0 {
+                spans_to_py(py, text, &spans, min_chunk_size, offsets)
+            } else {
+                chunker::chunk_by_tokens_sentence_overlap(
+                    text,
+                    max_tokens,
+                    overlap_tokens,
+                    overlap_sentences,
+                    &words,
+                )
+                .into_py(py)
+            }
+        } else if offsets || min_chunk_size > 0 {
+            let spans = chunker::token_spans(text, max_tokens, overlap_tokens, &words);
+            spans_to_py(py, text, &spans, min_chunk_size, offsets)
+        } else {
+            chunker::chunk_by_tokens(text, max_tokens, overlap_tokens, &words).into_py(py)
+        })
+    }
+
+    /// Token-aware text chunking on all CPU cores.
+    ///
+    /// Returns the same chunks as `chunk_by_tokens`, sliced out of the text
+    /// in parallel with Rayon once the word boundaries are known; faster for
+    /// large documents.
+    #[pyfunction]
+    #[pyo3(signature = (
+        text, max_tokens=256, overlap_tokens=32, offsets=false, min_chunk_size=0, word_chars=None,
+    ))]
+    fn chunk_by_tokens_parallel(
+        py: Python<'_>,
+        text: &str,
+        max_tokens: usize,
+        overlap_tokens: usize,
+        offsets: bool,
+        min_chunk_size: usize,
+        word_chars: Option<&str>,
+    ) -> PyObject {
+        let words = word_options(word_chars);
+        if min_chunk_size > 0 {
+            let spans = chunker::token_spans(text, max_tokens, overlap_tokens, &words);
+            spans_to_py(py, text, &spans, min_chunk_size, offsets)
+        } else if offsets {
+            chunker::chunk_by_tokens_parallel_offsets(text, max_tokens, overlap_tokens, &words)
+                .into_py(py)
+        } else {
+            chunker::chunk_by_tokens_parallel(text, max_tokens, overlap_tokens, &words).into_py(py)
+        }
+    }
+
+    /// The tokenizer options of a `word_chars` argument: the default apostrophe
+    /// if None.
+    fn word_options(word_chars: Option<&str>) -> tokenizer::TokenizerOptions {
+        word_chars
+            .map(tokenizer::TokenizerOptions::new)
+            .unwrap_or_default()
+    }
+
+    /// `word_options`, keeping numbers and dates whole if `normalize_numbers`
+    /// and hyphenated compounds if `keep_compounds`.
+    fn term_word_options(
+        word_chars: Option<&str>,
+        normalize_numbers: bool,
+        keep_compounds: bool,
+    ) -> tokenizer::TokenizerOptions {
+        let mut words = word_options(word_chars);
+        if normalize_numbers {
+            words = words.normalizing_numbers();
+        }
+        if keep_compounds {
+            words = words.keeping_compounds();
+        }
+        words
+    }
+
+    /// The text of a `str`, or of `bytes` holding UTF-8.
+    fn utf8_text<'a>(text: &'a Bound<'_, PyAny>) -> PyResult<&'a str> {
+        match text.downcast::<PyBytes>() {
+            Ok(bytes) => std::str::from_utf8(bytes.as_bytes()).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid UTF-8: {}", e))
+            }),
+            Err(_) => text.downcast::<PyString>()?.to_str(),
+        }
+    }
+
+    /// Chunk byte ranges for Python: (start, end) pairs, or if `packed`, one
+    /// bytes object of native-endian 64-bit start and end values.
+    fn spans_to_py_ranges(py: Python<'_>, spans: &[(usize, usize)], packed: bool) -> PyObject {
+        if packed {
+            let mut buf = Vec::with_capacity(spans.len() * 16);
+            for &(start, end) in spans {
+                buf.extend_from_slice(&(start as u64).to_ne_bytes());
+                buf.extend_from_slice(&(end as u64).to_ne_bytes());
+            }
+            PyBytes::new_bound(py, &buf).into_py(py)
+        } else {
+            spans.to_vec().into_py(py)
+        }
+    }
+
+    /// Byte ranges of `chunk_by_tokens`'s chunks, without copying any text.
+    ///
+    /// `text` is a `str` or UTF-8 `bytes`, and each (start, end) pair indexes
+    /// its UTF-8 bytes, so the chunks of a large document held as bytes can be
+    /// sliced out only when they're needed (e.g. `memoryview(data)[start:end]`
+    /// at embedding time). With `packed`, the ranges come as one bytes object
+    /// of native-endian 64-bit start and end values rather than a tuple per
+    /// chunk; `memoryview(spans).cast("Q")` reads them as integers.
+    #[pyfunction]
+    #[pyo3(signature = (text, max_tokens=256, overlap_tokens=32, packed=false, word_chars=None))]
+    fn token_spans(
+        py: Python<'_>,
+        text: &Bound<'_, PyAny>,
+        max_tokens: usize,
+        overlap_tokens: usize,
+        packed: bool,
+        word_chars: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let text = utf8_text(text)?;
+        let words = word_options(word_chars);
+        let spans =
+            py.allow_threads(|| chunker::token_spans(text, max_tokens, overlap_tokens, &words));
+        Ok(spans_to_py_ranges(py, &spans, packed))
+    }
+
+    /// Byte ranges of `chunk_text`'s chunks, without copying any text.
+    ///
+    /// Takes a `str` or UTF-8 `bytes` and returns ranges as `token_spans`
+    /// does, `packed` or not.
+    #[pyfunction]
+    #[pyo3(signature = (text, chunk_size=1000, overlap=100, packed=false))]
+    fn text_spans(
+        py: Python<'_>,
+        text: &Bound<'_, PyAny>,
+        chunk_size: usize,
+        overlap: usize,
+        packed: bool,
+    ) -> PyResult<PyObject> {
+        let text = utf8_text(text)?;
+        let spans = py.allow_threads(|| chunker::text_spans(text, chunk_size, overlap));
+        Ok(spans_to_py_ranges(py, &spans, packed))
+    }
+
+    /// Token-aware text chunking by a model's own tokens.
+    ///
+    /// Like `chunk_by_tokens`, but `max_tokens` and `overlap_tokens` count the
+    /// tokens of the HuggingFace `tokenizer.json` at `tokenizer` (loaded once
+    /// per path), so chunks fit the model's context window exactly.
+    #[pyfunction]
+    #[pyo3(signature = (
+        text, tokenizer, max_tokens=256, overlap_tokens=32, offsets=false, min_chunk_size=0,
+    ))]
+    fn chunk_by_model_tokens(
+        py: Python<'_>,
+        text: &str,
+        tokenizer: &str,
+        max_tokens: usize,
+        overlap_tokens: usize,
+        offsets: bool,
+        min_chunk_size: usize,
+    ) -> PyResult<PyObject> {
+        let to_py_err =
+            |e: anyhow::Error| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e));
+        let tokenizer = py
+            .allow_threads(|| chunker::load_tokenizer(tokenizer))
+            .map_err(to_py_err)?;
+        if offsets || min_chunk_size > 0 {
+            let spans = py
+                .allow_threads(|| {
+                    chunker::model_token_spans(text, &tokenizer, max_tokens, overlap_tokens)
+                })
+                .map_err(to_py_err)?;
+            Ok(spans_to_py(py, text, &spans, min_chunk_size, offsets))
+        } else {
+            let chunks = py
+                .allow_threads(|| {
+                    chunker::chunk_by_model_tokens(text, &tokenizer, max_tokens, overlap_tokens)
+                })
+                .map_err(to_py_err)?;
+            Ok(chunks.into_py(py))
+        }
+    }
+
+    /// Sentence-aware text chunking with overlap.
+    ///
+    /// Packs whole sentences into chunks of at most `max_chars` characters,
+    /// repeating the last `overlap_sentences` sentences of each chunk at the
+    /// start of the next. Abbreviations ("Dr.", "e.g.") and initials don't end
+    /// sentences; a sentence too long for a chunk is split between words.
+    #[pyfunction]
+    #[pyo3(signature = (text, max_chars=1000, overlap_sentences=1, offsets=false, min_chunk_size=0))]
+    fn chunk_by_sentences(
+        py: Python<'_>,
+        text: &str,
+        max_chars: usize,
+        overlap_sentences: usize,
+        offsets: bool,
+        min_chunk_size: usize,
+    ) -> PyObject {
+        if offsets || min_chunk_size > 0 {
+            let spans = chunker::sentence_chunk_spans(text, max_chars, overlap_sentences);
+            spans_to_py(py, text, &spans, min_chunk_size, offsets)
+        } else {
+            chunker::chunk_by_sentences(text, max_chars, overlap_sentences).into_py(py)
+        }
+    }
+
+    /// Recursive text chunking with overlap.
+    ///
+    /// Splits text at the first of `separators` and packs the pieces into
+    /// chunks of at most `max_chars` characters, splitting any piece too long
+    /// for a chunk at the next separator, down to single characters. A
+    /// separator is "code", "paragraph", "line", "sentence", "word", "re:"
+    /// and a regular expression to split before (e.g. "re:§" for legal
+    /// sections, "re:(?m)^Q:" for FAQs), or a literal string; the default is
+    /// ["paragraph", "line", "sentence", "word"]. Up to `overlap_chars`
+    /// characters of whole pieces are repeated between chunks.
+    #[pyfunction]
+    #[pyo3(signature = (
+        text, max_chars=1000, overlap_chars=100, separators=None, offsets=false, min_chunk_size=0,
+    ))]
+    fn chunk_recursive(
+        py: Python<'_>,
+        text: &str,
+        max_chars: usize,
+        overlap_chars: usize,
+        separators: Option<Vec<String>>,
+        offsets: bool,
+        min_chunk_size: usize,
+    ) -> PyResult<PyObject> {
+        let separators = match separators {
+            Some(names) => chunker::Separator::parse_all(&names),
+            None => chunker::Separator::parse_all(chunker::DEFAULT_SEPARATORS),
+        }
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+        if offsets || min_chunk_size > 0 {
+            let spans = chunker::recursive_spans(text, max_chars, overlap_chars, &separators);
+            Ok(spans_to_py(py, text, &spans, min_chunk_size, offsets))
+        } else {
+            Ok(chunker::chunk_recursive(text, max_chars, overlap_chars, &separators).into_py(py))
+        }
+    }
+
+    /// Code-aware text chunking.
+    ///
+    /// Packs whole top-level blocks of source code (functions, classes, ...,
+    /// with the comments and decorators above them) into chunks of at most
+    /// `max_chars` characters, found by indentation and bracket nesting rather
+    /// than by parsing any one language. Blocks too long for a chunk are split
+    /// at blank lines, then lines.
+    #[pyfunction]
+    #[pyo3(signature = (text, max_chars=1000, offsets=false, min_chunk_size=0))]
+    fn chunk_code(
+        py: Python<'_>,
+        text: &str,
+        max_chars: usize,
+        offsets: bool,
+        min_chunk_size: usize,
+    ) -> PyObject {
+        if offsets || min_chunk_size > 0 {
+            let spans = chunker::code_spans(text, max_chars);
+            spans_to_py(py, text, &spans, min_chunk_size, offsets)
+        } else {
+            chunker::chunk_code(text, max_chars).into_py(py)
+        }
+    }
+
+    /// Split text into its markdown tables and the text around them.
+    ///
+    /// Returns (is_table, text) pairs in order, so tables can be chunked whole
+    /// (see `chunk_markdown_table`) and the rest with any chunker.
+    #[pyfunction]
+    fn markdown_tables(text: &str) -> Vec<(bool, String)> {
+        markdown::split_tables(text)
+            .into_iter()
+            .map(|(is_table, part)| (is_table, part.to_string()))
+            .collect()
+    }
+
+    /// Chunk a markdown table without splitting its rows.
+    ///
+    /// A table of at most `max_chars` characters is one chunk; a longer one is
+    /// split between rows, each piece starting with the header row if
+    /// `repeat_header`.
+    #[pyfunction]
+    #[pyo3(signature = (table, max_chars=1000, repeat_header=true))]
+    fn chunk_markdown_table(table: &str, max_chars: usize, repeat_header: bool) -> Vec<String> {
+        markdown::split_table(table, max_chars, repeat_header)
+    }
+
+    /// Markdown-structure-aware text chunking.
+    ///
+    /// Splits markdown into chunks of at most `max_chars` characters along its
+    /// headings, splitting long sections as `chunk_recursive` does with
+    /// `overlap_chars` of overlap. Returns (heading_path, text) pairs; each
+    /// text starts with a line holding its section's breadcrumb, such as
+    /// "## Setup > ### Docker". Tables are kept whole in chunks of their own
+    /// where they fit, and otherwise split between rows, each piece repeating
+    /// the header row if `repeat_header`.
+    #[pyfunction]
+    #[pyo3(signature = (
+        text, max_chars=1000, overlap_chars=100, repeat_header=true, min_chunk_size=0,
+    ))]
+    fn chunk_markdown(
+        text: &str,
+        max_chars: usize,
+        overlap_chars: usize,
+        repeat_header: bool,
+        min_chunk_size: usize,
+    ) -> Vec<(Vec<String>, String)> {
+        markdown::chunks(
+            text,
+            max_chars,
+            overlap_chars,
+            repeat_header,
+            min_chunk_size,
+        )
+        .into_iter()
+        .map(|chunk| (chunk.path, chunk.text))
+        .collect()
+    }
+
+    /// Split text into sentences, as the sentence-aware chunkers see them.
+    #[pyfunction]
+    fn split_sentences(text: &str) -> Vec<String> {
+        chunker::sentence_spans(text)
+            .into_iter()
+            .map(|(start, end)| text[start..end].to_string())
+            .collect()
+    }
+
+    /// Semantic text chunking.
+    ///
+    /// Takes one embedding per sentence of `text` (in `split_sentences`
+    /// order) and ends a chunk wherever the cosine similarity between adjacent
+    /// sentences drops below `threshold`. A run of similar sentences longer
+    /// than `max_chars` characters is split between sentences.
+    #[pyfunction]
+    #[pyo3(signature = (
+        text, embeddings, threshold=0.3, max_chars=1000, offsets=false, min_chunk_size=0,
+    ))]
+    fn chunk_semantic(
+        py: Python<'_>,
+        text: &str,
+        embeddings: Vec<Vec<f32>>,
+        threshold: f32,
+        max_chars: usize,
+        offsets: bool,
+        min_chunk_size: usize,
+    ) -> PyResult<PyObject> {
+        let to_py_err =
+            |e: anyhow::Error| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e));
+        if offsets || min_chunk_size > 0 {
+            let spans = py
+                .allow_threads(|| chunker::semantic_spans(text, &embeddings, threshold, max_chars))
+                .map_err(to_py_err)?;
+            Ok(spans_to_py(py, text, &spans, min_chunk_size, offsets))
+        } else {
+            let chunks = py
+                .allow_threads(|| chunker::chunk_semantic(text, &embeddings, threshold, max_chars))
+                .map_err(to_py_err)?;
+            Ok(chunks.into_py(py))
+        }
+    }
+
+    /// Tokenize text into lowercase word tokens.
+    ///
+    /// Splits on characters other than letters, digits, and `word_chars`
+    /// (default an apostrophe, for "don't"; e.g. "'#+_" keeps "c++", "c#", and
+    /// "snake_case" whole). `stopwords` drops common words: a language code
+    /// ("en", "de", ...), "auto" to detect the text's language, or a list of
+    /// words. `stemmer` reduces words to their Snowball stems ("english", "de",
+    /// ..., or "porter"), so "running" and "runs" both become "run", and
+    /// `lemmatize` to their English dictionary forms first, so "better" becomes
+    /// "good". Text is NFKC normalized, and `fold_accents` also strips accents
+    /// ("résumé" → "resume"). With `normalize_numbers`, numbers and dates are
+    /// single tokens rather than split at their punctuation: "3.14" stays
+    /// "3.14", "1,000" becomes "1000", and dates ("March 1, 2024", "01.03.2024",
+    /// "03/01/2024") become ISO "2024-03-01". With `keep_compounds`, a
+    /// hyphenated compound is a token followed by its parts:
+    /// "state-of-the-art", "state", "of", "the", "art".
+    #[pyfunction]
+    #[pyo3(signature = (
+        text, stopwords=None, stemmer=None, fold_accents=false, word_chars=None, lemmatize=false,
+        normalize_numbers=false, keep_compounds=false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn tokenize(
+        text: &str,
+        stopwords: Option<bm25::StopwordList>,
+        stemmer: Option<&str>,
+        fold_accents: bool,
+        word_chars: Option<&str>,
+        lemmatize: bool,
+        normalize_numbers: bool,
+        keep_compounds: bool,
+    ) -> PyResult<Vec<String>> {
+        let options = token_options(
+            stopwords,
+            stemmer,
+            fold_accents,
+            term_word_options(word_chars, normalize_numbers, keep_compounds),
+            lemmatize,
+            || text.to_string(),
+        )?;
+        Ok(options.terms(text))
+    }
+
+    /// Tokenize many texts at once, in parallel.
+    ///
+    /// Returns each text's tokens, as `tokenize` with the same options would,
+    /// using all CPU cores or `n_threads` of them; faster than calling
+    /// `tokenize` per text for large corpora. With `stopwords="auto"`, the
+    /// language is detected from all the texts.
+    #[pyfunction]
+    #[pyo3(signature = (
+        texts, n_threads=None, stopwords=None, stemmer=None, fold_accents=false, word_chars=None,
+        lemmatize=false, normalize_numbers=false, keep_compounds=false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn tokenize_batch(
+        py: Python<'_>,
+        texts: Vec<String>,
+        n_threads: Option<usize>,
+        stopwords: Option<bm25::StopwordList>,
+        stemmer: Option<&str>,
+        fold_accents: bool,
+        word_chars: Option<&str>,
+        lemmatize: bool,
+        normalize_numbers: bool,
+        keep_compounds: bool,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let options = token_options(
+            stopwords,
+            stemmer,
+            fold_accents,
+            term_word_options(word_chars, normalize_numbers, keep_compounds),
+            lemmatize,
+            || bm25::language_sample(&texts),
+        )?;
+        py.allow_threads(|| options.terms_batch(&texts, n_threads))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))
+    }
+
+    /// The `TermOptions` of `tokenize`'s arguments, detecting the language of
+    /// `stopwords="auto"` from `sample()`.
+    fn token_options(
+        stopwords: Option<bm25::StopwordList>,
+        stemmer: Option<&str>,
+        fold_accents: bool,
+        words: tokenizer::TokenizerOptions,
+        lemmatize: bool,
+        sample: impl FnOnce() -> String,
+    ) -> PyResult<bm25::TermOptions> {
+        let to_py_err =
+            |e: anyhow::Error| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e));
+        let options = bm25::TermOptions {
+            analyzer: None,
+            stopwords: match stopwords {
+                Some(list) => list.resolve(sample).map_err(to_py_err)?,
+                None => Default::default(),
+            },
+            stemmer: stemmer
+                .map(analyzer::stemmer_language)
+                .transpose()
+                .map_err(to_py_err)?,
+            lemmatize,
+            fold_accents: false,
+            ngrams: 1,
+            words,
+            tokenizer: None,
+        };
+        Ok(if fold_accents {
+            options.folding_accents()
+        } else {
+            options
+        })
+    }
+
+    /// Word n-grams of text: each run of `n` consecutive tokens (see
+    /// `tokenize`), joined by a space, e.g. "machine learning" for n=2.
+    #[pyfunction]
+    #[pyo3(signature = (text, n=2))]
+    fn tokenize_ngrams(text: &str, n: usize) -> Vec<String> {
+        tokenizer::ngrams(&tokenizer::tokenize(text), n)
+    }
+
+    /// Tokenize text like `tokenize`, with the span of each token's word.
+    ///
+    /// Returns `(token, start, end)` tuples, where `start` and `end` are UTF-8
+    /// byte offsets into `text` (slice `text.encode()[start:end]`), for
+    /// highlighting matched terms in the original text. A number or date kept
+    /// whole by `normalize_numbers` spans all of its text ("March 1, 2024"),
+    /// as does a compound kept whole by `keep_compounds`, whose parts follow
+    /// with their own spans.
+    #[pyfunction]
+    #[pyo3(signature = (text, word_chars=None, normalize_numbers=false, keep_compounds=false))]
+    fn tokenize_with_spans(
+        text: &str,
+        word_chars: Option<&str>,
+        normalize_numbers: bool,
+        keep_compounds: bool,
+    ) -> Vec<(String, usize, usize)> {
+        term_word_options(word_chars, normalize_numbers, keep_compounds).tokenize_with_spans(text)
+    }
+
+    /// Detect the language of a document's text from its stopwords.
+    ///
+    /// Returns an ISO 639-1 code ("en", "de", ...) for the languages with
+    /// bundled stopwords, or None if the text gives too little to go on.
+    #[pyfunction]
+    fn detect_language(text: &str) -> Option<&'static str> {
+        analyzer::detect_language(text).map(analyzer::Language::code)
+    }
+
+    /// Hash a document's pages (or records, ...) to detect duplicates.
+    ///
+    /// Returns (page_hashes, document_hash): the SHA-256 (hex) of each page's
+    /// words and of all the document's words in order. Whitespace is ignored,
+    /// so copies differing only in line wrapping or spacing hash the same.
+    #[pyfunction]
+    fn content_hashes(py: Python<'_>, pages: Vec<String>) -> (Vec<String>, String) {
+        py.allow_threads(|| text::content_hashes(&pages))
+    }
+
+    /// Find chunks that nearly duplicate an earlier one.
+    ///
+    /// Returns, for each chunk, the index of an earlier chunk whose word
+    /// shingles it shares at least `threshold` of (Jaccard similarity, found
+    /// with MinHash), or None for chunks to keep.
+    #[pyfunction]
+    #[pyo3(signature = (chunks, threshold=0.95))]
+    fn near_duplicates(py: Python<'_>, chunks: Vec<String>, threshold: f64) -> Vec<Option<usize>> {
+        py.allow_threads(|| dedup::near_duplicates(&chunks, threshold))
+    }
+
+    /// Find garbage chunks: runs of page numbers, rules of repeated
+    /// characters, OCR noise, and the like.
+    ///
+    /// Returns, for each chunk, why it should be dropped or None to keep it:
+    /// "digits" if more than `max_digit_ratio` of its letters and digits are
+    /// digits, "repeated" if one character makes up more than
+    /// `max_repeat_ratio` of it, "noise" if more than `max_noise_ratio` of its
+    /// words are neither words nor numbers, and "alphabetic" if less than
+    /// `min_alpha_ratio` of it is letters (ignoring whitespace throughout).
+    #[pyfunction]
+    #[pyo3(signature = (
+        chunks,
+        max_digit_ratio=0.8,
+        max_repeat_ratio=0.5,
+        max_noise_ratio=0.5,
+        min_alpha_ratio=0.25,
+    ))]
+    fn garbage_chunks(
+        py: Python<'_>,
+        chunks: Vec<String>,
+        max_digit_ratio: f64,
+        max_repeat_ratio: f64,
+        max_noise_ratio: f64,
+        min_alpha_ratio: f64,
+    ) -> Vec<Option<&'static str>> {
+        let limits = quality::QualityLimits {
+            max_digit_ratio,
+            max_repeat_ratio,
+            max_noise_ratio,
+            min_alpha_ratio,
+        };
+        py.allow_threads(|| quality::garbage_chunks(&chunks, &limits))
+    }
+
+    /// Extract each chunk's keywords.
+    ///
+    /// Returns up to `top_k` keywords per chunk, best first: the words a chunk
+    /// uses most that the other `chunks` (typically those of one document)
+    /// don't, by TF-IDF. Stopwords of `language` (a name or code; guessed from
+    /// the chunks by default, else English) and numbers are skipped, and words
+    /// sharing a stem count as one.
+    #[pyfunction]
+    #[pyo3(signature = (chunks, top_k=5, language=None))]
+    fn chunk_keywords(
+        py: Python<'_>,
+        chunks: Vec<String>,
+        top_k: usize,
+        language: Option<&str>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let language = match language {
+            Some(name) => analyzer::Language::from_name(name).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unsupported language: {}",
+                    name
+                ))
+            })?,
+            None => {
+                analyzer::guess_language(&chunks.join(" ")).unwrap_or(analyzer::Language::English)
+            }
+        };
+        Ok(py.allow_threads(|| keywords::chunk_keywords(&chunks, top_k, language)))
+    }
+
+    /// Merge ranked result lists into one by Reciprocal Rank Fusion.
+    ///
+    /// `rankings` are lists of (id, score), best first, e.g. BM25 and vector
+    /// search results; ids are ints or strings, and scores are ignored. Each
+    /// document scores Σ w / (k + rank) over the rankings listing it (rank
+    /// from 1), with each ranking's weight w from `weights` (default 1, and a
+    /// ranking of weight 0 is left out). Returns (id, fused score) pairs,
+    /// best first, up to `top_k` if given.
+    #[pyfunction]
+    #[pyo3(signature = (rankings, k=60.0, weights=None, top_k=None))]
+    fn rrf_fuse(
+        py: Python<'_>,
+        rankings: Vec<Vec<(fusion::DocId, f64)>>,
+        k: f64,
+        weights: Option<Vec<f64>>,
+        top_k: Option<usize>,
+    ) -> PyResult<Vec<(fusion::DocId, f64)>> {
+        let to_py_err = |msg: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(msg);
+        if k.is_nan() || k < 0.0 {
+            return Err(to_py_err(format!("k must be at least 0, got {}", k)));
+        }
+        let weights = weights.unwrap_or_default();
+        if !weights.is_empty() && weights.len() != rankings.len() {
+            return Err(to_py_err(format!(
+                "Got {} weights for {} rankings",
+                weights.len(),
+                rankings.len()
+            )));
+        }
+        let rankings: Vec<Vec<fusion::DocId>> = rankings
+            .into_iter()
+            .map(|ranking| ranking.into_iter().map(|(id, _)| id).collect())
+            .collect();
+        let mut fused = py.allow_threads(|| fusion::rrf_fuse(&rankings, &weights, k));
+        if let Some(top_k) = top_k {
+            fused.truncate(top_k);
+        }
+        Ok(fused)
+    }
+
+    /// Count the number of word tokens in text (with `word_chars` as for
+    /// `tokenize`), or of a `SubwordTokenizer`'s tokens (the model's own) if
+    /// given.
+    #[pyfunction]
+    #[pyo3(signature = (text, tokenizer=None, word_chars=None))]
+    fn token_count(
+        py: Python<'_>,
+        text: &str,
+        tokenizer: Option<PyRef<'_, chunker::SubwordTokenizer>>,
+        word_chars: Option<&str>,
+    ) -> PyResult<usize> {
+        let Some(tokenizer) = tokenizer else {
+            return Ok(word_options(word_chars).token_count(text));
+        };
+        let tokenizer = tokenizer.tokenizer();
+        py.allow_threads(|| chunker::model_token_count(text, tokenizer, false))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))
+    }
+
+    /// Encrypt bytes with a passphrase (Argon2id key derivation + AES-256-GCM).
+    ///
+    /// Used to protect local caches and exports at rest.
+    #[pyfunction]
+    fn encrypt_bytes<'py>(
+        py: Python<'py>,
+        data: &[u8],
+        passphrase: &[u8],
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        crypto::encrypt(data, passphrase)
+            .map(|out| PyBytes::new_bound(py, &out))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))
+    }
+
+    /// Decrypt bytes produced by `encrypt_bytes`.
+    ///
+    /// Raises ValueError on a wrong passphrase or corrupted data.
+    #[pyfunction]
+    fn decrypt_bytes<'py>(
+        py: Python<'py>,
+        data: &[u8],
+        passphrase: &[u8],
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        crypto::decrypt(data, passphrase)
+            .map(|out| PyBytes::new_bound(py, &out))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))
+    }
+
+    /// Check whether bytes are in the encrypted-file format.
+    #[pyfunction]
+    fn is_encrypted(data: &[u8]) -> bool {
+        crypto::is_encrypted(data)
+    }
 
-/// RustyRAG Core — High-performance Rust backend.
-///
-/// Exposes:
-///   - extract_pdf_text: PDF parsing with memory-mapped I/O
-///   - chunk_text / chunk_text_parallel: Character-based chunking
-///   - chunk_by_tokens: Token-aware chunking
-///   - tokenize / token_count: Word-level tokenization
-///   - BM25Index: Keyword search index
-#[pymodule]
-fn rusty_rag_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(extract_pdf_text, m)?)?;
-    m.add_function(wrap_pyfunction!(chunk_text_parallel, m)?)?;
-    m.add_function(wrap_pyfunction!(chunk_text, m)?)?;
-    m.add_function(wrap_pyfunction!(chunk_by_tokens, m)?)?;
-    m.add_function(wrap_pyfunction!(tokenize, m)?)?;
-    m.add_function(wrap_pyfunction!(token_count, m)?)?;
-    m.add_class::<bm25::BM25Index>()?;
-    Ok(())
+    /// RustyRAG Core — High-performance Rust backend.
+    ///
+    /// Exposes:
+    ///   - extract_pdf_text: PDF parsing with memory-mapped I/O
+    ///   - extract_pdf_pages: Per-page PDF text for page citations
+    ///   - extract_pdf_sections: Per-page PDF text split by outline (bookmark) sections
+    ///   - extract_pdf_metadata: PDF document information (title, author, dates)
+    ///   - extract_pdf_form_fields: Filled-in PDF form fields as (page, label, value)
+    ///   - pdf_text_coverage: Which PDF pages have a text layer vs. are image-only
+    ///   - extract_pdf_images: Embedded PDF images (JPEG/PNG) for figure captions
+    ///   - extract_docx_text: Word document text
+    ///   - extract_html_text: Main content of HTML pages, minus boilerplate
+    ///   - extract_latex_text: LaTeX source text with headings, math, and captions
+    ///   - extract_epub: EPUB metadata and chapter text
+    ///   - extract_pptx: PowerPoint slide titles, text, and speaker notes
+    ///   - extract_email: Email (.eml / .mbox) headers and reply-stripped bodies
+    ///   - extract_subtitles: SRT/WebVTT transcripts as timed paragraphs
+    ///   - extract_document / document_formats: Any supported format, via the
+    ///     extractor registry (by file extension or MIME type)
+    ///   - read_text_file: Plain text / source code with encoding detection
+    ///   - extract_spreadsheet_text: CSV/XLSX rows as "column: value" records
+    ///   - markdown_sections: Markdown split into sections with heading paths
+    ///   - chunk_text / chunk_text_parallel: Character-based chunking
+    ///   - chunk_by_tokens: Token-aware chunking
+    ///   - chunk_by_tokens_parallel: Token-aware chunking with Rayon parallelism
+    ///   - token_spans / text_spans: Chunk byte ranges, without copying the text
+    ///   - chunk_by_model_tokens: Chunking by a HuggingFace tokenizer's tokens
+    ///   - ChunkStream: Token-aware chunking as a lazy iterator, for huge texts
+    ///   - chunk_by_sentences: Sentence-aware chunking
+    ///   - chunk_recursive: Paragraph → line → sentence → word chunking
+    ///   - chunk_code: Source code chunking along function and class boundaries
+    ///   - split_sentences / chunk_semantic: Chunking at topic shifts between
+    ///     sentence embeddings
+    ///   - chunk_markdown: Chunking along markdown headings, with breadcrumbs
+    ///   - markdown_tables / chunk_markdown_table: Keeping tables whole when chunking
+    ///   - Chunk: A chunk with its byte offsets and index, returned by the
+    ///     chunkers above (except chunk_markdown) when called with offsets=True
+    ///   - tokenize / token_count: Word-level tokenization
+    ///   - tokenize_batch: Tokenization of many texts in parallel
+    ///   - tokenize_with_spans: Tokens with their byte spans in the text
+    ///   - tokenize_ngrams: Word bigrams, trigrams, ... of a text
+    ///   - SubwordTokenizer: A HuggingFace tokenizer.json, to count a model's tokens
+    ///   - detect_language: Document language (ISO 639-1) from stopwords
+    ///   - content_hashes: Page and document content hashes for duplicate detection
+    ///   - near_duplicates: MinHash detection of near-duplicate chunks
+    ///   - garbage_chunks: Detection of digit runs, repeated characters, and OCR noise
+    ///   - chunk_keywords: TF-IDF keywords of each chunk
+    ///   - BM25Index: Keyword search index
+    ///   - TfIdfIndex: Classical TF-IDF cosine search over the same terms
+    ///   - rrf_fuse: Reciprocal Rank Fusion of BM25 and vector search rankings
+    ///   - SpellCorrector: Query spelling correction against the corpus vocabulary
+    ///   - TokenStats: Token-frequency statistics over a chunk collection
+    ///   - encrypt_bytes / decrypt_bytes: Passphrase encryption for data at rest
+    ///
+    /// The document extractors release the GIL while they work, so several
+    /// documents can be extracted in parallel from Python threads.
+    #[pymodule]
+    fn rusty_rag_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        m.add_function(wrap_pyfunction!(extract_pdf_text, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_pdf_pages, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_pdf_sections, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_pdf_metadata, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_pdf_form_fields, m)?)?;
+        m.add_function(wrap_pyfunction!(pdf_text_coverage, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_pdf_images, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_docx_text, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_html_text, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_latex_text, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_epub, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_pptx, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_email, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_subtitles, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_document, m)?)?;
+        m.add_function(wrap_pyfunction!(document_formats, m)?)?;
+        m.add_function(wrap_pyfunction!(read_text_file, m)?)?;
+        m.add_function(wrap_pyfunction!(extract_spreadsheet_text, m)?)?;
+        m.add_function(wrap_pyfunction!(markdown_sections, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_text_parallel, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_text, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_by_tokens, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_by_tokens_parallel, m)?)?;
+        m.add_function(wrap_pyfunction!(token_spans, m)?)?;
+        m.add_function(wrap_pyfunction!(text_spans, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_by_model_tokens, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_by_sentences, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_recursive, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_code, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_markdown, m)?)?;
+        m.add_function(wrap_pyfunction!(markdown_tables, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_markdown_table, m)?)?;
+        m.add_function(wrap_pyfunction!(split_sentences, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_semantic, m)?)?;
+        m.add_function(wrap_pyfunction!(tokenize, m)?)?;
+        m.add_function(wrap_pyfunction!(tokenize_batch, m)?)?;
+        m.add_function(wrap_pyfunction!(tokenize_with_spans, m)?)?;
+        m.add_function(wrap_pyfunction!(tokenize_ngrams, m)?)?;
+        m.add_function(wrap_pyfunction!(token_count, m)?)?;
+        m.add_function(wrap_pyfunction!(detect_language, m)?)?;
+        m.add_function(wrap_pyfunction!(content_hashes, m)?)?;
+        m.add_function(wrap_pyfunction!(near_duplicates, m)?)?;
+        m.add_function(wrap_pyfunction!(garbage_chunks, m)?)?;
+        m.add_function(wrap_pyfunction!(chunk_keywords, m)?)?;
+        m.add_function(wrap_pyfunction!(rrf_fuse, m)?)?;
+        m.add_function(wrap_pyfunction!(encrypt_bytes, m)?)?;
+        m.add_function(wrap_pyfunction!(decrypt_bytes, m)?)?;
+        m.add_function(wrap_pyfunction!(is_encrypted, m)?)?;
+        m.add_class::<chunker::Chunk>()?;
+        m.add_class::<chunker::PyChunkStream>()?;
+        m.add_class::<chunker::SubwordTokenizer>()?;
+        m.add_class::<bm25::BM25Index>()?;
+        m.add_class::<tfidf::TfIdfIndex>()?;
+        m.add_class::<spell::SpellCorrector>()?;
+        m.add_class::<stats::TokenStats>()?;
+        Ok(())
+    }
 }
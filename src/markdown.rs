@@ -0,0 +1,480 @@
+use crate::chunker::{self, Separator};
+
+/// Format rows as a markdown table, the first row being the header.
+///
+/// Short rows are padded with empty cells, and pipes and line breaks
+/// inside cells are escaped so each row stays on one line.
+pub fn table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let format_row = |cells: &[String]| {
+        let mut cells: Vec<String> = cells
+            .iter()
+            .map(|c| {
+                c.split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .replace('|', "\\|")
+            })
+            .collect();
+        cells.resize(columns, String::new());
+        format!("| {} |", cells.join(" | "))
+    };
+
+    let Some((header, body)) = rows.split_first() else {
+        return String::new();
+    };
+    let mut out = vec![format_row(header)];
+    out.push(format!("|{}", " --- |".repeat(columns)));
+    out.extend(body.iter().map(|row| format_row(row)));
+    out.join("\n")
+}
+
+/// Byte ranges of the markdown tables in `text`: a header row, a delimiter
+/// row (`| --- | :-: |`), and the rows after it, every line starting with
+/// a pipe.
+pub fn table_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        lines.push((offset, line.trim_end()));
+        offset += line.len();
+    }
+    let is_row = |line: &str| line.trim_start().starts_with('|');
+    let is_delimiter = |line: &str| {
+        is_row(line)
+            && line.contains('-')
+            && line
+                .trim()
+                .chars()
+                .all(|c| matches!(c, '|' | '-' | ':' | ' '))
+    };
+
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if !(is_row(lines[i].1) && lines.get(i + 1).is_some_and(|&(_, l)| is_delimiter(l))) {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 2;
+        while end < lines.len() && is_row(lines[end].1) {
+            end += 1;
+        }
+        let (last_start, last) = lines[end - 1];
+        spans.push((lines[i].0, last_start + last.len()));
+        i = end;
+    }
+    spans
+}
+
+/// `text` split into its markdown tables (see `table_spans`) and the text
+/// between them, in order, each flagged whether it's a table. The text
+/// between tables is trimmed, and left out where blank.
+pub fn split_tables(text: &str) -> Vec<(bool, &str)> {
+    let mut parts = Vec::new();
+    let mut position = 0;
+    for (start, end) in table_spans(text) {
+        parts.push((false, text[position..start].trim()));
+        parts.push((true, &text[start..end]));
+        position = end;
+    }
+    parts.push((false, text[position..].trim()));
+    parts.retain(|(_, part)| !part.is_empty());
+    parts
+}
+
+/// Splits a markdown table into pieces of at most `max_chars` characters,
+/// between rows so no row is ever cut. A table that fits is kept whole.
+/// With `repeat_header`, every piece starts with the table's header and
+/// delimiter rows, so its rows can still be read by column.
+pub fn split_table(table: &str, max_chars: usize, repeat_header: bool) -> Vec<String> {
+    let table = table.trim();
+    let lines: Vec<&str> = table.lines().collect();
+    if table.chars().count() <= max_chars || lines.len() <= 3 {
+        return vec![table.to_string()];
+    }
+
+    let header = lines[..2].join("\n");
+    let mut pieces = Vec::new();
+    let mut piece = header.clone();
+    let mut has_rows = false;
+    for row in &lines[2..] {
+        if has_rows && piece.chars().count() + 1 + row.chars().count() > max_chars {
+            pieces.push(std::mem::take(&mut piece));
+            if repeat_header {
+                piece = header.clone();
+            }
+        }
+        if !piece.is_empty() {
+            piece.push('\n');
+        }
+        piece.push_str(row);
+        has_rows = true;
+    }
+    pieces.push(piece);
+    pieces
+}
+
+/// A stretch of markdown under one heading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// Titles of the enclosing headings, outermost first; empty for text
+    /// before the first heading
+    pub path: Vec<String>,
+    /// Levels (1 for `#`) of the headings in `path`
+    pub levels: Vec<usize>,
+    /// The section's text, starting with its heading line
+    pub text: String,
+    /// The section's text after its heading
+    pub body: String,
+}
+
+impl Section {
+    /// The section's heading path as markdown headings, e.g.
+    /// "## Setup > ### Docker"; empty before the first heading.
+    pub fn breadcrumb(&self) -> String {
+        self.path
+            .iter()
+            .zip(&self.levels)
+            .map(|(title, &level)| format!("{} {}", "#".repeat(level), title))
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+}
+
+/// A chunk of a markdown section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// Titles of the section's headings, outermost first
+    pub path: Vec<String>,
+    /// The chunk's text, headed by the section's breadcrumb
+    pub text: String,
+}
+
+/// Split markdown into sections at ATX (`## Title`) and setext (underlined)
+/// headings, recording each section's heading path.
+///
+/// Headings inside fenced code blocks are ignored, and sections with no
+/// text besides their heading are dropped.
+pub fn sections(markdown: &str) -> Vec<Section> {
+    let mut out = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    // Lines of the current section; the first `heading_lines` are its heading
+    let mut lines: Vec<&str> = Vec::new();
+    let mut heading_lines = 0;
+    let mut fence: Option<String> = None;
+
+    for line in markdown.lines() {
+        if let Some(marker) = &fence {
+            if line.trim_start().starts_with(marker.as_str()) {
+                fence = None;
+            }
+            lines.push(line);
+            continue;
+        }
+        if let Some(marker) = fence_marker(line) {
+            fence = Some(marker);
+            lines.push(line);
+            continue;
+        }
+
+        // A setext underline also claims the paragraph line above it
+        let ((level, title), title_lines) = if let Some(heading) = atx_heading(line) {
+            (heading, 0)
+        } else if let Some(heading) = setext_heading(&lines[heading_lines..], line) {
+            (heading, 1)
+        } else {
+            lines.push(line);
+            continue;
+        };
+
+        let mut heading: Vec<&str> = lines.drain(lines.len() - title_lines..).collect();
+        heading.push(line);
+        push_section(&mut out, &headings, &lines, heading_lines);
+        heading_lines = heading.len();
+        lines = heading;
+        headings.retain(|(l, _)| *l < level);
+        headings.push((level, title));
+    }
+    push_section(&mut out, &headings, &lines, heading_lines);
+    out
+}
+
+fn push_section(
+    out: &mut Vec<Section>,
+    headings: &[(usize, String)],
+    lines: &[&str],
+    heading_lines: usize,
+) {
+    if lines[heading_lines..].iter().any(|line| !line.trim().is_empty()) {
+        out.push(Section {
+            path: headings.iter().map(|(_, title)| title.clone()).collect(),
+            levels: headings.iter().map(|(level, _)| *level).collect(),
+            text: lines.join("\n").trim().to_string(),
+            body: lines[heading_lines..].join("\n").trim().to_string(),
+        });
+    }
+}
+
+/// Split markdown into chunks of at most `max_chars` characters along its
+/// headings, each headed by its section's breadcrumb (see
+/// `Section::breadcrumb`) on a line of its own.
+///
+/// A section too long for one chunk is split as by
+/// `chunker::chunk_recursive`, with `overlap_chars` of overlap, so every
+/// piece of it carries the breadcrumb. The breadcrumb counts towards
+/// `max_chars`, though at least half of each chunk is left for the text.
+///
+/// Tables are chunks of their own, split only between rows if too long
+/// (see `split_table`, which `repeat_header` is passed to). Pieces of the
+/// text around them shorter than `min_chars` characters are merged into
+/// their neighbours (see `chunker::merge_short_spans`).
+pub fn chunks(
+    markdown: &str,
+    max_chars: usize,
+    overlap_chars: usize,
+    repeat_header: bool,
+    min_chars: usize,
+) -> Vec<Chunk> {
+    let separators =
+        Separator::parse_all(chunker::DEFAULT_SEPARATORS).expect("built-in separators");
+
+    let mut out = Vec::new();
+    for section in sections(markdown) {
+        let breadcrumb = section.breadcrumb();
+        let budget = if breadcrumb.is_empty() {
+            max_chars
+        } else {
+            max_chars
+                .saturating_sub(breadcrumb.chars().count() + 1)
+                .max(max_chars / 2)
+        };
+        let pieces = split_tables(&section.body)
+            .into_iter()
+            .flat_map(|(is_table, text)| {
+                if is_table {
+                    split_table(text, budget, repeat_header)
+                } else {
+                    let spans = chunker::recursive_spans(text, budget, overlap_chars, &separators);
+                    chunker::span_texts(text, &chunker::merge_short_spans(text, &spans, min_chars))
+                }
+            });
+        for piece in pieces {
+            let text = if breadcrumb.is_empty() {
+                piece
+            } else {
+                format!("{}\n{}", breadcrumb, piece)
+            };
+            out.push(Chunk {
+                path: section.path.clone(),
+                text,
+            });
+        }
+    }
+    out
+}
+
+/// Level and title of an ATX heading line (`### Title ##`).
+fn atx_heading(line: &str) -> Option<(usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let trimmed = line.trim();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim_end();
+    (!title.is_empty()).then(|| (level, title.to_string()))
+}
+
+/// Level and title of a setext heading: `line` underlines the last line of
+/// `body` if that is a paragraph line.
+fn setext_heading(body: &[&str], line: &str) -> Option<(usize, String)> {
+    let level = setext_level(line)?;
+    let title = body.last()?.trim();
+    let paragraph = !title.is_empty()
+        && !title.starts_with(['#', '-', '*', '+', '>', '|'])
+        && setext_level(title).is_none();
+    paragraph.then(|| (level, title.to_string()))
+}
+
+/// 1 for a `===` underline, 2 for `---`.
+fn setext_level(line: &str) -> Option<usize> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || line.len() - line.trim_start().len() > 3 {
+        return None;
+    }
+    if trimmed.chars().all(|c| c == '=') {
+        Some(1)
+    } else if trimmed.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// The fence that opens a fenced code block on this line (``` or ~~~).
+fn fence_marker(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    ["```", "~~~"].iter().find_map(|fence| {
+        trimmed.starts_with(fence).then(|| {
+            let c = fence.chars().next().unwrap();
+            trimmed.chars().take_while(|&x| x == c).collect()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_table() {
+        let rows = vec![row(&["Name", "Score"]), row(&["Ada", "97"])];
+        assert_eq!(
+            table(&rows),
+            "| Name | Score |\n| --- | --- |\n| Ada | 97 |"
+        );
+    }
+
+    #[test]
+    fn test_table_escapes_and_pads() {
+        let rows = vec![row(&["a | b", "c"]), row(&["two\nlines"])];
+        assert_eq!(
+            table(&rows),
+            "| a \\| b | c |\n| --- | --- |\n| two lines |  |"
+        );
+        assert_eq!(table(&[]), "");
+    }
+
+    fn paths(sections: &[Section]) -> Vec<String> {
+        sections.iter().map(|s| s.path.join(" > ")).collect()
+    }
+
+    #[test]
+    fn test_sections_heading_paths() {
+        let md = "Intro text.\n\n# Installation\n\n## Linux\napt install it\n\n\
+                  ## macOS ##\nbrew install it\n\n# Usage\nRun it.\n#hashtag, not a heading";
+        let sections = sections(md);
+        assert_eq!(
+            paths(&sections),
+            vec!["", "Installation > Linux", "Installation > macOS", "Usage"]
+        );
+        assert_eq!(sections[0].text, "Intro text.");
+        assert_eq!(sections[1].text, "## Linux\napt install it");
+        assert_eq!(sections[3].text, "# Usage\nRun it.\n#hashtag, not a heading");
+    }
+
+    #[test]
+    fn test_sections_setext_and_fences() {
+        let md = "Guide\n=====\nOverview.\n\nSetup\n-----\n```sh\n# not a heading\n```\n\n\
+                  - item\n---\nafter rule";
+        let sections = sections(md);
+        assert_eq!(paths(&sections), vec!["Guide", "Guide > Setup"]);
+        assert_eq!(sections[0].text, "Guide\n=====\nOverview.");
+        assert!(sections[1].text.contains("# not a heading"));
+        assert!(sections[1].text.ends_with("- item\n---\nafter rule"));
+    }
+
+    #[test]
+    fn test_chunks_carry_breadcrumbs() {
+        let md = "Preamble.\n\n## Setup\n\n### Docker\nRun the image. Mount a volume.\n\n\
+                  Setup\n=====\nAgain.";
+        assert_eq!(sections(md)[1].breadcrumb(), "## Setup > ### Docker");
+
+        let chunks = chunks(md, 40, 0, true, 0);
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "Preamble.",
+                "## Setup > ### Docker\nRun the image.",
+                "## Setup > ### Docker\nMount a volume.",
+                "# Setup\nAgain.",
+            ]
+        );
+        assert_eq!(chunks[1].path, ["Setup", "Docker"]);
+        assert!(chunks[0].path.is_empty());
+
+        // Pieces too short on their own are merged, even past `max_chars`
+        let merged = super::chunks(md, 40, 0, true, 16);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(
+            merged[1].text,
+            "## Setup > ### Docker\nRun the image. Mount a volume."
+        );
+    }
+
+    const TABLE: &str =
+        "| Name | Score |\n| --- | ---: |\n| Ada | 97 |\n| Alan | 88 |\n| Grace | 92 |";
+
+    #[test]
+    fn test_table_spans() {
+        let text = format!("Results:\n{}\nAfter.\n\n| not | a table |\nplain", TABLE);
+        let spans = table_spans(&text);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(&text[spans[0].0..spans[0].1], TABLE);
+        assert_eq!(
+            split_tables(&text),
+            [
+                (false, "Results:"),
+                (true, TABLE),
+                (false, "After.\n\n| not | a table |\nplain"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_table() {
+        assert_eq!(split_table(TABLE, 100, true), [TABLE]);
+        assert_eq!(
+            split_table(TABLE, 60, true),
+            [
+                "| Name | Score |\n| --- | ---: |\n| Ada | 97 |\n| Alan | 88 |",
+                "| Name | Score |\n| --- | ---: |\n| Grace | 92 |",
+            ]
+        );
+        assert_eq!(
+            split_table(TABLE, 60, false),
+            [
+                "| Name | Score |\n| --- | ---: |\n| Ada | 97 |\n| Alan | 88 |",
+                "| Grace | 92 |",
+            ]
+        );
+        // Rows are never cut, even when longer than a chunk, and the header
+        // always comes with a row
+        assert_eq!(
+            split_table(TABLE, 5, false),
+            [
+                "| Name | Score |\n| --- | ---: |\n| Ada | 97 |",
+                "| Alan | 88 |",
+                "| Grace | 92 |",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunks_keep_tables_whole() {
+        let md = format!("## Scores\n\nThe scores were close.\n{}\nAda won.", TABLE);
+        let chunks = chunks(&md, 120, 0, true, 0);
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "## Scores\nThe scores were close.".to_string(),
+                format!("## Scores\n{}", TABLE),
+                "## Scores\nAda won.".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! OCR fallback for PDF pages without a text layer (scanned documents).
+//!
+//! Enabled with the `ocr` cargo feature. Pages are rendered with `pdftoppm`
+//! (poppler-utils) and recognized with the `tesseract` CLI, both of which
+//! must be on PATH. `OCR_LANGUAGE` selects the tesseract language(s),
+//! e.g. "eng+deu" (default: "eng").
+
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Render resolution for OCR; 300 DPI is tesseract's sweet spot.
+const OCR_DPI: &str = "300";
+
+/// Attempts at a fresh scratch directory name before giving up.
+const SCRATCH_ATTEMPTS: usize = 16;
+
+/// Removes the per-page scratch directory even if OCR fails.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    /// Create a new, private directory for one page's rendering under the
+    /// system temp dir. Pages are confidential and the temp dir is shared,
+    /// so the name is random and the directory must not exist yet (an
+    /// existing one, or a symlink planted there, could be another user's),
+    /// and only its owner may open it.
+    fn create() -> Result<Self> {
+        for _ in 0..SCRATCH_ATTEMPTS {
+            let mut random = [0u8; 8];
+            getrandom::getrandom(&mut random).context("Failed to name the OCR scratch dir")?;
+            let path = std::env::temp_dir().join(format!(
+                "rusty_rag_ocr_{}_{:016x}",
+                std::process::id(),
+                u64::from_le_bytes(random)
+            ));
+            let mut builder = fs::DirBuilder::new();
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            match builder.create(&path) {
+                Ok(()) => return Ok(ScratchDir(path)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to create OCR scratch dir: {}", path.display())
+                    })
+                }
+            }
+        }
+        anyhow::bail!("Failed to create OCR scratch dir: every name tried was taken")
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The `pdftoppm` arguments rendering one page (1-based) of the PDF at
+/// `path`, opened with `password` if encrypted, to `<prefix>.png`.
+///
+/// pdftoppm only takes the password as an argument, so while a page
+/// renders it is visible to local users who list processes.
+fn pdftoppm_args(path: &Path, page: u32, password: Option<&str>, prefix: &Path) -> Vec<OsString> {
+    let page = page.to_string();
+    // -singlefile writes exactly <prefix>.png instead of <prefix>-<n>.png
+    let options = ["-r", OCR_DPI, "-gray", "-png", "-singlefile"];
+    let mut args: Vec<OsString> = ["-f", &page, "-l", &page]
+        .into_iter()
+        .chain(options)
+        .map(OsString::from)
+        .collect();
+    if let Some(password) = password {
+        args.push("-upw".into());
+        args.push(password.into());
+    }
+    args.push(path.into());
+    args.push(prefix.into());
+    args
+}
+
+/// Run OCR on one page (1-based) of the PDF at `path`, opened with
+/// `password` if it is encrypted.
+pub fn ocr_page(path: &Path, page: u32, password: Option<&str>) -> Result<String> {
+    let scratch = ScratchDir::create()?;
+
+    let prefix = scratch.0.join("page");
+    let status = Command::new("pdftoppm")
+        .args(pdftoppm_args(path, page, password, &prefix))
+        .status()
+        .context("Failed to run pdftoppm (is poppler-utils installed?)")?;
+    if !status.success() {
+        anyhow::bail!("pdftoppm failed to render page {} of {}", page, path.display());
+    }
+
+    let language = std::env::var("OCR_LANGUAGE").unwrap_or_else(|_| "eng".to_string());
+    let output = Command::new("tesseract")
+        .arg(prefix.with_extension("png"))
+        .args(["stdout", "-l", &language])
+        .output()
+        .context("Failed to run tesseract (is it installed?)")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "tesseract failed on page {} of {}: {}",
+            page,
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_dirs_are_fresh_and_private() {
+        let a = ScratchDir::create().unwrap();
+        let b = ScratchDir::create().unwrap();
+        assert_ne!(a.0, b.0);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&a.0).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        let path = a.0.clone();
+        drop(a);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_pdftoppm_args() {
+        let (pdf, prefix) = (Path::new("scan.pdf"), Path::new("out/page"));
+        let args = pdftoppm_args(pdf, 3, Some("hunter2"), prefix);
+        let at = args.iter().position(|a| a == "-upw").unwrap();
+        assert_eq!(args[at + 1], "hunter2");
+        assert_eq!(
+            args[args.len() - 2..],
+            [OsString::from("scan.pdf"), "out/page".into()]
+        );
+        assert!(!pdftoppm_args(pdf, 3, None, prefix).contains(&"-upw".into()));
+    }
+}
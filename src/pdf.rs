@@ -1,13 +1,1324 @@
 use anyhow::{Context, Result};
 use memmap2::Mmap;
+use pdf_extract::content::Content;
+use pdf_extract::encryption::{decrypt_object, PasswordAlgorithm};
+use pdf_extract::xref::XrefEntry;
+use pdf_extract::{
+    Dictionary, Document, EncryptionState, MediaBox, Object, ObjectId, OutputDev, OutputError,
+    Reader, Stream, Transform,
+};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
+use std::io::Write;
 use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+
+#[cfg(feature = "ocr")]
+use rayon::prelude::*;
+
+/// Text of a single PDF page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageText {
+    /// 1-based page number
+    pub page_number: u32,
+    /// Page text with normalized whitespace
+    pub text: String,
+}
+
+/// Cleanup passes applied to each page's extracted text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cleanup {
+    /// Strip running headers, footers, and page numbers
+    pub strip_furniture: bool,
+    /// Rejoin words hyphenated across line breaks
+    pub dehyphenate: bool,
+    /// Keep a blank line between paragraphs rather than dropping it
+    pub preserve_paragraphs: bool,
+    /// Join the lines of each paragraph into one
+    pub join_lines: bool,
+    /// Compose characters to Unicode normalization form C
+    pub nfc: bool,
+}
+
+impl Default for Cleanup {
+    fn default() -> Self {
+        Self {
+            strip_furniture: true,
+            dehyphenate: true,
+            preserve_paragraphs: true,
+            join_lines: false,
+            nfc: true,
+        }
+    }
+}
 
 /// Extracts all text content from a PDF file at the given path.
 ///
 /// Uses memory-mapped file I/O to handle datasets larger than available RAM.
 /// Returns the full text as a single `String` with normalized whitespace.
-pub fn extract_text(path: &str) -> Result<String> {
+///
+/// Tables are emitted inline as markdown so their structure survives
+/// chunking. With the `ocr` feature, pages without a text layer are run
+/// through OCR instead of being skipped. `password` opens encrypted
+/// (RC4/AES) PDFs; `cleanup` selects the passes run over each page.
+pub fn extract_text(path: &str, password: Option<&str>, cleanup: Cleanup) -> Result<String> {
+    let doc = open_document(path, password)?;
+    let pages = page_texts(&doc, path, password, cleanup)?;
+    Ok(pages
+        .into_iter()
+        .filter(|page| !page.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// Extracts text page by page so chunks can cite the page they came from.
+///
+/// Pages without any text are omitted; `page_number` always refers to the
+/// page's position in the original document.
+pub fn extract_pages(
+    path: &str,
+    password: Option<&str>,
+    cleanup: Cleanup,
+) -> Result<Vec<PageText>> {
+    let doc = open_document(path, password)?;
+    let pages = page_texts(&doc, path, password, cleanup)?;
+    Ok(pages
+        .into_iter()
+        .enumerate()
+        .filter(|(_, text)| !text.is_empty())
+        .map(|(i, text)| PageText {
+            page_number: i as u32 + 1,
+            text,
+        })
+        .collect())
+}
+
+/// Extract the normalized text of every page, in order, and clean it up.
+/// `password` is the one `doc` was opened with, for OCR to reopen it.
+///
+/// Fails if no page yields any text.
+#[cfg_attr(not(feature = "ocr"), allow(unused_variables))]
+fn page_texts(
+    doc: &Document,
+    path: &str,
+    password: Option<&str>,
+    cleanup: Cleanup,
+) -> Result<Vec<String>> {
+    // A page that fails to parse is left empty rather than failing the document
+    #[allow(unused_mut)]
+    let mut pages: Vec<String> = doc
+        .get_pages()
+        .keys()
+        .map(|&page_num| {
+            let mut output = LayoutOutput::default();
+            match pdf_extract::output_doc_page(doc, &mut output, page_num) {
+                Ok(()) => normalize(&page_text(&output), cleanup),
+                Err(_) => String::new(),
+            }
+        })
+        .collect();
+
+    #[cfg(feature = "ocr")]
+    let ocr_error = ocr_empty_pages(Path::new(path), password, &mut pages, cleanup);
+
+    if pages.iter().all(|page| page.is_empty()) {
+        #[cfg(feature = "ocr")]
+        if let Some(e) = ocr_error {
+            return Err(e.context(format!("OCR fallback failed for PDF: {}", path)));
+        }
+        let coverage = page_coverage(doc);
+        anyhow::bail!(
+            "No text could be extracted from the PDF ({} of {} pages are image-only). It may \
+             be image-based or encrypted (build with the `ocr` feature to OCR scanned pages): {}",
+            coverage.image_pages.len(),
+            coverage.page_count,
+            path
+        );
+    }
+
+    if cleanup.strip_furniture {
+        strip_furniture(&mut pages);
+    }
+    if cleanup.dehyphenate {
+        dehyphenate(&mut pages);
+    }
+    if cleanup.join_lines {
+        for page in pages.iter_mut() {
+            *page = join_lines(page);
+        }
+    }
+    Ok(pages)
+}
+
+/// Lines at the top and at the bottom of a page checked for furniture
+const FURNITURE_LINES: usize = 3;
+/// Pages with text needed before a line can count as repeated
+const FURNITURE_MIN_PAGES: usize = 3;
+
+/// Strip page furniture: running headers, footers, and page numbers.
+///
+/// These are the lines near the top or bottom of a page that recur on at
+/// least half the pages with text. Numbers are ignored when comparing
+/// lines, so "Page 3 of 10" matches "Page 4 of 10" and bare page numbers
+/// match each other. Lines in the middle of a page are never touched.
+fn strip_furniture(pages: &mut [String]) {
+    let with_text = pages.iter().filter(|page| !page.is_empty()).count();
+    if with_text < FURNITURE_MIN_PAGES {
+        return;
+    }
+
+    // Blank lines between paragraphs don't count towards the lines checked
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for page in pages.iter() {
+        let lines: Vec<&str> = page.lines().filter(|line| !line.is_empty()).collect();
+        let head = lines.iter().take(FURNITURE_LINES);
+        let tail = lines.iter().rev().take(FURNITURE_LINES);
+        let keys: HashSet<String> = head.chain(tail).filter_map(|l| furniture_key(l)).collect();
+        for key in keys {
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    let repeated = |line: &str| {
+        furniture_key(line)
+            .and_then(|key| counts.get(&key))
+            .is_some_and(|&count| count * 2 >= with_text)
+    };
+
+    for page in pages.iter_mut() {
+        let lines: Vec<&str> = page.lines().collect();
+        let (mut start, mut checked) = (0, 0);
+        while start < lines.len() && checked < FURNITURE_LINES {
+            if !lines[start].is_empty() {
+                if !repeated(lines[start]) {
+                    break;
+                }
+                checked += 1;
+            }
+            start += 1;
+        }
+        let (mut end, mut checked) = (lines.len(), 0);
+        while end > start && checked < FURNITURE_LINES {
+            if !lines[end - 1].is_empty() {
+                if !repeated(lines[end - 1]) {
+                    break;
+                }
+                checked += 1;
+            }
+            end -= 1;
+        }
+        if (start, end) != (0, lines.len()) {
+            *page = lines[start..end].join("\n");
+        }
+    }
+}
+
+/// Rejoin words split across line breaks ("infor-\nmation" becomes
+/// "information"), moving the rest of the word up onto the first line.
+///
+/// The hyphen is kept where it is likely part of the word: when the word
+/// continues with a capital or a digit ("Franco-\nPrussian", "COVID-\n19"),
+/// when the first part is already hyphenated ("state-of-the-\nart"), and
+/// when the document spells the word with a hyphen elsewhere but never
+/// without one. Soft hyphens at the end of a line are always dropped.
+fn dehyphenate(pages: &mut [String]) {
+    let vocabulary: HashSet<String> = pages
+        .iter()
+        .flat_map(|page| page.split_whitespace())
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    for page in pages.iter_mut() {
+        if !page.contains("-\n") && !page.contains("\u{ad}\n") {
+            continue;
+        }
+        let mut lines: Vec<String> = page.lines().map(String::from).collect();
+        let mut i = 0;
+        while i + 1 < lines.len() {
+            match rejoin(&lines[i], &lines[i + 1], &vocabulary) {
+                Some((line, rest)) => {
+                    lines[i] = line;
+                    if rest.is_empty() {
+                        lines.remove(i + 1);
+                    } else {
+                        lines[i + 1] = rest;
+                    }
+                }
+                None => i += 1,
+            }
+        }
+        *page = lines.join("\n");
+    }
+}
+
+/// If `line` ends with a word broken off by a hyphen, `line` completed with
+/// the first word of `next`, and what remains of `next`.
+fn rejoin(line: &str, next: &str, vocabulary: &HashSet<String>) -> Option<(String, String)> {
+    if line.starts_with('|') || next.starts_with('|') {
+        return None;
+    }
+    let (stem, soft) = match line.strip_suffix('\u{ad}') {
+        Some(stem) => (stem, true),
+        None => (line.strip_suffix('-')?, false),
+    };
+    let head = stem.rsplit(char::is_whitespace).next().unwrap_or(stem);
+    if !head.chars().last()?.is_alphabetic() {
+        return None;
+    }
+    let (tail, rest) = next.split_once(char::is_whitespace).unwrap_or((next, ""));
+    let first = tail.chars().next()?;
+    if !first.is_alphanumeric() {
+        return None;
+    }
+
+    let keep_hyphen = !soft
+        && (!first.is_lowercase() || head.contains('-') || {
+            let head = head.trim_start_matches(|c: char| !c.is_alphanumeric());
+            let tail = tail.trim_end_matches(|c: char| !c.is_alphanumeric());
+            let joined = format!("{head}{tail}").to_lowercase();
+            let hyphenated = format!("{head}-{tail}").to_lowercase();
+            vocabulary.contains(&hyphenated) && !vocabulary.contains(&joined)
+        });
+    let hyphen = if keep_hyphen { "-" } else { "" };
+    Some((format!("{stem}{hyphen}{tail}"), rest.trim_start().into()))
+}
+
+/// A line compared case-insensitively, with each run of digits standing
+/// for any number. Table rows are never furniture, so repeated table
+/// headers keep continued tables intact.
+fn furniture_key(line: &str) -> Option<String> {
+    if line.starts_with('|') {
+        return None;
+    }
+    let mut key = String::with_capacity(line.len());
+    for c in line.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_digit() {
+            if !key.ends_with('#') {
+                key.push('#');
+            }
+        } else if c.is_whitespace() {
+            if !key.ends_with(' ') {
+                key.push(' ');
+            }
+        } else {
+            key.push(c);
+        }
+    }
+    Some(key)
+}
+
+/// Which pages of a PDF have a text layer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextCoverage {
+    pub page_count: u32,
+    /// Pages that draw text
+    pub text_pages: Vec<u32>,
+    /// Pages that draw images but no text, i.e. scans needing OCR
+    pub image_pages: Vec<u32>,
+    /// Pages that draw neither
+    pub blank_pages: Vec<u32>,
+}
+
+/// Nesting depth of form XObjects followed when scanning page content.
+const MAX_FORM_DEPTH: usize = 8;
+
+/// Reports which pages have a text layer and which are image-only, so a
+/// poorly searchable document can be traced to its scanned pages.
+///
+/// Pages are classified from the operators in their content streams (and
+/// the forms those draw), without extracting any text.
+pub fn text_coverage(path: &str, password: Option<&str>) -> Result<TextCoverage> {
+    let doc = open_document(path, password)?;
+    Ok(page_coverage(&doc))
+}
+
+fn page_coverage(doc: &Document) -> TextCoverage {
+    let mut coverage = TextCoverage::default();
+    for (&page_number, &page_id) in doc.get_pages().iter() {
+        coverage.page_count += 1;
+        let resources: Vec<&Dictionary> = match doc.get_page_resources(page_id) {
+            Ok((resources, inherited)) => resources
+                .into_iter()
+                .chain(
+                    inherited
+                        .iter()
+                        .filter_map(|&id| doc.get_dictionary(id).ok()),
+                )
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let content = doc.get_page_content(page_id).unwrap_or_default();
+        match content_marks(doc, &content, &resources, 0) {
+            (true, _) => coverage.text_pages.push(page_number),
+            (false, true) => coverage.image_pages.push(page_number),
+            (false, false) => coverage.blank_pages.push(page_number),
+        }
+    }
+    coverage
+}
+
+/// Whether a content stream shows any text, and whether it draws any
+/// images, following the form XObjects it draws.
+fn content_marks(
+    doc: &Document,
+    content: &[u8],
+    resources: &[&Dictionary],
+    depth: usize,
+) -> (bool, bool) {
+    let Ok(content) = Content::decode(content) else {
+        return (false, false);
+    };
+    // Strings of spaces are sometimes shown to pad otherwise empty pages
+    let visible = |bytes: &[u8]| bytes.iter().any(|b| !b.is_ascii_whitespace());
+    let shows_text = |operand: &Object| match operand {
+        Object::String(bytes, _) => visible(bytes),
+        Object::Array(items) => items
+            .iter()
+            .any(|item| matches!(item, Object::String(bytes, _) if visible(bytes))),
+        _ => false,
+    };
+
+    let mut images = false;
+    for op in &content.operations {
+        match op.operator.as_str() {
+            "Tj" | "TJ" | "'" | "\"" if op.operands.last().is_some_and(shows_text) => {
+                return (true, images);
+            }
+            "BI" => images = true,
+            "Do" => {
+                let Some(name) = op.operands.first().and_then(|o| o.as_name().ok()) else {
+                    continue;
+                };
+                let xobject = resources.iter().find_map(|dict| {
+                    let xobjects = doc.get_dict_in_dict(dict, b"XObject").ok()?;
+                    let id = xobjects.get(name).ok()?.as_reference().ok()?;
+                    doc.get_object(id).and_then(Object::as_stream).ok()
+                });
+                let Some(stream) = xobject else { continue };
+                match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+                    Ok(b"Image") => images = true,
+                    Ok(b"Form") if depth < MAX_FORM_DEPTH => {
+                        // A form without resources of its own uses the page's
+                        let inner = doc.get_dict_in_dict(&stream.dict, b"Resources").ok();
+                        let resources: Vec<&Dictionary> = match inner {
+                            Some(inner) => vec![inner],
+                            None => resources.to_vec(),
+                        };
+                        let data = stream
+                            .decompressed_content()
+                            .unwrap_or_else(|_| stream.content.clone());
+                        let (text, form_images) = content_marks(doc, &data, &resources, depth + 1);
+                        if text {
+                            return (true, images);
+                        }
+                        images |= form_images;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    (false, images)
+}
+
+/// A stretch of PDF text under one outline (bookmark) entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageSection {
+    /// 1-based page number
+    pub page_number: u32,
+    /// Titles of the enclosing outline entries, outermost first; empty
+    /// before the first entry or if the PDF has no outline
+    pub path: Vec<String>,
+    /// Text with normalized whitespace
+    pub text: String,
+}
+
+/// Extracts text page by page, split where the PDF's outline (bookmarks)
+/// starts a new section and tagged with that section's title path.
+///
+/// A page holding the start of a section is split at the line with the
+/// section's title, or at the top of the page if the title isn't found, so
+/// text before it still belongs to the previous section. Without an
+/// outline this yields one span per page, like `extract_pages`.
+pub fn extract_sections(
+    path: &str,
+    password: Option<&str>,
+    cleanup: Cleanup,
+) -> Result<Vec<PageSection>> {
+    let doc = open_document(path, password)?;
+    let pages = page_texts(&doc, path, password, cleanup)?;
+    Ok(split_sections(&pages, &read_outline(&doc)))
+}
+
+/// An entry of a PDF's outline.
+#[derive(Debug, Clone, PartialEq)]
+struct OutlineEntry {
+    /// Nesting depth, 1 for top-level entries
+    level: usize,
+    title: String,
+    /// 1-based page the entry points to
+    page_number: u32,
+}
+
+/// Assign page text to the outline entries, in page order.
+fn split_sections(pages: &[String], outline: &[OutlineEntry]) -> Vec<PageSection> {
+    let mut entries = outline.to_vec();
+    entries.sort_by_key(|entry| entry.page_number);
+    let mut entries = entries.into_iter().peekable();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut out = Vec::new();
+
+    let mut push = |page_number: u32, headings: &[(usize, String)], text: &str| {
+        let text = text.trim();
+        if !text.is_empty() {
+            out.push(PageSection {
+                page_number,
+                path: headings.iter().map(|(_, title)| title.clone()).collect(),
+                text: text.to_string(),
+            });
+        }
+    };
+
+    for (i, text) in pages.iter().enumerate() {
+        let page_number = i as u32 + 1;
+        let mut rest = text.as_str();
+        while let Some(entry) = entries.next_if(|entry| entry.page_number <= page_number) {
+            let at = find_title(rest, &entry.title).unwrap_or(0);
+            push(page_number, &headings, &rest[..at]);
+            rest = &rest[at..];
+            headings.retain(|(level, _)| *level < entry.level);
+            headings.push((entry.level, entry.title));
+        }
+        push(page_number, &headings, rest);
+    }
+    out
+}
+
+/// Byte offset of the line where `title` appears as a heading: a line
+/// that starts with it, or the first line of it if it wraps, ignoring case
+/// and spacing.
+fn find_title(text: &str, title: &str) -> Option<usize> {
+    let squash = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let title = squash(title);
+    if title.is_empty() {
+        return None;
+    }
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let squashed = squash(line);
+        let wrapped = squashed.len() >= 10 && title.starts_with(&squashed);
+        if squashed.starts_with(&title) || wrapped {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Read the outline tree, in document order. Entries that don't point to
+/// a page of this document (e.g. web links) are left out.
+fn read_outline(doc: &Document) -> Vec<OutlineEntry> {
+    let first = doc
+        .catalog()
+        .and_then(|catalog| doc.get_dict_in_dict(catalog, b"Outlines"))
+        .and_then(|outlines| outlines.get(b"First"))
+        .and_then(Object::as_reference);
+    let Ok(first) = first else {
+        return Vec::new();
+    };
+
+    let pages: HashMap<ObjectId, u32> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(number, id)| (id, number))
+        .collect();
+    let mut walker = OutlineWalker {
+        doc,
+        pages,
+        names: named_destinations(doc),
+        visited: HashSet::new(),
+        entries: Vec::new(),
+    };
+    walker.walk(first, 1);
+    walker.entries
+}
+
+struct OutlineWalker<'a> {
+    doc: &'a Document,
+    /// Page object id → 1-based page number
+    pages: HashMap<ObjectId, u32>,
+    /// Named destinations, by name
+    names: HashMap<Vec<u8>, &'a Object>,
+    /// Outline items seen so far, to survive cyclic /Next or /First links
+    visited: HashSet<ObjectId>,
+    entries: Vec<OutlineEntry>,
+}
+
+impl<'a> OutlineWalker<'a> {
+    /// Visit the outline item `id`, its children, and its later siblings.
+    fn walk(&mut self, id: ObjectId, level: usize) {
+        let mut next = Some(id);
+        while let Some(id) = next {
+            if !self.visited.insert(id) {
+                return;
+            }
+            let Ok(item) = self.doc.get_dictionary(id) else {
+                return;
+            };
+
+            let title = item
+                .get(b"Title")
+                .ok()
+                .and_then(|title| pdf_extract::decode_text_string(resolve(self.doc, title)).ok())
+                .map(|title| title.split_whitespace().collect::<Vec<_>>().join(" "));
+            if let (Some(title), Some(page_number)) = (title, self.target_page(item)) {
+                if !title.is_empty() {
+                    self.entries.push(OutlineEntry {
+                        level,
+                        title,
+                        page_number,
+                    });
+                }
+            }
+
+            if let Ok(child) = item.get(b"First").and_then(Object::as_reference) {
+                self.walk(child, level + 1);
+            }
+            next = item.get(b"Next").and_then(Object::as_reference).ok();
+        }
+    }
+
+    /// Page an outline item jumps to, via /Dest or a GoTo action.
+    fn target_page(&self, item: &Dictionary) -> Option<u32> {
+        if let Ok(dest) = item.get(b"Dest") {
+            return self.destination_page(dest, 0);
+        }
+        let action = resolve(self.doc, item.get(b"A").ok()?).as_dict().ok()?;
+        if action.get(b"S").and_then(Object::as_name).ok()? != b"GoTo" {
+            return None;
+        }
+        self.destination_page(action.get(b"D").ok()?, 0)
+    }
+
+    /// Page of an explicit destination (`[page /XYZ ...]`), or of a named
+    /// one, which may in turn be a dictionary holding it under /D.
+    fn destination_page(&self, dest: &Object, depth: usize) -> Option<u32> {
+        if depth > 8 {
+            return None;
+        }
+        match resolve(self.doc, dest) {
+            Object::Array(items) => match items.first()? {
+                Object::Reference(id) => self.pages.get(id).copied(),
+                _ => None,
+            },
+            Object::Name(name) | Object::String(name, _) => {
+                self.destination_page(self.names.get(name)?, depth + 1)
+            }
+            Object::Dictionary(dict) => self.destination_page(dict.get(b"D").ok()?, depth + 1),
+            _ => None,
+        }
+    }
+}
+
+/// Follow an indirect reference, if `object` is one.
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> &'a Object {
+    doc.dereference(object)
+        .map(|(_, object)| object)
+        .unwrap_or(object)
+}
+
+/// Named destinations, from the catalog's /Dests dictionary (PDF 1.1) and
+/// the /Dests name tree under /Names.
+fn named_destinations(doc: &Document) -> HashMap<Vec<u8>, &Object> {
+    let mut names = HashMap::new();
+    let Ok(catalog) = doc.catalog() else {
+        return names;
+    };
+    if let Ok(dests) = doc.get_dict_in_dict(catalog, b"Dests") {
+        for (name, dest) in dests.iter() {
+            names.insert(name.clone(), dest);
+        }
+    }
+
+    let tree = doc
+        .get_dict_in_dict(catalog, b"Names")
+        .and_then(|names| doc.get_dict_in_dict(names, b"Dests"));
+    let mut nodes: Vec<&Dictionary> = tree.into_iter().collect();
+    let mut visited = HashSet::new();
+    while let Some(node) = nodes.pop() {
+        if let Ok(pairs) = node.get(b"Names").and_then(|n| resolve(doc, n).as_array()) {
+            for pair in pairs.chunks(2) {
+                if let [Object::String(name, _), dest] = pair {
+                    names.insert(name.clone(), dest);
+                }
+            }
+        }
+        if let Ok(kids) = node.get(b"Kids").and_then(|k| resolve(doc, k).as_array()) {
+            for kid in kids {
+                let Ok(id) = kid.as_reference() else { continue };
+                if visited.insert(id) {
+                    nodes.extend(doc.get_dictionary(id).ok());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Gap between words, in multiples of the font size, wide enough to mean
+/// a new table cell rather than a space
+const CELL_GAP: f64 = 1.0;
+/// Rows (including the header) needed before aligned text counts as a table
+const MIN_TABLE_ROWS: usize = 3;
+/// Most words a table's cells may average: table cells hold labels and
+/// figures, while rows of longer runs are prose that merely lines up (two
+/// columns read across, or justified lines)
+const MAX_MEAN_CELL_WORDS: f64 = 4.0;
+
+/// A run of text on one line, separated from its neighbours by a wide gap.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Cell {
+    /// Left edge, in points from the page's left
+    x: f64,
+    /// Right edge
+    end: f64,
+    text: String,
+}
+
+/// Records where text sits on the page, so aligned columns can be turned
+/// back into tables. Spacing follows pdf_extract's `PlainTextOutput`.
+#[derive(Default)]
+struct LayoutOutput {
+    page_height: f64,
+    lines: Vec<Vec<Cell>>,
+    /// Baseline and font size of each line's first character
+    line_tops: Vec<(f64, f64)>,
+    first_char: bool,
+    last_y: f64,
+    last_end: f64,
+}
+
+impl OutputDev for LayoutOutput {
+    fn begin_page(
+        &mut self,
+        _page_num: u32,
+        media_box: &MediaBox,
+        _art_box: Option<(f64, f64, f64, f64)>,
+    ) -> Result<(), OutputError> {
+        self.page_height = media_box.ury - media_box.lly;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &Transform,
+        width: f64,
+        _spacing: f64,
+        font_size: f64,
+        char: &str,
+    ) -> Result<(), OutputError> {
+        // Flip to top-down coordinates and scale the font size into page space
+        let (x, y) = (trm.m31, self.page_height - trm.m32);
+        let size_x = font_size * (trm.m11 + trm.m21);
+        let size_y = font_size * (trm.m12 + trm.m22);
+        let size = (size_x * size_y).abs().sqrt();
+
+        if self.first_char || self.lines.is_empty() {
+            let dy = (y - self.last_y).abs();
+            let new_line = self.lines.is_empty()
+                || dy > size * 1.5
+                || (x < self.last_end && dy > size * 0.5);
+            if new_line {
+                self.lines.push(vec![Cell::default()]);
+                self.line_tops.push((y, size));
+            } else if x > self.last_end + size * CELL_GAP {
+                self.lines.last_mut().unwrap().push(Cell::default());
+            } else if x > self.last_end + size * 0.1 {
+                self.lines.last_mut().unwrap().last_mut().unwrap().text.push(' ');
+            }
+        }
+
+        let cell = self.lines.last_mut().unwrap().last_mut().unwrap();
+        if cell.text.is_empty() {
+            cell.x = x;
+        }
+        cell.text.push_str(char);
+        cell.end = x + width * size;
+
+        self.first_char = false;
+        self.last_y = y;
+        self.last_end = cell.end;
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        self.first_char = true;
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+/// Gap between lines, relative to the page's usual line spacing, that
+/// starts a new paragraph
+const PARAGRAPH_GAP: f64 = 1.4;
+/// Line spacing, in multiples of the font size, assumed on pages with too
+/// few lines to measure it
+const DEFAULT_LINE_SPACING: f64 = 1.2;
+
+/// Render a page's lines as text, with a blank line between paragraphs:
+/// wherever the gap above a line is clearly wider than the page's usual
+/// (median) line spacing.
+fn page_text(output: &LayoutOutput) -> String {
+    let spacing: Vec<f64> = output
+        .line_tops
+        .windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0) / pair[0].1.max(1.0))
+        .collect();
+    let mut usual: Vec<f64> = spacing.iter().copied().filter(|&s| s > 0.0).collect();
+    usual.sort_by(f64::total_cmp);
+    let usual = if usual.len() >= 3 {
+        usual[usual.len() / 2]
+    } else {
+        DEFAULT_LINE_SPACING
+    };
+
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    for (i, &gap) in spacing.iter().enumerate() {
+        if gap > usual * PARAGRAPH_GAP {
+            paragraphs.push(layout_text(&output.lines[start..=i]));
+            start = i + 1;
+        }
+    }
+    paragraphs.push(layout_text(&output.lines[start..]));
+    paragraphs.join("\n\n")
+}
+
+/// Render laid-out lines as text, with runs of aligned rows as markdown
+/// tables.
+fn layout_text(lines: &[Vec<Cell>]) -> String {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let rows = table_at(&lines[i..]);
+        if rows.len() >= MIN_TABLE_ROWS && is_tabular(&rows) {
+            out.push(crate::markdown::table(&rows));
+            i += rows.len();
+        } else {
+            let words: Vec<&str> = lines[i]
+                .iter()
+                .map(|cell| cell.text.trim())
+                .filter(|text| !text.is_empty())
+                .collect();
+            out.push(words.join(" "));
+            i += 1;
+        }
+    }
+    out.join("\n")
+}
+
+/// The table whose header is `lines[0]`: it and the following rows whose
+/// cells each sit under one of the header's columns. Returns each row's
+/// cells by column, or nothing if the first line isn't a header.
+fn table_at(lines: &[Vec<Cell>]) -> Vec<Vec<String>> {
+    let header = &lines[0];
+    if header.len() < 2 {
+        return Vec::new();
+    }
+    lines
+        .iter()
+        .map_while(|line| align_row(line, header))
+        .collect()
+}
+
+/// Whether aligned `rows` read as a table: their cells are short, not
+/// runs of prose.
+fn is_tabular(rows: &[Vec<String>]) -> bool {
+    let cells: Vec<&String> = rows.iter().flatten().filter(|c| !c.is_empty()).collect();
+    let words: usize = cells.iter().map(|c| c.split_whitespace().count()).sum();
+    words as f64 <= cells.len() as f64 * MAX_MEAN_CELL_WORDS
+}
+
+/// Assign each cell of `line` to the header column it overlaps, left to
+/// right; None if the line doesn't fit the header's columns.
+fn align_row(line: &[Cell], header: &[Cell]) -> Option<Vec<String>> {
+    if line.len() < 2 {
+        return None;
+    }
+    let mut row = vec![String::new(); header.len()];
+    let mut next = 0;
+    for cell in line {
+        let col = (next..header.len())
+            .find(|&c| cell.x <= header[c].end && header[c].x <= cell.end)?;
+        row[col] = cell.text.trim().to_string();
+        next = col + 1;
+    }
+    Some(row)
+}
+
+/// Document information from a PDF's Info dictionary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    /// ISO 8601, e.g. "2023-04-01T12:30:00+02:00"
+    pub creation_date: Option<String>,
+    /// ISO 8601
+    pub modification_date: Option<String>,
+    pub page_count: usize,
+}
+
+/// Reads the title, author, dates, etc. from a PDF's Info dictionary.
+///
+/// Fields missing from the document (or blank) are `None`.
+pub fn extract_metadata(path: &str, password: Option<&str>) -> Result<PdfMetadata> {
+    let doc = open_document(path, password)?;
+
+    let info = doc
+        .trailer
+        .get(b"Info")
+        .and_then(|obj| match obj {
+            Object::Reference(id) => doc.get_dictionary(*id),
+            _ => obj.as_dict(),
+        })
+        .ok();
+    let field = |key: &[u8]| -> Option<String> {
+        let value = info?.get(key).ok()?;
+        let value = match value {
+            Object::Reference(id) => doc.get_object(*id).ok()?,
+            _ => value,
+        };
+        let text = pdf_extract::decode_text_string(value).ok()?;
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    };
+
+    Ok(PdfMetadata {
+        title: field(b"Title"),
+        author: field(b"Author"),
+        subject: field(b"Subject"),
+        keywords: field(b"Keywords"),
+        creator: field(b"Creator"),
+        producer: field(b"Producer"),
+        creation_date: field(b"CreationDate").and_then(|d| parse_pdf_date(&d)),
+        modification_date: field(b"ModDate").and_then(|d| parse_pdf_date(&d)),
+        page_count: doc.get_pages().len(),
+    })
+}
+
+/// Convert a PDF date ("D:YYYYMMDDHHmmSSOHH'mm'", every part after the
+/// year optional) to ISO 8601. Returns None if it can't be parsed.
+fn parse_pdf_date(raw: &str) -> Option<String> {
+    let s = raw.strip_prefix("D:").unwrap_or(raw);
+    let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 4 {
+        return None;
+    }
+    let part = |start: usize, default: &str| -> String {
+        digits.get(start..start + 2).unwrap_or(default).to_string()
+    };
+    let mut iso = format!(
+        "{}-{}-{}T{}:{}:{}",
+        &digits[..4],
+        part(4, "01"),
+        part(6, "01"),
+        part(8, "00"),
+        part(10, "00"),
+        part(12, "00"),
+    );
+
+    // Timezone: Z, or +HH'mm' / -HH'mm'
+    let tz = &s[digits.len()..];
+    match tz.chars().next() {
+        Some('Z') => iso.push('Z'),
+        Some(sign @ ('+' | '-')) => {
+            let tz_digits: String = tz[1..].chars().filter(|c| c.is_ascii_digit()).collect();
+            let hours = tz_digits.get(..2).unwrap_or("00");
+            let minutes = tz_digits.get(2..4).unwrap_or("00");
+            iso.push_str(&format!("{}{}:{}", sign, hours, minutes));
+        }
+        _ => {}
+    }
+    Some(iso)
+}
+
+/// A filled-in field of an interactive (AcroForm) PDF form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    /// 1-based number of the page showing the field, if known
+    pub page_number: Option<u32>,
+    /// The field's tooltip (/TU) if it has one, otherwise its fully
+    /// qualified name, e.g. "applicant.name"
+    pub label: String,
+    pub value: String,
+}
+
+/// Button field flags (/Ff): radio button, push button
+const FLAG_RADIO: i64 = 1 << 15;
+const FLAG_PUSHBUTTON: i64 = 1 << 16;
+/// Nesting depth of the form field tree followed
+const MAX_FIELD_DEPTH: usize = 16;
+
+/// Reads the values of a PDF's form fields, which text extraction misses:
+/// a filled-in form keeps them apart from the page content.
+///
+/// Text and choice fields give their text (several selections joined by
+/// ", "), checkboxes "Yes" or "No", and radio buttons the chosen option.
+/// Empty fields, push buttons, and signatures are left out.
+pub fn extract_form_fields(path: &str, password: Option<&str>) -> Result<Vec<FormField>> {
+    let doc = open_document(path, password)?;
+
+    // Fields are drawn by widget annotations, listed in each page's /Annots
+    let mut widget_pages = HashMap::new();
+    for (&page_number, &page_id) in doc.get_pages().iter() {
+        let annots = doc
+            .get_dictionary(page_id)
+            .and_then(|page| page.get(b"Annots"))
+            .and_then(|annots| resolve(&doc, annots).as_array());
+        for annot in annots.into_iter().flatten() {
+            if let Ok(id) = annot.as_reference() {
+                widget_pages.entry(id).or_insert(page_number);
+            }
+        }
+    }
+
+    let roots = doc
+        .catalog()
+        .and_then(|catalog| doc.get_dict_in_dict(catalog, b"AcroForm"))
+        .and_then(|form| form.get(b"Fields"))
+        .and_then(|fields| resolve(&doc, fields).as_array());
+    let mut reader = FieldReader {
+        doc: &doc,
+        widget_pages,
+        visited: HashSet::new(),
+        fields: Vec::new(),
+    };
+    for root in roots.into_iter().flatten() {
+        if let Ok(id) = root.as_reference() {
+            reader.read(id, &FieldScope::default(), 0);
+        }
+    }
+    Ok(reader.fields)
+}
+
+/// What a form field inherits from its ancestors in the field tree.
+#[derive(Clone, Default)]
+struct FieldScope<'a> {
+    /// Fully qualified name so far
+    name: String,
+    /// Field type (/FT): Tx, Ch, Btn, or Sig
+    kind: Option<&'a [u8]>,
+    flags: i64,
+    value: Option<&'a Object>,
+}
+
+struct FieldReader<'a> {
+    doc: &'a Document,
+    /// Widget annotation id → 1-based page number
+    widget_pages: HashMap<ObjectId, u32>,
+    /// Fields seen so far, to survive cyclic /Kids links
+    visited: HashSet<ObjectId>,
+    fields: Vec<FormField>,
+}
+
+impl<'a> FieldReader<'a> {
+    /// Visit the field `id` and the fields below it.
+    fn read(&mut self, id: ObjectId, parent: &FieldScope<'a>, depth: usize) {
+        if depth > MAX_FIELD_DEPTH || !self.visited.insert(id) {
+            return;
+        }
+        let doc = self.doc;
+        let Ok(node) = doc.get_dictionary(id) else {
+            return;
+        };
+        let text = |key: &[u8]| -> Option<String> {
+            let value = resolve(doc, node.get(key).ok()?);
+            let text = pdf_extract::decode_text_string(value).ok()?;
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        };
+
+        let mut scope = parent.clone();
+        if let Some(partial) = text(b"T") {
+            scope.name = if scope.name.is_empty() {
+                partial
+            } else {
+                format!("{}.{}", scope.name, partial)
+            };
+        }
+        if let Ok(kind) = node.get(b"FT").and_then(Object::as_name) {
+            scope.kind = Some(kind);
+        }
+        if let Ok(flags) = node.get(b"Ff").and_then(|f| resolve(doc, f).as_i64()) {
+            scope.flags = flags;
+        }
+        if let Ok(value) = node.get(b"V") {
+            scope.value = Some(resolve(doc, value));
+        }
+
+        // Kids with a name are fields of their own; the rest are widgets
+        // showing this field
+        let kids: Vec<ObjectId> = node
+            .get(b"Kids")
+            .and_then(|kids| resolve(doc, kids).as_array())
+            .map(|kids| {
+                kids.iter()
+                    .filter_map(|kid| kid.as_reference().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let (fields, widgets): (Vec<ObjectId>, Vec<ObjectId>) = kids
+            .into_iter()
+            .partition(|&kid| doc.get_dictionary(kid).is_ok_and(|kid| kid.has(b"T")));
+        if !fields.is_empty() {
+            for kid in fields {
+                self.read(kid, &scope, depth + 1);
+            }
+            return;
+        }
+
+        let value = scope
+            .value
+            .zip(scope.kind)
+            .and_then(|(value, kind)| form_value(value, kind, scope.flags));
+        let Some(value) = value else {
+            return;
+        };
+        let page_number = std::iter::once(id)
+            .chain(widgets)
+            .find_map(|widget| self.widget_pages.get(&widget).copied());
+        self.fields.push(FormField {
+            page_number,
+            label: text(b"TU").unwrap_or(scope.name),
+            value,
+        });
+    }
+}
+
+/// A form field's value as text, by its type and flags.
+fn form_value(value: &Object, kind: &[u8], flags: i64) -> Option<String> {
+    let text = |object: &Object| pdf_extract::decode_text_string(object).ok();
+    let value = match (kind, value) {
+        (b"Tx" | b"Ch", Object::String(..)) => text(value)?,
+        (b"Ch", Object::Array(items)) => items
+            .iter()
+            .filter_map(text)
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect::<Vec<_>>()
+            .join(", "),
+        (b"Btn", _) if flags & FLAG_PUSHBUTTON != 0 => return None,
+        (b"Btn", Object::Name(name)) if flags & FLAG_RADIO != 0 => {
+            if name == b"Off" {
+                return None;
+            }
+            String::from_utf8_lossy(name).into_owned()
+        }
+        (b"Btn", Object::Name(name)) => match name.as_slice() {
+            b"Off" => "No".to_string(),
+            _ => "Yes".to_string(),
+        },
+        _ => return None,
+    };
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// An image embedded in a PDF page, such as a figure or chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageImage {
+    /// 1-based number of the first page showing the image
+    pub page_number: u32,
+    pub width: u32,
+    pub height: u32,
+    /// The image as a JPEG or PNG file
+    pub data: Vec<u8>,
+}
+
+/// Extracts the images embedded in a PDF's pages, e.g. to caption figures.
+///
+/// JPEG images are returned as they are stored; 8-bit RGB and grayscale
+/// bitmaps are re-encoded as PNG. Images in other encodings (JPEG 2000,
+/// JBIG2, CCITT fax, indexed colour) are skipped, as are images narrower
+/// or shorter than `min_size` pixels, which are mostly icons and rules.
+/// An image drawn on several pages (a logo, say) is returned once.
+pub fn extract_images(path: &str, password: Option<&str>, min_size: u32) -> Result<Vec<PageImage>> {
+    let doc = open_document(path, password)?;
+    let mut seen = HashSet::new();
+    let mut images = Vec::new();
+
+    for (&page_number, &page_id) in doc.get_pages().iter() {
+        let Ok((resources, inherited)) = doc.get_page_resources(page_id) else {
+            continue;
+        };
+        let mut streams = Vec::new();
+        for dict in resources.into_iter().chain(
+            inherited
+                .iter()
+                .filter_map(|&id| doc.get_dictionary(id).ok()),
+        ) {
+            collect_image_streams(&doc, dict, &mut seen, &mut streams);
+        }
+        images.extend(
+            streams
+                .into_iter()
+                .filter_map(encode_image)
+                .filter(|(width, height, _)| *width >= min_size && *height >= min_size)
+                .map(|(width, height, data)| PageImage {
+                    page_number,
+                    width,
+                    height,
+                    data,
+                }),
+        );
+    }
+    Ok(images)
+}
+
+/// Image XObjects of a resource dictionary, including those drawn by its
+/// form XObjects; `seen` holds the object ids already visited.
+fn collect_image_streams<'a>(
+    doc: &'a Document,
+    resources: &'a Dictionary,
+    seen: &mut HashSet<ObjectId>,
+    out: &mut Vec<&'a Stream>,
+) {
+    let Ok(xobjects) = doc.get_dict_in_dict(resources, b"XObject") else {
+        return;
+    };
+    for (_, value) in xobjects.iter() {
+        let Ok(id) = value.as_reference() else {
+            continue;
+        };
+        if !seen.insert(id) {
+            continue;
+        }
+        let Ok(stream) = doc.get_object(id).and_then(Object::as_stream) else {
+            continue;
+        };
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") => out.push(stream),
+            Ok(b"Form") => {
+                if let Ok(inner) = doc.get_dict_in_dict(&stream.dict, b"Resources") {
+                    collect_image_streams(doc, inner, seen, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Width, height, and JPEG or PNG file contents of an image XObject, or
+/// None if it is stored in an encoding we can't hand on.
+fn encode_image(stream: &Stream) -> Option<(u32, u32, Vec<u8>)> {
+    let dict = &stream.dict;
+    let dimension = |key: &[u8]| {
+        dict.get(key)
+            .and_then(Object::as_i64)
+            .ok()
+            .and_then(|n| u32::try_from(n).ok())
+    };
+    let (width, height) = (dimension(b"Width")?, dimension(b"Height")?);
+    if dict
+        .get(b"ImageMask")
+        .and_then(Object::as_bool)
+        .unwrap_or(false)
+    {
+        return None;
+    }
+
+    let filters = stream.filters().unwrap_or_default();
+    if filters == [b"DCTDecode".as_slice()] {
+        return Some((width, height, stream.content.clone()));
+    }
+    if !filters.iter().all(|f| *f == b"FlateDecode") {
+        return None;
+    }
+
+    let channels = match dict.get(b"ColorSpace").ok()? {
+        Object::Name(name) if name == b"DeviceRGB" => 3,
+        Object::Name(name) if name == b"DeviceGray" => 1,
+        _ => return None,
+    };
+    if dict.get(b"BitsPerComponent").and_then(Object::as_i64).ok() != Some(8) {
+        return None;
+    }
+    let pixels = if filters.is_empty() {
+        stream.content.clone()
+    } else {
+        stream.decompressed_content().ok()?
+    };
+    let row_len = width as usize * channels;
+    let rows = pixels.get(..row_len * height as usize)?;
+    Some((width, height, encode_png(width, height, channels, rows)))
+}
+
+/// Encode 8-bit grayscale or RGB pixel rows as a PNG file.
+fn encode_png(width: u32, height: u32, channels: usize, pixels: &[u8]) -> Vec<u8> {
+    fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        out.extend((data.len() as u32).to_be_bytes());
+        let start = out.len();
+        out.extend(kind);
+        out.extend(data);
+        let mut crc = flate2::Crc::new();
+        crc.update(&out[start..]);
+        out.extend(crc.sum().to_be_bytes());
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // Bit depth 8; colour type 2 (RGB) or 0 (grayscale); default
+    // compression, filtering, and no interlacing
+    header.extend([8, if channels == 3 { 2 } else { 0 }, 0, 0, 0]);
+
+    // Each row is prefixed with its filter type, 0 (none)
+    let mut raw = Vec::with_capacity(pixels.len() + height as usize);
+    for row in pixels.chunks(width as usize * channels) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    // Writing to a Vec can't fail
+    let compressed = encoder
+        .write_all(&raw)
+        .and_then(|_| encoder.finish())
+        .unwrap_or_default();
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &compressed);
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// Validate the path, memory-map the file, and parse it.
+fn open_document(path: &str, password: Option<&str>) -> Result<Document> {
     let file_path = Path::new(path);
 
     if !file_path.exists() {
@@ -16,7 +1327,7 @@ pub fn extract_text(path: &str) -> Result<String> {
 
     if file_path
         .extension()
-        .map_or(true, |ext| ext.to_ascii_lowercase() != "pdf")
+        .is_none_or(|ext| !ext.eq_ignore_ascii_case("pdf"))
     {
         anyhow::bail!("File is not a PDF: {}", path);
     }
@@ -31,23 +1342,1227 @@ pub fn extract_text(path: &str) -> Result<String> {
     let mmap = unsafe { Mmap::map(&file) }
         .with_context(|| format!("Failed to memory-map file: {}", path))?;
 
-    let text = pdf_extract::extract_text_from_mem(&mmap[..])
-        .with_context(|| format!("Failed to extract text from PDF: {}", path))?;
+    load_document(&mmap[..], password).with_context(|| format!("Failed to read PDF: {}", path))
+}
 
-    // Normalize whitespace: collapse multiple spaces/newlines
-    let cleaned = text
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<&str>>()
-        .join("\n");
+/// Parse a PDF, decrypting it first if it is encrypted.
+///
+/// Encrypted PDFs without a password are tried with the empty user
+/// password, which is how "owner-password-only" PDFs are opened.
+fn load_document(buffer: &[u8], password: Option<&str>) -> Result<Document> {
+    let doc = Document::load_mem(buffer)?;
+    if !doc.is_encrypted() {
+        return Ok(doc);
+    }
 
-    if cleaned.is_empty() {
-        anyhow::bail!(
-            "No text could be extracted from the PDF. It may be image-based or encrypted: {}",
-            path
+    if doc.authenticate_password(password.unwrap_or("")).is_err() {
+        match password {
+            Some(_) => anyhow::bail!("Incorrect password for encrypted PDF"),
+            None => anyhow::bail!("PDF is password-protected; a password is required"),
+        }
+    }
+    decrypt_document(buffer, &doc, password.unwrap_or(""))
+}
+
+/// State for `decrypt_filter`, which lopdf only accepts as a plain `fn`.
+struct Decryption {
+    state: EncryptionState,
+    encrypt_id: ObjectId,
+    /// Object numbers stored directly in the file (not in object streams);
+    /// only these are encrypted individually
+    top_level: HashSet<u32>,
+    decrypted: HashSet<ObjectId>,
+}
+
+thread_local! {
+    static DECRYPTION: RefCell<Option<Decryption>> = const { RefCell::new(None) };
+}
+
+/// Re-read an encrypted PDF, decrypting objects as they are parsed.
+///
+/// lopdf only parses the objects of encrypted files it can open with the
+/// empty password, so the trailer's /Encrypt key is hidden from the reader
+/// (renamed in a copy of the buffer, which keeps every byte offset intact)
+/// and objects are decrypted in the load filter instead. Decrypting there,
+/// before object streams are unpacked, lets compressed objects load too.
+///
+/// The workaround has limits:
+/// - The whole file is copied out of the mmap to be patched, so an encrypted
+///   PDF takes its size in memory a second time while it loads.
+/// - `hide_encrypt_key` renames every `/Encrypt` in the raw bytes, not just
+///   the trailer's. One in plaintext, such as in an unencrypted stream or in
+///   metadata kept clear with /EncryptMetadata false, is renamed too, as is
+///   a compressed stream whose bytes happen to spell it.
+/// - lopdf takes the filter as a plain `fn`, so its state goes through the
+///   `DECRYPTION` thread local, set before the read and taken after it. That
+///   holds only while lopdf calls the filter on this thread, i.e. with its
+///   `rayon` feature off (pdf-extract disables lopdf's default features);
+///   with it on, objects in object streams would be lost.
+fn decrypt_document(buffer: &[u8], encrypted: &Document, password: &str) -> Result<Document> {
+    let algorithm = PasswordAlgorithm::try_from(encrypted)?;
+    let password = algorithm.sanitize_password(password)?;
+    let state = EncryptionState::decode(encrypted, &password)?;
+    let encrypt_id = encrypted
+        .trailer
+        .get(b"Encrypt")
+        .and_then(Object::as_reference)?;
+    let top_level = encrypted
+        .reference_table
+        .entries
+        .iter()
+        .filter(|(_, entry)| matches!(entry, XrefEntry::Normal { .. }))
+        .map(|(&id, _)| id)
+        .collect();
+
+    let patched = hide_encrypt_key(buffer);
+    DECRYPTION.with(|d| {
+        *d.borrow_mut() = Some(Decryption {
+            state,
+            encrypt_id,
+            top_level,
+            decrypted: HashSet::new(),
+        })
+    });
+    let reader = Reader {
+        buffer: &patched,
+        document: Document::new(),
+        encryption_state: None,
+        raw_objects: BTreeMap::new(),
+    };
+    let loaded = reader.read(Some(decrypt_filter));
+    let decryption = DECRYPTION.with(|d| d.borrow_mut().take());
+    let mut doc = loaded?;
+
+    // Streams whose /Length is an indirect reference are only filled in
+    // after the filter has run, so decrypt whatever it skipped
+    if let Some(d) = decryption {
+        for (&id, obj) in doc.objects.iter_mut() {
+            if d.top_level.contains(&id.0) && id != d.encrypt_id && !d.decrypted.contains(&id) {
+                decrypt_object(&d.state, id, obj)?;
+            }
+        }
+    }
+    Ok(doc)
+}
+
+fn decrypt_filter(id: ObjectId, obj: &mut Object) -> Option<(ObjectId, Object)> {
+    DECRYPTION.with(|d| {
+        if let Some(d) = d.borrow_mut().as_mut() {
+            let unread_stream = matches!(obj, Object::Stream(s) if s.content.is_empty());
+            if d.top_level.contains(&id.0)
+                && id != d.encrypt_id
+                && !unread_stream
+                && d.decrypted.insert(id)
+            {
+                // Undecryptable objects are left as they are, like lopdf does
+                let _ = decrypt_object(&d.state, id, obj);
+            }
+        }
+    });
+    Some((id, obj.clone()))
+}
+
+/// Rename every `/Encrypt` name token to `/Encryp_` (same length), wherever
+/// it appears (see `decrypt_document`).
+fn hide_encrypt_key(buffer: &[u8]) -> Vec<u8> {
+    const KEY: &[u8] = b"/Encrypt";
+    let mut out = buffer.to_vec();
+    let mut i = 0;
+    while let Some(pos) = out[i..].windows(KEY.len()).position(|w| w == KEY) {
+        let end = i + pos + KEY.len();
+        // Skip longer names such as /EncryptMetadata
+        let next = out.get(end).copied().unwrap_or(b' ');
+        if !next.is_ascii_alphanumeric() {
+            out[end - 1] = b'_';
+        }
+        i = end;
+    }
+    out
+}
+
+/// Fill in pages that have no text layer using OCR, in parallel.
+///
+/// Pages that fail OCR stay empty so one bad page doesn't sink the whole
+/// document; the first failure is returned for error reporting. Encrypted
+/// PDFs are rendered with `password`.
+#[cfg(feature = "ocr")]
+fn ocr_empty_pages(
+    path: &Path,
+    password: Option<&str>,
+    pages: &mut [String],
+    cleanup: Cleanup,
+) -> Option<anyhow::Error> {
+    let results: Vec<(usize, Result<String>)> = pages
+        .iter()
+        .enumerate()
+        .filter(|(_, page)| page.is_empty())
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>()
+        .into_par_iter()
+        .map(|i| (i, crate::ocr::ocr_page(path, i as u32 + 1, password)))
+        .collect();
+
+    let mut first_error = None;
+    for (i, result) in results {
+        match result {
+            Ok(text) => pages[i] = normalize(&text, cleanup),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    first_error
+}
+
+/// Normalize whitespace: trim lines and drop blank ones, or with
+/// `preserve_paragraphs` collapse each run of them into one. With `nfc`,
+/// characters are also composed to NFC.
+fn normalize(text: &str, cleanup: Cleanup) -> String {
+    let composed: String;
+    let text = if cleanup.nfc {
+        composed = text.nfc().collect();
+        &composed
+    } else {
+        text
+    };
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if !line.is_empty() {
+            lines.push(line);
+        } else if cleanup.preserve_paragraphs && lines.last().is_some_and(|l| !l.is_empty()) {
+            lines.push("");
+        }
+    }
+    if lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Join the lines of each paragraph into one, leaving table rows alone.
+fn join_lines(page: &str) -> String {
+    let mut out = String::with_capacity(page.len());
+    let mut prev: Option<&str> = None;
+    for line in page.lines() {
+        if let Some(prev) = prev {
+            let table = prev.starts_with('|') || line.starts_with('|');
+            let blank = prev.is_empty() || line.is_empty();
+            out.push(if table || blank { '\n' } else { ' ' });
+        }
+        out.push_str(line);
+        prev = Some(line);
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use pdf_extract::content::{Content, Operation};
+    use pdf_extract::{dictionary, Document, Object, Stream};
+    use std::path::PathBuf;
+
+    /// Write a PDF with one page per entry to a temp file.
+    /// Empty entries produce pages with no text layer.
+    pub(crate) fn write_test_pdf(name: &str, pages: &[&str]) -> PathBuf {
+        let pages = pages
+            .iter()
+            .map(|&text| {
+                if text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![(72, 720, text)]
+                }
+            })
+            .collect::<Vec<_>>();
+        write_layout_pdf(name, &pages)
+    }
+
+    /// Write a PDF whose pages place each (x, y, text) item at that position.
+    fn write_layout_pdf(name: &str, pages: &[Vec<(i64, i64, &str)>]) -> PathBuf {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+
+        let mut kids = Vec::new();
+        for items in pages {
+            let mut operations = Vec::new();
+            for &(x, y, text) in items {
+                operations.extend([
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![x.into(), y.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(text)]),
+                    Operation::new("ET", vec![]),
+                ]);
+            }
+            let content = Content { operations };
+            let content_id =
+                doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            });
+            kids.push(page_id.into());
+        }
+
+        let count = kids.len() as i64;
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => count,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let path = std::env::temp_dir().join(format!(
+            "rusty_rag_test_{}_{}.pdf",
+            std::process::id(),
+            name
+        ));
+        doc.save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_extract_text_joins_pages() {
+        let path = write_test_pdf("joins", &["First page text", "Second page text"]);
+        let text = extract_text(path.to_str().unwrap(), None, Cleanup::default()).unwrap();
+        assert!(text.contains("First page text"));
+        assert!(text.contains("Second page text"));
+        assert!(text.find("First").unwrap() < text.find("Second").unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_and_wrong_extension() {
+        let err = extract_text("/nonexistent/file.pdf", None, Cleanup::default()).unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+
+        let path = std::env::temp_dir().join(format!("rusty_rag_{}.txt", std::process::id()));
+        std::fs::write(&path, "not a pdf").unwrap();
+        let err = extract_text(path.to_str().unwrap(), None, Cleanup::default()).unwrap_err();
+        assert!(err.to_string().contains("not a PDF"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_extract_pages_numbers() {
+        let path = write_test_pdf("pages", &["Intro", "Methods", "Results"]);
+        let pages = extract_pages(path.to_str().unwrap(), None, Cleanup::default()).unwrap();
+        let numbers: Vec<u32> = pages.iter().map(|p| p.page_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert!(pages[1].text.contains("Methods"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[test]
+    fn test_extract_pages_skips_blank_pages() {
+        let path = write_test_pdf("gaps", &["Cover", "", "Body"]);
+        let pages = extract_pages(path.to_str().unwrap(), None, Cleanup::default()).unwrap();
+        let numbers: Vec<u32> = pages.iter().map(|p| p.page_number).collect();
+        assert_eq!(numbers, vec![1, 3]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_strip_furniture() {
+        let pages: Vec<Vec<(i64, i64, &str)>> = (1..=4)
+            .map(|n| {
+                let number = ["1", "2", "3", "4"][n - 1];
+                let body = ["Intro", "Methods", "Results", "Conclusion"][n - 1];
+                vec![
+                    (72, 760, "Annual Report 2023"),
+                    (72, 700, body),
+                    (300, 40, number),
+                ]
+            })
+            .collect();
+        let path = write_layout_pdf("furniture", &pages);
+        let path_str = path.to_str().unwrap();
+
+        let pages = extract_pages(path_str, None, Cleanup::default()).unwrap();
+        let texts: Vec<&str> = pages.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, ["Intro", "Methods", "Results", "Conclusion"]);
+
+        let cleanup = Cleanup {
+            strip_furniture: false,
+            ..Default::default()
+        };
+        let pages = extract_pages(path_str, None, cleanup).unwrap();
+        // The wide gaps around the body set it apart as its own paragraph
+        assert_eq!(pages[0].text, "Annual Report 2023\n\nIntro\n\n1");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let lines = [
+            (720, "Lines of the"),
+            (706, "first paragraph."),
+            (692, "Still first."),
+        ];
+        let more = [(660, "Second paragraph"), (646, "goes on here.")];
+        let page = lines
+            .iter()
+            .chain(&more)
+            .map(|&(y, text)| (72, y, text))
+            .collect();
+        let path = write_layout_pdf("paragraphs", &[page]);
+        let path_str = path.to_str().unwrap();
+
+        let text = extract_text(path_str, None, Cleanup::default()).unwrap();
+        assert_eq!(
+            text,
+            "Lines of the\nfirst paragraph.\nStill first.\n\nSecond paragraph\ngoes on here."
+        );
+
+        let cleanup = Cleanup {
+            join_lines: true,
+            ..Default::default()
+        };
+        let text = extract_text(path_str, None, cleanup).unwrap();
+        assert_eq!(
+            text,
+            "Lines of the first paragraph. Still first.\n\nSecond paragraph goes on here."
+        );
+
+        let cleanup = Cleanup {
+            preserve_paragraphs: false,
+            ..Default::default()
+        };
+        let text = extract_text(path_str, None, cleanup).unwrap();
+        assert_eq!(text.lines().count(), 5);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_dehyphenate() {
+        let mut pages = vec![
+            "Retrieval of infor-\nmation from docu-\nments.\nA self-\ncontained, state-of-the-\nart,".to_string(),
+            "Franco-\nPrussian COVID-\n19 | a-\n| b |\ntrailing dash -\nnext".to_string(),
+            "self-contained and soft\u{ad}\nhyphen".to_string(),
+        ];
+        dehyphenate(&mut pages);
+        assert_eq!(
+            pages[0],
+            "Retrieval of information\nfrom documents.\nA self-contained,\nstate-of-the-art,"
         );
+        assert_eq!(
+            pages[1],
+            "Franco-Prussian\nCOVID-19\n| a-\n| b |\ntrailing dash -\nnext"
+        );
+        assert_eq!(pages[2], "self-contained and softhyphen");
+    }
+
+    #[test]
+    fn test_furniture_needs_repetition() {
+        // Too few pages to tell furniture from content
+        let mut pages = vec!["Header\nA".to_string(), "Header\nB".to_string()];
+        strip_furniture(&mut pages);
+        assert_eq!(pages, ["Header\nA", "Header\nB"]);
+
+        // "Page N of M" varies only in its numbers; table rows are kept
+        let mut pages: Vec<String> = (1..=3)
+            .map(|n| format!("| a | b |\n| --- | --- |\n| {n} | x |\nPage {n} of 3"))
+            .collect();
+        // Repeats away from the edges of a page are content
+        pages.push("Appendix\nA\nB\nPage 9 of 3\nC\nD\nE".to_string());
+        strip_furniture(&mut pages);
+        assert_eq!(pages[2], "| a | b |\n| --- | --- |\n| 3 | x |");
+        assert_eq!(pages[3], "Appendix\nA\nB\nPage 9 of 3\nC\nD\nE");
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[test]
+    fn test_image_only_pdf_fails_without_ocr() {
+        let path = write_test_pdf("blank", &["", ""]);
+        let err = extract_text(path.to_str().unwrap(), None, Cleanup::default()).unwrap_err();
+        assert!(err.to_string().contains("image-based"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// AES-128 (V4) or RC4 (V2) encryption for a test PDF, after giving it
+    /// the file ID its key is derived from.
+    fn test_encryption_state(
+        doc: &mut Document,
+        user_password: &str,
+        aes: bool,
+    ) -> EncryptionState {
+        use pdf_extract::encryption::crypt_filters::{Aes128CryptFilter, CryptFilter};
+        use pdf_extract::{EncryptionVersion, Permissions};
+        use std::sync::Arc;
+
+        let id = Object::string_literal("0123456789abcdef");
+        doc.trailer.set("ID", Object::Array(vec![id.clone(), id]));
+        let version = if aes {
+            let filter: Arc<dyn CryptFilter> = Arc::new(Aes128CryptFilter);
+            EncryptionVersion::V4 {
+                document: doc,
+                encrypt_metadata: true,
+                crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filter)]),
+                stream_filter: b"StdCF".to_vec(),
+                string_filter: b"StdCF".to_vec(),
+                owner_password: "owner",
+                user_password,
+                permissions: Permissions::all(),
+            }
+        } else {
+            EncryptionVersion::V2 {
+                document: doc,
+                owner_password: "owner",
+                user_password,
+                key_length: 128,
+                permissions: Permissions::all(),
+            }
+        };
+        EncryptionState::try_from(version).unwrap()
+    }
+
+    /// Encrypt a test PDF in place with AES-128 (V4) or RC4 (V2).
+    fn encrypt_test_pdf(path: &Path, user_password: &str, aes: bool) {
+        let mut doc = Document::load(path).unwrap();
+        let state = test_encryption_state(&mut doc, user_password, aes);
+        doc.encrypt(&state).unwrap();
+        doc.save(path).unwrap();
+    }
+
+    /// Serialize `obj` in PDF syntax, strings in hex.
+    fn write_pdf_object(out: &mut Vec<u8>, obj: &Object) {
+        use std::io::Write;
+
+        match obj {
+            Object::Null => out.extend_from_slice(b"null"),
+            Object::Boolean(value) => write!(out, "{}", value).unwrap(),
+            Object::Integer(value) => write!(out, "{}", value).unwrap(),
+            Object::Real(value) => write!(out, "{}", value).unwrap(),
+            Object::Name(name) => {
+                out.push(b'/');
+                out.extend_from_slice(name);
+            }
+            Object::String(bytes, _) => {
+                out.push(b'<');
+                for byte in bytes {
+                    write!(out, "{:02x}", byte).unwrap();
+                }
+                out.push(b'>');
+            }
+            Object::Array(items) => {
+                out.push(b'[');
+                for item in items {
+                    write_pdf_object(out, item);
+                    out.push(b' ');
+                }
+                out.push(b']');
+            }
+            Object::Dictionary(dict) => {
+                out.extend_from_slice(b"<<");
+                for (key, value) in dict.iter() {
+                    out.push(b'/');
+                    out.extend_from_slice(key);
+                    out.push(b' ');
+                    write_pdf_object(out, value);
+                    out.push(b' ');
+                }
+                out.extend_from_slice(b">>");
+            }
+            Object::Stream(stream) => {
+                let mut dict = stream.dict.clone();
+                dict.set("Length", stream.content.len() as i64);
+                write_pdf_object(out, &Object::Dictionary(dict));
+                out.extend_from_slice(b"\nstream\n");
+                out.extend_from_slice(&stream.content);
+                out.extend_from_slice(b"\nendstream");
+            }
+            Object::Reference((id, generation)) => write!(out, "{} {} R", id, generation).unwrap(),
+        }
+    }
+
+    /// Rewrite a test PDF encrypted with AES-128 the way PDF 1.5 writers
+    /// store it: every object but the streams packed into one encrypted
+    /// object stream, indexed by a cross-reference stream rather than an
+    /// xref table and trailer.
+    fn encrypt_test_pdf_with_object_streams(path: &Path, user_password: &str) {
+        use pdf_extract::encryption::encrypt_object;
+        use pdf_extract::ObjectStream;
+
+        let mut doc = Document::load(path).unwrap();
+        let state = test_encryption_state(&mut doc, user_password, true);
+        let object_stream_id = (doc.max_id + 1, 0);
+        let encrypt_id = (doc.max_id + 2, 0);
+        let xref_id = (doc.max_id + 3, 0);
+        let size = doc.max_id as usize + 4;
+
+        let mut packed = ObjectStream::builder().build();
+        let mut packed_ids = Vec::new();
+        let mut top_level = Vec::new();
+        for (&id, obj) in &doc.objects {
+            if let Object::Stream(_) = obj {
+                let mut obj = obj.clone();
+                encrypt_object(&state, id, &mut obj).unwrap();
+                top_level.push((id, obj));
+            } else {
+                packed.add_object(id, obj.clone()).unwrap();
+                packed_ids.push(id);
+            }
+        }
+        let mut object_stream = Object::Stream(packed.to_stream_object().unwrap());
+        encrypt_object(&state, object_stream_id, &mut object_stream).unwrap();
+        top_level.push((object_stream_id, object_stream));
+        top_level.push((encrypt_id, Object::Dictionary(state.encode().unwrap())));
+
+        // Cross-reference entries: type, then offset or object stream, then
+        // index in it
+        let mut entries = vec![[0, 0, 0, 0, 0, 0xff, 0xff]; size];
+        let normal = |offset: usize| {
+            let [a, b, c, d] = (offset as u32).to_be_bytes();
+            [1, a, b, c, d, 0, 0]
+        };
+        let [a, b, c, d] = object_stream_id.0.to_be_bytes();
+        for (index, id) in packed_ids.iter().enumerate() {
+            let [e, f] = (index as u16).to_be_bytes();
+            entries[id.0 as usize] = [2, a, b, c, d, e, f];
+        }
+
+        let mut out = b"%PDF-1.5\n".to_vec();
+        for (id, obj) in &top_level {
+            entries[id.0 as usize] = normal(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", id.0).as_bytes());
+            write_pdf_object(&mut out, obj);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref_offset = out.len();
+        entries[xref_id.0 as usize] = normal(xref_offset);
+        let xref = Stream::new(
+            dictionary! {
+                "Type" => "XRef",
+                "Size" => size as i64,
+                "W" => vec![1.into(), 4.into(), 2.into()],
+                "Root" => doc.trailer.get(b"Root").unwrap().clone(),
+                "ID" => doc.trailer.get(b"ID").unwrap().clone(),
+                "Encrypt" => encrypt_id,
+            },
+            entries.concat(),
+        );
+        out.extend_from_slice(format!("{} 0 obj\n", xref_id.0).as_bytes());
+        write_pdf_object(&mut out, &Object::Stream(xref));
+        out.extend_from_slice(b"\nendobj\n");
+        out.extend_from_slice(format!("startxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+        std::fs::write(path, out).unwrap();
+    }
+
+    #[test]
+    fn test_password_protected_pdf() {
+        for aes in [true, false] {
+            let path = write_test_pdf(&format!("locked_{}", aes), &["Top secret", "Page two"]);
+            encrypt_test_pdf(&path, "hunter2", aes);
+            let path_str = path.to_str().unwrap();
+
+            let pages = extract_pages(path_str, Some("hunter2"), Cleanup::default()).unwrap();
+            assert_eq!(pages.len(), 2);
+            assert!(pages[0].text.contains("Top secret"));
+
+            let err = extract_text(path_str, Some("wrong"), Cleanup::default()).unwrap_err();
+            assert!(format!("{:#}", err).contains("Incorrect password"));
+            let err = extract_text(path_str, None, Cleanup::default()).unwrap_err();
+            assert!(format!("{:#}", err).contains("password is required"));
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    /// Whether `pdftoppm` and `tesseract` can be run, for OCR tests.
+    #[cfg(feature = "ocr")]
+    fn ocr_tools_installed() -> bool {
+        ["pdftoppm", "tesseract"]
+            .iter()
+            .all(|tool| std::process::Command::new(tool).arg("-v").output().is_ok())
+    }
+
+    #[cfg(feature = "ocr")]
+    #[test]
+    fn test_encrypted_image_only_pdf_is_ocred_with_password() {
+        if !ocr_tools_installed() {
+            eprintln!("pdftoppm or tesseract not installed; skipping");
+            return;
+        }
+        let path = write_test_pdf("locked_scan", &["", ""]);
+        encrypt_test_pdf(&path, "hunter2", true);
+        // The pages render and OCR to nothing, rather than failing to open
+        let err =
+            extract_text(path.to_str().unwrap(), Some("hunter2"), Cleanup::default()).unwrap_err();
+        assert!(err.to_string().contains("image-based"), "{:#}", err);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_pdf_with_object_streams() {
+        let path = write_test_pdf("locked_objstm", &["Packed secret", "Page two"]);
+        encrypt_test_pdf_with_object_streams(&path, "hunter2");
+        let raw = std::fs::read(&path).unwrap();
+        assert!(raw.windows(7).any(|w| w == b"/ObjStm"));
+        assert!(raw.windows(5).any(|w| w == b"/XRef"));
+        assert!(!raw.windows(6).any(|w| w == b"secret"));
+        let path_str = path.to_str().unwrap();
+
+        let pages = extract_pages(path_str, Some("hunter2"), Cleanup::default()).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].text.contains("Packed secret"));
+        assert!(pages[1].text.contains("Page two"));
+
+        let err = extract_text(path_str, Some("wrong"), Cleanup::default()).unwrap_err();
+        assert!(format!("{:#}", err).contains("Incorrect password"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_owner_password_only_pdf_opens_without_password() {
+        let path = write_test_pdf("owner_only", &["Readable by anyone"]);
+        encrypt_test_pdf(&path, "", true);
+        let text = extract_text(path.to_str().unwrap(), None, Cleanup::default()).unwrap();
+        assert!(text.contains("Readable by anyone"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_hide_encrypt_key_keeps_length() {
+        let buf = b"<< /Encrypt 5 0 R /EncryptMetadata true /Encrypt/X >>";
+        let out = hide_encrypt_key(buf);
+        assert_eq!(out.len(), buf.len());
+        assert_eq!(
+            &out[..],
+            &b"<< /Encryp_ 5 0 R /EncryptMetadata true /Encryp_/X >>"[..]
+        );
+    }
+
+    #[test]
+    fn test_extract_metadata() {
+        let path = write_test_pdf("meta", &["Body", "More"]);
+        let mut doc = Document::load(&path).unwrap();
+        let info_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Attention Is All You Need"),
+            "Author" => Object::string_literal("Vaswani et al."),
+            "Subject" => Object::string_literal("  "),
+            "CreationDate" => Object::string_literal("D:20170612173000+02'00'"),
+        });
+        doc.trailer.set("Info", info_id);
+        doc.save(&path).unwrap();
+
+        let meta = extract_metadata(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Attention Is All You Need"));
+        assert_eq!(meta.author.as_deref(), Some("Vaswani et al."));
+        assert_eq!(meta.subject, None);
+        assert_eq!(meta.creation_date.as_deref(), Some("2017-06-12T17:30:00+02:00"));
+        assert_eq!(meta.page_count, 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_pdf_date() {
+        assert_eq!(
+            parse_pdf_date("D:20230401123000Z").as_deref(),
+            Some("2023-04-01T12:30:00Z")
+        );
+        assert_eq!(parse_pdf_date("D:2023").as_deref(), Some("2023-01-01T00:00:00"));
+        assert_eq!(
+            parse_pdf_date("20230401-05'30").as_deref(),
+            Some("2023-04-01T00:00:00-05:30")
+        );
+        assert_eq!(parse_pdf_date("garbage"), None);
+    }
+
+    #[test]
+    fn test_table_extracted_as_markdown() {
+        let page = vec![
+            (72, 720, "Quarterly results"),
+            (72, 690, "Region"),
+            (200, 690, "Revenue"),
+            (320, 690, "Growth"),
+            (72, 675, "North"),
+            (200, 675, "1,200"),
+            (320, 675, "4%"),
+            (72, 660, "South"),
+            (320, 660, "n/a"),
+            (72, 630, "Figures are unaudited."),
+        ];
+        let path = write_layout_pdf("table", &[page]);
+        let text = extract_text(path.to_str().unwrap(), None, Cleanup::default()).unwrap();
+        assert_eq!(
+            text,
+            "Quarterly results\n\
+             | Region | Revenue | Growth |\n\
+             | --- | --- | --- |\n\
+             | North | 1,200 | 4% |\n\
+             | South |  | n/a |\n\
+             Figures are unaudited."
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_layout_text_without_table() {
+        let cell = |x: f64, text: &str| Cell {
+            x,
+            end: x + 50.0,
+            text: text.to_string(),
+        };
+        // A lone wide gap is not a table, nor are lines that don't line up
+        let lines = vec![
+            vec![cell(72.0, "Chapter 1"), cell(400.0, "Page 5")],
+            vec![cell(72.0, "Plain prose")],
+            vec![cell(72.0, "a | b"), cell(200.0, "c")],
+            vec![cell(300.0, "d"), cell(400.0, "e")],
+        ];
+        assert_eq!(
+            layout_text(&lines),
+            "Chapter 1 Page 5\nPlain prose\na | b c\nd e"
+        );
+        // Nor is a running header over a line that happens to match it
+        let lines = vec![
+            vec![cell(72.0, "Annual Report"), cell(400.0, "2023")],
+            vec![cell(72.0, "Summary"), cell(400.0, "p. 1")],
+        ];
+        assert_eq!(layout_text(&lines), "Annual Report 2023\nSummary p. 1");
+    }
+
+    #[test]
+    fn test_two_column_prose_is_not_a_table() {
+        // Columns whose lines the content stream gives row by row, across
+        // both columns, line up like a table's
+        let left = [
+            "Retrieval systems rank the passages",
+            "of a large corpus by how well they",
+            "match the words of a user query,",
+            "then pass the best ones to a model.",
+        ];
+        let right = [
+            "Dense retrievers embed the query",
+            "and each passage in the same space",
+            "and rank passages by the distance",
+            "between the two resulting vectors.",
+        ];
+        let page: Vec<(i64, i64, &str)> = (0..4)
+            .flat_map(|i| {
+                let y = 720 - 15 * i as i64;
+                [(72, y, left[i]), (320, y, right[i])]
+            })
+            .collect();
+        let path = write_layout_pdf("two_columns", &[page]);
+        let text = extract_text(path.to_str().unwrap(), None, Cleanup::default()).unwrap();
+        assert!(!text.contains('|'), "{}", text);
+        assert!(text.starts_with("Retrieval systems rank the passages Dense retrievers"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_extract_images() {
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        let path = write_test_pdf("images", &["Figure 1", "Figure 2"]);
+        let mut doc = Document::load(&path).unwrap();
+        let image = |width: i64, height: i64, color_space: &str, filter: Option<&str>, data| {
+            let mut dict = dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => width,
+                "Height" => height,
+                "ColorSpace" => color_space,
+                "BitsPerComponent" => 8,
+            };
+            if let Some(filter) = filter {
+                dict.set("Filter", filter);
+            }
+            Stream::new(dict, data)
+        };
+
+        let jpeg_data = b"\xFF\xD8not really a jpeg\xFF\xD9".to_vec();
+        let pixels: Vec<u8> = (0..120 * 100).map(|i| (i % 251) as u8).collect();
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(&pixels).unwrap();
+        let jpeg = doc.add_object(image(
+            640,
+            480,
+            "DeviceRGB",
+            Some("DCTDecode"),
+            jpeg_data.clone(),
+        ));
+        let bitmap = doc.add_object(image(
+            120,
+            100,
+            "DeviceGray",
+            Some("FlateDecode"),
+            encoder.finish().unwrap(),
+        ));
+        let icon = doc.add_object(image(16, 16, "DeviceRGB", None, vec![0; 16 * 16 * 3]));
+
+        // The JPEG is drawn on both pages, like a logo would be
+        let pages = doc.get_pages();
+        for (number, xobjects) in [(1, [jpeg, icon]), (2, [jpeg, bitmap])] {
+            let page = doc.get_object_mut(pages[&number]).unwrap();
+            let page = page.as_dict_mut().unwrap();
+            let mut resources = Dictionary::new();
+            for (i, id) in xobjects.into_iter().enumerate() {
+                resources.set(format!("Im{}", i), id);
+            }
+            page.set("Resources", dictionary! { "XObject" => resources });
+        }
+        doc.save(&path).unwrap();
+
+        let images = extract_images(path.to_str().unwrap(), None, 32).unwrap();
+        let sizes: Vec<(u32, u32, u32)> = images
+            .iter()
+            .map(|image| (image.page_number, image.width, image.height))
+            .collect();
+        assert_eq!(sizes, vec![(1, 640, 480), (2, 120, 100)]);
+        assert_eq!(images[0].data, jpeg_data);
+
+        let png = &images[1].data;
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // IHDR: 120 x 100, 8-bit grayscale
+        assert_eq!(png[16..26], [0, 0, 0, 120, 0, 0, 0, 100, 8, 0]);
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut rows = Vec::new();
+        ZlibDecoder::new(&png[41..41 + idat_len])
+            .read_to_end(&mut rows)
+            .unwrap();
+        let expected: Vec<u8> = pixels
+            .chunks(120)
+            .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+            .collect();
+        assert_eq!(rows, expected);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_text_coverage() {
+        let path = write_test_pdf("coverage", &["Intro", "", "", "", " "]);
+        let mut doc = Document::load(&path).unwrap();
+        let scan = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 2,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![0; 4],
+        ));
+        let draw = |name: &str| {
+            let content = Content {
+                operations: vec![Operation::new("Do", vec![Object::Name(name.into())])],
+            };
+            content.encode().unwrap()
+        };
+        // A form that only wraps the scan, as some scanners produce
+        let form = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "Resources" => dictionary! { "XObject" => dictionary! { "Im0" => scan } },
+            },
+            draw("Im0"),
+        ));
+
+        let pages = doc.get_pages();
+        for (number, name, id) in [(2, "Im0", scan), (4, "Fm0", form)] {
+            let content = doc.add_object(Stream::new(dictionary! {}, draw(name)));
+            let page = doc.get_object_mut(pages[&number]).unwrap();
+            let page = page.as_dict_mut().unwrap();
+            page.set(
+                "Resources",
+                dictionary! { "XObject" => dictionary! { name => id } },
+            );
+            page.set("Contents", content);
+        }
+        doc.save(&path).unwrap();
+
+        let coverage = text_coverage(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(
+            coverage,
+            TextCoverage {
+                page_count: 5,
+                text_pages: vec![1],
+                image_pages: vec![2, 4],
+                blank_pages: vec![3, 5],
+            }
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_extract_form_fields() {
+        let path = write_test_pdf("form", &["Application form", "Page two"]);
+        let mut doc = Document::load(&path).unwrap();
+        let widget = |mut field: Dictionary| {
+            field.set("Type", "Annot");
+            field.set("Subtype", "Widget");
+            field
+        };
+
+        let name = doc.add_object(widget(dictionary! {
+            "FT" => "Tx",
+            "T" => Object::string_literal("name"),
+            "TU" => Object::string_literal("Full name"),
+            "V" => Object::string_literal("Ada Lovelace"),
+        }));
+        let email = doc.add_object(widget(dictionary! {
+            "T" => Object::string_literal("email"),
+            "V" => Object::string_literal("ada@example.com"),
+        }));
+        let contact = doc.add_object(dictionary! {
+            "FT" => "Tx",
+            "T" => Object::string_literal("contact"),
+            "Kids" => vec![email.into()],
+        });
+        let subscribe = doc.add_object(widget(dictionary! {
+            "FT" => "Btn",
+            "T" => Object::string_literal("subscribe"),
+            "V" => "Yes",
+        }));
+        let terms = doc.add_object(widget(dictionary! {
+            "FT" => "Btn",
+            "T" => Object::string_literal("terms"),
+            "V" => "Off",
+        }));
+        // A radio group's options are widgets without names of their own
+        let options = [
+            doc.add_object(widget(dictionary! {})),
+            doc.add_object(widget(dictionary! {})),
+        ];
+        let plan = doc.add_object(dictionary! {
+            "FT" => "Btn",
+            "Ff" => FLAG_RADIO,
+            "T" => Object::string_literal("plan"),
+            "V" => "Premium",
+            "Kids" => options.iter().map(|&id| id.into()).collect::<Vec<Object>>(),
+        });
+        let submit = doc.add_object(widget(dictionary! {
+            "FT" => "Btn",
+            "Ff" => FLAG_PUSHBUTTON,
+            "T" => Object::string_literal("submit"),
+        }));
+        let notes = doc.add_object(widget(dictionary! {
+            "FT" => "Tx",
+            "T" => Object::string_literal("notes"),
+        }));
+
+        let pages = doc.get_pages();
+        let annots = [
+            (1, vec![name, options[0], options[1], submit]),
+            (2, vec![email, subscribe, terms, notes]),
+        ];
+        for (number, ids) in annots {
+            let page = doc.get_object_mut(pages[&number]).unwrap();
+            let annots: Vec<Object> = ids.into_iter().map(Object::from).collect();
+            page.as_dict_mut().unwrap().set("Annots", annots);
+        }
+        let fields: Vec<Object> = [name, contact, subscribe, terms, plan, submit, notes]
+            .into_iter()
+            .map(Object::from)
+            .collect();
+        let form = doc.add_object(dictionary! { "Fields" => fields });
+        doc.catalog_mut().unwrap().set("AcroForm", form);
+        doc.save(&path).unwrap();
+
+        let fields = extract_form_fields(path.to_str().unwrap(), None).unwrap();
+        let fields: Vec<(Option<u32>, &str, &str)> = fields
+            .iter()
+            .map(|f| (f.page_number, f.label.as_str(), f.value.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                (Some(1), "Full name", "Ada Lovelace"),
+                (Some(2), "contact.email", "ada@example.com"),
+                (Some(2), "subscribe", "Yes"),
+                (Some(2), "terms", "No"),
+                (Some(1), "plan", "Premium"),
+            ]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_extract_sections_from_outline() {
+        let path = write_layout_pdf(
+            "outline",
+            &[
+                vec![(72, 720, "Preface"), (72, 700, "Some preface text.")],
+                vec![
+                    (72, 720, "More preface."),
+                    (72, 690, "1 Introduction"),
+                    (72, 670, "Intro body."),
+                ],
+                vec![(72, 720, "1.1 Background"), (72, 700, "Background body.")],
+                vec![(72, 720, "Appendix text")],
+            ],
+        );
+        let mut doc = Document::load(&path).unwrap();
+        let pages = doc.get_pages();
+        let dest = |page: u32| -> Object {
+            vec![
+                pages[&page].into(),
+                "XYZ".into(),
+                Object::Null,
+                Object::Null,
+                Object::Null,
+            ]
+            .into()
+        };
+
+        // Entries point at pages directly, through a GoTo action to a
+        // name-tree destination, and by name into the catalog's /Dests
+        let outlines_id = doc.new_object_id();
+        let intro_id = doc.new_object_id();
+        let background_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("1.1  Background"),
+            "Parent" => intro_id,
+            "A" => dictionary! { "S" => "GoTo", "D" => Object::string_literal("bg") },
+        });
+        let appendix_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Appendix"),
+            "Parent" => outlines_id,
+            "Dest" => "app",
+        });
+        let link_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Website"),
+            "Parent" => outlines_id,
+            "Prev" => appendix_id,
+            "A" => dictionary! { "S" => "URI", "URI" => Object::string_literal("https://example.com") },
+        });
+        doc.get_object_mut(appendix_id)
+            .unwrap()
+            .as_dict_mut()
+            .unwrap()
+            .set("Next", link_id);
+        doc.objects.insert(
+            intro_id,
+            Object::Dictionary(dictionary! {
+                "Title" => Object::string_literal("1 Introduction"),
+                "Parent" => outlines_id,
+                "Dest" => dest(2),
+                "First" => background_id,
+                "Last" => background_id,
+                "Next" => appendix_id,
+            }),
+        );
+        doc.objects.insert(
+            outlines_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Outlines",
+                "First" => intro_id,
+                "Last" => link_id,
+            }),
+        );
+        let name_tree = dictionary! {
+            "Names" => vec![Object::string_literal("bg"), dictionary! { "D" => dest(3) }.into()],
+        };
+        let catalog = doc.catalog_mut().unwrap();
+        catalog.set("Outlines", outlines_id);
+        catalog.set("Names", dictionary! { "Dests" => name_tree });
+        catalog.set("Dests", dictionary! { "app" => dest(4) });
+        doc.save(&path).unwrap();
+
+        let sections = extract_sections(path.to_str().unwrap(), None, Cleanup::default()).unwrap();
+        let spans: Vec<(u32, String, &str)> = sections
+            .iter()
+            .map(|s| (s.page_number, s.path.join(" > "), s.text.as_str()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (1, "".into(), "Preface\nSome preface text."),
+                (2, "".into(), "More preface."),
+                (2, "1 Introduction".into(), "1 Introduction\nIntro body."),
+                (
+                    3,
+                    "1 Introduction > 1.1 Background".into(),
+                    "1.1 Background\nBackground body."
+                ),
+                (4, "Appendix".into(), "Appendix text"),
+            ]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_split_sections_without_title_on_page() {
+        let entry = |level, title: &str, page_number| OutlineEntry {
+            level,
+            title: title.to_string(),
+            page_number,
+        };
+        let pages = vec![
+            "Cover".to_string(),
+            "Text without the heading.".to_string(),
+            "A Very Long Chapter Title\nThat Wraps\nBody.".to_string(),
+        ];
+        let outline = vec![
+            entry(1, "Part One", 2),
+            entry(1, "A very long chapter title that wraps", 3),
+        ];
+        let paths: Vec<(u32, Vec<String>)> = split_sections(&pages, &outline)
+            .into_iter()
+            .map(|s| (s.page_number, s.path))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (1, vec![]),
+                (2, vec!["Part One".to_string()]),
+                (3, vec!["A very long chapter title that wraps".to_string()]),
+            ]
+        );
+        assert_eq!(
+            find_title("x\n  INTRO   duction\n", "intro duction"),
+            Some(2)
+        );
+        assert_eq!(find_title("Intro", ""), None);
     }
 
-    Ok(cleaned)
+    #[test]
+    fn test_normalize() {
+        let squash = Cleanup {
+            preserve_paragraphs: false,
+            ..Default::default()
+        };
+        assert_eq!(normalize("  a  \n\n\n   b\n", squash), "a\nb");
+        assert_eq!(normalize("\n \n", squash), "");
+        assert_eq!(normalize("\n a\n\n \nb\nc\n\n", Cleanup::default()), "a\n\nb\nc");
+        // "e" followed by a combining acute accent composes to "é"
+        assert_eq!(normalize("cafe\u{301}", Cleanup::default()), "caf\u{e9}");
+        let raw = Cleanup {
+            nfc: false,
+            ..Default::default()
+        };
+        assert_eq!(normalize("cafe\u{301}", raw), "cafe\u{301}");
+    }
+
+    #[test]
+    fn test_join_lines() {
+        assert_eq!(
+            join_lines("Lines of one\nparagraph.\n\nNext one.\n| a | b |\n| --- | --- |\nAfter"),
+            "Lines of one paragraph.\n\nNext one.\n| a | b |\n| --- | --- |\nAfter"
+        );
+    }
 }
+
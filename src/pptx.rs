@@ -0,0 +1,380 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::archive::ZipArchive;
+use crate::epub::resolve;
+use crate::xml::{attribute, local_name, XmlEvent, XmlReader};
+
+/// Relationship type of a slide's speaker notes, minus the namespace URI.
+const NOTES_RELATIONSHIP: &str = "/notesSlide";
+
+/// Placeholders whose text is slide furniture rather than content.
+const SKIPPED_PLACEHOLDERS: [&str; 4] = ["sldNum", "dt", "ftr", "hdr"];
+
+/// A slide read from a PowerPoint deck.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slide {
+    /// 1-based position in the deck
+    pub number: u32,
+    /// Text of the slide's title placeholder, if it has one
+    pub title: Option<String>,
+    /// The title as a `#` heading, then the body text, then any speaker
+    /// notes under a "Speaker notes:" line; tables are markdown
+    pub text: String,
+}
+
+/// Extracts the slides of a PowerPoint (.pptx) deck, in presentation order.
+///
+/// Each slide's text holds its title, the text of its shapes and tables,
+/// and its speaker notes. Slide numbers, dates, and footers are left out,
+/// as are slides without any text.
+pub fn extract_slides(path: &str) -> Result<Vec<Slide>> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    if file_path
+        .extension()
+        .is_none_or(|ext| !ext.eq_ignore_ascii_case("pptx"))
+    {
+        anyhow::bail!("File is not a PPTX: {}", path);
+    }
+
+    let data =
+        std::fs::read(file_path).with_context(|| format!("Failed to open file: {}", path))?;
+    let slides = read_deck(&data).with_context(|| format!("Failed to read PPTX: {}", path))?;
+
+    if slides.is_empty() {
+        anyhow::bail!("No text could be extracted from the PPTX: {}", path);
+    }
+    Ok(slides)
+}
+
+fn read_deck(data: &[u8]) -> Result<Vec<Slide>> {
+    let archive = ZipArchive::new(data)?;
+    let presentation = archive.read_string("ppt/presentation.xml")?;
+    let rels = archive.read_string("ppt/_rels/presentation.xml.rels")?;
+    let rels = relationships(&rels);
+
+    // The slide list gives the order; slide file names need not match it
+    let slide_ids: Vec<String> = XmlReader::new(&presentation)
+        .filter_map(|event| match event {
+            // `id` is the slide's numeric id; `r:id` names the relationship
+            XmlEvent::Start { name, attrs, .. } if local_name(name) == "sldId" => {
+                attribute(attrs, "r:id").map(|id| id.into_owned())
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut slides = Vec::new();
+    for (i, id) in slide_ids.iter().enumerate() {
+        let Some(target) = rels.iter().find(|r| &r.id == id).map(|r| &r.target) else {
+            continue;
+        };
+        let part = resolve("ppt", target);
+        let slide = read_shapes(&archive.read_string(&part)?);
+
+        // Notes hang off the slide's own relationships
+        let (dir, file) = part.rsplit_once('/').unwrap_or(("", &part));
+        let slide_rels = format!("{}/_rels/{}.rels", dir, file);
+        let notes = if archive.contains(&slide_rels) {
+            relationships(&archive.read_string(&slide_rels)?)
+                .into_iter()
+                .find(|r| r.kind.ends_with(NOTES_RELATIONSHIP))
+                .map(|r| resolve(dir, &r.target))
+        } else {
+            None
+        };
+        let notes = match notes {
+            Some(part) if archive.contains(&part) => read_shapes(&archive.read_string(&part)?).body,
+            _ => Vec::new(),
+        };
+
+        let mut blocks = Vec::new();
+        if let Some(title) = &slide.title {
+            blocks.push(format!("# {}", title));
+        }
+        blocks.extend(slide.body);
+        if !notes.is_empty() {
+            blocks.push("Speaker notes:".to_string());
+            blocks.extend(notes);
+        }
+        if blocks.is_empty() {
+            continue;
+        }
+        slides.push(Slide {
+            number: i as u32 + 1,
+            title: slide.title,
+            text: blocks.join("\n"),
+        });
+    }
+    Ok(slides)
+}
+
+/// An entry of a `.rels` part.
+struct Relationship {
+    id: String,
+    /// Relationship type URI
+    kind: String,
+    target: String,
+}
+
+fn relationships(rels: &str) -> Vec<Relationship> {
+    XmlReader::new(rels)
+        .filter_map(|event| match event {
+            XmlEvent::Start { name, attrs, .. } if local_name(name) == "Relationship" => {
+                Some(Relationship {
+                    id: attribute(attrs, "Id")?.into_owned(),
+                    kind: attribute(attrs, "Type").unwrap_or_default().into_owned(),
+                    target: attribute(attrs, "Target")?.into_owned(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Text of a slide (or notes page), split into its title and other blocks.
+#[derive(Default)]
+struct Shapes {
+    title: Option<String>,
+    /// Paragraphs and markdown tables, in document order
+    body: Vec<String>,
+}
+
+/// Shape being read.
+#[derive(Default)]
+struct Shape {
+    title: bool,
+    skipped: bool,
+    paragraphs: Vec<String>,
+}
+
+/// Walk a slide's shape tree, collecting the text of its text boxes,
+/// placeholders, and tables.
+fn read_shapes(xml: &str) -> Shapes {
+    let mut shapes = Shapes::default();
+    let mut shape = Shape::default();
+    let mut para = String::new();
+    // Rows of cells of the table being read
+    let mut table: Option<Vec<Vec<String>>> = None;
+    let mut in_text = false;
+
+    for event in XmlReader::new(xml) {
+        match event {
+            XmlEvent::Start {
+                name,
+                attrs,
+                self_closing,
+            } => match local_name(name) {
+                "sp" => shape = Shape::default(),
+                "ph" => {
+                    let kind = attribute(attrs, "type").unwrap_or_default();
+                    shape.title = matches!(kind.as_ref(), "title" | "ctrTitle");
+                    shape.skipped = SKIPPED_PLACEHOLDERS.contains(&kind.as_ref());
+                }
+                "p" => para.clear(),
+                "t" => in_text = !self_closing,
+                "br" => para.push('\n'),
+                "tbl" => table = Some(Vec::new()),
+                "tr" => {
+                    if let Some(rows) = &mut table {
+                        rows.push(Vec::new());
+                    }
+                }
+                "tc" => {
+                    if let Some(row) = table.as_mut().and_then(|rows| rows.last_mut()) {
+                        row.push(String::new());
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::End { name } => match local_name(name) {
+                "t" => in_text = false,
+                "p" => {
+                    let text = para.trim();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    match table
+                        .as_mut()
+                        .and_then(|rows| rows.last_mut())
+                        .and_then(|row| row.last_mut())
+                    {
+                        Some(cell) => {
+                            if !cell.is_empty() {
+                                cell.push(' ');
+                            }
+                            cell.push_str(text);
+                        }
+                        None => shape.paragraphs.push(text.to_string()),
+                    }
+                }
+                "sp" => {
+                    let shape = std::mem::take(&mut shape);
+                    if shape.skipped || shape.paragraphs.is_empty() {
+                        continue;
+                    }
+                    if shape.title && shapes.title.is_none() {
+                        shapes.title = Some(shape.paragraphs.join(" "));
+                    } else {
+                        shapes.body.extend(shape.paragraphs);
+                    }
+                }
+                "tbl" => {
+                    let Some(rows) = table.take() else { continue };
+                    if rows.iter().flatten().any(|cell| !cell.is_empty()) {
+                        shapes.body.push(crate::markdown::table(&rows));
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::Text(text) if in_text => para.push_str(&text),
+            XmlEvent::Text(_) => {}
+        }
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::build_zip;
+
+    const PRESENTATION: &str = r#"<p:presentation xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <p:sldIdLst><p:sldId id="256" r:id="rId3"/><p:sldId id="257" r:id="rId2"/><p:sldId id="258" r:id="rId4"/></p:sldIdLst>
+</p:presentation>"#;
+
+    const RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide1.xml"/>
+  <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide2.xml"/>
+  <Relationship Id="rId4" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide3.xml"/>
+</Relationships>"#;
+
+    fn shape(placeholder: Option<&str>, paragraphs: &[&str]) -> String {
+        let ph = placeholder.map_or(String::new(), |kind| format!(r#"<p:ph type="{}"/>"#, kind));
+        let paragraphs: String = paragraphs
+            .iter()
+            .map(|text| format!("<a:p><a:r><a:t>{}</a:t></a:r></a:p>", text))
+            .collect();
+        format!(
+            "<p:sp><p:nvSpPr><p:cNvPr id=\"2\" name=\"Shape\"/><p:nvPr>{}</p:nvPr></p:nvSpPr>\
+             <p:txBody><a:bodyPr/>{}</p:txBody></p:sp>",
+            ph, paragraphs
+        )
+    }
+
+    fn slide(shapes: &str) -> String {
+        format!(
+            r#"<p:sld xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main"><p:cSld><p:spTree>{}</p:spTree></p:cSld></p:sld>"#,
+            shapes
+        )
+    }
+
+    #[test]
+    fn test_read_shapes() {
+        let cell = |text: &str| {
+            format!(
+                "<a:tc><a:txBody><a:p><a:r><a:t>{}</a:t></a:r></a:p></a:txBody></a:tc>",
+                text
+            )
+        };
+        let table = format!(
+            "<p:graphicFrame><a:graphic><a:graphicData><a:tbl><a:tr>{}{}</a:tr><a:tr>{}{}</a:tr></a:tbl></a:graphicData></a:graphic></p:graphicFrame>",
+            cell("Quarter"),
+            cell("Revenue"),
+            cell("Q1"),
+            cell("1.2M")
+        );
+        let xml = slide(&format!(
+            "{}{}{}{}",
+            shape(Some("title"), &["Results &amp; outlook"]),
+            shape(Some("body"), &["Revenue grew", "", "Costs fell"]),
+            table,
+            shape(Some("sldNum"), &["7"])
+        ));
+        let shapes = read_shapes(&xml);
+        assert_eq!(shapes.title.as_deref(), Some("Results & outlook"));
+        assert_eq!(
+            shapes.body,
+            [
+                "Revenue grew",
+                "Costs fell",
+                "| Quarter | Revenue |\n| --- | --- |\n| Q1 | 1.2M |"
+            ]
+        );
+    }
+
+    fn write_pptx(name: &str) -> std::path::PathBuf {
+        let slide1 = slide(&shape(Some("ctrTitle"), &["Roadmap"]));
+        let slide2 = slide(&format!(
+            "{}{}",
+            shape(Some("title"), &["Agenda"]),
+            shape(None, &["Budget review"])
+        ));
+        let slide3 = slide(&shape(Some("sldNum"), &["3"]));
+        let slide_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/notesSlide" Target="../notesSlides/notesSlide1.xml"/></Relationships>"#;
+        let notes = slide(&format!(
+            "{}{}",
+            shape(Some("body"), &["Mention the Q3 delay"]),
+            shape(Some("sldNum"), &["2"])
+        ));
+        let data = build_zip(
+            &[
+                ("ppt/presentation.xml", PRESENTATION.as_bytes()),
+                ("ppt/_rels/presentation.xml.rels", RELS.as_bytes()),
+                ("ppt/slides/slide1.xml", slide1.as_bytes()),
+                ("ppt/slides/slide2.xml", slide2.as_bytes()),
+                ("ppt/slides/slide3.xml", slide3.as_bytes()),
+                ("ppt/slides/_rels/slide1.xml.rels", slide_rels.as_bytes()),
+                ("ppt/notesSlides/notesSlide1.xml", notes.as_bytes()),
+            ],
+            false,
+        );
+        let path = std::env::temp_dir().join(format!(
+            "rusty_rag_test_{}_{}.pptx",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_extract_slides() {
+        let path = write_pptx("deck");
+        let slides = extract_slides(path.to_str().unwrap()).unwrap();
+        // slide2.xml comes first in the slide list; slide3.xml has no text
+        assert_eq!(
+            slides,
+            [
+                Slide {
+                    number: 1,
+                    title: Some("Agenda".into()),
+                    text: "# Agenda\nBudget review".into(),
+                },
+                Slide {
+                    number: 2,
+                    title: Some("Roadmap".into()),
+                    text: "# Roadmap\nSpeaker notes:\nMention the Q3 delay".into(),
+                },
+            ]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = extract_slides("/nonexistent/deck.pptx").unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.pptx", std::process::id()));
+        std::fs::write(&path, b"not a zip").unwrap();
+        let err = extract_slides(path.to_str().unwrap()).unwrap_err();
+        assert!(format!("{:#}", err).contains("Failed to read PPTX"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
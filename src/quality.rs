@@ -0,0 +1,152 @@
+//! Detection of garbage chunks: text that would only add noise to the
+//! index, such as page-number runs, rules of repeated characters, and the
+//! symbol soup OCR produces from images and scans.
+
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// How much of a chunk may look like garbage before it is dropped.
+#[derive(Debug, Clone, Copy)]
+pub struct QualityLimits {
+    /// Largest share of a chunk's letters and digits that may be digits
+    pub max_digit_ratio: f64,
+    /// Largest share of a chunk's non-space characters that may be one
+    /// character repeated
+    pub max_repeat_ratio: f64,
+    /// Largest share of a chunk's words that may be noise: words mixing
+    /// letters with digits or symbols, or holding no letters or digits
+    pub max_noise_ratio: f64,
+    /// Smallest share of a chunk's non-space characters that must be letters
+    pub min_alpha_ratio: f64,
+}
+
+impl Default for QualityLimits {
+    fn default() -> Self {
+        QualityLimits {
+            max_digit_ratio: 0.8,
+            max_repeat_ratio: 0.5,
+            max_noise_ratio: 0.5,
+            min_alpha_ratio: 0.25,
+        }
+    }
+}
+
+/// For each chunk, why it is garbage ("digits", "repeated", "noise", or
+/// "alphabetic" for too few letters), or `None` for chunks to keep.
+pub fn garbage_chunks(chunks: &[String], limits: &QualityLimits) -> Vec<Option<&'static str>> {
+    chunks
+        .par_iter()
+        .map(|c| garbage_reason(c, limits))
+        .collect()
+}
+
+/// Why `chunk` is garbage, checked in the order of `garbage_chunks`'
+/// reasons, or `None`.
+fn garbage_reason(chunk: &str, limits: &QualityLimits) -> Option<&'static str> {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    let (mut letters, mut digits, mut visible) = (0usize, 0usize, 0usize);
+    for c in chunk.chars().filter(|c| !c.is_whitespace()) {
+        visible += 1;
+        if c.is_alphabetic() {
+            letters += 1;
+        } else if c.is_numeric() {
+            digits += 1;
+        }
+        *counts.entry(c).or_default() += 1;
+    }
+    if visible == 0 {
+        return Some("alphabetic");
+    }
+
+    let ratio = |part: usize, whole: usize| part as f64 / whole as f64;
+    if letters + digits > 0 && ratio(digits, letters + digits) > limits.max_digit_ratio {
+        return Some("digits");
+    }
+    let most_repeated = counts.values().copied().max().unwrap_or(0);
+    if visible > 1 && ratio(most_repeated, visible) > limits.max_repeat_ratio {
+        return Some("repeated");
+    }
+    let words: Vec<&str> = chunk.split_whitespace().collect();
+    let noise = words.iter().filter(|word| is_noise(word)).count();
+    if ratio(noise, words.len()) > limits.max_noise_ratio {
+        return Some("noise");
+    }
+    if ratio(letters, visible) < limits.min_alpha_ratio {
+        return Some("alphabetic");
+    }
+    None
+}
+
+/// Whether a word looks like OCR noise rather than a word or a number.
+///
+/// Punctuation around a word is ignored, as are apostrophes, hyphens, and
+/// periods inside it ("don't", "e.g.") and separators inside numbers
+/// ("1,234.5", "2024-03-01", "12:30"). Markdown table borders ("|",
+/// "---") aren't noise.
+fn is_noise(word: &str) -> bool {
+    if word
+        .chars()
+        .all(|c| matches!(c, '|' | '-' | ':' | '+' | '='))
+    {
+        return false;
+    }
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+    if core.is_empty() {
+        return true;
+    }
+    let is_word = core
+        .chars()
+        .all(|c| c.is_alphabetic() || matches!(c, '\'' | '’' | '-' | '.'));
+    let is_number = core
+        .chars()
+        .all(|c| c.is_numeric() || matches!(c, ',' | '.' | ':' | '/' | '-'));
+    !is_word && !is_number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_garbage_reasons() {
+        let limits = QualityLimits::default();
+        let reason = |text: &str| garbage_reason(text, &limits);
+
+        assert_eq!(reason("The index is rebuilt nightly, e.g. at 02:00."), None);
+        assert_eq!(
+            reason("| Name | Score |\n| --- | ---: |\n| Ada | 97 |\n| Alan | 88 |"),
+            None
+        );
+        assert_eq!(reason("Revenue grew 12% to $1,234.5m in 2024-03."), None);
+        assert_eq!(
+            reason("12 13 14 15 16 17 18 19 20 21 page 3"),
+            Some("digits")
+        );
+        assert_eq!(
+            reason("Contents .............................. 4"),
+            Some("repeated")
+        );
+        assert_eq!(reason("=================="), Some("repeated"));
+        assert_eq!(reason("l1I| ~^% rn,m; w0rd the j@ck %&*"), Some("noise"));
+        assert_eq!(reason("(* ) {} [] => .. ; the"), Some("noise"));
+        assert_eq!(reason(" \n "), Some("alphabetic"));
+    }
+
+    #[test]
+    fn test_limits_are_configurable() {
+        let chunks = vec![
+            "2023 2024 2025 2026 2027 sum".to_string(),
+            "Plain words.".to_string(),
+        ];
+        assert_eq!(
+            garbage_chunks(&chunks, &QualityLimits::default()),
+            [Some("digits"), None]
+        );
+        let lenient = QualityLimits {
+            max_digit_ratio: 1.0,
+            min_alpha_ratio: 0.0,
+            ..QualityLimits::default()
+        };
+        assert_eq!(garbage_chunks(&chunks, &lenient), [None, None]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Boolean operators in keyword queries.
+//!
+//! BM25 scores a query as a bag of words: a document matching most of them
+//! ranks high even without the one that matters, and a query can't rule a
+//! topic out. Operators in the query string fix both, in the style of
+//! search engines:
+//!   +word or AND  the word must occur ("transformer AND attention")
+//!   -word or NOT  the word must not occur ("transformer -vision")
+//!   OR            the default: the word counts toward the score only
+//! Operators are whole, whitespace-separated words (`AND`, `OR`, `NOT` in
+//! capitals) or a prefix on one, so "covid-19" and "and" stay words.
+
+/// A query split into the words it scores by and those it requires or
+/// rules out.
+#[derive(Debug, Default, PartialEq)]
+pub struct BooleanQuery {
+    /// The words scored, required ones included, without operators
+    pub text: String,
+    /// Words every result must contain
+    pub required: Vec<String>,
+    /// Words no result may contain
+    pub excluded: Vec<String>,
+}
+
+/// How a query word is used.
+#[derive(Clone, Copy, PartialEq)]
+enum Occur {
+    Should,
+    Must,
+    MustNot,
+}
+
+impl BooleanQuery {
+    /// Parse `query`'s operators.
+    pub fn parse(query: &str) -> Self {
+        let mut words: Vec<(&str, Occur)> = Vec::new();
+        // The operator applying to the next word, and whether an AND
+        // came before it
+        let mut next = Occur::Should;
+        let mut and = false;
+        for word in query.split_whitespace() {
+            match word {
+                "AND" => {
+                    if let Some(last) = words.last_mut().filter(|(_, o)| *o == Occur::Should) {
+                        last.1 = Occur::Must;
+                    }
+                    and = true;
+                }
+                "OR" => {
+                    next = Occur::Should;
+                    and = false;
+                }
+                "NOT" => next = Occur::MustNot,
+                _ => {
+                    let signed = |sign| word.strip_prefix(sign).filter(|rest| !rest.is_empty());
+                    let (word, occur) = if let Some(rest) = signed('+') {
+                        (rest, Occur::Must)
+                    } else if let Some(rest) = signed('-') {
+                        (rest, Occur::MustNot)
+                    } else if and && next == Occur::Should {
+                        (word, Occur::Must)
+                    } else {
+                        (word, next)
+                    };
+                    words.push((word, occur));
+                    next = Occur::Should;
+                    and = false;
+                }
+            }
+        }
+
+        let mut parsed = BooleanQuery::default();
+        let mut scored: Vec<&str> = Vec::new();
+        for (word, occur) in words {
+            match occur {
+                Occur::Should => scored.push(word),
+                Occur::Must => {
+                    scored.push(word);
+                    parsed.required.push(word.to_string());
+                }
+                Occur::MustNot => parsed.excluded.push(word.to_string()),
+            }
+        }
+        parsed.text = scored.join(" ");
+        parsed
+    }
+
+    /// Whether the query requires or excludes any word.
+    pub fn has_operators(&self) -> bool {
+        !self.required.is_empty() || !self.excluded.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> (String, Vec<String>, Vec<String>) {
+        let parsed = BooleanQuery::parse(query);
+        (parsed.text, parsed.required, parsed.excluded)
+    }
+
+    #[test]
+    fn test_prefixes() {
+        let (text, required, excluded) = parse("transformer +attention -vision");
+        assert_eq!(text, "transformer attention");
+        assert_eq!(required, ["attention"]);
+        assert_eq!(excluded, ["vision"]);
+        // Hyphens inside words, and a lone sign, aren't operators
+        let parsed = BooleanQuery::parse("covid-19 - cases über");
+        assert_eq!(parsed.text, "covid-19 - cases über");
+        assert!(!parsed.has_operators());
+    }
+
+    #[test]
+    fn test_keywords() {
+        let (text, required, excluded) = parse("rust AND python NOT java OR go");
+        assert_eq!(text, "rust python go");
+        assert_eq!(required, ["rust", "python"]);
+        assert_eq!(excluded, ["java"]);
+        // Only capitals are operators
+        assert!(!BooleanQuery::parse("salt and pepper or not").has_operators());
+    }
+}
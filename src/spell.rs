@@ -0,0 +1,305 @@
+//! Query spelling correction against the corpus vocabulary.
+//!
+//! SymSpell-style: every vocabulary word is indexed under all of its
+//! deletions up to `max_distance` characters. A misspelled query word is
+//! looked up by its own deletions, so candidates are found without
+//! generating every possible edit. Candidates are ranked by
+//! Damerau-Levenshtein (optimal string alignment) distance, then by how
+//! often they occur in the corpus.
+
+use std::collections::{HashMap, HashSet};
+
+use pyo3::prelude::*;
+
+use crate::tokenizer;
+
+/// Words shorter than this are left alone; too many corrections are
+/// equally plausible for them.
+const MIN_WORD_LEN: usize = 4;
+
+/// Words up to this length are corrected by at most one edit.
+const SHORT_WORD_LEN: usize = 5;
+
+/// Corrects query words to the closest terms found in the indexed documents.
+///
+/// Construct from Python with:
+///     corrector = SpellCorrector(["chunk 1 text", "chunk 2 text", ...])
+///     corrector.correct("trnasformer atention")  # "transformer attention"
+#[pyclass]
+pub struct SpellCorrector {
+    /// Word → number of occurrences in the corpus
+    words: HashMap<String, usize>,
+    /// Deletion variant → vocabulary words that produce it
+    deletes: HashMap<String, Vec<String>>,
+    /// Maximum edit distance of a correction
+    max_distance: usize,
+}
+
+impl SpellCorrector {
+    /// Build the vocabulary and deletion index from documents.
+    pub fn new(documents: &[String], max_distance: usize) -> Self {
+        let mut words: HashMap<String, usize> = HashMap::new();
+        for doc in documents {
+            for token in tokenizer::tokenize(doc) {
+                *words.entry(token).or_insert(0) += 1;
+            }
+        }
+
+        let mut deletes: HashMap<String, Vec<String>> = HashMap::new();
+        for word in words.keys() {
+            if !is_correctable(word) {
+                continue;
+            }
+            for variant in deletions(word, max_distance) {
+                deletes.entry(variant).or_default().push(word.clone());
+            }
+        }
+
+        SpellCorrector {
+            words,
+            deletes,
+            max_distance,
+        }
+    }
+
+    /// Return the best correction for a lowercase word, or None if the
+    /// word is already known or nothing is close enough.
+    pub fn suggest(&self, word: &str) -> Option<&str> {
+        if self.words.contains_key(word) {
+            return None;
+        }
+        let max_distance = allowed_edits(word, self.max_distance);
+        if max_distance == 0 {
+            return None;
+        }
+
+        let mut best: Option<(usize, usize, &str)> = None;
+        let mut seen: HashSet<&str> = HashSet::new();
+        for variant in deletions(word, max_distance) {
+            let Some(candidates) = self.deletes.get(&variant) else {
+                continue;
+            };
+            for candidate in candidates {
+                if !seen.insert(candidate) {
+                    continue;
+                }
+                let distance = osa_distance(word, candidate);
+                if distance > max_distance {
+                    continue;
+                }
+                let freq = self.words[candidate];
+                // Prefer the smallest distance, then the most frequent word,
+                // then alphabetical order so results are deterministic
+                let better = match best {
+                    None => true,
+                    Some((d, f, w)) => {
+                        (distance, std::cmp::Reverse(freq), candidate.as_str())
+                            < (d, std::cmp::Reverse(f), w)
+                    }
+                };
+                if better {
+                    best = Some((distance, freq, candidate));
+                }
+            }
+        }
+        best.map(|(_, _, w)| w)
+    }
+
+    /// Correct every word in `query`, keeping punctuation and spacing.
+    pub fn correct(&self, query: &str) -> String {
+        let mut out = String::with_capacity(query.len());
+        let mut word = String::new();
+        for c in query.chars() {
+            if c.is_alphanumeric() || c == '\'' {
+                word.push(c);
+            } else {
+                self.push_corrected(&mut out, &word);
+                word.clear();
+                out.push(c);
+            }
+        }
+        self.push_corrected(&mut out, &word);
+        out
+    }
+
+    fn push_corrected(&self, out: &mut String, word: &str) {
+        if word.is_empty() {
+            return;
+        }
+        match self.suggest(&word.to_lowercase()) {
+            Some(fix) => out.push_str(&match_case(word, fix)),
+            None => out.push_str(word),
+        }
+    }
+}
+
+#[pymethods]
+impl SpellCorrector {
+    #[new]
+    #[pyo3(signature = (documents, max_distance=2))]
+    fn py_new(documents: Vec<String>, max_distance: usize) -> Self {
+        SpellCorrector::new(&documents, max_distance)
+    }
+
+    /// Return the query with misspelled words replaced by corpus terms.
+    #[pyo3(name = "correct")]
+    fn py_correct(&self, query: &str) -> String {
+        self.correct(query)
+    }
+
+    /// Return the correction for a single word, or None.
+    #[pyo3(name = "suggest")]
+    fn py_suggest(&self, word: &str) -> Option<String> {
+        self.suggest(&word.to_lowercase()).map(str::to_string)
+    }
+
+    /// Number of distinct words in the vocabulary.
+    fn __len__(&self) -> usize {
+        self.words.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SpellCorrector(words={}, max_distance={})",
+            self.words.len(),
+            self.max_distance
+        )
+    }
+}
+
+/// Only alphabetic words of a reasonable length are corrected; numbers,
+/// codes like "gpt4", and short words are left as typed.
+fn is_correctable(word: &str) -> bool {
+    word.chars().count() >= MIN_WORD_LEN
+        && word.chars().all(|c| c.is_alphabetic() || c == '\'')
+}
+
+/// The most edits a correction of `word` may take, up to `max_distance`:
+/// none for words `is_correctable` leaves alone, and at most one for short
+/// words, which tolerate fewer.
+pub(crate) fn allowed_edits(word: &str, max_distance: usize) -> usize {
+    if !is_correctable(word) {
+        0
+    } else if word.chars().count() <= SHORT_WORD_LEN {
+        max_distance.min(1)
+    } else {
+        max_distance
+    }
+}
+
+/// All strings reachable from `word` by deleting up to `max_distance`
+/// characters, including `word` itself.
+fn deletions(word: &str, max_distance: usize) -> HashSet<String> {
+    let mut result: HashSet<String> = HashSet::new();
+    result.insert(word.to_string());
+    let mut frontier = vec![word.to_string()];
+    for _ in 0..max_distance {
+        let mut next = Vec::new();
+        for w in &frontier {
+            let chars: Vec<char> = w.chars().collect();
+            if chars.len() <= 1 {
+                continue;
+            }
+            for i in 0..chars.len() {
+                let variant: String = chars[..i].iter().chain(&chars[i + 1..]).collect();
+                if result.insert(variant.clone()) {
+                    next.push(variant);
+                }
+            }
+        }
+        frontier = next;
+    }
+    result
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent
+/// transpositions), which counts "teh" → "the" as a single edit.
+pub(crate) fn osa_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Apply the capitalization of `original` to `fix` (all caps or leading
+/// capital); otherwise return `fix` as is.
+fn match_case(original: &str, fix: &str) -> String {
+    if original.chars().count() > 1 && original.chars().all(|c| !c.is_lowercase()) {
+        return fix.to_uppercase();
+    }
+    let mut chars = original.chars();
+    if chars.next().is_some_and(char::is_uppercase) {
+        let mut fixed = fix.chars();
+        return match fixed.next() {
+            Some(first) => first.to_uppercase().chain(fixed).collect(),
+            None => String::new(),
+        };
+    }
+    fix.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corrector() -> SpellCorrector {
+        let docs = vec![
+            "The transformer architecture relies on attention.".to_string(),
+            "Self attention lets the transformer weigh every token.".to_string(),
+            "Retrieval augmented generation grounds answers in documents.".to_string(),
+        ];
+        SpellCorrector::new(&docs, 2)
+    }
+
+    #[test]
+    fn test_corrects_typos() {
+        let sc = corrector();
+        assert_eq!(sc.correct("trnasformer atention"), "transformer attention");
+        assert_eq!(sc.suggest("retreival"), Some("retrieval"));
+    }
+
+    #[test]
+    fn test_known_and_short_words_unchanged() {
+        let sc = corrector();
+        assert_eq!(sc.suggest("attention"), None);
+        assert_eq!(sc.correct("how does it wrk"), "how does it wrk");
+    }
+
+    #[test]
+    fn test_preserves_case_and_punctuation() {
+        let sc = corrector();
+        assert_eq!(sc.correct("What is Atention?"), "What is Attention?");
+        assert_eq!(sc.correct("TRANSFROMER, please"), "TRANSFORMER, please");
+    }
+
+    #[test]
+    fn test_too_far_left_alone() {
+        let sc = corrector();
+        assert_eq!(sc.suggest("xyzzyplugh"), None);
+        assert_eq!(sc.correct("gpt4 specs"), "gpt4 specs");
+    }
+
+    #[test]
+    fn test_osa_distance() {
+        assert_eq!(osa_distance("teh", "the"), 1);
+        assert_eq!(osa_distance("kitten", "sitting"), 3);
+        assert_eq!(osa_distance("", "abc"), 3);
+    }
+}
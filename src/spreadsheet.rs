@@ -0,0 +1,599 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::archive::ZipArchive;
+use crate::xml::{attribute, local_name, XmlEvent, XmlReader};
+
+/// A sheet of cell values, the first non-empty row being its header.
+struct Sheet {
+    /// Worksheet name; None for CSV files
+    name: Option<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// Extracts a spreadsheet (.csv, .tsv, or .xlsx) as one record per row.
+///
+/// Each row becomes a block of `column: value` lines, named after the
+/// header row, so the values stay retrievable once the rows are chunked.
+/// Records are separated by blank lines, and each worksheet of a workbook
+/// starts with a `# Sheet name` heading.
+pub fn extract_text(path: &str) -> Result<String> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    let extension = file_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    let data =
+        || std::fs::read(file_path).with_context(|| format!("Failed to open file: {}", path));
+    let sheets = match extension.as_deref() {
+        Some("csv" | "tsv") => {
+            let text = crate::text::decode(&data()?)
+                .with_context(|| format!("Failed to read CSV: {}", path))?;
+            vec![Sheet {
+                name: None,
+                rows: parse_csv(&text),
+            }]
+        }
+        Some("xlsx") => {
+            read_workbook(&data()?).with_context(|| format!("Failed to read XLSX: {}", path))?
+        }
+        _ => anyhow::bail!("File is not a CSV or XLSX spreadsheet: {}", path),
+    };
+
+    let text = sheets
+        .iter()
+        .filter_map(|sheet| {
+            let records = records(&sheet.rows);
+            if records.is_empty() {
+                return None;
+            }
+            Some(match &sheet.name {
+                Some(name) => format!("# {}\n\n{}", name, records),
+                None => records,
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    if text.is_empty() {
+        anyhow::bail!("No rows could be extracted from the spreadsheet: {}", path);
+    }
+    Ok(text)
+}
+
+/// Render the rows below the header as `column: value` records.
+///
+/// Empty cells are left out, and so are rows with no values; columns
+/// without a header are called "Column N".
+fn records(rows: &[Vec<String>]) -> String {
+    let clean = |cell: &str| cell.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut rows = rows
+        .iter()
+        .skip_while(|row| row.iter().all(|cell| cell.trim().is_empty()));
+    let Some(header) = rows.next() else {
+        return String::new();
+    };
+
+    let mut out = Vec::new();
+    for row in rows {
+        let lines: Vec<String> = row
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| !value.trim().is_empty())
+            .map(|(i, value)| {
+                let column = header
+                    .get(i)
+                    .map(|name| clean(name))
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("Column {}", i + 1));
+                format!("{}: {}", column, clean(value))
+            })
+            .collect();
+        if !lines.is_empty() {
+            out.push(lines.join("\n"));
+        }
+    }
+    out.join("\n\n")
+}
+
+/// Parse delimited text (RFC 4180 quoting), guessing the delimiter.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let delimiter = sniff_delimiter(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            quoted = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' {
+            row.push(std::mem::take(&mut field));
+            rows.push(std::mem::take(&mut row));
+        } else if c != '\r' {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// The most frequent of `,`, `;`, tab, and `|` on the first line, outside
+/// quotes; a comma if there are none.
+fn sniff_delimiter(text: &str) -> char {
+    let mut counts = [(',', 0), (';', 0), ('\t', 0), ('|', 0)];
+    let mut quoted = false;
+    for c in text.chars() {
+        if c == '\n' && !quoted {
+            break;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if !quoted {
+            if let Some(entry) = counts.iter_mut().find(|(d, _)| *d == c) {
+                entry.1 += 1;
+            }
+        }
+    }
+    counts
+        .iter()
+        .filter(|(_, n)| *n > 0)
+        .max_by_key(|(_, n)| *n)
+        .map_or(',', |(d, _)| *d)
+}
+
+/// Read every worksheet of an Excel workbook, in tab order.
+fn read_workbook(data: &[u8]) -> Result<Vec<Sheet>> {
+    let archive = ZipArchive::new(data)?;
+    let optional = |name: &str| -> Result<String> {
+        if archive.contains(name) {
+            archive.read_string(name)
+        } else {
+            Ok(String::new())
+        }
+    };
+
+    let workbook = archive.read_string("xl/workbook.xml")?;
+    let rels = optional("xl/_rels/workbook.xml.rels")?;
+    let shared = shared_strings(&optional("xl/sharedStrings.xml")?);
+    let date_styles = date_styles(&optional("xl/styles.xml")?);
+    let mut date1904 = false;
+
+    let mut sheets = Vec::new();
+    for event in XmlReader::new(&workbook) {
+        let XmlEvent::Start { name, attrs, .. } = event else {
+            continue;
+        };
+        match local_name(name) {
+            "workbookPr" => {
+                date1904 = attribute(attrs, "date1904").is_some_and(|v| v == "1" || v == "true");
+            }
+            "sheet" => {
+                let sheet_name = attribute(attrs, "name").map(|n| n.into_owned());
+                let Some(target) = attribute(attrs, "id").and_then(|id| relationship(&rels, &id))
+                else {
+                    continue;
+                };
+                // Targets are relative to xl/ unless absolute
+                let part = match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => format!("xl/{}", target),
+                };
+                let context = Cells {
+                    shared: &shared,
+                    date_styles: &date_styles,
+                    date1904,
+                };
+                sheets.push(Sheet {
+                    name: sheet_name,
+                    rows: context.rows(&archive.read_string(&part)?),
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(sheets)
+}
+
+/// Target of the relationship with the given id.
+fn relationship(rels: &str, id: &str) -> Option<String> {
+    XmlReader::new(rels).find_map(|event| match event {
+        XmlEvent::Start { name, attrs, .. }
+            if local_name(name) == "Relationship"
+                && attribute(attrs, "Id").is_some_and(|v| v == id) =>
+        {
+            attribute(attrs, "Target").map(|t| t.into_owned())
+        }
+        _ => None,
+    })
+}
+
+/// The workbook's shared string table (`xl/sharedStrings.xml`).
+fn shared_strings(xml: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    // Phonetic guides (<rPh>) repeat the string's reading, not its text
+    let mut in_phonetic = false;
+
+    for event in XmlReader::new(xml) {
+        match event {
+            XmlEvent::Start {
+                name, self_closing, ..
+            } => match local_name(name) {
+                "si" => current.clear(),
+                "t" => in_text = !self_closing,
+                "rPh" => in_phonetic = !self_closing,
+                _ => {}
+            },
+            XmlEvent::End { name } => match local_name(name) {
+                "si" => strings.push(std::mem::take(&mut current)),
+                "t" => in_text = false,
+                "rPh" => in_phonetic = false,
+                _ => {}
+            },
+            XmlEvent::Text(text) if in_text && !in_phonetic => current.push_str(&text),
+            XmlEvent::Text(_) => {}
+        }
+    }
+    strings
+}
+
+/// For each cell style (`<cellXfs>` entry), whether it formats numbers as
+/// dates or times.
+fn date_styles(xml: &str) -> Vec<bool> {
+    let mut custom: Vec<(String, String)> = Vec::new();
+    let mut styles = Vec::new();
+    let mut in_cell_xfs = false;
+
+    for event in XmlReader::new(xml) {
+        match event {
+            XmlEvent::Start {
+                name,
+                attrs,
+                self_closing,
+            } => match local_name(name) {
+                "numFmt" => {
+                    if let (Some(id), Some(code)) =
+                        (attribute(attrs, "numFmtId"), attribute(attrs, "formatCode"))
+                    {
+                        custom.push((id.into_owned(), code.into_owned()));
+                    }
+                }
+                "cellXfs" => in_cell_xfs = !self_closing,
+                "xf" if in_cell_xfs => {
+                    let id = attribute(attrs, "numFmtId").unwrap_or_default();
+                    let is_date = match custom.iter().find(|(custom_id, _)| *custom_id == id) {
+                        Some((_, code)) => is_date_format(code),
+                        // Built-in date and time formats
+                        None => id
+                            .parse::<u32>()
+                            .is_ok_and(|n| (14..=22).contains(&n) || (45..=47).contains(&n)),
+                    };
+                    styles.push(is_date);
+                }
+                _ => {}
+            },
+            XmlEvent::End { name } if local_name(name) == "cellXfs" => in_cell_xfs = false,
+            _ => {}
+        }
+    }
+    styles
+}
+
+/// Whether a number format code shows a date or time, i.e. has day, year,
+/// hour, or second placeholders outside quoted text and `[...]` sections.
+fn is_date_format(code: &str) -> bool {
+    let mut quoted = false;
+    let mut bracketed = false;
+    let mut escaped = false;
+    for c in code.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            '[' if !quoted => bracketed = true,
+            ']' if !quoted => bracketed = false,
+            'd' | 'D' | 'y' | 'Y' | 'h' | 'H' | 's' | 'S' if !quoted && !bracketed => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// What's needed to turn a worksheet's raw cell values into text.
+struct Cells<'a> {
+    shared: &'a [String],
+    date_styles: &'a [bool],
+    date1904: bool,
+}
+
+impl Cells<'_> {
+    /// The rows of a worksheet part, with cells placed in their columns.
+    fn rows(&self, xml: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row: Vec<String> = Vec::new();
+        // Column, type, and style of the cell being read
+        let mut cell: (usize, String, usize) = (0, String::new(), 0);
+        let mut value = String::new();
+        let mut in_value = false;
+        let mut in_phonetic = false;
+
+        for event in XmlReader::new(xml) {
+            match event {
+                XmlEvent::Start {
+                    name,
+                    attrs,
+                    self_closing,
+                } => match local_name(name) {
+                    "row" if self_closing => rows.push(Vec::new()),
+                    "row" => row.clear(),
+                    "c" => {
+                        let column = attribute(attrs, "r")
+                            .and_then(|r| column_index(&r))
+                            .unwrap_or(row.len());
+                        let kind = attribute(attrs, "t").unwrap_or_default().into_owned();
+                        let style = attribute(attrs, "s")
+                            .and_then(|s| s.parse().ok())
+                            .unwrap_or(0);
+                        cell = (column, kind, style);
+                        value.clear();
+                    }
+                    // <v> holds the value; inline strings use <is><t>
+                    "v" | "t" => in_value = !self_closing,
+                    "rPh" => in_phonetic = !self_closing,
+                    _ => {}
+                },
+                XmlEvent::End { name } => match local_name(name) {
+                    "v" | "t" => in_value = false,
+                    "rPh" => in_phonetic = false,
+                    "c" => {
+                        let (column, kind, style) = &cell;
+                        let text = self.value(kind, &value, *style);
+                        if !text.is_empty() {
+                            if row.len() <= *column {
+                                row.resize(column + 1, String::new());
+                            }
+                            row[*column] = text;
+                        }
+                    }
+                    "row" => rows.push(std::mem::take(&mut row)),
+                    _ => {}
+                },
+                XmlEvent::Text(text) if in_value && !in_phonetic => value.push_str(&text),
+                XmlEvent::Text(_) => {}
+            }
+        }
+        rows
+    }
+
+    /// Text of a cell given its type (`t` attribute) and raw value.
+    fn value(&self, kind: &str, raw: &str, style: usize) -> String {
+        match kind {
+            "s" => raw
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| self.shared.get(i))
+                .cloned()
+                .unwrap_or_default(),
+            "b" => if raw.trim() == "1" { "TRUE" } else { "FALSE" }.to_string(),
+            "str" | "inlineStr" | "e" => raw.to_string(),
+            _ => match raw.trim().parse::<f64>() {
+                Ok(serial) if self.date_styles.get(style).copied().unwrap_or(false) => {
+                    format_serial(serial, self.date1904)
+                }
+                _ => raw.trim().to_string(),
+            },
+        }
+    }
+}
+
+/// Zero-based column of a cell reference: `A1` → 0, `AB7` → 27.
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphabetic)
+        .collect();
+    if letters.is_empty() || letters.len() > 3 {
+        return None;
+    }
+    let number = letters.iter().fold(0, |n, b| {
+        n * 26 + (b.to_ascii_uppercase() - b'A') as usize + 1
+    });
+    Some(number - 1)
+}
+
+/// Format an Excel date serial as ISO 8601: `2024-03-01`, with the time
+/// (`2024-03-01 14:30`) if it has one, or just the time below one day.
+fn format_serial(serial: f64, date1904: bool) -> String {
+    let days = serial.floor();
+    let minutes = ((serial - days) * 24.0 * 60.0).round() as i64;
+    let time = format!("{:02}:{:02}", minutes / 60 % 24, minutes % 60);
+    if days == 0.0 && !date1904 {
+        return time;
+    }
+    // Serial 0 is 1899-12-30 in the 1900 system (which counts the
+    // nonexistent 1900-02-29), 1904-01-01 in the 1904 system
+    let offset = if date1904 { 24107 } else { 25569 };
+    let (year, month, day) = civil_from_days(days as i64 - offset);
+    let date = format!("{:04}-{:02}-{:02}", year, month, day);
+    if minutes == 0 {
+        date
+    } else {
+        format!("{} {}", date, time)
+    }
+}
+
+/// Year, month, and day of a count of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's algorithm, with eras of 400 years from 0000-03-01
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::build_zip;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "name,notes,score\nAda,\"likes \"\"maths\"\", engines\",97\n\"Grace\",\"multi\nline\",\n";
+        assert_eq!(
+            parse_csv(csv),
+            vec![
+                row(&["name", "notes", "score"]),
+                row(&["Ada", "likes \"maths\", engines", "97"]),
+                row(&["Grace", "multi\nline", ""]),
+            ]
+        );
+        assert_eq!(
+            parse_csv("a;b;\"c;d\"\n1;2;3"),
+            vec![row(&["a", "b", "c;d"]), row(&["1", "2", "3"])]
+        );
+        assert_eq!(parse_csv("a\tb\n1\t2")[1], row(&["1", "2"]));
+    }
+
+    #[test]
+    fn test_records() {
+        let rows = vec![
+            row(&["", ""]),
+            row(&["Name", "City", ""]),
+            row(&["Ada", "", "extra"]),
+            row(&["", "", ""]),
+            row(&["Grace", "New\n York"]),
+        ];
+        assert_eq!(
+            records(&rows),
+            "Name: Ada\nColumn 3: extra\n\nName: Grace\nCity: New York"
+        );
+        assert_eq!(records(&[]), "");
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(format_serial(45352.0, false), "2024-03-01");
+        assert_eq!(format_serial(45352.6041666667, false), "2024-03-01 14:30");
+        assert_eq!(format_serial(0.75, false), "18:00");
+        assert_eq!(format_serial(0.0, true), "1904-01-01");
+        assert!(is_date_format("dd/mm/yyyy"));
+        assert!(is_date_format("[$-409]h:mm AM/PM"));
+        assert!(!is_date_format("0.00\" days\""));
+        assert!(!is_date_format("[Red]#,##0"));
+        assert_eq!(column_index("A1"), Some(0));
+        assert_eq!(column_index("AB7"), Some(27));
+        assert_eq!(column_index("12"), None);
+    }
+
+    const WORKBOOK: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <workbookPr/>
+  <sheets><sheet name="People" sheetId="1" r:id="rId2"/><sheet name="Empty" sheetId="2" r:id="rId1"/></sheets>
+</workbook>"#;
+
+    const RELS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="worksheet" Target="worksheets/sheet2.xml"/>
+  <Relationship Id="rId2" Type="worksheet" Target="/xl/worksheets/sheet1.xml"/>
+</Relationships>"#;
+
+    const SHARED: &str = r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <si><t>Name</t></si><si><t>Joined</t></si>
+  <si><r><t>Ada </t></r><r><rPr><b/></rPr><t>Lovelace</t></r></si>
+  <si><t>東京</t><rPh sb="0" eb="2"><t>トウキョウ</t></rPh></si>
+</sst>"#;
+
+    const STYLES: &str = r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <numFmts count="1"><numFmt numFmtId="164" formatCode="yyyy\-mm\-dd"/></numFmts>
+  <cellStyleXfs count="1"><xf numFmtId="0"/></cellStyleXfs>
+  <cellXfs count="2"><xf numFmtId="0"/><xf numFmtId="164" applyNumberFormat="1"/></cellXfs>
+</styleSheet>"#;
+
+    const SHEET: &str = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>
+  <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c><c r="C1" t="inlineStr"><is><t>Active</t></is></c><c r="D1"><v>12</v></c></row>
+  <row r="2"><c r="A2" t="s"><v>2</v></c><c r="B2" s="1"><v>45352</v></c><c r="C2" t="b"><v>1</v></c></row>
+  <row r="4"><c r="A4" t="s"><v>3</v></c><c r="D4"><f>D1*2</f><v>24</v></c></row>
+</sheetData></worksheet>"#;
+
+    fn write_xlsx(name: &str) -> std::path::PathBuf {
+        let empty = r#"<worksheet><sheetData/></worksheet>"#;
+        let data = build_zip(
+            &[
+                ("xl/workbook.xml", WORKBOOK.as_bytes()),
+                ("xl/_rels/workbook.xml.rels", RELS.as_bytes()),
+                ("xl/sharedStrings.xml", SHARED.as_bytes()),
+                ("xl/styles.xml", STYLES.as_bytes()),
+                ("xl/worksheets/sheet1.xml", SHEET.as_bytes()),
+                ("xl/worksheets/sheet2.xml", empty.as_bytes()),
+            ],
+            false,
+        );
+        let path = std::env::temp_dir().join(format!(
+            "rusty_rag_test_{}_{}.xlsx",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_extract_xlsx() {
+        let path = write_xlsx("people");
+        assert_eq!(
+            extract_text(path.to_str().unwrap()).unwrap(),
+            "# People\n\nName: Ada Lovelace\nJoined: 2024-03-01\nActive: TRUE\n\n\
+             Name: 東京\n12: 24"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_extract_csv_and_errors() {
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.csv", std::process::id()));
+        std::fs::write(&path, "\u{FEFF}city,country\r\nOslo,Norway\r\n").unwrap();
+        assert_eq!(
+            extract_text(path.to_str().unwrap()).unwrap(),
+            "city: Oslo\ncountry: Norway"
+        );
+
+        std::fs::write(&path, "city,country\n").unwrap();
+        let err = extract_text(path.to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("No rows"));
+        std::fs::remove_file(path).unwrap();
+
+        let err = extract_text("/nonexistent/data.xlsx").unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Token-frequency statistics over a chunk collection.
+//!
+//! Counts how often each term occurs, and in how many chunks, as the BM25
+//! index would see them: its vocabulary size, most frequent terms, and
+//! hapaxes (terms seen once). Inspecting these before indexing shows what
+//! the tokenizer settings do to a corpus, and the terms found in most chunks
+//! make a starting point for a custom stopword list.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::analyzer;
+use crate::bm25::TermOptions;
+use crate::tokenizer::TokenizerOptions;
+
+/// Term counts of a chunk collection, built up one batch at a time.
+///
+/// Construct from Python with:
+///     stats = TokenStats().add(["chunk 1 text", "chunk 2 text", ...])
+///     stats.top_terms(20)  # [("the", 412), ("data", 97), ...]
+///
+/// Terms are tokenized as `tokenize` does with the same options, so
+/// `stemmer="english"` counts "run" and "running" as one term.
+#[pyclass]
+pub struct TokenStats {
+    /// How chunks become terms
+    options: TermOptions,
+    /// Term → number of occurrences
+    counts: HashMap<String, usize>,
+    /// Term → number of chunks containing it
+    chunk_counts: HashMap<String, usize>,
+    /// Terms seen in all chunks
+    n_tokens: usize,
+    /// Chunks added
+    n_chunks: usize,
+}
+
+impl TokenStats {
+    /// Empty statistics counting terms as `options` makes them.
+    pub fn new(options: TermOptions) -> Self {
+        TokenStats {
+            options,
+            counts: HashMap::new(),
+            chunk_counts: HashMap::new(),
+            n_tokens: 0,
+            n_chunks: 0,
+        }
+    }
+
+    /// Count the terms of `chunks`, tokenized in parallel.
+    pub fn add(&mut self, chunks: &[String]) {
+        let terms: Vec<Vec<String>> = chunks
+            .par_iter()
+            .map(|chunk| self.options.terms(chunk))
+            .collect();
+        for chunk_terms in terms {
+            self.n_tokens += chunk_terms.len();
+            let distinct: HashSet<&String> = chunk_terms.iter().collect();
+            for term in distinct {
+                *self.chunk_counts.entry(term.clone()).or_default() += 1;
+            }
+            for term in chunk_terms {
+                *self.counts.entry(term).or_default() += 1;
+            }
+        }
+        self.n_chunks += chunks.len();
+    }
+
+    /// Up to `n` terms with their number of occurrences, or of chunks
+    /// containing them if `by_chunks`; most frequent first, ties in
+    /// alphabetical order.
+    pub fn top_terms(&self, n: usize, by_chunks: bool) -> Vec<(String, usize)> {
+        let counts = if by_chunks {
+            &self.chunk_counts
+        } else {
+            &self.counts
+        };
+        let mut terms: Vec<(&String, usize)> = counts.iter().map(|(t, &c)| (t, c)).collect();
+        terms.sort_by_key(|&(term, count)| (Reverse(count), term));
+        terms
+            .into_iter()
+            .take(n)
+            .map(|(term, count)| (term.clone(), count))
+            .collect()
+    }
+
+    /// The terms seen exactly once, in alphabetical order.
+    pub fn hapaxes(&self) -> Vec<String> {
+        let mut hapaxes: Vec<String> = self
+            .counts
+            .iter()
+            .filter(|&(_, &count)| count == 1)
+            .map(|(term, _)| term.clone())
+            .collect();
+        hapaxes.sort();
+        hapaxes
+    }
+}
+
+#[pymethods]
+impl TokenStats {
+    /// Args:
+    ///     stemmer: Snowball language ("english", "de", ...) or "porter" to
+    ///         count terms by stem.
+    ///     fold_accents: Whether accents are stripped from words.
+    ///     word_chars: Characters kept in words besides letters and digits
+    ///         (default an apostrophe).
+    ///     lemmatize: Whether English words are counted by their lemma.
+    ///     normalize_numbers: Whether numbers and dates are single terms in
+    ///         one spelling.
+    ///     keep_compounds: Whether hyphenated compounds are terms as well
+    ///         as their parts.
+    #[new]
+    #[pyo3(signature = (
+        stemmer=None, fold_accents=false, word_chars=None, lemmatize=false,
+        normalize_numbers=false, keep_compounds=false,
+    ))]
+    fn py_new(
+        stemmer: Option<&str>,
+        fold_accents: bool,
+        word_chars: Option<&str>,
+        lemmatize: bool,
+        normalize_numbers: bool,
+        keep_compounds: bool,
+    ) -> PyResult<Self> {
+        let stemmer = stemmer
+            .map(analyzer::stemmer_language)
+            .transpose()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+        let mut words = word_chars.map(TokenizerOptions::new).unwrap_or_default();
+        if normalize_numbers {
+            words = words.normalizing_numbers();
+        }
+        if keep_compounds {
+            words = words.keeping_compounds();
+        }
+        let options = TermOptions {
+            stemmer,
+            lemmatize,
+            words,
+            ..TermOptions::default()
+        };
+        Ok(TokenStats::new(if fold_accents {
+            options.folding_accents()
+        } else {
+            options
+        }))
+    }
+
+    /// Count the terms of more chunks; returns the statistics, so calls
+    /// chain.
+    #[pyo3(name = "add")]
+    fn py_add<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        py: Python<'py>,
+        chunks: Vec<String>,
+    ) -> PyRefMut<'py, Self> {
+        let stats = &mut *slf;
+        py.allow_threads(|| stats.add(&chunks));
+        slf
+    }
+
+    /// Return up to `n` (term, count) pairs, most frequent first. With
+    /// `by_chunks`, counts are numbers of chunks containing the term, so
+    /// the top terms are stopword candidates.
+    #[pyo3(name = "top_terms", signature = (n=20, by_chunks=false))]
+    fn py_top_terms(&self, n: usize, by_chunks: bool) -> Vec<(String, usize)> {
+        self.top_terms(n, by_chunks)
+    }
+
+    /// Return the terms seen exactly once, alphabetically.
+    #[pyo3(name = "hapaxes")]
+    fn py_hapaxes(&self) -> Vec<String> {
+        self.hapaxes()
+    }
+
+    /// Number of distinct terms.
+    #[getter]
+    fn vocabulary_size(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Number of terms seen exactly once.
+    #[getter]
+    fn hapax_count(&self) -> usize {
+        self.counts.values().filter(|&&count| count == 1).count()
+    }
+
+    /// Number of terms in all chunks.
+    #[getter]
+    fn token_count(&self) -> usize {
+        self.n_tokens
+    }
+
+    /// Number of chunks added.
+    #[getter]
+    fn chunk_count(&self) -> usize {
+        self.n_chunks
+    }
+
+    fn __len__(&self) -> usize {
+        self.counts.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TokenStats(chunks={}, tokens={}, vocabulary={})",
+            self.n_chunks,
+            self.n_tokens,
+            self.counts.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut stats = TokenStats::new(TermOptions::default());
+        stats.add(&chunks(&["The cat and the dog", "The bird"]));
+        stats.add(&chunks(&["A cat"]));
+        assert_eq!(stats.n_chunks, 3);
+        assert_eq!(stats.n_tokens, 9);
+        assert_eq!(stats.counts.len(), 6);
+        assert_eq!(
+            stats.top_terms(2, false),
+            [("the".to_string(), 3), ("cat".to_string(), 2)]
+        );
+        // "the" is in two chunks, as is "cat", which sorts first
+        assert_eq!(
+            stats.top_terms(2, true),
+            [("cat".to_string(), 2), ("the".to_string(), 2)]
+        );
+        assert_eq!(stats.hapaxes(), ["a", "and", "bird", "dog"]);
+    }
+
+    #[test]
+    fn test_stemmed_terms() {
+        let options = TermOptions {
+            stemmer: Some(analyzer::Language::English),
+            ..TermOptions::default()
+        };
+        let mut stats = TokenStats::new(options);
+        stats.add(&chunks(&["running runs run"]));
+        assert_eq!(stats.top_terms(5, false), [("run".to_string(), 3)]);
+        assert!(stats.hapaxes().is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+//! Bundled stopword lists for the languages supported by the analyzer.
+//!
+//! Lists are lowercase and derived from the Snowball project's stopword
+//! lists, trimmed to the most frequent function words.
+
+pub const ENGLISH: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "am", "an", "and", "any", "are",
+    "aren't", "as", "at", "be", "because", "been", "before", "being", "below", "between", "both",
+    "but", "by", "can", "can't", "cannot", "could", "couldn't", "did", "didn't", "do", "does",
+    "doesn't", "doing", "don't", "down", "during", "each", "few", "for", "from", "further", "had",
+    "hadn't", "has", "hasn't", "have", "haven't", "having", "he", "her", "here", "hers",
+    "herself", "him", "himself", "his", "how", "i", "if", "in", "into", "is", "isn't", "it",
+    "it's", "its", "itself", "just", "me", "more", "most", "my", "myself", "no", "nor", "not",
+    "of", "off", "on", "once", "only", "or", "other", "our", "ours", "ourselves", "out", "over",
+    "own", "same", "she", "should", "so", "some", "such", "than", "that", "the", "their",
+    "theirs", "them", "themselves", "then", "there", "these", "they", "this", "those", "through",
+    "to", "too", "under", "until", "up", "very", "was", "wasn't", "we", "were", "weren't", "what",
+    "when", "where", "which", "while", "who", "whom", "why", "will", "with", "won't", "would",
+    "you", "your", "yours", "yourself", "yourselves",
+];
+
+pub const GERMAN: &[&str] = &[
+    "aber", "alle", "allem", "allen", "aller", "alles", "als", "also", "am", "an", "ander",
+    "andere", "anderen", "auch", "auf", "aus", "bei", "bin", "bis", "bist", "da", "damit", "dann",
+    "das", "dass", "dein", "deine", "dem", "den", "der", "des", "dich", "die", "dies", "diese",
+    "diesem", "diesen", "dieser", "dieses", "dir", "doch", "dort", "du", "durch", "ein", "eine",
+    "einem", "einen", "einer", "eines", "er", "es", "etwas", "euch", "euer", "für", "gegen",
+    "hab", "habe", "haben", "hat", "hatte", "hier", "hin", "ich", "ihm", "ihn", "ihnen", "ihr",
+    "ihre", "im", "in", "ist", "jede", "jetzt", "kann", "kein", "keine", "man", "mein", "meine",
+    "mich", "mir", "mit", "muss", "nach", "nicht", "nichts", "noch", "nun", "nur", "ob", "oder",
+    "ohne", "sehr", "sein", "seine", "sich", "sie", "sind", "so", "soll", "um", "und", "uns",
+    "unser", "unter", "viel", "vom", "von", "vor", "war", "waren", "was", "weil", "welche",
+    "wenn", "werden", "wie", "wieder", "will", "wir", "wird", "wo", "zu", "zum", "zur", "über",
+];
+
+pub const FRENCH: &[&str] = &[
+    "au", "aux", "avec", "ce", "ces", "cette", "dans", "de", "des", "du", "elle", "elles", "en",
+    "est", "et", "eu", "il", "ils", "je", "la", "le", "les", "leur", "leurs", "lui", "ma", "mais",
+    "me", "même", "mes", "moi", "mon", "ne", "nos", "notre", "nous", "on", "ont", "ou", "où",
+    "par", "pas", "pour", "qu", "que", "qui", "sa", "se", "ses", "son", "sont", "sur", "ta", "te",
+    "tes", "toi", "ton", "tu", "un", "une", "vos", "votre", "vous", "été", "être", "avoir", "ai",
+    "as", "avait", "était", "plus", "comme", "tout", "tous", "aussi", "y", "sans", "si", "entre",
+];
+
+pub const SPANISH: &[&str] = &[
+    "a", "al", "algo", "algunos", "ante", "antes", "como", "con", "contra", "cual", "cuando",
+    "de", "del", "desde", "donde", "durante", "e", "el", "ella", "ellas", "ellos", "en", "entre",
+    "era", "es", "esa", "ese", "eso", "esta", "estaba", "estar", "este", "esto", "estos", "está",
+    "están", "fue", "ha", "hay", "la", "las", "le", "les", "lo", "los", "me", "mi", "mis",
+    "mucho", "muy", "más", "ni", "no", "nos", "nosotros", "o", "otra", "otro", "para", "pero",
+    "poco", "por", "porque", "que", "quien", "qué", "se", "sea", "ser", "si", "sin", "sobre",
+    "son", "su", "sus", "también", "te", "tiene", "todo", "todos", "tu", "un", "una", "uno",
+    "unos", "y", "ya", "yo", "él",
+];
+
+pub const ITALIAN: &[&str] = &[
+    "a", "ad", "al", "alla", "alle", "agli", "ai", "anche", "che", "chi", "ci", "come", "con",
+    "cui", "da", "dal", "dalla", "dei", "del", "della", "delle", "dello", "degli", "di", "e",
+    "ed", "era", "gli", "ha", "hanno", "ho", "i", "il", "in", "io", "la", "le", "lei", "lo",
+    "loro", "lui", "ma", "mi", "mio", "ne", "nel", "nella", "noi", "non", "o", "per", "perché",
+    "più", "quella", "quello", "questa", "questo", "se", "si", "sono", "su", "sua", "suo",
+    "sul", "sulla", "ti", "tra", "tu", "un", "una", "uno", "voi", "è",
+];
+
+pub const PORTUGUESE: &[&str] = &[
+    "a", "ao", "aos", "as", "até", "com", "como", "da", "das", "de", "dela", "dele", "do", "dos",
+    "e", "ela", "ele", "eles", "em", "entre", "era", "essa", "esse", "esta", "este", "eu", "foi",
+    "há", "isso", "isto", "já", "lhe", "mais", "mas", "me", "mesmo", "meu", "minha", "muito",
+    "na", "nas", "nem", "no", "nos", "não", "num", "numa", "o", "os", "ou", "para", "pela",
+    "pelo", "por", "quando", "que", "quem", "se", "sem", "ser", "seu", "seus", "sua", "suas",
+    "são", "também", "te", "tem", "um", "uma", "você", "à", "é",
+];
+
+pub const DUTCH: &[&str] = &[
+    "aan", "al", "als", "bij", "dan", "dat", "de", "der", "deze", "die", "dit", "doch", "door",
+    "een", "en", "er", "geen", "had", "heb", "hebben", "heeft", "hem", "het", "hier", "hij",
+    "hoe", "hun", "ik", "in", "is", "ja", "je", "kan", "maar", "me", "meer", "men", "met", "mij",
+    "na", "naar", "niet", "nog", "nu", "of", "om", "omdat", "ons", "ook", "op", "over", "te",
+    "tot", "u", "uit", "van", "veel", "voor", "was", "wat", "we", "wel", "werd", "wie", "wij",
+    "wordt", "zal", "ze", "zich", "zij", "zijn", "zo", "zou",
+];
+
+pub const SWEDISH: &[&str] = &[
+    "alla", "att", "av", "blev", "bli", "de", "dem", "den", "denna", "deras", "det", "detta",
+    "dig", "din", "du", "där", "efter", "eller", "en", "er", "ett", "från", "för", "ha", "hade",
+    "han", "hans", "har", "henne", "hon", "honom", "hur", "här", "i", "icke", "inte", "jag",
+    "kan", "man", "med", "men", "mig", "min", "mot", "mycket", "ni", "nu", "när", "och", "om",
+    "oss", "på", "samma", "sedan", "sig", "sin", "sina", "sitt", "skulle", "som", "så", "till",
+    "under", "upp", "ut", "vad", "var", "vi", "vid", "åt", "är", "över",
+];
+
+pub const DANISH: &[&str] = &[
+    "af", "alle", "at", "blev", "bliver", "da", "de", "dem", "den", "denne", "der", "deres",
+    "det", "dette", "dig", "din", "du", "efter", "eller", "en", "end", "er", "et", "for", "fra",
+    "ham", "han", "hans", "har", "havde", "hende", "hun", "hvad", "hvis", "hvor", "i", "ikke",
+    "jeg", "kan", "man", "med", "meget", "men", "mig", "min", "mod", "når", "og", "også", "om",
+    "op", "os", "på", "sig", "sin", "skal", "som", "til", "ud", "under", "var", "vi", "vil",
+    "være", "været",
+];
+
+pub const NORWEGIAN: &[&str] = &[
+    "alle", "at", "av", "bare", "da", "de", "dei", "deg", "dem", "den", "denne", "der", "det",
+    "dette", "du", "eller", "en", "er", "et", "ett", "etter", "for", "fra", "ha", "hadde", "han",
+    "hans", "har", "hennes", "hun", "hva", "hvis", "hvor", "i", "ikke", "inn", "jeg", "kan",
+    "man", "med", "meg", "men", "mot", "mye", "nå", "når", "og", "også", "om", "opp", "oss",
+    "over", "på", "seg", "sin", "skal", "som", "til", "ut", "var", "vi", "vil", "være", "vært",
+];
+
+pub const FINNISH: &[&str] = &[
+    "ei", "eivät", "eli", "ennen", "että", "he", "heidän", "hän", "hänen", "ja", "jo", "joka",
+    "jos", "jotka", "kanssa", "kuin", "kun", "me", "mikä", "minä", "minun", "mitä", "mukaan",
+    "mutta", "myös", "ne", "niin", "nyt", "olen", "oli", "olivat", "olla", "on", "ovat", "se",
+    "sekä", "sen", "siitä", "sinä", "tai", "tämä", "tässä", "te", "vaan", "vain", "voi", "yli",
+];
+
+pub const RUSSIAN: &[&str] = &[
+    "а", "без", "бы", "был", "была", "были", "было", "быть", "в", "вам", "вас", "во", "вот",
+    "все", "всё", "вы", "где", "да", "для", "до", "его", "ее", "её", "если", "есть", "еще",
+    "ещё", "же", "за", "и", "из", "или", "им", "их", "к", "как", "когда", "кто", "ли", "мне",
+    "мы", "на", "не", "него", "нет", "ни", "но", "о", "об", "он", "она", "они", "оно", "от",
+    "по", "при", "с", "так", "также", "то", "только", "у", "уже", "что", "это", "этот", "я",
+];
@@ -0,0 +1,292 @@
+use anyhow::Result;
+use std::path::Path;
+
+/// A silence at least this long (in ms) between cues starts a new paragraph.
+const PAUSE_MS: u64 = 2_000;
+/// Once a paragraph spans this long (in ms), it ends at the next sentence end.
+const PARAGRAPH_MS: u64 = 30_000;
+/// Paragraphs are cut at this length (in ms) even mid-sentence.
+const MAX_PARAGRAPH_MS: u64 = 90_000;
+
+/// A stretch of a transcript, timed in milliseconds from the start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Speaker, from a WebVTT `<v Name>` voice tag.
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+impl Cue {
+    /// The cue's text, headed by its speaker ("Alice: ...") if known.
+    pub fn labeled_text(&self) -> String {
+        match &self.speaker {
+            Some(speaker) => format!("{}: {}", speaker, self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+/// Extracts the transcript of a subtitle file (.srt or .vtt) as
+/// paragraphs: consecutive cues merged until a pause, a change of
+/// speaker, or a sentence end once the paragraph runs long.
+pub fn extract_paragraphs(path: &str) -> Result<Vec<Cue>> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    let paragraphs = paragraphs(&parse_cues(&crate::text::read_file(path)?));
+    if paragraphs.is_empty() {
+        anyhow::bail!("No subtitle cues found in: {}", path);
+    }
+    Ok(paragraphs)
+}
+
+/// Cues of an SRT or WebVTT file, in order, with markup removed.
+///
+/// Blocks without a timing line (the WEBVTT header, NOTE, STYLE, and
+/// REGION blocks) are skipped, as are cues without text.
+pub fn parse_cues(source: &str) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    for block in source.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let Some((start_ms, end_ms)) = parse_timing(timing) else {
+            continue;
+        };
+
+        let mut speaker = None;
+        let mut text = String::new();
+        for line in lines {
+            let (voice, line) = clean_line(line);
+            speaker = speaker.or(voice);
+            if !line.is_empty() {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&line);
+            }
+        }
+        if !text.is_empty() {
+            cues.push(Cue {
+                start_ms,
+                end_ms,
+                speaker,
+                text,
+            });
+        }
+    }
+    cues
+}
+
+/// Merges cues into paragraphs.
+///
+/// Auto-generated captions repeat each line in the next cue as they
+/// scroll, so text a cue shares with the end of the paragraph so far is
+/// only kept once.
+pub fn paragraphs(cues: &[Cue]) -> Vec<Cue> {
+    let mut paragraphs: Vec<Cue> = Vec::new();
+    for cue in cues {
+        if let Some(paragraph) = paragraphs.last_mut() {
+            let length = cue.end_ms.saturating_sub(paragraph.start_ms);
+            let sentence_end = paragraph.text.ends_with(['.', '?', '!', '…']);
+            let breaks = cue.start_ms.saturating_sub(paragraph.end_ms) >= PAUSE_MS
+                || (cue.speaker.is_some() && cue.speaker != paragraph.speaker)
+                || (paragraph.start_ms + PARAGRAPH_MS <= cue.start_ms && sentence_end)
+                || length > MAX_PARAGRAPH_MS;
+            if !breaks {
+                let new_text = unrepeated(&paragraph.text, &cue.text);
+                if !new_text.is_empty() {
+                    paragraph.text.push(' ');
+                    paragraph.text.push_str(new_text);
+                }
+                paragraph.end_ms = paragraph.end_ms.max(cue.end_ms);
+                continue;
+            }
+        }
+        paragraphs.push(cue.clone());
+    }
+    paragraphs
+}
+
+/// The part of `next` that doesn't repeat the end of `text`: the longest
+/// prefix of `next` (in whole words) that `text` ends with is dropped.
+fn unrepeated<'a>(text: &str, next: &'a str) -> &'a str {
+    let mut rest = next;
+    for end in next.match_indices(' ').map(|(i, _)| i).chain([next.len()]) {
+        let repeated = text
+            .strip_suffix(&next[..end])
+            .is_some_and(|before| before.is_empty() || before.ends_with(' '));
+        if repeated {
+            rest = next[end..].trim_start();
+        }
+    }
+    rest
+}
+
+/// Start and end of a timing line such as
+/// "00:01:02,500 --> 00:01:04,000" (SRT) or "01:02.500 --> 01:04.000
+/// align:start" (WebVTT, whose hours are optional).
+fn parse_timing(line: &str) -> Option<(u64, u64)> {
+    let (start, end) = line.split_once("-->")?;
+    let end = end.split_whitespace().next()?;
+    Some((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+/// Milliseconds of a `[hh:]mm:ss[,.]mmm` timestamp.
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (clock, millis) = timestamp.split_once([',', '.']).unwrap_or((timestamp, "0"));
+    let mut seconds = 0;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    let millis = format!("{:0<3}", millis);
+    Some(seconds * 1000 + millis.get(..3)?.parse::<u64>().ok()?)
+}
+
+/// A cue line without its markup (`<i>`, `<c.yellow>`, `{\an8}`, ...) and
+/// entities, and the speaker named by a `<v Name>` voice tag, if any.
+fn clean_line(line: &str) -> (Option<String>, String) {
+    let mut speaker = None;
+    let mut text = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find(['<', '{']) {
+        text.push_str(&rest[..open]);
+        let close = if rest[open..].starts_with('<') {
+            '>'
+        } else {
+            '}'
+        };
+        let Some(len) = rest[open..].find(close) else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = &rest[open + 1..open + len];
+        if let Some(name) = tag.strip_prefix("v ").or_else(|| {
+            tag.strip_prefix("v.")
+                .and_then(|tag| tag.split_once(' ').map(|(_, name)| name))
+        }) {
+            speaker = Some(name.trim().to_string());
+        }
+        rest = &rest[open + len + 1..];
+    }
+    text.push_str(rest);
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    (
+        speaker,
+        text.split_whitespace().collect::<Vec<_>>().join(" "),
+    )
+}
+
+/// A time as "hh:mm:ss".
+pub fn format_time(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start_ms: u64, end_ms: u64, speaker: Option<&str>, text: &str) -> Cue {
+        Cue {
+            start_ms,
+            end_ms,
+            speaker: speaker.map(String::from),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "1\n00:00:01,000 --> 00:00:03,500\n<i>Welcome to</i> the\nlecture.\n\n\
+                   2\n00:00:04,000 --> 00:00:06,000\n{\\an8}Today: B-trees &amp; LSM trees\n\n\
+                   3\n00:00:07,000 --> 00:00:08,000\n\n";
+        assert_eq!(
+            parse_cues(srt),
+            vec![
+                cue(1_000, 3_500, None, "Welcome to the lecture."),
+                cue(4_000, 6_000, None, "Today: B-trees & LSM trees"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_vtt() {
+        let vtt = "WEBVTT\n\nNOTE recorded 2024-03-01\n\n\
+                   intro\n01:02.5 --> 01:04.000 align:start\n<v Alice>Shall we start?\n\n\
+                   1:00:00.000 --> 1:00:01.250\n<v.loud Bob>Yes.</v>\n";
+        assert_eq!(
+            parse_cues(vtt),
+            vec![
+                cue(62_500, 64_000, Some("Alice"), "Shall we start?"),
+                cue(3_600_000, 3_601_250, Some("Bob"), "Yes."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let cues = vec![
+            cue(0, 2_000, Some("Alice"), "So the index"),
+            cue(2_000, 4_000, Some("Alice"), "is rebuilt nightly."),
+            // A change of speaker
+            cue(4_000, 6_000, Some("Bob"), "Why nightly?"),
+            // A pause
+            cue(9_000, 11_000, None, "Cost, mostly."),
+            cue(11_000, 40_000, None, "Rebuilds are expensive."),
+            // Past PARAGRAPH_MS, after a sentence end
+            cue(40_000, 42_000, None, "Next topic."),
+        ];
+        let merged = paragraphs(&cues);
+        let texts: Vec<&str> = merged.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "So the index is rebuilt nightly.",
+                "Why nightly?",
+                "Cost, mostly. Rebuilds are expensive.",
+                "Next topic.",
+            ]
+        );
+        assert_eq!((merged[0].start_ms, merged[0].end_ms), (0, 4_000));
+        assert_eq!(merged[1].labeled_text(), "Bob: Why nightly?");
+        assert_eq!(
+            merged[2].labeled_text(),
+            "Cost, mostly. Rebuilds are expensive."
+        );
+    }
+
+    #[test]
+    fn test_rolling_captions() {
+        let cues = vec![
+            cue(0, 2_000, None, "we'll look at"),
+            cue(2_000, 4_000, None, "we'll look at hash joins"),
+            cue(4_000, 6_000, None, "hash joins and sort merge"),
+        ];
+        assert_eq!(
+            paragraphs(&cues)[0].text,
+            "we'll look at hash joins and sort merge"
+        );
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(3_723_999), "01:02:03");
+        assert_eq!(parse_timestamp("00:01:02,5"), Some(62_500));
+    }
+}
@@ -0,0 +1,131 @@
+//! Synonym expansion for BM25 queries.
+//!
+//! Keyword search only matches the words a query uses, so "car" misses a
+//! passage about an "automobile". Expanding each query word with its
+//! synonyms lets such passages match while retrieval stays keyword-based:
+//! no embeddings, and every match can be traced to a word in the text.
+//!
+//! A small bundled table covers common everyday and technical words; a
+//! corpus with its own vocabulary passes its own map.
+
+use std::collections::HashMap;
+
+/// Groups of interchangeable words and phrases; each word of a group
+/// expands to the rest.
+const GROUPS: &[&[&str]] = &[
+    // Everyday words
+    &["car", "automobile", "auto"],
+    &["buy", "purchase"],
+    &["sell", "vend"],
+    &["begin", "start", "commence"],
+    &["end", "finish", "conclude"],
+    &["big", "large", "huge"],
+    &["small", "little", "tiny"],
+    &["fast", "quick", "rapid"],
+    &["slow", "sluggish"],
+    &["help", "assist", "support"],
+    &["show", "display"],
+    &["answer", "reply", "response"],
+    &["ask", "inquire", "query"],
+    &["choose", "select", "pick"],
+    &["need", "require"],
+    &["get", "obtain", "acquire"],
+    &["make", "create", "build"],
+    &["fix", "repair", "mend"],
+    &["break", "fracture"],
+    &["hide", "conceal"],
+    &["rich", "wealthy"],
+    &["poor", "impoverished"],
+    &["happy", "glad", "cheerful"],
+    &["sad", "unhappy", "sorrowful"],
+    &["angry", "mad", "furious"],
+    &["smart", "intelligent", "clever"],
+    &["hard", "difficult"],
+    &["easy", "simple"],
+    &["right", "correct"],
+    &["wrong", "incorrect"],
+    &["often", "frequently"],
+    &["maybe", "perhaps"],
+    &["enough", "sufficient"],
+    &["house", "home", "residence"],
+    &["job", "occupation", "employment"],
+    &["doctor", "physician"],
+    &["lawyer", "attorney"],
+    &["child", "kid"],
+    &["movie", "film"],
+    &["shop", "store"],
+    &["street", "road"],
+    &["trip", "journey", "voyage"],
+    &["money", "cash", "funds"],
+    &["price", "cost"],
+    &["salary", "wage", "pay"],
+    &["illness", "disease", "sickness"],
+    &["medicine", "medication", "drug"],
+    // Business and technical words
+    &["customer", "client"],
+    &["employee", "staff", "worker"],
+    &["company", "firm", "business"],
+    &["revenue", "income", "turnover"],
+    &["profit", "earnings"],
+    &["meeting", "conference"],
+    &["error", "fault", "mistake"],
+    &["bug", "defect"],
+    &["problem", "issue"],
+    &["delete", "remove", "erase"],
+    &["install", "setup"],
+    &["configure", "set up"],
+    &["settings", "configuration", "preferences"],
+    &["login", "log in", "sign in"],
+    &["logout", "log out", "sign out"],
+    &["password", "passphrase"],
+    &["folder", "directory"],
+    &["app", "application", "program"],
+    &["computer", "pc"],
+    &["laptop", "notebook"],
+    &["phone", "telephone", "mobile"],
+    &["email", "e-mail", "mail"],
+    &["internet", "web"],
+    &["website", "site", "web page"],
+    &["download", "fetch"],
+    &["upload", "transfer"],
+    &["speed", "velocity"],
+    &["picture", "image", "photo"],
+    &["manual", "guide", "handbook"],
+    &["document", "file"],
+];
+
+/// Word → its synonyms, from the bundled groups.
+pub fn builtin() -> HashMap<String, Vec<String>> {
+    let mut synonyms: HashMap<String, Vec<String>> = HashMap::new();
+    for group in GROUPS {
+        for &word in *group {
+            let others = group.iter().filter(|&&other| other != word);
+            synonyms
+                .entry(word.to_string())
+                .or_default()
+                .extend(others.map(|other| other.to_string()));
+        }
+    }
+    synonyms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_groups_expand_both_ways() {
+        let synonyms = builtin();
+        assert_eq!(synonyms["car"], ["automobile", "auto"]);
+        assert_eq!(synonyms["automobile"], ["car", "auto"]);
+        assert_eq!(synonyms["login"], ["log in", "sign in"]);
+        assert!(!synonyms.contains_key("zebra"));
+    }
+
+    #[test]
+    fn test_groups_are_lowercase() {
+        for word in GROUPS.iter().flat_map(|group| group.iter()) {
+            assert_eq!(*word, word.to_lowercase());
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Bytes inspected when sniffing for UTF-16 or binary content.
+const SNIFF_BYTES: usize = 8192;
+
+/// Reads a text file (notes, source code, ...) as a `String`, detecting its
+/// encoding with `decode`.
+pub fn read_file(path: &str) -> Result<String> {
+    let file_path = Path::new(path);
+
+    if !file_path.exists() {
+        anyhow::bail!("File not found: {}", path);
+    }
+
+    let data =
+        std::fs::read(file_path).with_context(|| format!("Failed to open file: {}", path))?;
+    decode(&data).with_context(|| format!("Failed to read text file: {}", path))
+}
+
+/// Decode text of unknown encoding.
+///
+/// A byte-order mark wins; otherwise UTF-16 is recognised by its pattern of
+/// zero bytes, valid UTF-8 is taken as such, and anything else is read as
+/// Windows-1252 (a superset of Latin-1). Line endings are normalized to
+/// `\n`, and binary data is rejected.
+pub fn decode(data: &[u8]) -> Result<String> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(data) {
+        return Ok(decode_with(encoding, &data[bom_len..]));
+    }
+    if let Some(encoding) = sniff_utf16(data) {
+        return Ok(decode_with(encoding, data));
+    }
+
+    let head = &data[..data.len().min(SNIFF_BYTES)];
+    if head.contains(&0) {
+        anyhow::bail!("File appears to be binary, not text");
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) => Ok(text.replace("\r\n", "\n")),
+        Err(_) => Ok(decode_with(WINDOWS_1252, data)),
+    }
+}
+
+fn decode_with(encoding: &'static Encoding, data: &[u8]) -> String {
+    encoding
+        .decode_without_bom_handling(data)
+        .0
+        .replace("\r\n", "\n")
+}
+
+/// UTF-16 without a BOM: mostly-ASCII text has a zero in every other byte.
+fn sniff_utf16(data: &[u8]) -> Option<&'static Encoding> {
+    let head = &data[..data.len().min(SNIFF_BYTES) & !1];
+    if head.len() < 4 {
+        return None;
+    }
+    let pairs = head.len() / 2;
+    let zeros_at = |offset: usize| {
+        head.iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    if odd * 10 >= pairs * 7 && even * 10 <= pairs {
+        Some(UTF_16LE)
+    } else if even * 10 >= pairs * 7 && odd * 10 <= pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Content hash of extracted text: the SHA-256 (in hex) of its words.
+///
+/// Whitespace is ignored, so copies of a document that differ only in line
+/// wrapping or spacing hash the same.
+pub fn content_hash(text: &str) -> String {
+    hash_words(text.split_whitespace())
+}
+
+/// Content hashes of a document's pages (in parallel), and of the whole
+/// document: the hash of all its pages' words in order.
+pub fn content_hashes(pages: &[String]) -> (Vec<String>, String) {
+    let page_hashes = pages.par_iter().map(|page| content_hash(page)).collect();
+    let document_hash = hash_words(pages.iter().flat_map(|page| page.split_whitespace()));
+    (page_hashes, document_hash)
+}
+
+fn hash_words<'a>(words: impl Iterator<Item = &'a str>) -> String {
+    let mut hasher = Sha256::new();
+    for (i, word) in words.enumerate() {
+        if i > 0 {
+            hasher.update(b" ");
+        }
+        hasher.update(word.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, little_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if little_endian {
+                    unit.to_le_bytes()
+                } else {
+                    unit.to_be_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_utf8_with_and_without_bom() {
+        assert_eq!(decode("naïve café".as_bytes()).unwrap(), "naïve café");
+        assert_eq!(decode(b"\xEF\xBB\xBFhello").unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_utf16() {
+        for le in [true, false] {
+            let bom: &[u8] = if le { b"\xFF\xFE" } else { b"\xFE\xFF" };
+            let mut data = bom.to_vec();
+            data.extend(utf16("# Notes\r\nfn main() {}", le));
+            assert_eq!(decode(&data).unwrap(), "# Notes\nfn main() {}");
+            // Without a BOM too
+            assert_eq!(decode(&utf16("plain notes", le)).unwrap(), "plain notes");
+        }
+    }
+
+    #[test]
+    fn test_legacy_encoding_and_binary() {
+        // "café – 10€" in Windows-1252
+        assert_eq!(decode(b"caf\xE9 \x96 10\x80").unwrap(), "café – 10€");
+        let err = decode(b"\x7FELF\x02\x01\x01\x00\x00\x00").unwrap_err();
+        assert!(err.to_string().contains("binary"));
+    }
+
+    #[test]
+    fn test_read_file() {
+        let path = std::env::temp_dir().join(format!("rusty_rag_test_{}.py", std::process::id()));
+        std::fs::write(&path, "def main():\n    pass\n").unwrap();
+        assert_eq!(
+            read_file(path.to_str().unwrap()).unwrap(),
+            "def main():\n    pass\n"
+        );
+        std::fs::remove_file(path).unwrap();
+
+        let err = read_file("/nonexistent/notes.txt").unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+    }
+
+    #[test]
+    fn test_content_hashes() {
+        assert_eq!(
+            content_hash(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            content_hash("Deep  learning\nfor\tretrieval"),
+            content_hash("Deep learning for retrieval")
+        );
+        assert_ne!(content_hash("Deep learning"), content_hash("deep learning"));
+
+        let pages = vec!["Page one\n".to_string(), "  Page two".to_string()];
+        let (page_hashes, document_hash) = content_hashes(&pages);
+        assert_eq!(
+            page_hashes,
+            vec![content_hash("Page one"), content_hash("Page two")]
+        );
+        assert_eq!(document_hash, content_hash("Page one Page two"));
+    }
+}
@@ -0,0 +1,243 @@
+//! TF-IDF cosine scoring, for comparison with classical pipelines.
+//!
+//! Scores match scikit-learn's `TfidfVectorizer` with its defaults, given
+//! the same terms:
+//!   idf(t)    = ln((1 + N) / (1 + df(t))) + 1
+//!   w(t, d)   = tf(t, d) · idf(t)
+//! (ln(N / df(t)) + 1 without `smooth_idf`; 1 + ln(tf) in place of tf with
+//! `sublinear_tf`). Document and query weights are L2-normalized, so a
+//! score is the cosine of the two vectors, between 0 and 1.
+//!
+//! Documents and queries become terms as in `BM25Index`, whose postings
+//! the index is built on; only the weighting differs.
+
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+
+use crate::bm25::{self, BM25Index, StopwordList, TermOptions};
+
+/// Classical TF-IDF retrieval over a collection of documents.
+///
+/// Construct from Python with:
+///     index = TfIdfIndex(["chunk 1 text", "chunk 2 text", ...])
+///     index.search("query", top_k=10)  # [(doc_index, cosine), ...]
+///
+/// Takes the term options of `BM25Index`, so both rank the same terms.
+/// Unlike scikit-learn's default token pattern, one-character words are
+/// terms too.
+#[pyclass(module = "rusty_rag.rusty_rag_core")]
+pub struct TfIdfIndex {
+    /// The documents' terms and postings; BM25's parameters go unused
+    index: BM25Index,
+    /// Per term ID, its IDF
+    idf: Vec<f64>,
+    /// Per document, the L2 norm of its term weights
+    norms: Vec<f64>,
+    /// Whether term frequencies are dampened to 1 + ln(tf)
+    sublinear_tf: bool,
+}
+
+impl TfIdfIndex {
+    /// Index `documents`, made into terms by `options`.
+    pub fn new(
+        documents: Vec<String>,
+        options: TermOptions,
+        sublinear_tf: bool,
+        smooth_idf: bool,
+    ) -> Self {
+        let index = BM25Index::with_languages(documents, 1.2, 0.75, options, &[]);
+        let n_docs = index.n_docs() as f64;
+        let idf: Vec<f64> = index
+            .postings()
+            .iter()
+            .map(|postings| {
+                let df = postings.len() as f64;
+                if smooth_idf {
+                    ((1.0 + n_docs) / (1.0 + df)).ln() + 1.0
+                } else {
+                    (n_docs / df).ln() + 1.0
+                }
+            })
+            .collect();
+        let mut tfidf = TfIdfIndex {
+            idf,
+            norms: vec![0.0; index.n_docs()],
+            index,
+            sublinear_tf,
+        };
+        let mut squares = vec![0.0; tfidf.index.n_docs()];
+        for (postings, &idf) in tfidf.index.postings().iter().zip(&tfidf.idf) {
+            for posting in postings {
+                squares[posting.doc] += (tfidf.tf_weight(posting.positions.len()) * idf).powi(2);
+            }
+        }
+        tfidf.norms = squares.into_iter().map(f64::sqrt).collect();
+        tfidf
+    }
+
+    /// The weight of a term `tf` times in a document or query, before IDF.
+    fn tf_weight(&self, tf: usize) -> f64 {
+        if self.sublinear_tf {
+            1.0 + (tf as f64).ln()
+        } else {
+            tf as f64
+        }
+    }
+
+    /// The query's indexed terms by ID, with their normalized weights.
+    /// Terms the index lacks have no dimension, so they don't count.
+    fn query_weights(&self, query: &str) -> Vec<(usize, f64)> {
+        let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+        for term in self.index.analyze(query) {
+            if let Some(id) = self.index.term_id(&term) {
+                *counts.entry(id as usize).or_default() += 1;
+            }
+        }
+        let weights: Vec<(usize, f64)> = counts
+            .into_iter()
+            .map(|(id, tf)| (id, self.tf_weight(tf) * self.idf[id]))
+            .collect();
+        let norm = weights.iter().map(|&(_, w)| w * w).sum::<f64>().sqrt();
+        weights.into_iter().map(|(id, w)| (id, w / norm)).collect()
+    }
+
+    /// The cosine similarity of every document to `query`, in document
+    /// order; 0 for documents with no query term.
+    pub fn get_scores(&self, query: &str) -> Vec<f64> {
+        let mut scores = vec![0.0; self.index.n_docs()];
+        for (id, query_weight) in self.query_weights(query) {
+            for posting in &self.index.postings()[id] {
+                let weight = self.tf_weight(posting.positions.len()) * self.idf[id];
+                scores[posting.doc] += query_weight * weight / self.norms[posting.doc];
+            }
+        }
+        scores
+    }
+
+    /// The `top_k` documents most similar to `query`, as (document index,
+    /// cosine) pairs, best first; ties go to the earlier document.
+    /// Documents with no query term are left out.
+    pub fn search(&self, query: &str, top_k: usize) -> Vec<(usize, f64)> {
+        let mut scores: Vec<(usize, f64)> = self
+            .get_scores(query)
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, score)| score > 0.0)
+            .collect();
+        bm25::keep_top(&mut scores, top_k);
+        scores
+    }
+}
+
+#[pymethods]
+impl TfIdfIndex {
+    /// Args:
+    ///     documents: The texts to index.
+    ///     language, stopwords, stemmer, fold_accents, ngrams, word_chars,
+    ///         lemmatize, normalize_numbers, keep_compounds, lowercase: How
+    ///         texts become terms, as for `BM25Index`.
+    ///     sublinear_tf: Whether term frequencies count as 1 + ln(tf)
+    ///         (default false), like scikit-learn's option.
+    ///     smooth_idf: Whether IDF counts an extra document containing
+    ///         every term (default true), like scikit-learn's option.
+    #[new]
+    #[pyo3(signature = (
+        documents, language=None, stopwords=None, stemmer=None, fold_accents=false, ngrams=1,
+        word_chars=None, lemmatize=false, normalize_numbers=false, keep_compounds=false,
+        lowercase=true, sublinear_tf=false, smooth_idf=true,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        py: Python<'_>,
+        documents: Vec<String>,
+        language: Option<&str>,
+        stopwords: Option<StopwordList>,
+        stemmer: Option<&str>,
+        fold_accents: bool,
+        ngrams: usize,
+        word_chars: Option<&str>,
+        lemmatize: bool,
+        normalize_numbers: bool,
+        keep_compounds: bool,
+        lowercase: bool,
+        sublinear_tf: bool,
+        smooth_idf: bool,
+    ) -> PyResult<Self> {
+        let options = bm25::term_options(
+            &documents,
+            language,
+            stopwords,
+            stemmer,
+            fold_accents,
+            ngrams,
+            word_chars,
+            lemmatize,
+            normalize_numbers,
+            keep_compounds,
+            lowercase,
+        )
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:#}", e)))?;
+        Ok(py.allow_threads(|| TfIdfIndex::new(documents, options, sublinear_tf, smooth_idf)))
+    }
+
+    /// Return up to `top_k` (document_index, cosine) tuples, most similar
+    /// first. Only documents sharing a term with the query are returned.
+    #[pyo3(name = "search", signature = (query, top_k=10))]
+    fn py_search(&self, py: Python<'_>, query: &str, top_k: usize) -> Vec<(usize, f64)> {
+        py.allow_threads(|| self.search(query, top_k))
+    }
+
+    /// Return the cosine similarity of every document to the query, in
+    /// index order, 0.0 for documents sharing no term with it.
+    #[pyo3(name = "get_scores")]
+    fn py_get_scores(&self, py: Python<'_>, query: &str) -> Vec<f64> {
+        py.allow_threads(|| self.get_scores(query))
+    }
+
+    fn __len__(&self) -> usize {
+        self.index.n_docs()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TfIdfIndex(n_docs={}, vocab_size={}, sublinear_tf={})",
+            self.index.n_docs(),
+            self.idf.len(),
+            self.sublinear_tf
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(texts: &[&str]) -> TfIdfIndex {
+        let documents = texts.iter().map(|t| t.to_string()).collect();
+        TfIdfIndex::new(documents, TermOptions::default(), false, true)
+    }
+
+    #[test]
+    fn test_matches_tfidf_vectorizer() {
+        // TfidfVectorizer's defaults: smoothed IDF, raw counts, L2 norm,
+        // worked out for "cat" (idf ln(4/3) + 1) in each document
+        let index = index(&["the cat sat", "the dog sat", "the cat ate the cat food"]);
+        let scores = index.get_scores("cat");
+        let expected = [0.6198053799406072, 0.0, 0.6366011735459216];
+        for (score, expected) in scores.iter().zip(expected) {
+            assert!((score - expected).abs() < 1e-9, "{:?}", scores);
+        }
+        let hits = index.search("cat", 10);
+        assert_eq!(hits.iter().map(|h| h.0).collect::<Vec<_>>(), [2, 0]);
+    }
+
+    #[test]
+    fn test_cosine_of_identical_text_is_one() {
+        let index = index(&["red apples", "green pears"]);
+        let hits = index.search("Red apples, zebra", 1);
+        assert_eq!(hits[0].0, 0);
+        assert!((hits[0].1 - 1.0).abs() < 1e-9);
+        assert!(index.search("zebra", 10).is_empty());
+    }
+}
@@ -1,21 +1,398 @@
-/// Word-level tokenizer for text processing and BM25 scoring.
-///
-/// Splits on non-alphanumeric characters (preserving apostrophes for
-/// contractions like "don't"), lowercases everything, and filters empties.
+//! Word-level tokenizer for text processing and BM25 scoring.
+//!
+//! NFKC-normalizes text (so ligatures, full-width letters, and other
+//! typographic variants match their plain forms), splits on non-alphanumeric
+//! characters (preserving apostrophes for contractions like "don't"),
+//! lowercases everything, and filters empties. Accents can optionally be
+//! folded off, so "résumé" matches "resume".
+//!
+//! Which characters besides letters and digits belong to words is set by
+//! `TokenizerOptions`, e.g. to keep "C++", "C#", or "snake_case" whole; the
+//! token chunkers take the same options, so they count the same words. The
+//! options can also keep numbers and dates whole, in one spelling: "3.14"
+//! and "1,000" stay one token ("3.14", "1000"), and "March 1, 2024",
+//! "1 Mar 2024", and "03/01/2024" all become "2024-03-01". Hyphenated
+//! compounds can be kept too, alongside their parts: "state-of-the-art"
+//! gives "state-of-the-art", "state", "of", "the", "art", so a query
+//! matches with or without the hyphens. Case can be kept, for text where
+//! it tells words apart ("CO" and "Co" in chemistry, "Map" and "map" in
+//! code).
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::{is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+/// Which characters make up words: letters, digits, and `word_chars`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenizerOptions {
+    /// Characters kept inside words besides letters and digits; an
+    /// apostrophe by default, for contractions like "don't"
+    pub word_chars: Vec<char>,
+    /// Whether numbers and dates are single tokens in a normal form rather
+    /// than split at their punctuation
+    pub normalize_numbers: bool,
+    /// Whether a hyphenated compound is a token of its own, followed by
+    /// its parts
+    pub keep_compounds: bool,
+    /// Whether tokens keep their case rather than being lowercased
+    pub keep_case: bool,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        TokenizerOptions {
+            word_chars: vec!['\''],
+            normalize_numbers: false,
+            keep_compounds: false,
+            keep_case: false,
+        }
+    }
+}
+
+impl TokenizerOptions {
+    /// Options keeping the characters of `word_chars` (e.g. "'-_#+") in
+    /// words, in place of the default apostrophe.
+    pub fn new(word_chars: &str) -> Self {
+        TokenizerOptions {
+            word_chars: word_chars.chars().collect(),
+            ..TokenizerOptions::default()
+        }
+    }
+
+    /// Keep numbers and dates whole and normalized (see `numbers`).
+    pub fn normalizing_numbers(self) -> Self {
+        TokenizerOptions {
+            normalize_numbers: true,
+            ..self
+        }
+    }
+
+    /// Keep hyphenated compounds whole as well as split into their parts.
+    pub fn keeping_compounds(self) -> Self {
+        TokenizerOptions {
+            keep_compounds: true,
+            ..self
+        }
+    }
+
+    /// Keep the case of words rather than lowercasing them.
+    pub fn keeping_case(self) -> Self {
+        TokenizerOptions {
+            keep_case: true,
+            ..self
+        }
+    }
+
+    /// Whether `c` belongs to a word.
+    pub fn is_word_char(&self, c: char) -> bool {
+        c.is_alphanumeric() || self.word_chars.contains(&c)
+    }
+
+    /// Whether `c` belongs to a word or, keeping compounds, joins two.
+    fn in_word(&self, c: char) -> bool {
+        self.is_word_char(c) || (self.keep_compounds && c == '-')
+    }
+
+    /// Tokenize text into word tokens, lowercase unless keeping case, and
+    /// without accents if `fold_accents`.
+    pub fn tokenize(&self, text: &str, fold_accents: bool) -> Vec<String> {
+        let text = normalize(text, fold_accents);
+        if !self.normalize_numbers {
+            return self.words(&text).collect();
+        }
+        let mut tokens = Vec::new();
+        let mut last = 0;
+        for (start, end, number) in numbers(&text) {
+            tokens.extend(self.words(&text[last..start]));
+            tokens.push(number);
+            last = end;
+        }
+        tokens.extend(self.words(&text[last..]));
+        tokens
+    }
+
+    /// Count the number of word tokens in text.
+    pub fn token_count(&self, text: &str) -> usize {
+        if self.normalize_numbers || self.keep_compounds {
+            return self.tokenize(text, false).len();
+        }
+        normalize(text, false)
+            .split(|c: char| !self.is_word_char(c))
+            .filter(|s| !s.is_empty())
+            .count()
+    }
+
+    /// The words of normalized text, lowercase unless keeping case.
+    fn words<'a>(&'a self, text: &'a str) -> impl Iterator<Item = String> + 'a {
+        text.split(|c: char| !self.in_word(c))
+            .filter(|s| !s.is_empty())
+            .flat_map(|s| {
+                self.word_tokens(if self.keep_case {
+                    s.to_string()
+                } else {
+                    s.to_lowercase()
+                })
+            })
+    }
+
+    /// The tokens of a word: itself, or keeping compounds, a hyphenated
+    /// word followed by its parts ("e-mail" → "e-mail", "e", "mail").
+    fn word_tokens(&self, word: String) -> Vec<String> {
+        if !self.keep_compounds {
+            return vec![word];
+        }
+        // Hyphens only join words, unless they're word characters
+        let compound = if self.word_chars.contains(&'-') {
+            word.as_str()
+        } else {
+            word.trim_matches('-')
+        };
+        let parts: Vec<&str> = compound.split('-').filter(|p| !p.is_empty()).collect();
+        if parts.len() < 2 {
+            return if compound.is_empty() {
+                vec![]
+            } else {
+                vec![compound.to_string()]
+            };
+        }
+        std::iter::once(compound)
+            .chain(parts)
+            .map(String::from)
+            .collect()
+    }
+
+    /// Tokenize text like `tokenize`, pairing each token with the byte range
+    /// `(start, end)` of the word it came from in `text`.
+    ///
+    /// Words are found in the original text, combining marks included, and
+    /// normalized one by one, so the spans stay valid for highlighting even
+    /// where normalization changes lengths ("ﬁle" spans 5 bytes, not 4). A
+    /// word that normalizes to several tokens ("½" → "1", "2") gives each
+    /// its span. A compound kept whole spans all its parts, which follow
+    /// with their own spans.
+    pub fn tokenize_with_spans(&self, text: &str) -> Vec<(String, usize, usize)> {
+        let mut spans = Vec::new();
+        let mut last = 0;
+        if self.normalize_numbers {
+            for (start, end, number) in numbers(text) {
+                self.push_word_spans(text, last, start, &mut spans);
+                spans.push((number, start, end));
+                last = end;
+            }
+        }
+        self.push_word_spans(text, last, text.len(), &mut spans);
+        spans
+    }
+
+    /// Push the tokens of `text[from..to]` with their words' spans.
+    fn push_word_spans(
+        &self,
+        text: &str,
+        from: usize,
+        to: usize,
+        spans: &mut Vec<(String, usize, usize)>,
+    ) {
+        let mut start = None;
+        for (i, c) in text[from..to]
+            .char_indices()
+            .map(|(i, c)| (from + i, c))
+            .chain(std::iter::once((to, ' ')))
+        {
+            let in_word = self.in_word(c) || is_combining_mark(c);
+            match (start, in_word) {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    let tokens = self.tokenize(&text[s..i], false);
+                    if tokens.len() > 1 && self.keep_compounds && tokens[0].contains('-') {
+                        spans.push((tokens[0].clone(), s, i));
+                        self.parts().push_word_spans(text, s, i, spans);
+                    } else {
+                        for token in tokens {
+                            spans.push((token, s, i));
+                        }
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// These options splitting words at hyphens, for a compound's parts.
+    fn parts(&self) -> Self {
+        TokenizerOptions {
+            word_chars: self
+                .word_chars
+                .iter()
+                .copied()
+                .filter(|&c| c != '-')
+                .collect(),
+            keep_compounds: false,
+            ..self.clone()
+        }
+    }
+
+    /// Byte ranges of the words of `text`, as written (not normalized);
+    /// what the token chunkers count.
+    pub fn word_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut word_spans: Vec<(usize, usize)> = Vec::new();
+        let mut in_word = false;
+        let mut word_start = 0;
+
+        for (i, c) in text.char_indices() {
+            if self.is_word_char(c) {
+                if !in_word {
+                    word_start = i;
+                    in_word = true;
+                }
+            } else if in_word {
+                word_spans.push((word_start, i));
+                in_word = false;
+            }
+        }
+        if in_word {
+            word_spans.push((word_start, text.len()));
+        }
+        word_spans
+    }
+}
+
+/// Month names and abbreviations, matched case-insensitively.
+const MONTHS: &str = "jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|\
+                      aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?";
+
+/// Numbers and dates, tried in order at each position: ISO dates,
+/// day-first dates with dots or dashes ("01.03.2024"), month-first dates
+/// with slashes ("03/01/2024", or day-first where the first number can't be
+/// a month), dates with month names, numbers with thousands separators, and
+/// decimals (or versions and IP addresses, kept as they are).
+fn number_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        let pattern = format!(
+            "(?i)\\b(?:(?P<iso_y>[0-9]{{4}})-(?P<iso_m>[0-9]{{1,2}})-(?P<iso_d>[0-9]{{1,2}})\
+             |(?P<dot_d>[0-9]{{1,2}})[.-](?P<dot_m>[0-9]{{1,2}})[.-](?P<dot_y>[0-9]{{4}})\
+             |(?P<us_m>[0-9]{{1,2}})/(?P<us_d>[0-9]{{1,2}})/(?P<us_y>[0-9]{{4}})\
+             |(?P<mdy_m>{m})\\.?\\s+(?P<mdy_d>[0-9]{{1,2}})(?:st|nd|rd|th)?,?\\s+\
+             (?P<mdy_y>[0-9]{{4}})\
+             |(?P<dmy_d>[0-9]{{1,2}})(?:st|nd|rd|th)?\\s+(?P<dmy_m>{m})\\.?,?\\s+\
+             (?P<dmy_y>[0-9]{{4}})\
+             |(?P<my_m>{m})\\.?\\s+(?P<my_y>[0-9]{{4}})\
+             |(?P<thousands>[0-9]{{1,3}}(?:,[0-9]{{3}})+(?:\\.[0-9]+)?)\
+             |(?P<decimal>[0-9]+(?:\\.[0-9]+)+))\\b",
+            m = MONTHS
+        );
+        Regex::new(&pattern).expect("valid number pattern")
+    })
+}
+
+/// The numbers and dates of `text` as (start, end, normal form): dates as
+/// ISO "YYYY-MM-DD" (or "YYYY-MM" without a day), numbers without
+/// thousands separators. Impossible dates ("2024-13-45") are skipped.
+fn numbers(text: &str) -> impl Iterator<Item = (usize, usize, String)> + '_ {
+    number_pattern().captures_iter(text).filter_map(|caps| {
+        let whole = caps.get(0)?;
+        Some((whole.start(), whole.end(), normal_number(&caps)?))
+    })
+}
+
+/// The normal form of a `number_pattern` match.
+fn normal_number(caps: &Captures) -> Option<String> {
+    let int = |name: &str| caps.name(name).and_then(|m| m.as_str().parse::<u32>().ok());
+    let month = |name: &str| {
+        let name = caps.name(name)?.as_str().to_lowercase();
+        let months = [
+            "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+        ];
+        months
+            .iter()
+            .position(|m| name.starts_with(m))
+            .map(|i| i as u32 + 1)
+    };
+
+    if let Some(year) = int("iso_y") {
+        return iso_date(year, int("iso_m")?, int("iso_d"));
+    }
+    if let Some(year) = int("dot_y") {
+        return iso_date(year, int("dot_m")?, int("dot_d"));
+    }
+    if let Some(year) = int("us_y") {
+        let (m, d) = (int("us_m")?, int("us_d")?);
+        // "14/03/2024" can only be day-first
+        let (m, d) = if m > 12 { (d, m) } else { (m, d) };
+        return iso_date(year, m, Some(d));
+    }
+    if let Some(year) = int("mdy_y") {
+        return iso_date(year, month("mdy_m")?, int("mdy_d"));
+    }
+    if let Some(year) = int("dmy_y") {
+        return iso_date(year, month("dmy_m")?, int("dmy_d"));
+    }
+    if let Some(year) = int("my_y") {
+        return iso_date(year, month("my_m")?, None);
+    }
+    if let Some(number) = caps.name("thousands") {
+        return Some(number.as_str().replace(',', ""));
+    }
+    caps.name("decimal").map(|m| m.as_str().to_string())
+}
+
+/// "YYYY-MM-DD", or "YYYY-MM" without a day; None for an impossible date.
+fn iso_date(year: u32, month: u32, day: Option<u32>) -> Option<String> {
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    match day {
+        Some(day) if (1..=31).contains(&day) => {
+            Some(format!("{:04}-{:02}-{:02}", year, month, day))
+        }
+        Some(_) => None,
+        None => Some(format!("{:04}-{:02}", year, month)),
+    }
+}
 
 /// Tokenize text into lowercase word tokens.
 pub fn tokenize(text: &str) -> Vec<String> {
-    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_lowercase())
+    tokenize_with(text, false)
+}
+
+/// Tokenize text into lowercase word tokens, without accents if
+/// `fold_accents`.
+pub fn tokenize_with(text: &str, fold_accents: bool) -> Vec<String> {
+    TokenizerOptions::default().tokenize(text, fold_accents)
+}
+
+/// Runs of `n` consecutive tokens, each joined by a space ("machine
+/// learning"); none if there are fewer than `n` tokens or `n` is 0.
+pub fn ngrams(tokens: &[String], n: usize) -> Vec<String> {
+    if n == 0 {
+        return vec![];
+    }
+    tokens.windows(n).map(|window| window.join(" ")).collect()
+}
+
+/// NFKC-normalize `text` and strip its accents and other combining marks
+/// ("Crème Brûlée" → "Creme Brulee").
+pub fn fold_accents(text: &str) -> String {
+    text.nfkd()
+        .filter(|&c| !is_combining_mark(c))
+        .nfc()
         .collect()
 }
 
-/// Count the number of word tokens in text.
-pub fn token_count(text: &str) -> usize {
-    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
-        .filter(|s| !s.is_empty())
-        .count()
+/// `text` in NFKC form, without accents if `fold`; borrowed when already
+/// normalized, as most text is.
+fn normalize(text: &str, fold: bool) -> Cow<'_, str> {
+    if fold {
+        Cow::Owned(fold_accents(text))
+    } else if is_nfkc_quick(text.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.nfkc().collect())
+    }
 }
 
 #[cfg(test)]
@@ -37,7 +414,7 @@ mod tests {
     #[test]
     fn test_empty_input() {
         assert!(tokenize("").is_empty());
-        assert_eq!(token_count(""), 0);
+        assert_eq!(TokenizerOptions::default().token_count(""), 0);
     }
 
     #[test]
@@ -54,9 +431,163 @@ mod tests {
 
     #[test]
     fn test_token_count() {
-        assert_eq!(token_count("Hello World"), 2);
-        assert_eq!(token_count("one"), 1);
-        assert_eq!(token_count("a b c d e"), 5);
+        assert_eq!(TokenizerOptions::default().token_count("Hello World"), 2);
+        assert_eq!(TokenizerOptions::default().token_count("one"), 1);
+        assert_eq!(TokenizerOptions::default().token_count("a b c d e"), 5);
+    }
+
+    #[test]
+    fn test_nfkc_normalization() {
+        // Ligature, full-width letters, and a decomposed "é"
+        let tokens = tokenize("ﬁle ＡＰＩ cafe\u{301}");
+        assert_eq!(tokens, vec!["file", "api", "café"]);
+        assert_eq!(TokenizerOptions::default().token_count("ﬁle ＡＰＩ"), 2);
+    }
+
+    #[test]
+    fn test_accent_folding() {
+        assert_eq!(
+            tokenize_with("Résumé naïve Ærø", true),
+            vec!["resume", "naive", "ærø"]
+        );
+        assert_eq!(tokenize("résumé"), vec!["résumé"]);
+        assert_eq!(fold_accents("Crème Brûlée"), "Creme Brulee");
+    }
+
+    #[test]
+    fn test_ngrams() {
+        let tokens = tokenize("Machine learning models learn");
+        assert_eq!(
+            ngrams(&tokens, 2),
+            vec!["machine learning", "learning models", "models learn"]
+        );
+        assert_eq!(ngrams(&tokens, 4), vec!["machine learning models learn"]);
+        assert!(ngrams(&tokens, 5).is_empty());
+        assert!(ngrams(&tokens, 0).is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_with_spans() {
+        let text = "Don't ﬁle cafe\u{301}, ok?";
+        let spans = TokenizerOptions::default().tokenize_with_spans(text);
+        let tokens: Vec<String> = spans.iter().map(|(t, _, _)| t.clone()).collect();
+        assert_eq!(tokens, tokenize(text));
+        assert_eq!(spans[0], ("don't".to_string(), 0, 5));
+        assert_eq!(&text[spans[1].1..spans[1].2], "ﬁle");
+        assert_eq!(&text[spans[2].1..spans[2].2], "cafe\u{301}");
+        assert_eq!(&text[spans[3].1..spans[3].2], "ok");
+        assert!(TokenizerOptions::default()
+            .tokenize_with_spans("")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_word_chars() {
+        let code = TokenizerOptions::new("'#+_");
+        assert_eq!(
+            code.tokenize("C# and C++ use snake_case, don't they?", false),
+            vec!["c#", "and", "c++", "use", "snake_case", "don't", "they"]
+        );
+        assert_eq!(code.token_count("C++ rocks"), 2);
+        assert_eq!(
+            TokenizerOptions::new("-").tokenize("state-of-the-art isn't", false),
+            vec!["state-of-the-art", "isn", "t"]
+        );
+        assert_eq!(code.word_spans("a C++ b"), vec![(0, 1), (2, 5), (6, 7)]);
+        assert_eq!(
+            TokenizerOptions::default().word_spans("C++ don't"),
+            vec![(0, 1), (4, 9)]
+        );
+    }
+
+    #[test]
+    fn test_keep_case() {
+        let options = TokenizerOptions::default().keeping_case();
+        assert_eq!(
+            options.tokenize("CO and Co in HashMap", false),
+            vec!["CO", "and", "Co", "in", "HashMap"]
+        );
+        assert_eq!(
+            TokenizerOptions::default().tokenize("CO and Co", false),
+            vec!["co", "and", "co"]
+        );
+    }
+
+    #[test]
+    fn test_keep_compounds() {
+        let options = TokenizerOptions::default().keeping_compounds();
+        assert_eq!(
+            options.tokenize("state-of-the-art", false),
+            vec!["state-of-the-art", "state", "of", "the", "art"]
+        );
+        // Hyphens that don't join words are dropped
+        assert_eq!(
+            options.tokenize("a -- e-mail-", false),
+            vec!["a", "e-mail", "e", "mail"]
+        );
+        assert_eq!(options.token_count("e-mail me"), 4);
+        // With hyphens as word characters too, parts still follow
+        assert_eq!(
+            TokenizerOptions::new("-")
+                .keeping_compounds()
+                .tokenize("x-ray", false),
+            vec!["x-ray", "x", "ray"]
+        );
+
+        let text = "Try e-mail";
+        let spans = options.tokenize_with_spans(text);
+        let tokens: Vec<String> = spans.iter().map(|(t, _, _)| t.clone()).collect();
+        assert_eq!(tokens, options.tokenize(text, false));
+        assert_eq!(&text[spans[1].1..spans[1].2], "e-mail");
+        assert_eq!(&text[spans[2].1..spans[2].2], "e");
+        assert_eq!(&text[spans[3].1..spans[3].2], "mail");
+    }
+
+    #[test]
+    fn test_normalize_numbers() {
+        let options = TokenizerOptions::default().normalizing_numbers();
+        assert_eq!(
+            options.tokenize("Pi is 3.14, revenue $1,234,567.50 in version 1.2.3", false),
+            vec![
+                "pi",
+                "is",
+                "3.14",
+                "revenue",
+                "1234567.50",
+                "in",
+                "version",
+                "1.2.3"
+            ]
+        );
+        for date in [
+            "2024-03-01",
+            "2024-3-1",
+            "01.03.2024",
+            "03/01/2024",
+            "March 1, 2024",
+            "1st Mar. 2024",
+        ] {
+            assert_eq!(options.tokenize(date, false), vec!["2024-03-01"], "{date}");
+        }
+        assert_eq!(options.tokenize("14/03/2024", false), vec!["2024-03-14"]);
+        assert_eq!(
+            options.tokenize("Released in September 2023.", false),
+            vec!["released", "in", "2023-09"]
+        );
+        // Impossible dates are left to the word rules
+        assert_eq!(
+            options.tokenize("2024-13-45", false),
+            vec!["2024", "13", "45"]
+        );
+        assert_eq!(options.token_count("1,000 users on 2024-03-01"), 4);
+        assert_eq!(tokenize("3.14"), vec!["3", "14"]);
+
+        let text = "Paid 1,000 on March 1, 2024.";
+        let spans = options.tokenize_with_spans(text);
+        let tokens: Vec<&str> = spans.iter().map(|(t, _, _)| t.as_str()).collect();
+        assert_eq!(tokens, options.tokenize(text, false));
+        assert_eq!(&text[spans[1].1..spans[1].2], "1,000");
+        assert_eq!(&text[spans[3].1..spans[3].2], "March 1, 2024");
     }
 
     #[test]
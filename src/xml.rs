@@ -0,0 +1,310 @@
+use std::borrow::Cow;
+
+/// A piece of markup produced by `XmlReader`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlEvent<'a> {
+    /// Opening tag; `attrs` is the raw attribute text, read with `attribute`
+    Start {
+        name: &'a str,
+        attrs: &'a str,
+        self_closing: bool,
+    },
+    End {
+        name: &'a str,
+    },
+    /// Character data with entities decoded (CDATA sections included)
+    Text(Cow<'a, str>),
+}
+
+/// A lenient pull tokenizer for XML (and XML-like markup).
+///
+/// It does not check well-formedness: callers only need the tags and text
+/// in document order. Comments, processing instructions, and doctypes are
+/// skipped, and a `<` that can't start a tag is kept as text.
+pub struct XmlReader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// Skip everything up to and including the closing tag `</name>`
+    /// (matched case-insensitively), for elements whose content is not
+    /// markup, like HTML's `<script>`.
+    pub fn skip_to_end(&mut self, name: &str) {
+        let close = format!("</{}", name);
+        let rest = &self.input[self.pos..];
+        let found = rest.match_indices("</").map(|(i, _)| i).find(|&i| {
+            rest[i..]
+                .get(..close.len())
+                .is_some_and(|s| s.eq_ignore_ascii_case(&close))
+        });
+        match found {
+            Some(i) => {
+                self.pos += i;
+                self.skip_past(">");
+            }
+            None => self.pos = self.input.len(),
+        }
+    }
+
+    /// Advance past `marker`, or to the end of input if it never appears.
+    fn skip_past(&mut self, marker: &str) {
+        self.pos = self.input[self.pos..]
+            .find(marker)
+            .map_or(self.input.len(), |i| self.pos + i + marker.len());
+    }
+}
+
+impl<'a> Iterator for XmlReader<'a> {
+    type Item = XmlEvent<'a>;
+
+    fn next(&mut self) -> Option<XmlEvent<'a>> {
+        loop {
+            let rest = &self.input[self.pos..];
+            if rest.is_empty() {
+                return None;
+            }
+
+            if !starts_tag(rest) {
+                // Text runs to the next real tag
+                let end = rest
+                    .char_indices()
+                    .skip(1)
+                    .find(|&(i, c)| c == '<' && starts_tag(&rest[i..]))
+                    .map_or(rest.len(), |(i, _)| i);
+                self.pos += end;
+                return Some(XmlEvent::Text(unescape(&rest[..end])));
+            }
+
+            if rest.starts_with("<!--") {
+                self.skip_past("-->");
+            } else if let Some(data) = rest.strip_prefix("<![CDATA[") {
+                let end = data.find("]]>").unwrap_or(data.len());
+                self.skip_past("]]>");
+                return Some(XmlEvent::Text(Cow::Borrowed(&data[..end])));
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>");
+            } else if rest.starts_with("<!") {
+                self.skip_past(">");
+            } else if let Some(close) = rest.strip_prefix("</") {
+                let end = close.find('>').unwrap_or(close.len());
+                self.skip_past(">");
+                return Some(XmlEvent::End {
+                    name: close[..end].trim(),
+                });
+            } else {
+                let end = tag_end(rest);
+                self.pos += end;
+                let tag = rest[1..end].trim_end_matches('>');
+                let (tag, self_closing) = match tag.strip_suffix('/') {
+                    Some(tag) => (tag, true),
+                    None => (tag, false),
+                };
+                let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+                return Some(XmlEvent::Start {
+                    name: &tag[..name_end],
+                    attrs: &tag[name_end..],
+                    self_closing,
+                });
+            }
+        }
+    }
+}
+
+/// Whether `s` begins with something that can open a tag.
+fn starts_tag(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next() == Some('<')
+        && chars
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || matches!(c, '/' | '!' | '?' | '_'))
+}
+
+/// Byte offset just past the `>` closing the tag at the start of `s`,
+/// ignoring any `>` inside quoted attribute values.
+fn tag_end(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Strip a namespace prefix: `w:p` → `p`.
+pub fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Look up an attribute in a tag's raw attribute text by local name.
+pub fn attribute<'a>(attrs: &'a str, key: &str) -> Option<Cow<'a, str>> {
+    let mut rest = attrs;
+    loop {
+        rest = rest.trim_start();
+        let eq = rest.find('=')?;
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next()?;
+        let (raw, after) = if quote == '"' || quote == '\'' {
+            let end = value[1..].find(quote)? + 1;
+            (&value[1..end], &value[end + 1..])
+        } else {
+            let end = value
+                .find(|c: char| c.is_whitespace())
+                .unwrap_or(value.len());
+            (&value[..end], &value[end..])
+        };
+        if name == key || local_name(name) == key {
+            return Some(unescape(raw));
+        }
+        rest = after;
+    }
+}
+
+/// Decode character references, XML's predefined entities, and common HTML
+/// ones; unknown entities are kept as they are.
+pub fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest
+            .find(';')
+            .filter(|&semi| semi <= 12)
+            .and_then(|semi| Some((decode_entity(&rest[1..semi])?, semi)));
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        // Common HTML named entities
+        "nbsp" => Some('\u{a0}'),
+        "ndash" => Some('–'),
+        "mdash" => Some('—'),
+        "hellip" => Some('…'),
+        "lsquo" => Some('‘'),
+        "rsquo" => Some('’'),
+        "ldquo" => Some('“'),
+        "rdquo" => Some('”'),
+        "laquo" => Some('«'),
+        "raquo" => Some('»'),
+        "bull" => Some('•'),
+        "middot" => Some('·'),
+        "copy" => Some('©'),
+        "reg" => Some('®'),
+        "trade" => Some('™'),
+        "deg" => Some('°'),
+        "times" => Some('×'),
+        "euro" => Some('€'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        let xml =
+            r#"<?xml version="1.0"?><!-- note --><w:p a="1"><w:t>Hi &amp; bye</w:t><w:br/></w:p>"#;
+        let events: Vec<XmlEvent> = XmlReader::new(xml).collect();
+        assert_eq!(
+            events,
+            vec![
+                XmlEvent::Start {
+                    name: "w:p",
+                    attrs: r#" a="1""#,
+                    self_closing: false
+                },
+                XmlEvent::Start {
+                    name: "w:t",
+                    attrs: "",
+                    self_closing: false
+                },
+                XmlEvent::Text(Cow::Borrowed("Hi & bye")),
+                XmlEvent::End { name: "w:t" },
+                XmlEvent::Start {
+                    name: "w:br",
+                    attrs: "",
+                    self_closing: true
+                },
+                XmlEvent::End { name: "w:p" },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lenient_text_and_cdata() {
+        let events: Vec<XmlEvent> = XmlReader::new("a < b<x><![CDATA[<raw>]]></x>").collect();
+        assert_eq!(events[0], XmlEvent::Text(Cow::Borrowed("a < b")));
+        assert_eq!(events[2], XmlEvent::Text(Cow::Borrowed("<raw>")));
+    }
+
+    #[test]
+    fn test_attribute_and_local_name() {
+        let attrs = r#" w:val="Heading1" title='a &gt; b' x=y"#;
+        assert_eq!(attribute(attrs, "val").as_deref(), Some("Heading1"));
+        assert_eq!(attribute(attrs, "title").as_deref(), Some("a > b"));
+        assert_eq!(attribute(attrs, "x").as_deref(), Some("y"));
+        assert_eq!(attribute(attrs, "missing"), None);
+        assert_eq!(local_name("w:tbl"), "tbl");
+        assert_eq!(local_name("body"), "body");
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(
+            unescape("&lt;a&gt; &#65;&#x42; &bogus; & x"),
+            "<a> AB &bogus; & x"
+        );
+        assert_eq!(unescape("a&mdash;b&hellip;"), "a—b…");
+    }
+
+    #[test]
+    fn test_skip_to_end() {
+        let mut reader = XmlReader::new("<script>if (a < b) {}</SCRIPT><p>after</p>");
+        reader.next();
+        reader.skip_to_end("script");
+        assert!(matches!(
+            reader.next(),
+            Some(XmlEvent::Start { name: "p", .. })
+        ));
+    }
+}
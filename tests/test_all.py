@@ -193,11 +193,52 @@ def test_rust_core():
     try:
         from rusty_rag import (
             tokenize,
+            tokenize_batch,
+            tokenize_ngrams,
+            tokenize_with_spans,
             token_count,
+            detect_language,
+            content_hashes,
+            near_duplicates,
+            garbage_chunks,
+            chunk_keywords,
             chunk_text,
             chunk_text_parallel,
             chunk_by_tokens,
+            chunk_by_tokens_parallel,
+            token_spans,
+            text_spans,
+            chunk_by_model_tokens,
+            SubwordTokenizer,
+            Chunk,
+            ChunkStream,
+            chunk_by_sentences,
+            chunk_recursive,
+            chunk_markdown,
+            markdown_tables,
+            chunk_markdown_table,
+            chunk_code,
+            split_sentences,
+            chunk_semantic,
             BM25Index,
+            TfIdfIndex,
+            SpellCorrector,
+            TokenStats,
+            rrf_fuse,
+            encrypt_bytes,
+            decrypt_bytes,
+            extract_docx_text,
+            extract_html_text,
+            extract_latex_text,
+            extract_epub,
+            extract_pptx,
+            extract_email,
+            extract_subtitles,
+            markdown_sections,
+            read_text_file,
+            extract_spreadsheet_text,
+            extract_document,
+            document_formats,
         )
     except ImportError:
         fail("Import rusty_rag", "Module not found. Run: maturin develop --release")
@@ -210,10 +251,89 @@ def test_rust_core():
     assert tokens == ["hello", "world", "this", "is", "a", "rag", "test"], f"Got: {tokens}"
     ok("tokenize()", f"{len(tokens)} tokens")
 
+    filtered = tokenize("This is a RAG test.", stopwords="auto")
+    assert filtered == ["rag", "test"], f"Got: {filtered}"
+    assert tokenize("A RAG test", stopwords=["rag"]) == ["a", "test"]
+    ok("tokenize(stopwords=...)", "language and custom lists")
+
+    stems = tokenize("The runners are running", stemmer="english")
+    assert stems == ["the", "runner", "are", "run"], f"Got: {stems}"
+    ok("tokenize(stemmer='english')", "Snowball stems")
+
+    assert tokenize("ﬁle ＡＰＩ") == ["file", "api"]
+    folded = tokenize("Résumé naïve", fold_accents=True)
+    assert folded == ["resume", "naive"], f"Got: {folded}"
+    ok("tokenize(fold_accents=True)", "NFKC + accent folding")
+
+    texts = [f"Document {i}: Résumé of the RAG test." for i in range(100)]
+    batch = tokenize_batch(texts, n_threads=2, fold_accents=True)
+    assert batch == [tokenize(t, fold_accents=True) for t in texts], f"Got: {batch[0]!r}"
+    assert tokenize_batch([]) == []
+    ok("tokenize_batch()", f"{len(batch)} texts in parallel")
+
+    text = "Crème brûlée, ﬁle it!"
+    spans = tokenize_with_spans(text)
+    assert [t for t, _, _ in spans] == tokenize(text), f"Got: {spans!r}"
+    encoded = text.encode()
+    assert [encoded[s:e].decode() for _, s, e in spans] == ["Crème", "brûlée", "ﬁle", "it"]
+    ok("tokenize_with_spans()", "byte spans in the original text")
+
+    code_words = tokenize("C++ and C# in snake_case", word_chars="'#+_")
+    assert code_words == ["c++", "and", "c#", "in", "snake_case"], f"Got: {code_words!r}"
+    assert token_count("state-of-the-art", word_chars="-") == 1
+    hyphenated = chunk_by_tokens("state-of-the-art models", 2, 0, word_chars="'-")
+    assert hyphenated == ["state-of-the-art models"], f"Got: {hyphenated!r}"
+    ok("tokenize(word_chars=...)", "'c++', 'c#', 'snake_case' kept whole")
+
+    figures = tokenize("Pi is 3.14; 1,000 users joined on March 1, 2024", normalize_numbers=True)
+    assert figures == ["pi", "is", "3.14", "1000", "users", "joined", "on", "2024-03-01"], (
+        f"Got: {figures!r}"
+    )
+    ok("tokenize(normalize_numbers=True)", "numbers kept whole, dates in ISO form")
+
+    bigrams = tokenize_ngrams("Machine learning, at scale", 2)
+    assert bigrams == ["machine learning", "learning at", "at scale"], f"Got: {bigrams}"
+    assert tokenize_ngrams("one two", 3) == []
+    ok("tokenize_ngrams()", f"{len(bigrams)} bigrams")
+
     count = token_count("The quick brown fox jumps")
     assert count == 5, f"Expected 5, got {count}"
     ok("token_count()", f"{count} tokens")
 
+    lang = detect_language("Der Hund und die Katze sind nicht im Haus, aber der Garten ist groß.")
+    assert lang == "de", f"Expected 'de', got {lang!r}"
+    assert detect_language("12345 67890") is None, "Digits should have no language"
+    ok("detect_language()", "German detected, digits undetermined")
+
+    page_hashes, doc_hash = content_hashes(["Page  one\n", "Page two"])
+    rewrapped_hashes, rewrapped_doc = content_hashes(["Page one", "Page\ntwo"])
+    assert page_hashes == rewrapped_hashes and doc_hash == rewrapped_doc
+    assert len(set(page_hashes)) == 2 and len(doc_hash) == 64, f"Got: {doc_hash}"
+    ok("content_hashes()", "whitespace-insensitive page and document hashes")
+
+    footer = "Confidential: do not distribute this report outside the company. "
+    duplicates = near_duplicates([footer * 3, "Revenue grew.", (footer * 3).upper()])
+    assert duplicates == [None, None, 0], f"Got: {duplicates!r}"
+    ok("near_duplicates()", "repeated boilerplate chunks detected")
+
+    reasons = garbage_chunks(["Revenue grew 12%.", "1 2 3 4 5 6", "=" * 20, "~^% j@ck |1l"])
+    assert reasons == [None, "digits", "repeated", "noise"], f"Got: {reasons!r}"
+    lenient = garbage_chunks(["1 2 3 4 5 6"], max_digit_ratio=1.0, min_alpha_ratio=0)
+    assert lenient == [None], f"Got: {lenient!r}"
+    ok("garbage_chunks()", "digit runs, rules, and OCR noise flagged")
+
+    keywords = chunk_keywords(
+        ["Install it with Docker; Docker volumes keep data.", "Indexes speed up queries."],
+        top_k=2,
+    )
+    assert keywords == [["docker", "install"], ["indexes", "speed"]], f"Got: {keywords!r}"
+    try:
+        chunk_keywords(["text"], language="klingon")
+        raise AssertionError("Expected a ValueError for an unknown language")
+    except ValueError:
+        pass
+    ok("chunk_keywords()", "TF-IDF keywords per chunk")
+
     # ── Character chunking ──
     text = "word " * 1000  # 5000 chars
     chunks = chunk_text(text, 500, 50)
@@ -224,6 +344,12 @@ def test_rust_core():
     assert chunks == parallel
     ok("chunk_text_parallel()", "matches sequential output")
 
+    unicode_text = "Grüße aus Köln — 東京 🚀. " * 100
+    unicode_chunks = chunk_text(unicode_text, 100, 10)
+    assert all(len(c) <= 100 for c in unicode_chunks), f"Got: {unicode_chunks[:2]!r}"
+    assert unicode_chunks == chunk_text_parallel(unicode_text, 100, 10)
+    ok("chunk_text(unicode)", "sized in characters, never splits one")
+
     # ── Token-aware chunking ──
     text2 = "The quick brown fox. " * 100  # ~500 words
     token_chunks = chunk_by_tokens(text2, 50, 10)
@@ -233,6 +359,171 @@ def test_rust_core():
         wc = token_count(chunk)
         assert wc <= 50, f"Chunk {i} has {wc} tokens, expected ≤50"
     ok("chunk_by_tokens()", f"{len(token_chunks)} token-aware chunks")
+    assert chunk_by_tokens_parallel(text2, 50, 10) == token_chunks
+    ok("chunk_by_tokens_parallel()", "same chunks as chunk_by_tokens")
+
+    data = text2.encode("utf-8")
+    spans = token_spans(data, 50, 10)
+    assert [data[s:e].decode("utf-8") for s, e in spans] == token_chunks
+    assert token_spans(text2, 50, 10) == spans
+    packed = memoryview(token_spans(data, 50, 10, packed=True)).cast("Q")
+    assert list(zip(packed[::2], packed[1::2])) == spans, f"Got: {packed.tolist()!r}"
+    cafe = "Café crème".encode("utf-8")
+    assert text_spans(cafe, 4, 0) == [(0, 5), (5, 10), (10, 12)]
+    try:
+        token_spans(b"\xff\xfe")
+        raise AssertionError("Expected a ValueError for invalid UTF-8")
+    except ValueError:
+        pass
+    ok("token_spans() / text_spans()", "byte ranges, packed for memoryview")
+
+    located = chunk_by_tokens("Ünïcode text, with offsets", 2, 0, offsets=True)
+    assert all(isinstance(chunk, Chunk) for chunk in located)
+    assert [chunk.index for chunk in located] == [0, 1]
+    source = "Ünïcode text, with offsets".encode()
+    for chunk in located:
+        span = source[chunk.start_byte : chunk.end_byte].decode()
+        assert span == chunk.text, f"Got: {chunk!r}"
+    ok("chunk_by_tokens(offsets=True)", "Chunk objects with byte offsets")
+
+    story = "A one two. B three four five. C six. D seven eight."
+    sentence_overlap = chunk_by_tokens(story, 5, 0, overlap_sentences=0)
+    assert sentence_overlap == [
+        "A one two. B three",
+        "B three four five. C",
+        "C six. D seven eight",
+    ], f"Got: {sentence_overlap!r}"
+    ok("chunk_by_tokens(overlap_sentences=)", "cut-off sentences repeated whole")
+
+    stream = ChunkStream(text2, 50, 10)
+    first = next(stream)
+    assert first.text == token_chunks[0] and first.index == 0
+    assert [first.text] + [chunk.text for chunk in stream] == token_chunks
+    ok("ChunkStream", "lazily yields chunk_by_tokens' chunks")
+
+    import json
+    import pickle
+    import tempfile
+
+    # A word-level tokenizer.json in which punctuation marks are tokens too
+    tokenizer_json = {
+        "version": "1.0",
+        "truncation": None,
+        "padding": None,
+        "added_tokens": [],
+        "normalizer": None,
+        "pre_tokenizer": {"type": "Whitespace"},
+        "post_processor": None,
+        "decoder": None,
+        "model": {"type": "WordLevel", "vocab": {"[UNK]": 0}, "unk_token": "[UNK]"},
+    }
+    with tempfile.TemporaryDirectory() as tmp:
+        tokenizer_path = str(Path(tmp) / "tokenizer.json")
+        Path(tokenizer_path).write_text(json.dumps(tokenizer_json))
+        model_chunks = chunk_by_model_tokens("Hi, you! Hi, you!", tokenizer_path, 4, 0)
+        assert model_chunks == ["Hi, you!", "Hi, you!"], f"Got: {model_chunks!r}"
+
+        subword = SubwordTokenizer(tokenizer_path)
+        assert subword.token_count("Hi, you!") == 4
+        assert token_count("Hi, you!", tokenizer=subword) == 4
+        assert subword.tokenize("Hi, you!") == ["[UNK]", "[UNK]", "[UNK]", "[UNK]"]
+        by_tokenizer = chunk_by_tokens("Hi, you! Hi, you!", 4, 0, tokenizer=subword)
+        assert by_tokenizer == model_chunks, f"Got: {by_tokenizer!r}"
+        ok("SubwordTokenizer", "token_count and chunk_by_tokens in model tokens")
+    try:
+        chunk_by_model_tokens("text", "/nonexistent/tokenizer.json")
+        raise AssertionError("Expected a ValueError for a missing tokenizer")
+    except ValueError:
+        pass
+    ok("chunk_by_model_tokens()", "chunks sized by a tokenizer.json's tokens")
+
+    # ── Sentence-aware chunking ──
+    prose = "Dr. Smith met Mr. Jones. They spoke at length. Then they left for lunch."
+    sentence_chunks = chunk_by_sentences(prose, 50, 1)
+    assert sentence_chunks == [
+        "Dr. Smith met Mr. Jones. They spoke at length.",
+        "They spoke at length. Then they left for lunch.",
+    ], f"Got: {sentence_chunks!r}"
+    ok("chunk_by_sentences()", "abbreviations kept, one sentence of overlap")
+
+    # ── Recursive chunking ──
+    doc = "Intro.\n\nA longer paragraph. It has two sentences.\n\nEnd."
+    recursive_chunks = chunk_recursive(doc, 30, 0)
+    assert recursive_chunks == [
+        "Intro.",
+        "A longer paragraph.",
+        "It has two sentences.",
+        "End.",
+    ], f"Got: {recursive_chunks!r}"
+    custom = chunk_recursive("a | b | c", 5, 0, separators=[" | "])
+    assert custom == ["a | b", "c"], f"Got: {custom!r}"
+    ok("chunk_recursive()", "paragraphs split into sentences only when too long")
+
+    merged = chunk_recursive(doc, 30, 0, min_chunk_size=10)
+    assert merged == [
+        "Intro.\n\nA longer paragraph.",
+        "It has two sentences.\n\nEnd.",
+    ], f"Got: {merged!r}"
+    merged_offsets = chunk_recursive(doc, 30, 0, offsets=True, min_chunk_size=10)
+    assert [c.text for c in merged_offsets] == merged
+    assert [c.index for c in merged_offsets] == [0, 1]
+    ok("chunk_recursive(min_chunk_size)", "short fragments merged into a neighbour")
+
+    faq = "Q: Why?\nA: Because.\nQ: How?\nA: Like so."
+    faq_chunks = chunk_recursive(faq, 25, 0, separators=["re:(?m)^Q:", "line"])
+    assert faq_chunks == ["Q: Why?\nA: Because.", "Q: How?\nA: Like so."], (
+        f"Got: {faq_chunks!r}"
+    )
+    try:
+        chunk_recursive(faq, separators=["re:("])
+        raise AssertionError("Expected a ValueError for an invalid pattern")
+    except ValueError:
+        pass
+    ok("chunk_recursive(re: separators)", "splits before each match, keeping it")
+
+    # ── Markdown chunking ──
+    guide = "Intro.\n\n## Setup\n\n### Docker\nRun the image."
+    md_chunks = chunk_markdown(guide, 200, 0)
+    assert md_chunks == [
+        ([], "Intro."),
+        (["Setup", "Docker"], "## Setup > ### Docker\nRun the image."),
+    ], f"Got: {md_chunks!r}"
+    ok("chunk_markdown()", "chunks headed by their heading path")
+
+    # ── Table-preserving chunking ──
+    scores = "| Name | Score |\n| --- | --- |\n| Ada | 97 |\n| Alan | 88 |"
+    parts = markdown_tables(f"Results:\n{scores}\nAda won.")
+    assert parts == [(False, "Results:"), (True, scores), (False, "Ada won.")], (
+        f"Got: {parts!r}"
+    )
+    pieces = chunk_markdown_table(scores, 50)
+    assert pieces == [
+        "| Name | Score |\n| --- | --- |\n| Ada | 97 |",
+        "| Name | Score |\n| --- | --- |\n| Alan | 88 |",
+    ], f"Got: {pieces!r}"
+    assert chunk_markdown_table(scores, 200) == [scores]
+    ok("markdown_tables() / chunk_markdown_table()", "tables split between rows only")
+
+    # ── Code chunking ──
+    source = "import os\n\n\ndef a():\n    return 1\n\n\ndef b():\n    return 2\n"
+    code_chunks = chunk_code(source, 40)
+    assert code_chunks == [
+        "import os\n\n\ndef a():\n    return 1",
+        "def b():\n    return 2",
+    ], f"Got: {code_chunks!r}"
+    ok("chunk_code()", "functions kept whole")
+
+    # ── Semantic chunking ──
+    topics = "Cats purr. Cats nap. Rust has traits. Traits are interfaces."
+    sentences = split_sentences(topics)
+    assert len(sentences) == 4, f"Got: {sentences!r}"
+    vectors = [[1.0, 0.1], [0.9, 0.2], [0.1, 1.0], [0.2, 0.9]]
+    semantic_chunks = chunk_semantic(topics, vectors, 0.5)
+    assert semantic_chunks == [
+        "Cats purr. Cats nap.",
+        "Rust has traits. Traits are interfaces.",
+    ], f"Got: {semantic_chunks!r}"
+    ok("chunk_semantic()", "chunks break where sentence embeddings diverge")
 
     # ── BM25 ──
     docs = [
@@ -261,6 +552,509 @@ def test_rust_core():
     ok("BM25 no-match", "returns empty for unknown terms")
 
     print(f"\n  {DIM}repr: {repr(index)}{RESET}")
+
+    # Language analyzer: stemming + stopwords
+    stemmed = BM25Index(["The runner was running fast", "A quiet library"], language="en")
+    hits = stemmed.search("runs", 5)
+    assert [idx for idx, _ in hits] == [0], f"Got: {hits}"
+    assert stemmed.search("the", 5) == []
+    ok("BM25Index(language='en')", "stemmed match, stopwords dropped")
+
+    unstemmed = BM25Index(["The runner was running fast", "A quiet library"], stopwords="en")
+    assert unstemmed.search("the", 5) == [] and unstemmed.search("runs", 5) == []
+    assert [idx for idx, _ in unstemmed.search("running", 5)] == [0]
+    try:
+        BM25Index(["text"], stopwords="klingon")
+        raise AssertionError("Expected ValueError for an unknown language")
+    except ValueError:
+        pass
+    ok("BM25Index(stopwords='en')", "stopwords dropped without stemming")
+
+    porter = BM25Index(["The runner was running fast", "A quiet library"], stemmer="porter")
+    assert [idx for idx, _ in porter.search("runs", 5)] == [0]
+    assert porter.language is None
+    try:
+        BM25Index(["text"], language="en", stemmer="english")
+        raise AssertionError("Expected ValueError for language with stemmer")
+    except ValueError:
+        pass
+    ok("BM25Index(stemmer='porter')", "stemmed match, stopwords kept")
+
+    accented = BM25Index(["Le résumé du projet", "Un café"], fold_accents=True)
+    assert [idx for idx, _ in accented.search("resume", 5)] == [0]
+    ok("BM25Index(fold_accents=True)", "'resume' matches 'résumé'")
+
+    phrases = BM25Index(
+        ["the learning curve of a machine operator", "an introduction to machine learning"],
+        ngrams=2,
+    )
+    hits = phrases.search("machine learning", 5)
+    assert hits[0][0] == 1 and hits[0][1] > hits[1][1], f"Got: {hits}"
+    ok("BM25Index(ngrams=2)", "phrase match ranks first")
+
+    languages = BM25Index(["Tips for C++ developers", "C is a small language"], word_chars="'+")
+    assert [idx for idx, _ in languages.search("C++", 5)] == [0]
+    ok("BM25Index(word_chars=...)", "'C++' doesn't match 'C'")
+
+    lemmas = tokenize("The children ran better", lemmatize=True)
+    assert lemmas == ["the", "child", "run", "good"], f"Got: {lemmas!r}"
+    lemmatized = BM25Index(["A good result for the child", "A bad year"], lemmatize=True)
+    assert [idx for idx, _ in lemmatized.search("better children", 5)] == [0]
+    ok("BM25Index(lemmatize=True)", "'better children' matches 'good ... child'")
+
+    dated = BM25Index(["Released on 2024-03-01", "Released on 2023-05-02"], normalize_numbers=True)
+    assert [idx for idx, _ in dated.search("March 1st, 2024", 5)] == [0]
+    ok("BM25Index(normalize_numbers=True)", "'March 1st, 2024' matches '2024-03-01'")
+
+    compounds = tokenize("An e-mail", keep_compounds=True)
+    assert compounds == ["an", "e-mail", "e", "mail"], f"Got: {compounds!r}"
+    spans = tokenize_with_spans("An e-mail", keep_compounds=True)
+    assert spans[1] == ("e-mail", 3, 9) and spans[3] == ("mail", 5, 9), f"Got: {spans!r}"
+    hyphenated = BM25Index(["A state-of-the-art model", "The art museum"], keep_compounds=True)
+    hits = hyphenated.search("state-of-the-art", 5)
+    assert hits[0][0] == 0 and hits[0][1] > hits[1][1], f"Got: {hits}"
+    ok("tokenize(keep_compounds=True)", "'e-mail' kept whole and split")
+
+    mixed = BM25Index(
+        ["The runner was running fast", "Die Hunde laufen im Park"],
+        language="en",
+        languages=["en", "de"],
+    )
+    hits = mixed.search("Hund runs", 5)
+    assert sorted(idx for idx, _ in hits) == [0, 1], f"Got: {hits}"
+    try:
+        BM25Index(["text"], languages=["en", "de"])
+        raise AssertionError("Expected ValueError for too many languages")
+    except ValueError:
+        pass
+    ok("BM25Index(languages=[...])", "each document stemmed in its own language")
+
+    with tempfile.TemporaryDirectory() as tmp:
+        index_path = str(Path(tmp) / "index.bm25")
+        mixed.save(index_path)
+        loaded = BM25Index.load(index_path)
+        Path(index_path).write_bytes(b"not an index")
+        try:
+            BM25Index.load(index_path)
+            raise AssertionError("Expected RuntimeError for a file that isn't an index")
+        except RuntimeError:
+            pass
+    assert loaded.search("Hund runs", 5) == mixed.search("Hund runs", 5)
+    assert len(loaded) == 2 and loaded.language == "en"
+    ok("BM25Index.save() / load()", "loaded index searches the same")
+
+    unpickled = pickle.loads(pickle.dumps(mixed))
+    assert unpickled.search("Hund runs", 5) == mixed.search("Hund runs", 5)
+    assert len(unpickled) == 2 and unpickled.language == "en"
+    ok("pickle.dumps(BM25Index)", "unpickled index searches the same")
+    restored = BM25Index.from_bytes(mixed.to_bytes())
+    assert restored.search("Hund runs", 5) == mixed.search("Hund runs", 5)
+    try:
+        BM25Index.from_bytes(b"not an index")
+        raise AssertionError("Expected RuntimeError for bytes that aren't an index")
+    except RuntimeError:
+        pass
+    ok("BM25Index.to_bytes() / from_bytes()", "index round-trips through bytes")
+
+    phrased = BM25Index(["a network of neural cells", "the neural network"])
+    hits = phrased.search_phrase("neural network", 5)
+    assert [idx for idx, _ in hits] == [1], f"Got: {hits}"
+    ok("BM25Index.search_phrase()", "words apart don't match the phrase")
+
+    stored = BM25Index(
+        ["the cat sat", "the dog ran"], store_documents=True, ids=["a.txt#1", "b.txt#1"]
+    )
+    hits = stored.search_with_docs("dog", 5)
+    assert [(i, text) for i, _, text in hits] == [("b.txt#1", "the dog ran")], f"Got: {hits}"
+    with tempfile.TemporaryDirectory() as tmp:
+        stored.save(str(Path(tmp) / "index.bm25"))
+        loaded = BM25Index.load(str(Path(tmp) / "index.bm25"))
+    assert loaded.search_with_docs("cat", 5)[0][::2] == ("a.txt#1", "the cat sat")
+    unnamed = BM25Index(["the cat sat"], store_documents=True)
+    assert unnamed.search_with_docs("cat", 5)[0][::2] == (0, "the cat sat")
+    try:
+        phrased.search_with_docs("neural", 5)
+        raise AssertionError("Expected ValueError without stored documents")
+    except ValueError:
+        pass
+    ok("BM25Index.search_with_docs()", "(id, score, text) of each match")
+
+    fielded = BM25Index(
+        [
+            {"title": "Rust", "body": "a guide to safe systems code"},
+            {"title": "Python", "body": "calling Rust from Python, and Rust from C"},
+            "notes on Go",
+        ],
+        field_weights={"title": 3.0},
+        store_documents=True,
+    )
+    assert fielded.fields == [("title", 3.0), ("body", 1.0)], f"Got: {fielded.fields!r}"
+    hits = fielded.search_with_docs("rust", 5)
+    assert [i for i, _, _ in hits] == [0, 1], f"Got: {hits}"
+    assert hits[0][2] == "Rust\n\na guide to safe systems code", f"Got: {hits[0]!r}"
+    try:
+        BM25Index(["x"], field_weights={"title": -1.0})
+        raise AssertionError("Expected ValueError for a negative weight")
+    except ValueError:
+        pass
+    ok("BM25Index(field_weights=...)", "a title match outranks body matches")
+
+    scoped = BM25Index(
+        ["rust ownership", "rust borrowing", "rust bindings for python"],
+        metadata=[
+            {"source": "a.pdf", "page": 1},
+            {"source": "b.pdf", "page": 2},
+            {"source": "a.pdf", "page": 3},
+        ],
+        store_documents=True,
+    )
+    hits = scoped.search("rust", 5, filter={"source": "a.pdf"})
+    assert sorted(idx for idx, _ in hits) == [0, 2], f"Got: {hits}"
+    hits = scoped.search("rust", 5, filter={"source": ["b.pdf", "c.pdf"], "page": 2})
+    assert [idx for idx, _ in hits] == [1], f"Got: {hits}"
+    hits = scoped.search_with_docs("rust", 5, filter={"page": 3})
+    assert [text for _, _, text in hits] == ["rust bindings for python"], f"Got: {hits}"
+    with tempfile.TemporaryDirectory() as tmp:
+        scoped.save(str(Path(tmp) / "index.bm25"))
+        loaded = BM25Index.load(str(Path(tmp) / "index.bm25"))
+    hits = loaded.search_phrase("rust bindings", 5, filter={"source": "a.pdf"})
+    assert [idx for idx, _ in hits] == [2], f"Got: {hits}"
+    try:
+        phrased.search("neural", 5, filter={"source": "a.pdf"})
+        raise AssertionError("Expected ValueError without metadata")
+    except ValueError:
+        pass
+    ok("BM25Index.search(filter=...)", "results scoped by document metadata")
+
+    expanded = BM25Index(["the automobile was parked", "a red bicycle"], synonyms="builtin")
+    hits = expanded.search("car", 5)
+    assert [idx for idx, _ in hits] == [0], f"Got: {hits}"
+    custom = BM25Index(["a lorry on the road"], synonyms={"Truck": ["lorry"]})
+    assert custom.search("truck", 5), "Custom synonym didn't match"
+    assert not BM25Index(["a lorry on the road"]).search("truck", 5)
+    try:
+        BM25Index(["x"], synonyms="wordnet")
+        raise AssertionError("Expected ValueError for unknown synonyms")
+    except ValueError:
+        pass
+    ok("BM25Index(synonyms=...)", "'car' matches 'automobile'")
+
+    queries = ["rust", "python", "haskell"]
+    batched = scoped.search_many(queries, 5)
+    assert batched == [scoped.search(q, 5) for q in queries], f"Got: {batched}"
+    batched = scoped.search_many(queries, 5, filter={"source": "b.pdf"})
+    assert [[idx for idx, _ in hits] for hits in batched] == [[1], [], []], f"Got: {batched}"
+    ok("BM25Index.search_many()", "same results as one search per query")
+
+    dense = scoped.get_scores("bindings")
+    assert len(dense) == len(scoped) and dense[:2] == [0.0, 0.0], f"Got: {dense}"
+    assert dense[2] == scoped.search("bindings", 1)[0][1], f"Got: {dense}"
+    ok("BM25Index.get_scores()", "one score per document, zeros included")
+
+    boolean = BM25Index(["transformer models for vision", "transformer models for language"])
+    assert [doc for doc, _ in boolean.search("transformer -vision")] == [1]
+    assert [doc for doc, _ in boolean.search("models AND vision")] == [0]
+    assert boolean.get_scores("transformer NOT language")[1] == 0.0
+    ok("BM25Index.search('a -b')", "+/-, AND, and NOT require or exclude words")
+
+    fuzzy = BM25Index(["transformer models", "recurrent networks"], fuzzy=1)
+    hits = fuzzy.search("tranformer")
+    assert [doc for doc, _ in hits] == [0], f"Got: {hits}"
+    assert hits[0][1] == fuzzy.search("transformer")[0][1] / 2, f"Got: {hits}"
+    try:
+        BM25Index(["a"], fuzzy=3)
+        raise AssertionError("Expected ValueError for fuzzy=3")
+    except ValueError:
+        pass
+    ok("BM25Index(fuzzy=1)", "'tranformer' finds 'transformer' at half the score")
+
+    passages = ["Intro text. Transformers rely on attention layers.", "Unrelated text."]
+    stored = BM25Index(passages, language="en", store_documents=True)
+    snippet = stored.highlight("transformer attention", 0, window=5)
+    assert snippet == "text. <mark>Transformers</mark> rely on <mark>attention</mark>", snippet
+    assert stored.highlight("layers", 0, window=1, pre="*", post="*") == "*layers*"
+    try:
+        stored.highlight("text", 2)
+        raise AssertionError("Expected IndexError for a missing document")
+    except IndexError:
+        pass
+    ok("BM25Index.highlight()", f"{snippet!r}")
+
+    vectors = stored.sparse_vectors()
+    assert len(vectors) == 2 and sorted(vectors[0]) == ["indices", "values"]
+    sparse_query = stored.sparse_query("attention layers")
+    dot = sum(
+        value * dict(zip(vectors[0]["indices"], vectors[0]["values"])).get(index, 0.0)
+        for index, value in zip(sparse_query["indices"], sparse_query["values"])
+    )
+    assert abs(dot - stored.get_scores("attention layers")[0]) < 1e-4, dot
+    ok("BM25Index.sparse_vectors() / sparse_query()", "dot product = BM25 score")
+
+    stemmed = BM25Index(["the cats were running", "a cat sat", "dogs bark"], language="en")
+    assert stemmed.vocabulary()["cat"] == 2, f"Got: {stemmed.vocabulary()!r}"
+    frequencies = stemmed.term_frequencies(0)
+    assert frequencies == {"cat": 1, "run": 1}, f"Got: {frequencies!r}"
+    assert stemmed.idf("Cats") == stemmed.idf("cat", analyze=False) is not None
+    assert stemmed.idf("Cats") < stemmed.idf("barking"), "Rarer terms should weigh more"
+    assert stemmed.idf("the") is None and stemmed.idf("zebra") is None
+    try:
+        stemmed.term_frequencies(3)
+        raise AssertionError("Expected IndexError for a missing document")
+    except IndexError:
+        pass
+    ok("BM25Index.idf() / term_frequencies() / vocabulary()", "term statistics as indexed")
+
+    formulas = ["Fe(OH)3 is rust", "Fe is iron"]
+    custom = BM25Index(formulas, tokenizer=str.split)
+    assert [i for i, _ in custom.search("Fe(OH)3")] == [0], f"Got: {custom.search('Fe(OH)3')!r}"
+    assert custom.search("fe(oh)3") == [], "A custom tokenizer keeps case unless it folds it"
+    cased = BM25Index(["Rust is a language", "rust on iron"], lowercase=False)
+    assert [i for i, _ in cased.search("Rust")] == [0], f"Got: {cased.search('Rust')!r}"
+    with tempfile.TemporaryDirectory() as tmp:
+        try:
+            custom.save(str(Path(tmp) / "custom.bm25"))
+            raise AssertionError("Expected RuntimeError saving a custom tokenizer")
+        except RuntimeError:
+            pass
+
+    def broken(text):
+        raise KeyError(text)
+
+    try:
+        BM25Index(formulas, tokenizer=broken)
+        raise AssertionError("Expected the tokenizer's KeyError")
+    except KeyError:
+        pass
+    ok("BM25Index(tokenizer=...)", "custom tokens, case-sensitive terms")
+
+    bm25_ranking = [(0, 7.1), (2, 3.4), (5, 1.0)]
+    vector_ranking = [("doc-9", 0.92), (2, 0.88), (0, 0.80)]
+    fused = rrf_fuse([bm25_ranking, vector_ranking])
+    assert [doc for doc, _ in fused] == [0, 2, "doc-9", 5], f"Got: {fused}"
+    assert abs(fused[0][1] - (1 / 61 + 1 / 63)) < 1e-12, f"Got: {fused[0]!r}"
+    fused = rrf_fuse([bm25_ranking, vector_ranking], weights=[0.0, 1.0], top_k=1)
+    assert fused == [("doc-9", 1 / 61)], f"Got: {fused}"
+    try:
+        rrf_fuse([bm25_ranking], weights=[1.0, 2.0])
+        raise AssertionError("Expected ValueError for mismatched weights")
+    except ValueError:
+        pass
+    ok("rrf_fuse()", "documents ranked by several lists come first")
+
+    tfidf = TfIdfIndex(["the cat sat", "the dog sat", "the cat ate the cat food"])
+    hits = tfidf.search("cat")
+    assert [doc for doc, _ in hits] == [2, 0], f"Got: {hits}"
+    assert abs(hits[1][1] - 0.6198053799406072) < 1e-9, f"Got: {hits}"
+    assert tfidf.get_scores("dog")[1] > 0 and len(tfidf) == 3
+    stemmed_tfidf = TfIdfIndex(["Running dogs", "A cat"], language="en", sublinear_tf=True)
+    assert stemmed_tfidf.search("run")[0][0] == 0, f"Got: {stemmed_tfidf.search('run')}"
+    ok("TfIdfIndex.search()", "cosine scores as TfidfVectorizer computes them")
+
+    # Spelling correction against the corpus vocabulary
+    corrector = SpellCorrector(docs)
+    fixed = corrector.correct("nueral netwroks")
+    assert fixed == "neural networks", f"Got: {fixed}"
+    ok("SpellCorrector.correct()", f"'nueral netwroks' → '{fixed}'")
+
+    # Corpus token statistics
+    stats = TokenStats().add(["The cat and the dog", "The bird"]).add(["A cat"])
+    assert stats.vocabulary_size == 6 and stats.token_count == 9, f"Got: {stats!r}"
+    assert stats.top_terms(2) == [("the", 3), ("cat", 2)], f"Got: {stats.top_terms(2)!r}"
+    assert stats.top_terms(1, by_chunks=True) == [("cat", 2)]
+    assert stats.hapax_count == 4 and stats.hapaxes() == ["a", "and", "bird", "dog"]
+    ok("TokenStats", f"{stats.vocabulary_size} terms, {stats.hapax_count} hapaxes")
+
+    # ── Encryption at rest ──
+    secret = b"confidential chunk text"
+    sealed = encrypt_bytes(secret, b"passphrase")
+    assert sealed != secret and decrypt_bytes(sealed, b"passphrase") == secret
+    try:
+        decrypt_bytes(sealed, b"wrong")
+        fail("decrypt_bytes()", "Should have raised for wrong passphrase")
+    except ValueError:
+        ok("encrypt_bytes() / decrypt_bytes()", "roundtrip + wrong passphrase rejected")
+
+    # ── DOCX extraction ──
+    import tempfile
+    import zipfile
+
+    w = 'xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"'
+    document = (
+        f"<w:document {w}><w:body>"
+        '<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Results</w:t></w:r></w:p>'
+        "<w:p><w:r><w:t>Accuracy improved.</w:t></w:r></w:p>"
+        "</w:body></w:document>"
+    )
+    with tempfile.TemporaryDirectory() as tmp:
+        docx_path = Path(tmp) / "sample.docx"
+        with zipfile.ZipFile(docx_path, "w", zipfile.ZIP_DEFLATED) as zf:
+            zf.writestr("word/document.xml", document)
+        docx_text = extract_docx_text(str(docx_path))
+    assert docx_text == "# Results\nAccuracy improved.", f"Got: {docx_text!r}"
+    ok("extract_docx_text()", "heading + paragraph")
+
+    # ── HTML extraction ──
+    page = (
+        "<html><body><nav><a href='/'>Home</a></nav>"
+        "<article><h1>Results</h1><p>Accuracy improved, latency dropped, and the "
+        "index shrank by a third after switching to hybrid retrieval.</p></article>"
+        "<footer>Copyright</footer></body></html>"
+    )
+    with tempfile.TemporaryDirectory() as tmp:
+        html_path = Path(tmp) / "page.html"
+        html_path.write_text(page, encoding="utf-8")
+        html_text = extract_html_text(str(html_path))
+    assert html_text.startswith("# Results\nAccuracy improved"), f"Got: {html_text!r}"
+    assert "Home" not in html_text and "Copyright" not in html_text
+    ok("extract_html_text()", "nav/footer stripped")
+
+    # ── Subtitles ──
+    vtt = (
+        "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\n<v Alice>Shall we review the index?\n\n"
+        "00:00:03.000 --> 00:00:05.500\n<v Alice>It grew again.\n\n"
+        "00:00:09.000 --> 00:00:11.000\n<v Bob>Let's shard it.\n"
+    )
+    with tempfile.TemporaryDirectory() as tmp:
+        vtt_path = Path(tmp) / "meeting.vtt"
+        vtt_path.write_text(vtt, encoding="utf-8")
+        paragraphs = extract_subtitles(str(vtt_path))
+    assert paragraphs == [
+        (1.0, 5.5, "Alice: Shall we review the index? It grew again."),
+        (9.0, 11.0, "Bob: Let's shard it."),
+    ], f"Got: {paragraphs}"
+    ok("extract_subtitles()", "cues merged into timed paragraphs")
+
+    # ── LaTeX extraction ──
+    paper = (
+        "\\documentclass{article}\n\\title{Sparse Retrieval}\n\\begin{document}\n"
+        "\\section{Method}\\label{sec:method}\n"
+        "We score with $s(q, d) = \\sum_t w_t$~\\cite{bm25}.\n"
+        "\\begin{figure}\\includegraphics{plot.pdf}\\caption{Recall at 10.}\\end{figure}\n"
+        "\\end{document}\n"
+    )
+    with tempfile.TemporaryDirectory() as tmp:
+        tex_path = Path(tmp) / "paper.tex"
+        tex_path.write_text(paper, encoding="utf-8")
+        tex_text = extract_latex_text(str(tex_path))
+    expected = (
+        "Sparse Retrieval\n\n# Method\n\n"
+        "We score with s(q, d) = \\sum_t w_t.\nFigure: Recall at 10."
+    )
+    assert tex_text == expected, f"Got: {tex_text!r}"
+    ok("extract_latex_text()", "section, math, and caption kept")
+
+    # ── EPUB extraction ──
+    container = (
+        '<container><rootfiles><rootfile full-path="OEBPS/book.opf"/></rootfiles></container>'
+    )
+    opf = (
+        '<package><metadata xmlns:dc="http://purl.org/dc/elements/1.1/">'
+        "<dc:title>Field Notes</dc:title></metadata><manifest>"
+        '<item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/>'
+        '<item id="c2" href="c2.xhtml" media-type="application/xhtml+xml"/>'
+        '</manifest><spine><itemref idref="c1"/><itemref idref="c2"/></spine></package>'
+    )
+    with tempfile.TemporaryDirectory() as tmp:
+        epub_path = Path(tmp) / "book.epub"
+        with zipfile.ZipFile(epub_path, "w", zipfile.ZIP_DEFLATED) as zf:
+            zf.writestr("META-INF/container.xml", container)
+            zf.writestr("OEBPS/book.opf", opf)
+            zf.writestr("OEBPS/c1.xhtml", "<html><body><h1>Birds</h1><p>Owls.</p></body></html>")
+            zf.writestr("OEBPS/c2.xhtml", "<html><body><h1>Trees</h1><p>Oaks.</p></body></html>")
+        book = extract_epub(str(epub_path))
+    assert book["title"] == "Field Notes", f"Got: {book}"
+    titles = [title for title, _ in book["chapters"]]
+    assert titles == ["Birds", "Trees"], f"Got: {titles}"
+    ok("extract_epub()", f"{len(titles)} chapters in spine order")
+
+    # ── PowerPoint extraction ──
+    def slide(title, body):
+        shapes = "".join(
+            f'<p:sp><p:nvSpPr><p:nvPr>{ph}</p:nvPr></p:nvSpPr>'
+            f"<p:txBody><a:p><a:r><a:t>{text}</a:t></a:r></a:p></p:txBody></p:sp>"
+            for ph, text in [('<p:ph type="title"/>', title), ("", body)]
+        )
+        return f"<p:sld><p:cSld><p:spTree>{shapes}</p:spTree></p:cSld></p:sld>"
+
+    rels = "".join(
+        f'<Relationship Id="rId{n}" Type="slide" Target="slides/slide{n}.xml"/>'
+        for n in (1, 2)
+    )
+    with tempfile.TemporaryDirectory() as tmp:
+        pptx_path = Path(tmp) / "deck.pptx"
+        with zipfile.ZipFile(pptx_path, "w", zipfile.ZIP_DEFLATED) as zf:
+            zf.writestr(
+                "ppt/presentation.xml",
+                '<p:presentation><p:sldIdLst><p:sldId id="256" r:id="rId1"/>'
+                '<p:sldId id="257" r:id="rId2"/></p:sldIdLst></p:presentation>',
+            )
+            zf.writestr("ppt/_rels/presentation.xml.rels", f"<Relationships>{rels}</Relationships>")
+            zf.writestr("ppt/slides/slide1.xml", slide("Roadmap", "Ship v2"))
+            zf.writestr("ppt/slides/slide2.xml", slide("Risks", "Hiring"))
+        slides = extract_pptx(str(pptx_path))
+    assert slides[1] == (2, "Risks", "# Risks\nHiring"), f"Got: {slides}"
+    ok("extract_pptx()", f"{len(slides)} slides with titles")
+
+    # ── Email extraction ──
+    eml = (
+        "From: Ada <ada@example.com>\r\nSubject: Launch plan\r\n"
+        "Date: Tue, 1 Jul 2003 10:52:37 +0200\r\n\r\n"
+        "We launch Monday.\r\n\r\nOn Mon, Bob wrote:\r\n> When do we launch?\r\n"
+        "-- \r\nAda\r\n"
+    )
+    with tempfile.TemporaryDirectory() as tmp:
+        eml_path = Path(tmp) / "launch.eml"
+        eml_path.write_text(eml, encoding="utf-8", newline="")
+        messages = extract_email(str(eml_path))
+    message = messages[0]
+    assert message["text"] == "We launch Monday.", f"Got: {message['text']!r}"
+    assert message["date"] == "2003-07-01T10:52:37+02:00", f"Got: {message['date']}"
+    ok("extract_email()", "quoted reply and signature stripped")
+
+    # ── Markdown sections ──
+    md = "# Install\n\n## Linux\napt install rusty-rag\n\n## macOS\nbrew install rusty-rag"
+    sections = markdown_sections(md)
+    paths = [" > ".join(path) for path, _ in sections]
+    assert paths == ["Install > Linux", "Install > macOS"], f"Got: {paths}"
+    assert sections[0][1] == "## Linux\napt install rusty-rag", f"Got: {sections[0]}"
+    ok("markdown_sections()", " | ".join(paths))
+
+    # ── Extractor registry ──
+    formats = {f["name"]: f for f in document_formats()}
+    assert ".pdf" in formats["pdf"]["extensions"], f"Got: {formats}"
+    assert "text/html" in formats["html"]["mime_types"], f"Got: {formats}"
+    with tempfile.TemporaryDirectory() as tmp:
+        md_path = Path(tmp) / "README.MD"
+        md_path.write_text(md, encoding="utf-8")
+        spans = extract_document(str(md_path))
+        try:
+            extract_document(str(md_path), "application/x-unknown")
+            raise AssertionError("Unknown MIME type should be rejected")
+        except RuntimeError as e:
+            assert "Unsupported MIME type" in str(e), f"Got: {e}"
+    assert [section for _, section, _ in spans] == paths, f"Got: {spans}"
+    ok("extract_document()", f"{len(formats)} formats, markdown by extension")
+
+    # ── Text files ──
+    with tempfile.TemporaryDirectory() as tmp:
+        utf16_path = Path(tmp) / "notes.txt"
+        utf16_path.write_bytes("Caf\u00e9 notes\r\nline two".encode("utf-16"))
+        latin1_path = Path(tmp) / "legacy.py"
+        latin1_path.write_bytes("# na\u00efve\n".encode("latin-1"))
+        texts = [read_text_file(str(utf16_path)), read_text_file(str(latin1_path))]
+    assert texts == ["Caf\u00e9 notes\nline two", "# na\u00efve\n"], f"Got: {texts}"
+    ok("read_text_file()", "UTF-16 and Latin-1 decoded")
+
+    # ── Spreadsheets ──
+    with tempfile.TemporaryDirectory() as tmp:
+        csv_path = Path(tmp) / "people.csv"
+        csv_path.write_text('name,city\nAda,London\n"Hopper, Grace",New York\n')
+        records = extract_spreadsheet_text(str(csv_path))
+    assert records == (
+        "name: Ada\ncity: London\n\nname: Hopper, Grace\ncity: New York"
+    ), f"Got: {records!r}"
+    ok("extract_spreadsheet_text()", "CSV rows as column: value records")
+
     return True
 
 
@@ -272,7 +1066,15 @@ def test_pdf_extraction():
     section("PDF Extraction (no services needed)")
 
     try:
-        from rusty_rag import extract_pdf_text
+        from rusty_rag import (
+            extract_pdf_text,
+            extract_pdf_pages,
+            extract_pdf_sections,
+            extract_pdf_metadata,
+            extract_pdf_images,
+            extract_pdf_form_fields,
+            pdf_text_coverage,
+        )
     except ImportError:
         fail("Import", "rusty_rag not installed")
         return False
@@ -293,6 +1095,45 @@ def test_pdf_extraction():
     assert "bm25" in text_lower, "Missing expected content: 'bm25'"
     ok("Content verification", "key terms found in extracted text")
 
+    # Per-page extraction for page citations
+    pages = extract_pdf_pages(str(pdf_path))
+    assert pages and pages[0][0] == 1, f"Expected pages starting at 1, got {pages[:1]}"
+    assert "\n".join(t for _, t in pages) == text, "Pages should join to the full text"
+    ok("extract_pdf_pages()", f"{len(pages)} pages")
+
+    raw = extract_pdf_pages(str(pdf_path), strip_furniture=False)
+    assert sum(len(t) for _, t in raw) >= sum(len(t) for _, t in pages), "Cleanup added text"
+    ok("extract_pdf_pages(strip_furniture=False)", "headers and footers kept")
+
+    joined = extract_pdf_pages(str(pdf_path), join_lines=True)
+    assert all("\n" not in t.replace("\n\n", "") for _, t in joined if "|" not in t), (
+        "Paragraph lines should be joined"
+    )
+    ok("extract_pdf_pages(join_lines=True)", "one line per paragraph")
+
+    # Without an outline, sections are one untitled span per page
+    spans = extract_pdf_sections(str(pdf_path))
+    assert [(p, t) for p, _, t in spans] == pages, "Sections should match pages"
+    assert all(path == [] for _, path, _ in spans), f"Unexpected sections: {spans[:1]}"
+    ok("extract_pdf_sections()", f"{len(spans)} spans, no outline")
+
+    meta = extract_pdf_metadata(str(pdf_path))
+    assert meta["page_count"] >= len(pages), f"Bad page count: {meta}"
+    ok("extract_pdf_metadata()", f"{meta['page_count']} pages, title={meta['title']!r}")
+
+    fields = extract_pdf_form_fields(str(pdf_path))
+    assert fields == [], f"Sample PDF has no form, got {fields[:1]}"
+    ok("extract_pdf_form_fields()", "no fields in a PDF without a form")
+
+    coverage = pdf_text_coverage(str(pdf_path))
+    assert coverage["image_pages"] == [], f"Unexpected image-only pages: {coverage}"
+    assert len(coverage["text_pages"]) >= len(pages), f"Bad coverage: {coverage}"
+    ok("pdf_text_coverage()", f"{len(coverage['text_pages'])} pages with text")
+
+    images = extract_pdf_images(str(pdf_path))
+    assert images == [], f"Text-only sample should have no figures, got {len(images)}"
+    ok("extract_pdf_images()", "no figures in text-only PDF")
+
     # Test chunk pipeline on extracted text
     from rusty_rag import chunk_by_tokens, token_count
     chunks = chunk_by_tokens(text, 256, 32)